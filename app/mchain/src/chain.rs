@@ -0,0 +1,147 @@
+/// What goes to disk besides the event log: the state without its log, and
+/// how many records of the log it covers.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+struct Persisted {
+	parameters: lib_mock_chain::Parameters,
+	height: u64,
+	events: u64,
+	snapshot: lib_mock_chain::Snapshot
+}
+
+/// [`lib_mock_chain::MockChain`] cut into blocks.
+///
+/// Transactions apply as they arrive; [`Chain::produce_block`] then moves
+/// the chain clock forward by exactly `block_seconds` and writes the state to
+/// `path`, so a replay of the same transactions between the same blocks
+/// always ends in the same state. The event log only grows, so it goes to
+/// its own file next to `path` and each block appends the records it added
+/// instead of rewriting the log. A restarted service picks up at the last
+/// persisted block.
+#[derive(Debug)]
+pub struct Chain {
+	chain: lib_mock_chain::MockChain,
+	height: u64,
+	block_seconds: u64,
+	path: Option<std::path::PathBuf>,
+	// records of the event log already appended to disk
+	persisted: usize
+}
+
+#[bon::bon]
+impl Chain {
+	#[builder]
+	pub fn new(
+		seed: u64,
+		block_seconds: u64,
+		parameters: Option<lib_mock_chain::Parameters>,
+		path: Option<std::path::PathBuf>
+	) -> crate::Result<Self> {
+		let parameters: lib_mock_chain::Parameters = parameters.unwrap_or_default();
+		let mut new: Self = Self {
+			chain: lib_mock_chain::MockChain::with_parameters(seed, parameters),
+			height: 0,
+			block_seconds,
+			path,
+			persisted: 0
+		};
+		if let Some(path) = &new.path
+		&& path.exists() {
+			let content: String = std::fs::read_to_string(path)?;
+			let Persisted {
+				parameters,
+				height,
+				events,
+				snapshot
+			} = serde_json::from_str(&content)?;
+			let events: usize = usize::try_from(events)?;
+			let log: Vec<lib_mock_chain::Record> = read_log(&log_of(path), events)?;
+			let mut chain: lib_mock_chain::MockChain = lib_mock_chain::MockChain::with_parameters(seed, parameters);
+			chain.restore(snapshot.join(log));
+			new.chain = chain;
+			new.height = height;
+			new.persisted = events;
+		}
+		Ok(new)
+	}
+}
+
+impl Chain {
+	pub const fn height(&self) -> u64 {
+		self.height
+	}
+
+	pub const fn chain(&self) -> &lib_mock_chain::MockChain {
+		&self.chain
+	}
+
+	pub const fn chain_mut(&mut self) -> &mut lib_mock_chain::MockChain {
+		&mut self.chain
+	}
+
+	pub fn produce_block(&mut self) -> crate::Result {
+		self.chain.advance(self.block_seconds);
+		self.height = self.height.saturating_add(1);
+		self.persist()
+	}
+
+	fn persist(&mut self) -> crate::Result {
+		let Some(path) = &self.path else {
+			return Ok(())
+		};
+		let (snapshot, log): (lib_mock_chain::Snapshot, Vec<lib_mock_chain::Record>) = self.chain.snapshot().split();
+		let mut appended: String = String::new();
+		for record in log.iter().skip(self.persisted) {
+			appended.push_str(&serde_json::to_string(record)?);
+			appended.push('\n');
+		}
+		// log first, the state only counts records that made it to disk
+		let mut file: std::fs::File = std::fs::OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(log_of(path))?;
+		std::io::Write::write_all(&mut file, appended.as_bytes())?;
+		file.sync_data()?;
+		let persisted: Persisted = Persisted {
+			parameters: self.chain.parameters().to_owned(),
+			height: self.height,
+			events: u64::try_from(log.len())?,
+			snapshot
+		};
+		let content: String = serde_json::to_string(&persisted)?;
+		// write then rename, a crash mid write keeps the previous block
+		let staging: std::path::PathBuf = path.with_extension("tmp");
+		std::fs::write(&staging, content)?;
+		std::fs::rename(&staging, path)?;
+		self.persisted = log.len();
+		Ok(())
+	}
+}
+
+/// File the event log of the state at `path` is appended to.
+fn log_of(path: &std::path::Path) -> std::path::PathBuf {
+	path.with_extension("events")
+}
+
+/// Reads the first `events` records of the log at `path`, cutting off what a
+/// crash between appending the log and writing the state left behind.
+fn read_log(path: &std::path::Path, events: usize) -> crate::Result<Vec<lib_mock_chain::Record>> {
+	let content: String = if path.exists() {
+		std::fs::read_to_string(path)?
+	} else {
+		String::new()
+	};
+	let lines: Vec<&str> = content.lines().collect();
+	let kept: &[&str] = lines.get(..events).ok_or("event log is shorter than the persisted state")?;
+	if lines.len() > events {
+		let truncated: String = kept.iter().map(|line| format!("{line}\n")).collect();
+		let staging: std::path::PathBuf = path.with_extension("events.tmp");
+		std::fs::write(&staging, truncated)?;
+		std::fs::rename(&staging, path)?;
+	}
+	kept.iter()
+		.map(|line| serde_json::from_str(line).map_err(Into::into))
+		.collect()
+}
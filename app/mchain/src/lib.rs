@@ -0,0 +1,6 @@
+pub mod chain;
+pub mod service;
+
+pub use lib_mchain_proto as proto;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
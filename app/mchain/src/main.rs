@@ -1,83 +1,76 @@
+//! Mock chain shared by the nodes of a local simulation, served over gRPC
+//! in place of a live chain.
 
+#[derive(Debug)]
+#[derive(clap::Parser)]
+struct Cli {
+	#[arg(long, default_value = "127.0.0.1:50051")]
+	listen: std::net::SocketAddr,
 
-// erc20, domain registration and management
-// event sourcing 
+	/// Chain clock at genesis, in seconds.
+	#[arg(long, default_value_t = 0)]
+	seed: u64,
 
-pub struct Address(Vec<u8>);
-
-impl From<u64> for Address {
-	fn from(value: u64) -> Self {
-		let value: Vec<u8> = value.to_le_bytes().to_vec();
-		Self(value)
-	}
-}
+	/// Chain seconds each block moves the clock by.
+	#[arg(long, default_value_t = 5)]
+	block_seconds: u64,
 
+	/// Wall clock milliseconds between blocks, `0` to only produce blocks on
+	/// request.
+	#[arg(long, default_value_t = 5000)]
+	block_interval: u64,
 
+	/// File the state is persisted to after every block.
+	#[arg(long)]
+	state: Option<std::path::PathBuf>,
 
-#[rocket::get("/token_name")]
-fn name() -> &'static str {
-	""
+	/// Let anyone fund any account, for local simulations only.
+	#[arg(long)]
+	faucet: bool
 }
 
-#[rocket::get("/token/mint")]
-fn token_mint(account: u32) {
-	
-}
+#[tokio::main]
+async fn main() -> mchain::Result {
+	let cli: Cli = clap::Parser::parse();
 
+	fern::Dispatch::new()
+		.format(|out, message, record| {
+			out.finish(format_args!("[{}] {}", record.level(), message))
+		})
+		.level(log::LevelFilter::Info)
+		.chain(std::io::stdout())
+		.apply()?;
 
-struct M {
-	owner: u32,
-	domain_to_ownership: std::collections::HashMap<String, u32>,
-	domain_to_expiry_timestamp: std::collections::HashMap<String, u64>,
-	name: String,
-	symbol: String,
-	total_supply: u64
-}
+	let chain: mchain::chain::Chain = mchain::chain::Chain::builder()
+		.seed(cli.seed)
+		.block_seconds(cli.block_seconds)
+		.maybe_path(cli.state)
+		.build()?;
 
-impl M {
-	
-}
+	log::info!("mock chain at height {} listening on {}", chain.height(), cli.listen);
 
-fn c(domain: &str) -> u32 {
-	let chars: Vec<_> = domain.chars().collect();
-	let len: usize = chars.len();
-	if len == 0 {
-		return 1
-	}
-	let mut freq = std::collections::HashMap::new();
-	for char in &chars {
-		freq.entry(char).or_insert(0);
+	let service: mchain::service::Service = mchain::service::Service::builder()
+		.chain(chain)
+		.faucet(cli.faucet)
+		.build();
+
+	if cli.block_interval > 0 {
+		let service: mchain::service::Service = service.to_owned();
+		let mut interval: tokio::time::Interval = tokio::time::interval(std::time::Duration::from_millis(cli.block_interval));
+		tokio::spawn(async move {
+			loop {
+				interval.tick().await;
+				if let Err(error) = service.produce_block().await {
+					log::error!("failed to produce block: {}", error);
+				}
+			}
+		});
 	}
-	
-	let mut entropy = 0;
-	let total_len = len as u32;
-	
-	
-	0
-}
 
-pub struct Chain;
+	tonic::transport::Server::builder()
+		.add_service(mchain::proto::mock_chain_server::MockChainServer::new(service))
+		.serve(cli.listen)
+		.await?;
 
-impl Chain {
-	pub fn register(&self) {
-		
-	}
+	Ok(())
 }
-
-fn main() {
-	std::process::Command::new("stellar").args(["container", "start", "local"]);
-	std::process::Command::new("stellar").args(["keys", "generate", "steve"]);
-	std::process::Command::new("stellar").args(["keys", "fund", "steve", "--network", "testnet"]);
-	
-	std::process::Command::new("stellar").args([
-		"contract", "invoke",
-		"--id", "<id>",
-		"--source", "steve",
-		"--network", "testnet",
-		"--", 
-		"sign_attestation",
-		"--owner", "<pk>",
-		"--foreign_public_key", "",
-		"--foreign_signature", ""
-	]);
-}
\ No newline at end of file
@@ -0,0 +1,338 @@
+use crate::proto;
+use tokio_stream::StreamExt as _;
+
+type Status<T> = std::result::Result<tonic::Response<T>, tonic::Status>;
+
+type Records = std::pin::Pin<Box<dyn tokio_stream::Stream<Item = std::result::Result<proto::Record, tonic::Status>> + Send>>;
+
+/// gRPC face of a [`chain::Chain`](crate::chain::Chain).
+///
+/// Every call that changes state publishes the [`lib_mock_chain::Record`]s it
+/// appended, which [`proto::mock_chain_server::MockChain::events`] streams
+/// after replaying the log from the requested sequence. Calls on behalf of
+/// an account carry the signature of its attested key over
+/// [`lib_mchain_proto::authorization_message`], and `fund` only mints coins
+/// out of thin air with the faucet on.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Service {
+	chain: std::sync::Arc<tokio::sync::Mutex<crate::chain::Chain>>,
+	records: tokio::sync::broadcast::Sender<lib_mock_chain::Record>,
+	faucet: bool
+}
+
+#[bon::bon]
+impl Service {
+	#[builder]
+	pub fn new(chain: crate::chain::Chain, #[builder(default)] faucet: bool) -> Self {
+		let (records, _): (tokio::sync::broadcast::Sender<lib_mock_chain::Record>, tokio::sync::broadcast::Receiver<lib_mock_chain::Record>) = tokio::sync::broadcast::channel(1024);
+		Self {
+			chain: std::sync::Arc::new(tokio::sync::Mutex::new(chain)),
+			records,
+			faucet
+		}
+	}
+
+	pub async fn produce_block(&self) -> crate::Result<proto::StatusResponse> {
+		self.apply(|chain| chain.produce_block()).await?;
+		let chain: tokio::sync::MutexGuard<_> = self.chain.lock().await;
+		Ok(status_of(&chain))
+	}
+
+	async fn apply<T, E>(&self, f: impl FnOnce(&mut crate::chain::Chain) -> std::result::Result<T, E>) -> std::result::Result<T, E> {
+		let mut chain: tokio::sync::MutexGuard<_> = self.chain.lock().await;
+		let from: usize = chain.chain().events().len();
+		let out: std::result::Result<T, E> = f(&mut chain);
+		for record in chain.chain().events().iter().skip(from) {
+			// nobody listening is fine
+			self.records.send(record.to_owned()).ok();
+		}
+		out
+	}
+
+	async fn transact(&self, f: impl FnOnce(&mut lib_mock_chain::MockChain) -> lib_mock_chain::Result) -> Status<proto::Empty> {
+		self.apply(|chain| f(chain.chain_mut())).await.map_err(status)?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	/// Applies `f` once `authorization` checks out for `account` calling
+	/// `method` with `request`.
+	async fn authorized<R, T>(
+		&self,
+		method: &str,
+		account: &lib_mock_chain::Account,
+		request: &R,
+		authorization: &[u8],
+		f: impl FnOnce(&mut lib_mock_chain::MockChain) -> lib_mock_chain::Result<T>
+	) -> std::result::Result<T, tonic::Status>
+	where
+		R: prost::Message {
+		self.apply(|chain| {
+			authorize(chain.chain_mut(), method, account, request, authorization)?;
+			f(chain.chain_mut())
+		}).await.map_err(status)
+	}
+
+	async fn read<T>(&self, f: impl FnOnce(&lib_mock_chain::MockChain) -> T) -> tonic::Response<T> {
+		let chain: tokio::sync::MutexGuard<_> = self.chain.lock().await;
+		tonic::Response::new(f(chain.chain()))
+	}
+}
+
+#[tonic::async_trait]
+impl proto::mock_chain_server::MockChain for Service {
+	type EventsStream = Records;
+
+	async fn status(&self, _: tonic::Request<proto::Empty>) -> Status<proto::StatusResponse> {
+		let chain: tokio::sync::MutexGuard<_> = self.chain.lock().await;
+		Ok(tonic::Response::new(status_of(&chain)))
+	}
+
+	async fn produce_block(&self, _: tonic::Request<proto::Empty>) -> Status<proto::StatusResponse> {
+		let status: proto::StatusResponse = Self::produce_block(self).await.map_err(|error| tonic::Status::internal(error.to_string()))?;
+		Ok(tonic::Response::new(status))
+	}
+
+	async fn fund(&self, request: tonic::Request<proto::FundRequest>) -> Status<proto::Empty> {
+		if !self.faucet {
+			return Err(tonic::Status::permission_denied("funding needs the faucet"))
+		}
+		let proto::FundRequest {
+			account,
+			amount
+		} = request.into_inner();
+		self.transact(|chain| chain.fund(&account.into(), amount)).await
+	}
+
+	async fn transfer(&self, request: tonic::Request<proto::TransferRequest>) -> Status<proto::Empty> {
+		let mut request: proto::TransferRequest = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let from: lib_mock_chain::Account = request.from.to_owned().into();
+		let to: lib_mock_chain::Account = request.to.to_owned().into();
+		self.authorized("Transfer", &from, &request, &authorization, |chain| chain.transfer(&from, &to, request.amount)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn account_of(&self, request: tonic::Request<proto::Account>) -> Status<proto::AccountResponse> {
+		let account: lib_mock_chain::Account = request.into_inner().account.into();
+		Ok(self.read(|chain| proto::AccountResponse {
+			balance: chain.balance_of(&account),
+			locked: chain.locked_balance_of(&account),
+			claimable: chain.claimable_of(&account),
+			total_spend: chain.total_spend(&account),
+			total_claim: chain.total_claim(&account),
+			age: chain.age(&account),
+			nonce: chain.nonce_of(&account)
+		}).await)
+	}
+
+	async fn lock(&self, request: tonic::Request<proto::LockRequest>) -> Status<proto::Empty> {
+		let mut request: proto::LockRequest = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let account: lib_mock_chain::Account = request.account.to_owned().into();
+		self.authorized("Lock", &account, &request, &authorization, |chain| chain.lock(&account, request.amount, request.duration)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn lock_of(&self, request: tonic::Request<proto::Account>) -> Status<proto::LockResponse> {
+		let account: lib_mock_chain::Account = request.into_inner().account.into();
+		Ok(self.read(|chain| proto::LockResponse {
+			amount: chain.locked_balance_of(&account),
+			until: chain.lock_expiry_of(&account)
+		}).await)
+	}
+
+	async fn attest(&self, request: tonic::Request<proto::AttestRequest>) -> Status<proto::Empty> {
+		let mut request: proto::AttestRequest = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let account: lib_mock_chain::Account = request.account.to_owned().into();
+		let key: lib_mock_chain::Key = request.key.to_owned().into();
+		self.transact(|chain| {
+			// the first key proves itself, any later one needs the current key
+			if chain.foreign_attestation(&account).is_some() {
+				authorize(chain, "Attest", &account, &request, &authorization)?;
+			}
+			chain.attest(&account, &key, &request.signature)
+		}).await
+	}
+
+	async fn attestation(&self, request: tonic::Request<proto::Key>) -> Status<proto::AttestationResponse> {
+		let key: lib_mock_chain::Key = request.into_inner().key.into();
+		Ok(self.read(|chain| proto::AttestationResponse {
+			account: chain.attestation(&key).map(|account| account.0.to_owned())
+		}).await)
+	}
+
+	async fn foreign_attestation(&self, request: tonic::Request<proto::Account>) -> Status<proto::ForeignAttestationResponse> {
+		let account: lib_mock_chain::Account = request.into_inner().account.into();
+		Ok(self.read(|chain| proto::ForeignAttestationResponse {
+			key: chain.foreign_attestation(&account).map(|key| key.0.to_owned())
+		}).await)
+	}
+
+	async fn open_commitment(&self, request: tonic::Request<proto::CommitmentRequest>) -> Status<proto::Empty> {
+		let mut request: proto::CommitmentRequest = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let relay: lib_mock_chain::Account = request.relay.to_owned().into();
+		let account: lib_mock_chain::Account = request.account.to_owned().into();
+		self.authorized("OpenCommitment", &relay, &request, &authorization, |chain| chain.open_commitment(&relay, &account)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn accept_commitment(&self, request: tonic::Request<proto::AuthorizedAccount>) -> Status<proto::Empty> {
+		let mut request: proto::AuthorizedAccount = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let account: lib_mock_chain::Account = request.account.to_owned().into();
+		self.authorized("AcceptCommitment", &account, &request, &authorization, |chain| chain.accept_commitment(&account)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn mint(&self, request: tonic::Request<proto::Domain>) -> Status<proto::Empty> {
+		let mut request: proto::Domain = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let owner: lib_mock_chain::Account = request.owner.to_owned().into();
+		self.authorized("Mint", &owner, &request, &authorization, |chain| chain.mint(&owner, &request.domain)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn renew(&self, request: tonic::Request<proto::Domain>) -> Status<proto::Empty> {
+		let mut request: proto::Domain = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let owner: lib_mock_chain::Account = request.owner.to_owned().into();
+		self.authorized("Renew", &owner, &request, &authorization, |chain| chain.renew(&owner, &request.domain)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn publish_agreement_key(&self, request: tonic::Request<proto::AgreementKeyRequest>) -> Status<proto::Empty> {
+		let mut request: proto::AgreementKeyRequest = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let owner: lib_mock_chain::Account = request.owner.to_owned().into();
+		self.authorized("PublishAgreementKey", &owner, &request, &authorization, |chain| chain.publish_agreement_key(&owner, &request.domain, &request.key)).await?;
+		Ok(tonic::Response::new(proto::Empty {}))
+	}
+
+	async fn owner(&self, request: tonic::Request<proto::OwnerRequest>) -> Status<proto::OwnerResponse> {
+		let domain: String = request.into_inner().domain;
+		Ok(self.read(|chain| proto::OwnerResponse {
+			owner: chain.owner_of(&domain).map(|owner| owner.0.to_owned()),
+			expires_at: chain.expiry_of(&domain),
+			traffic: chain.traffic(&domain),
+			renew_fee: chain.renew_fee(&domain),
+			agreement_key: chain.agreement_key_of(&domain).map(<[u8]>::to_vec)
+		}).await)
+	}
+
+	async fn fee(&self, _: tonic::Request<proto::Empty>) -> Status<proto::FeeResponse> {
+		Ok(self.read(|chain| proto::FeeResponse {
+			fee: chain.fee(),
+			congestion_charge: chain.congestion_charge(),
+			mint_fee: chain.parameters().mint_fee
+		}).await)
+	}
+
+	async fn submit_proof(&self, request: tonic::Request<proto::ProofRequest>) -> Status<proto::Empty> {
+		let proto::ProofRequest {
+			src,
+			dst,
+			relays,
+			domain,
+			nonce,
+			src_sig,
+			dst_sig,
+			relay_sigs,
+			header
+		} = request.into_inner();
+		let proof: lib_mock_chain::Proof = lib_mock_chain::Proof {
+			transit: lib_mock_chain::Transit {
+				domain,
+				src: src.into(),
+				dst: dst.into(),
+				relays: relays.into_iter().map(Into::into).collect(),
+				nonce
+			},
+			header,
+			src_sig,
+			dst_sig,
+			relay_sigs
+		};
+		self.transact(|chain| chain.submit_proof(&proof)).await
+	}
+
+	async fn claim(&self, request: tonic::Request<proto::AuthorizedAccount>) -> Status<proto::Amount> {
+		let mut request: proto::AuthorizedAccount = request.into_inner();
+		let authorization: Vec<u8> = std::mem::take(&mut request.authorization);
+		let account: lib_mock_chain::Account = request.account.to_owned().into();
+		let amount: u64 = self.authorized("Claim", &account, &request, &authorization, |chain| chain.claim(&account)).await?;
+		Ok(tonic::Response::new(proto::Amount {
+			amount
+		}))
+	}
+
+	async fn events(&self, request: tonic::Request<proto::EventsRequest>) -> Status<Self::EventsStream> {
+		let from: u64 = request.into_inner().from;
+		// subscribe under the lock so nothing lands between backlog and live
+		let chain: tokio::sync::MutexGuard<_> = self.chain.lock().await;
+		let receiver: tokio::sync::broadcast::Receiver<_> = self.records.subscribe();
+		let backlog: Vec<lib_mock_chain::Record> = chain.chain()
+			.events()
+			.iter()
+			.filter(|record| record.sequence >= from)
+			.cloned()
+			.collect();
+		drop(chain);
+
+		let backlog: Records = Box::pin(tokio_stream::iter(backlog).map(|record| record_of(&record)));
+		let live: Records = Box::pin(tokio_stream::wrappers::BroadcastStream::new(receiver)
+			.map(|record| record.map_err(|error| tonic::Status::data_loss(error.to_string())))
+			.filter(move |record| record.as_ref().map_or(true, |record| record.sequence >= from))
+			.map(|record| record.and_then(|record| record_of(&record))));
+		let stream: Self::EventsStream = Box::pin(tokio_stream::StreamExt::chain(backlog, live));
+		Ok(tonic::Response::new(stream))
+	}
+}
+
+fn status_of(chain: &crate::chain::Chain) -> proto::StatusResponse {
+	proto::StatusResponse {
+		height: chain.height(),
+		now: chain.chain().now(),
+		events: u64::try_from(chain.chain().events().len()).unwrap_or(u64::MAX)
+	}
+}
+
+/// Uses up the nonce of `account` if `authorization` signs `method` with
+/// `request`, whose own authorization is already taken out.
+fn authorize<R>(chain: &mut lib_mock_chain::MockChain, method: &str, account: &lib_mock_chain::Account, request: &R, authorization: &[u8]) -> lib_mock_chain::Result
+where
+	R: prost::Message {
+	let message: Vec<u8> = lib_mchain_proto::authorization_message(method, chain.nonce_of(account), request);
+	chain.authorize(account, &message, authorization)
+}
+
+fn record_of(record: &lib_mock_chain::Record) -> std::result::Result<proto::Record, tonic::Status> {
+	let event: String = serde_json::to_string(&record.event).map_err(|error| tonic::Status::internal(error.to_string()))?;
+	Ok(proto::Record {
+		sequence: record.sequence,
+		at: record.at,
+		event
+	})
+}
+
+fn status(error: lib_mock_chain::Error) -> tonic::Status {
+	let message: String = error.to_string();
+	match error {
+		lib_mock_chain::Error::InsufficientBalance { .. }
+		| lib_mock_chain::Error::NothingToClaim => tonic::Status::failed_precondition(message),
+		lib_mock_chain::Error::ZeroAmount
+		| lib_mock_chain::Error::InvalidAttestation
+		| lib_mock_chain::Error::InvalidProof => tonic::Status::invalid_argument(message),
+		lib_mock_chain::Error::Overflow => tonic::Status::out_of_range(message),
+		lib_mock_chain::Error::NotAttested
+		| lib_mock_chain::Error::NoCommitment
+		| lib_mock_chain::Error::UnknownDomain(_)
+		| lib_mock_chain::Error::NoAgreementKey(_) => tonic::Status::not_found(message),
+		lib_mock_chain::Error::DomainTaken(_)
+		| lib_mock_chain::Error::ProofReplayed => tonic::Status::already_exists(message),
+		lib_mock_chain::Error::NotOwner(_)
+		| lib_mock_chain::Error::Unauthorized => tonic::Status::permission_denied(message)
+	}
+}
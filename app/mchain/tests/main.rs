@@ -0,0 +1,272 @@
+use mchain::proto;
+use mchain::proto::mock_chain_server::MockChain as _;
+use tokio_stream::StreamExt as _;
+
+type AgileAlgorithm = lib_cryptography_algorithm_agile::AgileAlgorithm;
+
+type SecretKey = lib_cryptography::secret_key::SecretKey<AgileAlgorithm>;
+
+fn service(path: Option<std::path::PathBuf>) -> mchain::Result<mchain::service::Service> {
+	let chain: mchain::chain::Chain = mchain::chain::Chain::builder()
+		.seed(100)
+		.block_seconds(5)
+		.maybe_path(path)
+		.build()?;
+	Ok(mchain::service::Service::builder()
+		.chain(chain)
+		.faucet(true)
+		.build())
+}
+
+fn account(name: &str) -> Vec<u8> {
+	name.as_bytes().to_vec()
+}
+
+fn sign(secret_key: &SecretKey, message: &[u8]) -> lib_cryptography::Result<Vec<u8>> {
+	let message: bytes::Bytes = bytes::Bytes::copy_from_slice(message);
+	let message: lib_bytes::NonEmpty = message.try_into()?;
+	let message: lib_cryptography::message::Message = message.into();
+	let signature: lib_cryptography::signature::Signature<AgileAlgorithm> = <AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(secret_key, &message)?;
+	let signature: lib_bytes::NonEmpty = signature.into();
+	let signature: bytes::Bytes = signature.into();
+	Ok(signature.to_vec())
+}
+
+/// Fresh ed25519 key with its signature over `name`.
+fn key(name: &str) -> lib_cryptography::Result<(proto::AttestRequest, SecretKey)> {
+	let pair: lib_cryptography::pair::Pair<AgileAlgorithm> = AgileAlgorithm::generate_with(lib_cryptography_algorithm_agile::Tag::Ed25519)?;
+	let (public_key, secret_key) = pair.into();
+	let public_key: lib_bytes::NonEmpty = public_key.into();
+	let public_key: bytes::Bytes = public_key.into();
+	let request: proto::AttestRequest = proto::AttestRequest {
+		account: account(name),
+		key: public_key.to_vec(),
+		signature: sign(&secret_key, name.as_bytes())?,
+		authorization: Vec::new()
+	};
+	Ok((request, secret_key))
+}
+
+/// Attests a fresh key to `name`, returning its secret.
+async fn party(service: &mchain::service::Service, name: &str) -> mchain::Result<SecretKey> {
+	let (request, secret_key) = key(name).map_err(|error| error.to_string())?;
+	service.attest(tonic::Request::new(request)).await?;
+	Ok(secret_key)
+}
+
+/// Signs `method` with `request` for `name` at its current nonce.
+async fn authorization<R>(service: &mchain::service::Service, name: &str, secret_key: &SecretKey, method: &str, request: &R) -> mchain::Result<Vec<u8>>
+where
+	R: prost::Message {
+	let nonce: u64 = account_of(service, name).await?.nonce;
+	let authorization: Vec<u8> = sign(secret_key, &proto::authorization_message(method, nonce, request)).map_err(|error| error.to_string())?;
+	Ok(authorization)
+}
+
+async fn fund(service: &mchain::service::Service, name: &str, amount: u64) -> mchain::Result {
+	service.fund(tonic::Request::new(proto::FundRequest {
+		account: account(name),
+		amount
+	})).await?;
+	Ok(())
+}
+
+async fn account_of(service: &mchain::service::Service, name: &str) -> mchain::Result<proto::AccountResponse> {
+	let response: tonic::Response<_> = service.account_of(tonic::Request::new(proto::Account {
+		account: account(name)
+	})).await?;
+	Ok(response.into_inner())
+}
+
+fn code<T>(result: Result<T, tonic::Status>) -> Result<(), tonic::Code> {
+	result.map(|_| ()).map_err(|status| status.code())
+}
+
+#[tokio::test]
+async fn transfers_and_domains() -> mchain::Result {
+	let service: mchain::service::Service = service(None)?;
+	let alice: SecretKey = party(&service, "alice").await?;
+	let bob: SecretKey = party(&service, "bob").await?;
+	fund(&service, "alice", 1000).await?;
+	let mut transfer: proto::TransferRequest = proto::TransferRequest {
+		from: account("alice"),
+		to: account("bob"),
+		amount: 400,
+		authorization: Vec::new()
+	};
+	transfer.authorization = authorization(&service, "alice", &alice, "Transfer", &transfer).await?;
+	service.transfer(tonic::Request::new(transfer)).await?;
+	let mut mint: proto::Domain = proto::Domain {
+		owner: account("alice"),
+		domain: String::from("hello.an"),
+		authorization: Vec::new()
+	};
+	mint.authorization = authorization(&service, "alice", &alice, "Mint", &mint).await?;
+	service.mint(tonic::Request::new(mint)).await?;
+	assert_eq!(account_of(&service, "alice").await?.balance, 300);
+	assert_eq!(account_of(&service, "bob").await?.balance, 400);
+	let mut taken: proto::Domain = proto::Domain {
+		owner: account("bob"),
+		domain: String::from("hello.an"),
+		authorization: Vec::new()
+	};
+	taken.authorization = authorization(&service, "bob", &bob, "Mint", &taken).await?;
+	assert_eq!(code(service.mint(tonic::Request::new(taken)).await), Err(tonic::Code::AlreadyExists));
+	let owner: proto::OwnerResponse = service.owner(tonic::Request::new(proto::OwnerRequest {
+		domain: String::from("hello.an")
+	})).await?.into_inner();
+	assert_eq!(owner.owner, Some(account("alice")));
+	assert_eq!(owner.agreement_key, None);
+	let mut foreign: proto::AgreementKeyRequest = proto::AgreementKeyRequest {
+		owner: account("bob"),
+		domain: String::from("hello.an"),
+		key: vec![1; 32],
+		authorization: Vec::new()
+	};
+	foreign.authorization = authorization(&service, "bob", &bob, "PublishAgreementKey", &foreign).await?;
+	assert_eq!(code(service.publish_agreement_key(tonic::Request::new(foreign)).await), Err(tonic::Code::PermissionDenied));
+	let mut publish: proto::AgreementKeyRequest = proto::AgreementKeyRequest {
+		owner: account("alice"),
+		domain: String::from("hello.an"),
+		key: vec![1; 32],
+		authorization: Vec::new()
+	};
+	publish.authorization = authorization(&service, "alice", &alice, "PublishAgreementKey", &publish).await?;
+	service.publish_agreement_key(tonic::Request::new(publish)).await?;
+	let owner: proto::OwnerResponse = service.owner(tonic::Request::new(proto::OwnerRequest {
+		domain: String::from("hello.an")
+	})).await?.into_inner();
+	assert_eq!(owner.agreement_key, Some(vec![1; 32]));
+	Ok(())
+}
+
+#[tokio::test]
+async fn calls_need_the_attested_key() -> mchain::Result {
+	let service: mchain::service::Service = service(None)?;
+	let alice: SecretKey = party(&service, "alice").await?;
+	let mallory: SecretKey = party(&service, "mallory").await?;
+	fund(&service, "alice", 10).await?;
+	let unsigned: proto::TransferRequest = proto::TransferRequest {
+		from: account("alice"),
+		to: account("mallory"),
+		amount: 10,
+		authorization: Vec::new()
+	};
+	let mut forged: proto::TransferRequest = unsigned.to_owned();
+	forged.authorization = authorization(&service, "alice", &mallory, "Transfer", &unsigned).await?;
+	let mut signed: proto::TransferRequest = unsigned.to_owned();
+	signed.authorization = authorization(&service, "alice", &alice, "Transfer", &unsigned).await?;
+	assert_eq!(code(service.transfer(tonic::Request::new(unsigned)).await), Err(tonic::Code::PermissionDenied));
+	assert_eq!(code(service.transfer(tonic::Request::new(forged)).await), Err(tonic::Code::PermissionDenied));
+	let mut claim: proto::AuthorizedAccount = proto::AuthorizedAccount {
+		account: account("alice"),
+		authorization: Vec::new()
+	};
+	// signed for another method
+	claim.authorization = signed.authorization.to_owned();
+	assert_eq!(code(service.claim(tonic::Request::new(claim)).await), Err(tonic::Code::PermissionDenied));
+	service.transfer(tonic::Request::new(signed.to_owned())).await?;
+	assert_eq!(code(service.transfer(tonic::Request::new(signed)).await), Err(tonic::Code::PermissionDenied));
+	assert_eq!(account_of(&service, "alice").await?.nonce, 1);
+	let (mut rekey, _) = key("alice").map_err(|error| error.to_string())?;
+	rekey.authorization = authorization(&service, "alice", &mallory, "Attest", &rekey).await?;
+	assert_eq!(code(service.attest(tonic::Request::new(rekey)).await), Err(tonic::Code::PermissionDenied));
+	Ok(())
+}
+
+#[tokio::test]
+async fn funding_needs_the_faucet() -> mchain::Result {
+	let chain: mchain::chain::Chain = mchain::chain::Chain::builder()
+		.seed(100)
+		.block_seconds(5)
+		.build()?;
+	let service: mchain::service::Service = mchain::service::Service::builder()
+		.chain(chain)
+		.build();
+	assert_eq!(code(service.fund(tonic::Request::new(proto::FundRequest {
+		account: account("alice"),
+		amount: 10
+	})).await), Err(tonic::Code::PermissionDenied));
+	Ok(())
+}
+
+#[tokio::test]
+async fn blocks_advance_the_clock_deterministically() -> mchain::Result {
+	let service: mchain::service::Service = service(None)?;
+	let alice: SecretKey = party(&service, "alice").await?;
+	fund(&service, "alice", 10).await?;
+	let mut lock: proto::LockRequest = proto::LockRequest {
+		account: account("alice"),
+		amount: 10,
+		duration: 7,
+		authorization: Vec::new()
+	};
+	lock.authorization = authorization(&service, "alice", &alice, "Lock", &lock).await?;
+	service.lock(tonic::Request::new(lock)).await?;
+	let status: proto::StatusResponse = service.produce_block().await?;
+	assert_eq!((status.height, status.now), (1, 105));
+	assert_eq!(account_of(&service, "alice").await?.locked, 10);
+	let status: proto::StatusResponse = service.produce_block().await?;
+	assert_eq!((status.height, status.now), (2, 110));
+	assert_eq!(account_of(&service, "alice").await?.balance, 10);
+	Ok(())
+}
+
+#[tokio::test]
+async fn state_survives_a_restart() -> mchain::Result {
+	let path: std::path::PathBuf = std::env::temp_dir().join(format!("mchain-{}.json", std::process::id()));
+	let first: mchain::service::Service = service(Some(path.to_owned()))?;
+	fund(&first, "alice", 42).await?;
+	first.produce_block().await?;
+	// not part of any block, lost on restart
+	fund(&first, "alice", 1).await?;
+	let second: mchain::service::Service = service(Some(path.to_owned()))?;
+	std::fs::remove_file(&path)?;
+	std::fs::remove_file(path.with_extension("events"))?;
+	let status: proto::StatusResponse = second.status(tonic::Request::new(proto::Empty {})).await?.into_inner();
+	assert_eq!((status.height, status.events), (1, 1));
+	assert_eq!(account_of(&second, "alice").await?.balance, 42);
+	Ok(())
+}
+
+#[tokio::test]
+async fn event_log_is_appended_per_block() -> mchain::Result {
+	let path: std::path::PathBuf = std::env::temp_dir().join(format!("mchain-log-{}.json", std::process::id()));
+	let log: std::path::PathBuf = path.with_extension("events");
+	let first: mchain::service::Service = service(Some(path.to_owned()))?;
+	fund(&first, "alice", 1).await?;
+	first.produce_block().await?;
+	fund(&first, "alice", 2).await?;
+	first.produce_block().await?;
+	first.produce_block().await?;
+	let content: String = std::fs::read_to_string(&log)?;
+	assert_eq!(content.lines().count(), 2);
+	// a crash after appending the log but before writing the state
+	std::fs::write(&log, format!("{content}{}", content.lines().last().unwrap_or_default()))?;
+	let second: mchain::service::Service = service(Some(path.to_owned()))?;
+	let kept: usize = std::fs::read_to_string(&log)?.lines().count();
+	std::fs::remove_file(&path)?;
+	std::fs::remove_file(&log)?;
+	assert_eq!(kept, 2);
+	let status: proto::StatusResponse = second.status(tonic::Request::new(proto::Empty {})).await?.into_inner();
+	assert_eq!((status.height, status.events), (3, 2));
+	assert_eq!(account_of(&second, "alice").await?.balance, 3);
+	Ok(())
+}
+
+#[tokio::test]
+async fn events_replay_then_follow() -> mchain::Result {
+	let service: mchain::service::Service = service(None)?;
+	fund(&service, "alice", 1).await?;
+	fund(&service, "alice", 2).await?;
+	let mut events: <mchain::service::Service as proto::mock_chain_server::MockChain>::EventsStream = service.events(tonic::Request::new(proto::EventsRequest {
+		from: 1
+	})).await?.into_inner();
+	fund(&service, "alice", 3).await?;
+	let mut sequences: Vec<u64> = Vec::new();
+	while sequences.len() < 2 && let Some(record) = events.next().await {
+		sequences.push(record?.sequence);
+	}
+	assert_eq!(sequences, vec![1, 2]);
+	Ok(())
+}
@@ -1,7 +1,9 @@
 use super::*;
 
 pub mod bootstrap;
+pub mod chain;
 pub mod client;
+pub mod identity;
 pub mod mode;
 pub mod relay;
 pub mod server;
@@ -14,6 +16,8 @@ pub struct Config {
     #[serde(rename = "grpc-endpoint")]
     pub grpc_endpoint: Option<std::net::SocketAddr>,
     pub dial: Option<Vec<libp2p::Multiaddr>>,
+    pub identity: Option<identity::Identity>,
+    pub chain: Option<chain::Chain>,
     pub bootstrap: Option<bootstrap::Bootstrap>,
     pub client: Option<client::Client>,
     pub server: Option<server::Server>,
@@ -26,6 +30,8 @@ impl Config {
     pub fn new(
         grpc_endpoint: Option<std::net::SocketAddr>,
         dial: Option<Vec<libp2p::Multiaddr>>,
+        identity: Option<identity::Identity>,
+        chain: Option<chain::Chain>,
         bootstrap: Option<bootstrap::Bootstrap>,
         client: Option<client::Client>,
         server: Option<server::Server>,
@@ -34,6 +40,8 @@ impl Config {
         Self {
             grpc_endpoint,
             dial,
+            identity,
+            chain,
             bootstrap,
             client,
             server,
@@ -47,9 +55,24 @@ impl Config {
         }
         let content: String = std::fs::read_to_string(path)?;
         let new: Self = toml::from_str(&content)?;
+        new.validate()?;
         Ok(Some(new))
     }
 
+    /// Rejects combinations the node cannot run with before anything boots.
+    /// The soroban contract verifies plain Ed25519, so any other identity
+    /// could never attest.
+    pub fn validate(&self) -> Result {
+        if let Some(chain) = &self.chain
+        && chain.backend == Some(chain::Backend::Soroban)
+        && let Some(identity) = &self.identity
+        && let Some(algorithm) = identity.algorithm
+        && algorithm != identity::Algorithm::Ed25519 {
+            return Err(format!("the soroban backend attests ed25519 identities only, not {:?}", algorithm).into())
+        }
+        Ok(())
+    }
+
     pub fn from_toml() -> Result<Option<Self>> {
         let path: std::path::PathBuf = std::env::current_dir()?;
         let path: std::path::PathBuf = path.join("an.toml");
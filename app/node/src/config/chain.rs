@@ -0,0 +1,59 @@
+/// Where the node reads and writes economic state.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Default)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// In process chain, private to the node, starting at `seed`.
+    #[default]
+    Mock,
+    /// Remote `mchain` service at `endpoint`.
+    Mchain,
+    /// `contract` through the Soroban RPC at `endpoint`, signing with the
+    /// hex secret key in the `CHAIN_SECRET_KEY` environment variable.
+    Soroban
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Chain {
+    pub backend: Option<Backend>,
+    pub endpoint: Option<String>,
+    pub seed: Option<u64>,
+    /// Strkey of the dns contract.
+    pub contract: Option<String>,
+    #[serde(rename = "network-passphrase")]
+    pub network_passphrase: Option<String>,
+    /// Hex of the on chain account the node identity attests to, in the
+    /// chain's own address encoding.
+    pub account: Option<String>
+}
+
+#[bon::bon]
+impl Chain {
+    #[builder]
+    pub fn new(
+        backend: Option<Backend>,
+        endpoint: Option<String>,
+        seed: Option<u64>,
+        contract: Option<String>,
+        network_passphrase: Option<String>,
+        account: Option<String>
+    ) -> Self {
+        Self {
+            backend,
+            endpoint,
+            seed,
+            contract,
+            network_passphrase,
+            account
+        }
+    }
+}
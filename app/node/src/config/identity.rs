@@ -0,0 +1,44 @@
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    Ed25519,
+    Dilithium3,
+    Ed25519Dilithium3
+}
+
+impl From<Algorithm> for lib_cryptography_algorithm_agile::Tag {
+    fn from(value: Algorithm) -> Self {
+        match value {
+            Algorithm::Ed25519 => Self::Ed25519,
+            Algorithm::Dilithium3 => Self::Dilithium3,
+            Algorithm::Ed25519Dilithium3 => Self::Ed25519Dilithium3
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Identity {
+    pub algorithm: Option<Algorithm>,
+    /// File the identity secret key is kept in, created on first boot.
+    pub path: Option<std::path::PathBuf>
+}
+
+#[bon::bon]
+impl Identity {
+    #[builder]
+    pub fn new(algorithm: Option<Algorithm>, path: Option<std::path::PathBuf>) -> Self {
+        Self {
+            algorithm,
+            path
+        }
+    }
+}
@@ -4,15 +4,20 @@
 #[derive(serde::Deserialize)]
 pub struct Relay {
     #[serde(rename = "identity-cache-size")]
-    pub identity_cache_size: Option<usize>
+    pub identity_cache_size: Option<usize>,
+    /// Signature algorithms reservations may be signed with, every one when
+    /// unset.
+    #[serde(rename = "accepted-algorithms")]
+    pub accepted_algorithms: Option<Vec<super::identity::Algorithm>>
 }
 
 #[bon::bon]
 impl Relay {
     #[builder]
-    pub fn new(identity_cache_size: Option<usize>) -> Self {
+    pub fn new(identity_cache_size: Option<usize>, accepted_algorithms: Option<Vec<super::identity::Algorithm>>) -> Self {
         Self {
-            identity_cache_size
+            identity_cache_size,
+            accepted_algorithms
         }
     }
-}
\ No newline at end of file
+}
@@ -4,15 +4,18 @@
 #[derive(serde::Deserialize)]
 pub struct Server {
     #[serde(rename = "identity-cache-size")]
-    pub identity_cache_size: Option<usize>
+    pub identity_cache_size: Option<usize>,
+    /// Domain the server answers for, whose record carries its agreement key.
+    pub domain: Option<String>
 }
 
 #[bon::bon]
 impl Server {
     #[builder]
-    pub fn new(identity_cache_size: Option<usize>) -> Self {
+    pub fn new(identity_cache_size: Option<usize>, domain: Option<String>) -> Self {
         Self {
-            identity_cache_size
+            identity_cache_size,
+            domain
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,247 @@
+use super::*;
+
+pub mod mchain;
+pub mod middleware;
+pub mod mock;
+pub mod soroban;
+
+/// The node's one [`Dns`] backend, chosen by the `[chain]` section of the
+/// config. Cloning shares the backend, so every subsystem needing economic
+/// data talks to the same chain through the same async interface.
+#[derive(Clone)]
+pub struct Handle {
+	dns: std::sync::Arc<dyn Dns + Send + Sync>,
+	metrics: Vec<(&'static str, std::sync::Arc<middleware::Metrics>)>,
+	account: Option<Address>
+}
+
+impl Handle {
+	pub fn new<T>(dns: T) -> Self
+	where
+		T: 'static,
+		T: Send,
+		T: Sync,
+		T: Dns {
+		Self {
+			dns: std::sync::Arc::new(dns),
+			metrics: Vec::new(),
+			account: None
+		}
+	}
+
+	/// `backend` behind the [`middleware`] stack: cached and coalesced
+	/// reads, then a circuit breaker over retried reads, so a slow or down
+	/// chain stalls nobody for long.
+	pub fn layered<T>(backend: T) -> Self
+	where
+		T: 'static,
+		T: Send,
+		T: Sync,
+		T: Dns {
+		let retry: middleware::retry::Retry = middleware::retry::Retry::builder()
+			.attempts(3)
+			.base_delay(std::time::Duration::from_millis(100))
+			.max_delay(std::time::Duration::from_secs(2))
+			.build();
+		let breaker: middleware::breaker::Breaker = middleware::breaker::Breaker::builder()
+			.threshold(5)
+			.cooldown(std::time::Duration::from_secs(30))
+			.build();
+		let coalesce: middleware::coalesce::Coalesce = middleware::coalesce::Coalesce::default();
+		let cache: middleware::cache::Cache = middleware::cache::Cache::new(std::time::Duration::from_secs(5));
+		let metrics: Vec<(&'static str, std::sync::Arc<middleware::Metrics>)> = vec![
+			("cache", middleware::Layer::metrics(&cache)),
+			("coalesce", middleware::Layer::metrics(&coalesce)),
+			("breaker", middleware::Layer::metrics(&breaker)),
+			("retry", middleware::Layer::metrics(&retry))
+		];
+		let dns = middleware::Layered::new(retry, backend);
+		let dns = middleware::Layered::new(breaker, dns);
+		let dns = middleware::Layered::new(coalesce, dns);
+		let dns = middleware::Layered::new(cache, dns);
+		Self {
+			dns: std::sync::Arc::new(dns),
+			metrics,
+			account: None
+		}
+	}
+
+	/// Counters of every middleware layer, outermost first.
+	pub fn metrics(&self) -> Vec<(&'static str, middleware::Snapshot)> {
+		self.metrics
+			.iter()
+			.map(|(name, metrics)| (*name, metrics.snapshot()))
+			.collect()
+	}
+
+	/// Account the backend acts as, which the node identity attests to;
+	/// `None` when the node runs without one.
+	pub fn account(&self) -> Option<&Address> {
+		self.account.as_ref()
+	}
+
+	/// `mchain` acts as `account`, which it then requires, and signs its
+	/// calls with `secret_key`, the node identity; soroban acts as the
+	/// account of its own secret key, which `account` has to match when
+	/// given. Without a `[chain]` section the node runs on a private mock
+	/// chain, so it boots with no chain at all, acting as its identity key
+	/// unless `account` says otherwise.
+	pub fn from_config(chain: Option<&config::chain::Chain>, account: Option<Address>, secret_key: &identity::SecretKey) -> Result<Self> {
+		let backend: config::chain::Backend = chain.and_then(|chain| chain.backend).unwrap_or_default();
+		let (new, account): (Self, Option<Address>) = match backend {
+			config::chain::Backend::Mock => {
+				let acting_as: Address = match account {
+					Some(account) => account,
+					// a private chain nobody else signs on, so the identity
+					// key itself is as good an account as any
+					None => {
+						let public_key: identity::PublicKey = secret_key.public_key()?;
+						let public_key: lib_bytes::NonEmpty = public_key.into();
+						let public_key: bytes::Bytes = public_key.into();
+						Address(public_key.to_vec())
+					}
+				};
+				let seed: u64 = chain.and_then(|chain| chain.seed).unwrap_or_default();
+				let chain: lib_mock_chain::MockChain = lib_mock_chain::MockChain::new(seed);
+				let chain: std::sync::Arc<std::sync::Mutex<_>> = std::sync::Arc::new(std::sync::Mutex::new(chain));
+				(Self::layered(mock::Mock::new(chain, acting_as.to_owned())), Some(acting_as))
+			},
+			config::chain::Backend::Mchain => {
+				let account: Address = account.ok_or("mchain backend requires an account")?;
+				let endpoint: String = chain
+					.and_then(|chain| chain.endpoint.to_owned())
+					.ok_or("mchain backend requires an endpoint")?;
+				(Self::layered(mchain::Mchain::new(endpoint, account.to_owned(), secret_key.to_owned())?), Some(account))
+			},
+			config::chain::Backend::Soroban => {
+				let endpoint: String = chain
+					.and_then(|chain| chain.endpoint.to_owned())
+					.ok_or("soroban backend requires an endpoint")?;
+				let contract: lib_soroban_rpc::xdr::ContractId = chain
+					.and_then(|chain| chain.contract.as_deref())
+					.ok_or("soroban backend requires a contract")?
+					.parse()?;
+				let secret_key: String = std::env::var(env_key::CHAIN_SECRET_KEY)?;
+				let secret_key: [u8; 32] = hex::decode(secret_key)?
+					.try_into()
+					.map_err(|_| "chain secret key must be 32 bytes")?;
+				let client: lib_soroban_rpc::Client = lib_soroban_rpc::Client::builder()
+					.endpoint(endpoint)
+					.maybe_network_passphrase(chain.and_then(|chain| chain.network_passphrase.to_owned()))
+					.secret_key(secret_key)
+					.build();
+				let soroban: soroban::Soroban = soroban::Soroban::new(client, contract);
+				let acting_as: Address = soroban.account()?;
+				if let Some(account) = account
+				&& account != acting_as {
+					return Err("chain account does not match the soroban secret key".into())
+				}
+				(Self::layered(soroban), Some(acting_as))
+			}
+		};
+		log::info!("chain backend: {:?}", backend);
+		Ok(Self {
+			account,
+			..new
+		})
+	}
+}
+
+impl std::fmt::Debug for Handle {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Handle")
+			.field("metrics", &self.metrics)
+			.finish_non_exhaustive()
+	}
+}
+
+#[async_trait::async_trait]
+impl Dns for Handle {
+	async fn receive_attestation(
+		&self,
+		pk: identity::PublicKey,
+		sg: identity::Signature
+	) -> Result {
+		self.dns.receive_attestation(pk, sg).await
+	}
+
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>> {
+		self.dns.transit_message(transit)
+	}
+
+	async fn receive_proof(&self, proof: Proof) -> Result {
+		self.dns.receive_proof(proof).await
+	}
+
+	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
+		self.dns.attestation(pk).await
+	}
+
+	async fn foreign_attestation(&self) -> Result<identity::PublicKey> {
+		self.dns.foreign_attestation().await
+	}
+
+	async fn locked_balance_of(&self, owner: identity::PublicKey) -> Result<Balance> {
+		self.dns.locked_balance_of(owner).await
+	}
+
+	async fn locked_balance_timeout_of(&self, owner: identity::PublicKey) -> Result<std::time::Instant> {
+		self.dns.locked_balance_timeout_of(owner).await
+	}
+
+	async fn open_commitment(&self, account: identity::PublicKey) -> Result {
+		self.dns.open_commitment(account).await
+	}
+
+	async fn accept_commitment(&self) -> Result {
+		self.dns.accept_commitment().await
+	}
+
+	async fn account_has_sufficient_balance(&self, account: identity::PublicKey) -> Result<bool> {
+		self.dns.account_has_sufficient_balance(account).await
+	}
+
+	async fn lock(&self, amount: Balance, duration: Duration) -> Result {
+		self.dns.lock(amount, duration).await
+	}
+
+	async fn renew(&self, domain: Domain) -> Result {
+		self.dns.renew(domain).await
+	}
+
+	async fn mint(&self, domain: Domain) -> Result {
+		self.dns.mint(domain).await
+	}
+
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result {
+		self.dns.publish_agreement_key(domain, key).await
+	}
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty> {
+		self.dns.agreement_key(domain).await
+	}
+
+	async fn congestion_charge(&self) -> Result<Fee> {
+		self.dns.congestion_charge().await
+	}
+
+	async fn fee(&self) -> Result<Fee> {
+		self.dns.fee().await
+	}
+
+	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
+		self.dns.traffic(domain).await
+	}
+
+	async fn total_spend(&self) -> Result<Balance> {
+		self.dns.total_spend().await
+	}
+
+	async fn total_claim(&self) -> Result<Balance> {
+		self.dns.total_claim().await
+	}
+
+	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
+		self.dns.age(pk).await
+	}
+}
@@ -0,0 +1,342 @@
+use super::*;
+
+type Client = lib_mchain_proto::mock_chain_client::MockChainClient<tonic::transport::Channel>;
+
+/// [`Dns`] over a remote `mchain` service, acting as `account`. The channel
+/// connects on first use and reconnects on its own, so a node can boot
+/// before the chain does. Calls on behalf of `account` are signed by
+/// `secret_key`, the node identity attested to it.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Mchain {
+	client: Client,
+	account: Vec<u8>,
+	secret_key: identity::SecretKey,
+	// one signed call at a time, each signs over the nonce the last used up
+	calls: std::sync::Arc<tokio::sync::Mutex<()>>
+}
+
+impl Mchain {
+	pub fn new(endpoint: String, account: Address, secret_key: identity::SecretKey) -> Result<Self> {
+		let Address(account) = account;
+		let channel: tonic::transport::Channel = tonic::transport::Endpoint::from_shared(endpoint)?.connect_lazy();
+		Ok(Self {
+			client: Client::new(channel),
+			account,
+			secret_key,
+			calls: std::sync::Arc::new(tokio::sync::Mutex::new(()))
+		})
+	}
+
+	/// Signs `method` with `request`, its authorization still empty, at the
+	/// current nonce of the account; hold `calls` until the call lands.
+	async fn authorization<R>(&self, method: &str, request: &R) -> Result<Vec<u8>>
+	where
+		R: prost::Message {
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(self.account.to_owned()).await?;
+		let message: bytes::Bytes = lib_mchain_proto::authorization_message(method, summary.nonce, request).into();
+		let message: lib_bytes::NonEmpty = message.try_into()?;
+		let signature: identity::Signature = <identity::Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&self.secret_key, &message.into())?;
+		Ok(signature_of(&signature))
+	}
+
+	fn account(&self) -> lib_mchain_proto::Account {
+		lib_mchain_proto::Account {
+			account: self.account.to_owned()
+		}
+	}
+
+	async fn account_of(&self, pk: &identity::PublicKey) -> Result<Vec<u8>> {
+		let response: lib_mchain_proto::AttestationResponse = self.client
+			.to_owned()
+			.attestation(lib_mchain_proto::Key {
+				key: key_of(pk)
+			})
+			.await?
+			.into_inner();
+		Ok(response.account.ok_or("key is not attested to any account")?)
+	}
+
+	async fn summary_of(&self, account: Vec<u8>) -> Result<lib_mchain_proto::AccountResponse> {
+		let response: lib_mchain_proto::AccountResponse = self.client
+			.to_owned()
+			.account_of(lib_mchain_proto::Account {
+				account
+			})
+			.await?
+			.into_inner();
+		Ok(response)
+	}
+
+	async fn fees(&self) -> Result<lib_mchain_proto::FeeResponse> {
+		let response: lib_mchain_proto::FeeResponse = self.client
+			.to_owned()
+			.fee(lib_mchain_proto::Empty {})
+			.await?
+			.into_inner();
+		Ok(response)
+	}
+}
+
+#[async_trait::async_trait]
+impl Dns for Mchain {
+	async fn receive_attestation(
+		&self,
+		pk: identity::PublicKey,
+		sg: identity::Signature
+	) -> Result {
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::AttestRequest = lib_mchain_proto::AttestRequest {
+			account: self.account.to_owned(),
+			key: key_of(&pk),
+			signature: signature_of(&sg),
+			authorization: Vec::new()
+		};
+		// replacing an attested key takes the current one, which is ours
+		// again when the node restarts
+		if self.foreign_attestation().await.is_ok() {
+			request.authorization = self.authorization("Attest", &request).await?;
+		}
+		self.client
+			.to_owned()
+			.attest(request)
+			.await?;
+		Ok(())
+	}
+
+	/// The service settles on a [`lib_mock_chain::MockChain`], so parties
+	/// sign the same message as on the in process mock.
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>> {
+		Ok(mock::transit_of(transit).message()?)
+	}
+
+	async fn receive_proof(&self, proof: Proof) -> Result {
+		let (src_sig, relay_sigs, dst_sig) = proof.signatures()?;
+		let transit: &Transit = &proof.transit;
+		self.client
+			.to_owned()
+			.submit_proof(lib_mchain_proto::ProofRequest {
+				src: key_of(&transit.src),
+				dst: key_of(&transit.dst),
+				relays: transit.relays.iter().map(key_of).collect(),
+				domain: transit.domain.0.to_owned(),
+				nonce: transit.nonce,
+				src_sig: signature_of(src_sig),
+				dst_sig: signature_of(dst_sig),
+				relay_sigs: relay_sigs.iter().map(signature_of).collect(),
+				header: proof.header().to_vec()
+			})
+			.await?;
+		Ok(())
+	}
+
+	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
+		let account: Vec<u8> = self.account_of(&pk).await?;
+		Ok(account.into())
+	}
+
+	async fn foreign_attestation(&self) -> Result<identity::PublicKey> {
+		let response: lib_mchain_proto::ForeignAttestationResponse = self.client
+			.to_owned()
+			.foreign_attestation(self.account())
+			.await?
+			.into_inner();
+		let key: bytes::Bytes = response.key.ok_or("account is not attested by any key")?.into();
+		let key: lib_bytes::NonEmpty = key.try_into()?;
+		Ok(key.into())
+	}
+
+	async fn locked_balance_of(&self, owner: identity::PublicKey) -> Result<Balance> {
+		let owner: Vec<u8> = self.account_of(&owner).await?;
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(owner).await?;
+		Ok(Balance(summary.locked))
+	}
+
+	async fn locked_balance_timeout_of(&self, owner: identity::PublicKey) -> Result<std::time::Instant> {
+		let owner: Vec<u8> = self.account_of(&owner).await?;
+		let lock: lib_mchain_proto::LockResponse = self.client
+			.to_owned()
+			.lock_of(lib_mchain_proto::Account {
+				account: owner
+			})
+			.await?
+			.into_inner();
+		let until: u64 = lock.until.ok_or("no locked balance")?;
+		let status: lib_mchain_proto::StatusResponse = self.client
+			.to_owned()
+			.status(lib_mchain_proto::Empty {})
+			.await?
+			.into_inner();
+		let remaining: std::time::Duration = std::time::Duration::from_secs(until.saturating_sub(status.now));
+		Ok(std::time::Instant::now() + remaining)
+	}
+
+	async fn open_commitment(&self, account: identity::PublicKey) -> Result {
+		let account: Vec<u8> = self.account_of(&account).await?;
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::CommitmentRequest = lib_mchain_proto::CommitmentRequest {
+			relay: self.account.to_owned(),
+			account,
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("OpenCommitment", &request).await?;
+		self.client
+			.to_owned()
+			.open_commitment(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn accept_commitment(&self) -> Result {
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::AuthorizedAccount = lib_mchain_proto::AuthorizedAccount {
+			account: self.account.to_owned(),
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("AcceptCommitment", &request).await?;
+		self.client
+			.to_owned()
+			.accept_commitment(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn account_has_sufficient_balance(&self, account: identity::PublicKey) -> Result<bool> {
+		let account: Vec<u8> = self.account_of(&account).await?;
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(account).await?;
+		let fees: lib_mchain_proto::FeeResponse = self.fees().await?;
+		Ok(summary.locked >= fees.fee)
+	}
+
+	async fn lock(&self, amount: Balance, duration: Duration) -> Result {
+		let Balance(amount) = amount;
+		let Duration(duration) = duration;
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::LockRequest = lib_mchain_proto::LockRequest {
+			account: self.account.to_owned(),
+			amount,
+			duration,
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("Lock", &request).await?;
+		self.client
+			.to_owned()
+			.lock(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn renew(&self, domain: Domain) -> Result {
+		let Domain(domain) = domain;
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::Domain = lib_mchain_proto::Domain {
+			owner: self.account.to_owned(),
+			domain,
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("Renew", &request).await?;
+		self.client
+			.to_owned()
+			.renew(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn mint(&self, domain: Domain) -> Result {
+		let Domain(domain) = domain;
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::Domain = lib_mchain_proto::Domain {
+			owner: self.account.to_owned(),
+			domain,
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("Mint", &request).await?;
+		self.client
+			.to_owned()
+			.mint(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result {
+		let Domain(domain) = domain;
+		let key: bytes::Bytes = key.into();
+		let _call: tokio::sync::MutexGuard<()> = self.calls.lock().await;
+		let mut request: lib_mchain_proto::AgreementKeyRequest = lib_mchain_proto::AgreementKeyRequest {
+			owner: self.account.to_owned(),
+			domain,
+			key: key.to_vec(),
+			authorization: Vec::new()
+		};
+		request.authorization = self.authorization("PublishAgreementKey", &request).await?;
+		self.client
+			.to_owned()
+			.publish_agreement_key(request)
+			.await?;
+		Ok(())
+	}
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty> {
+		let Domain(domain) = domain;
+		let owner: lib_mchain_proto::OwnerResponse = self.client
+			.to_owned()
+			.owner(lib_mchain_proto::OwnerRequest {
+				domain
+			})
+			.await?
+			.into_inner();
+		let key: bytes::Bytes = owner.agreement_key.ok_or("domain has no agreement key")?.into();
+		let key: lib_bytes::NonEmpty = key.try_into()?;
+		Ok(key)
+	}
+
+	async fn congestion_charge(&self) -> Result<Fee> {
+		let fees: lib_mchain_proto::FeeResponse = self.fees().await?;
+		Ok(Fee(fees.congestion_charge))
+	}
+
+	async fn fee(&self) -> Result<Fee> {
+		let fees: lib_mchain_proto::FeeResponse = self.fees().await?;
+		Ok(Fee(fees.fee))
+	}
+
+	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
+		let Domain(domain) = domain;
+		let owner: lib_mchain_proto::OwnerResponse = self.client
+			.to_owned()
+			.owner(lib_mchain_proto::OwnerRequest {
+				domain
+			})
+			.await?
+			.into_inner();
+		Ok(Traffic(owner.traffic))
+	}
+
+	async fn total_spend(&self) -> Result<Balance> {
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(self.account.to_owned()).await?;
+		Ok(Balance(summary.total_spend))
+	}
+
+	async fn total_claim(&self) -> Result<Balance> {
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(self.account.to_owned()).await?;
+		Ok(Balance(summary.total_claim))
+	}
+
+	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
+		let account: Vec<u8> = self.account_of(&pk).await?;
+		let summary: lib_mchain_proto::AccountResponse = self.summary_of(account).await?;
+		Ok(summary.age.map(|age| Age(std::time::Duration::from_secs(age))))
+	}
+}
+
+fn key_of(pk: &identity::PublicKey) -> Vec<u8> {
+	let pk: lib_bytes::NonEmpty = (**pk).to_owned();
+	let pk: bytes::Bytes = pk.into();
+	pk.to_vec()
+}
+
+fn signature_of(sg: &identity::Signature) -> Vec<u8> {
+	let sg: lib_bytes::NonEmpty = sg.to_owned().into();
+	let sg: bytes::Bytes = sg.into();
+	sg.to_vec()
+}
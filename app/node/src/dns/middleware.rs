@@ -0,0 +1,400 @@
+use super::*;
+
+pub mod breaker;
+pub mod cache;
+pub mod coalesce;
+pub mod retry;
+
+/// Answer of a [`Dns`] call with its type erased, so one [`Layer`] serves
+/// every method. Shared so cached and coalesced answers clone cheaply.
+pub type Value = std::sync::Arc<dyn std::any::Any + Send + Sync>;
+
+/// Rest of the stack below a [`Layer`], called once per attempt.
+pub type Next<'a> = &'a (dyn Fn() -> futures::future::BoxFuture<'a, Result<Value>> + Send + Sync);
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("dns circuit open after {failures} consecutive failures, retrying in {retry_in:?}")]
+	CircuitOpen {
+		failures: u32,
+		retry_in: std::time::Duration
+	},
+
+	/// Failure of the call a coalesced caller waited on.
+	#[error("{0}")]
+	Coalesced(String)
+}
+
+/// Whether `error` is a failure to reach the chain rather than an answer
+/// from it. Only these are worth a retry or count against the chain; a
+/// rejected transaction or a missing account fails the same way every time.
+pub fn is_transport(error: &(dyn std::error::Error + 'static)) -> bool {
+	std::iter::successors(Some(error), |error| error.source()).any(|error| {
+		if let Some(status) = error.downcast_ref::<tonic::Status>() {
+			return matches!(
+				status.code(),
+				tonic::Code::Unavailable | tonic::Code::DeadlineExceeded | tonic::Code::Aborted | tonic::Code::ResourceExhausted
+			)
+		}
+		if let Some(error) = error.downcast_ref::<lib_soroban_rpc::Error>() {
+			return matches!(error, lib_soroban_rpc::Error::Transport(_) | lib_soroban_rpc::Error::Timeout(_))
+		}
+		error.is::<tonic::transport::Error>() || error.is::<std::io::Error>()
+	})
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub enum Kind {
+	Read,
+	Write
+}
+
+/// Which [`Dns`] method, on which account, public key or domain. Calls with
+/// no argument act on the backend's own account and leave `key` empty.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub struct Call {
+	pub method: &'static str,
+	pub key: bytes::Bytes,
+	pub kind: Kind
+}
+
+impl Call {
+	pub fn read(method: &'static str, key: bytes::Bytes) -> Self {
+		Self {
+			method,
+			key,
+			kind: Kind::Read
+		}
+	}
+
+	pub fn write(method: &'static str, key: bytes::Bytes) -> Self {
+		Self {
+			method,
+			key,
+			kind: Kind::Write
+		}
+	}
+}
+
+/// Counters of one [`Layer`]. What counts as a hit is up to the layer: an
+/// answer it gave without reaching the next layer, or for [`retry::Retry`]
+/// a call it saved.
+#[derive(Debug)]
+#[derive(Default)]
+pub struct Metrics {
+	calls: std::sync::atomic::AtomicU64,
+	hits: std::sync::atomic::AtomicU64,
+	errors: std::sync::atomic::AtomicU64,
+	latency_micros: std::sync::atomic::AtomicU64
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+pub struct Snapshot {
+	pub calls: u64,
+	pub hits: u64,
+	pub errors: u64,
+	pub hit_rate: f64,
+	pub mean_latency: std::time::Duration
+}
+
+impl Metrics {
+	pub fn record(&self, hit: bool, ok: bool, elapsed: std::time::Duration) {
+		let elapsed: u64 = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+		self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		self.latency_micros.fetch_add(elapsed, std::sync::atomic::Ordering::Relaxed);
+		if hit {
+			self.hits.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		}
+		if !ok {
+			self.errors.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+		}
+	}
+
+	pub fn snapshot(&self) -> Snapshot {
+		let calls: u64 = self.calls.load(std::sync::atomic::Ordering::Relaxed);
+		let hits: u64 = self.hits.load(std::sync::atomic::Ordering::Relaxed);
+		let errors: u64 = self.errors.load(std::sync::atomic::Ordering::Relaxed);
+		let latency_micros: u64 = self.latency_micros.load(std::sync::atomic::Ordering::Relaxed);
+		let (hit_rate, mean_latency) = match calls {
+			0 => (0.0, std::time::Duration::ZERO),
+			calls => (hits as f64 / calls as f64, std::time::Duration::from_micros(latency_micros / calls))
+		};
+		Snapshot {
+			calls,
+			hits,
+			errors,
+			hit_rate,
+			mean_latency
+		}
+	}
+}
+
+/// One concern wrapped around every call of a [`Dns`] backend.
+#[async_trait::async_trait]
+pub trait Layer {
+	/// Answers `call`, through `next` as many times as the layer needs.
+	async fn call(&self, call: &Call, next: Next<'_>) -> Result<Value>;
+
+	fn metrics(&self) -> std::sync::Arc<Metrics>;
+}
+
+/// [`Dns`] backend `inner` behind `layer`. Layers compose by nesting,
+/// outermost first:
+///
+/// `Layered::new(cache, Layered::new(breaker, Layered::new(retry, backend)))`
+pub struct Layered<L, T> {
+	layer: L,
+	inner: T
+}
+
+impl<L, T> Layered<L, T> {
+	pub fn new(layer: L, inner: T) -> Self {
+		Self {
+			layer,
+			inner
+		}
+	}
+
+	pub const fn layer(&self) -> &L {
+		&self.layer
+	}
+
+	pub const fn inner(&self) -> &T {
+		&self.inner
+	}
+}
+
+impl<L, T> Layered<L, T>
+where
+	L: Layer,
+	L: Send,
+	L: Sync,
+	T: Dns,
+	T: Send,
+	T: Sync {
+	async fn through<'a, R>(&'a self, call: Call, f: impl Fn(&'a T) -> futures::future::BoxFuture<'a, Result<R>> + Send + Sync) -> Result<R>
+	where
+		R: 'static,
+		R: Clone,
+		R: Send,
+		R: Sync {
+		let next = || -> futures::future::BoxFuture<'a, Result<Value>> {
+			let answer: futures::future::BoxFuture<'a, Result<R>> = f(&self.inner);
+			Box::pin(async move {
+				let answer: R = answer.await?;
+				let answer: Value = std::sync::Arc::new(answer);
+				Ok(answer)
+			})
+		};
+		let value: Value = self.layer.call(&call, &next).await?;
+		let value: std::sync::Arc<R> = value.downcast().map_err(|_| "dns layer answered with the wrong type")?;
+		Ok(R::clone(&value))
+	}
+}
+
+#[async_trait::async_trait]
+impl<L, T> Dns for Layered<L, T>
+where
+	L: Layer,
+	L: Send,
+	L: Sync,
+	T: Dns,
+	T: Send,
+	T: Sync {
+	async fn receive_attestation(
+		&self,
+		pk: identity::PublicKey,
+		sg: identity::Signature
+	) -> Result {
+		self.through(Call::write("receive_attestation", key_of(&pk)), |dns| dns.receive_attestation(pk.to_owned(), sg.to_owned())).await
+	}
+
+	// encoding only, nothing to cache or retry
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>> {
+		self.inner.transit_message(transit)
+	}
+
+	async fn receive_proof(&self, proof: Proof) -> Result {
+		self.through(Call::write("receive_proof", key_of(&proof.transit.src)), |dns| dns.receive_proof(proof.to_owned())).await
+	}
+
+	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
+		self.through(Call::read("attestation", key_of(&pk)), |dns| dns.attestation(pk.to_owned())).await
+	}
+
+	async fn foreign_attestation(&self) -> Result<identity::PublicKey> {
+		self.through(Call::read("foreign_attestation", bytes::Bytes::new()), |dns| dns.foreign_attestation()).await
+	}
+
+	async fn locked_balance_of(&self, owner: identity::PublicKey) -> Result<Balance> {
+		self.through(Call::read("locked_balance_of", key_of(&owner)), |dns| dns.locked_balance_of(owner.to_owned())).await
+	}
+
+	async fn locked_balance_timeout_of(&self, owner: identity::PublicKey) -> Result<std::time::Instant> {
+		self.through(Call::read("locked_balance_timeout_of", key_of(&owner)), |dns| dns.locked_balance_timeout_of(owner.to_owned())).await
+	}
+
+	async fn open_commitment(&self, account: identity::PublicKey) -> Result {
+		self.through(Call::write("open_commitment", key_of(&account)), |dns| dns.open_commitment(account.to_owned())).await
+	}
+
+	async fn accept_commitment(&self) -> Result {
+		self.through(Call::write("accept_commitment", bytes::Bytes::new()), |dns| dns.accept_commitment()).await
+	}
+
+	async fn account_has_sufficient_balance(&self, account: identity::PublicKey) -> Result<bool> {
+		self.through(Call::read("account_has_sufficient_balance", key_of(&account)), |dns| dns.account_has_sufficient_balance(account.to_owned())).await
+	}
+
+	async fn lock(&self, amount: Balance, duration: Duration) -> Result {
+		self.through(Call::write("lock", bytes::Bytes::new()), |dns| dns.lock(amount.to_owned(), duration.to_owned())).await
+	}
+
+	async fn renew(&self, domain: Domain) -> Result {
+		self.through(Call::write("renew", domain.0.to_owned().into()), |dns| dns.renew(domain.to_owned())).await
+	}
+
+	async fn mint(&self, domain: Domain) -> Result {
+		self.through(Call::write("mint", domain.0.to_owned().into()), |dns| dns.mint(domain.to_owned())).await
+	}
+
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result {
+		self.through(Call::write("publish_agreement_key", domain.0.to_owned().into()), |dns| dns.publish_agreement_key(domain.to_owned(), key.to_owned())).await
+	}
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty> {
+		self.through(Call::read("agreement_key", domain.0.to_owned().into()), |dns| dns.agreement_key(domain.to_owned())).await
+	}
+
+	async fn congestion_charge(&self) -> Result<Fee> {
+		self.through(Call::read("congestion_charge", bytes::Bytes::new()), |dns| dns.congestion_charge()).await
+	}
+
+	async fn fee(&self) -> Result<Fee> {
+		self.through(Call::read("fee", bytes::Bytes::new()), |dns| dns.fee()).await
+	}
+
+	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
+		self.through(Call::read("traffic", domain.0.to_owned().into()), |dns| dns.traffic(domain.to_owned())).await
+	}
+
+	async fn total_spend(&self) -> Result<Balance> {
+		self.through(Call::read("total_spend", bytes::Bytes::new()), |dns| dns.total_spend()).await
+	}
+
+	async fn total_claim(&self) -> Result<Balance> {
+		self.through(Call::read("total_claim", bytes::Bytes::new()), |dns| dns.total_claim()).await
+	}
+
+	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
+		self.through(Call::read("age", key_of(&pk)), |dns| dns.age(pk.to_owned())).await
+	}
+}
+
+fn key_of(pk: &identity::PublicKey) -> bytes::Bytes {
+	let pk: lib_bytes::NonEmpty = (**pk).to_owned();
+	pk.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// How a [`Script`] answers one call.
+	#[derive(Debug)]
+	#[derive(Clone)]
+	#[derive(Copy)]
+	pub(super) enum Outcome {
+		Answer(u32),
+		Unreachable,
+		Refused
+	}
+
+	/// Backend stand in answering calls with its outcomes in order, then
+	/// with `Answer(0)`, counting the calls that reached it.
+	#[derive(Debug)]
+	pub(super) struct Script {
+		outcomes: std::sync::Mutex<std::collections::VecDeque<Outcome>>,
+		calls: std::sync::atomic::AtomicU32
+	}
+
+	impl Script {
+		pub(super) fn new(outcomes: impl IntoIterator<Item = Outcome>) -> Self {
+			Self {
+				outcomes: std::sync::Mutex::new(outcomes.into_iter().collect()),
+				calls: std::sync::atomic::AtomicU32::default()
+			}
+		}
+
+		pub(super) fn next(&self) -> futures::future::BoxFuture<'_, Result<Value>> {
+			self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+			let outcome: Outcome = self.outcomes
+				.lock()
+				.ok()
+				.and_then(|mut outcomes| outcomes.pop_front())
+				.unwrap_or(Outcome::Answer(0));
+			Box::pin(async move {
+				match outcome {
+					Outcome::Answer(answer) => {
+						let answer: Value = std::sync::Arc::new(answer);
+						Ok(answer)
+					},
+					Outcome::Unreachable => Err(tonic::Status::unavailable("chain down").into()),
+					Outcome::Refused => Err(tonic::Status::not_found("no such account").into())
+				}
+			})
+		}
+
+		pub(super) fn calls(&self) -> u32 {
+			self.calls.load(std::sync::atomic::Ordering::Relaxed)
+		}
+	}
+
+	/// [`Script::next`] answering after `delay`, so concurrent calls overlap.
+	pub(super) fn slow(script: &Script, delay: std::time::Duration) -> futures::future::BoxFuture<'_, Result<Value>> {
+		let answer: futures::future::BoxFuture<'_, Result<Value>> = script.next();
+		Box::pin(async move {
+			tokio::time::sleep(delay).await;
+			answer.await
+		})
+	}
+
+	pub(super) fn answer_of(value: &Value) -> Option<u32> {
+		value.downcast_ref().copied()
+	}
+
+	#[test]
+	fn only_transport_failures_are_transport() {
+		let unreachable: Box<dyn std::error::Error> = tonic::Status::unavailable("chain down").into();
+		let refused: Box<dyn std::error::Error> = tonic::Status::not_found("no such account").into();
+		let other: Box<dyn std::error::Error> = "malformed answer".into();
+		assert!(is_transport(&*unreachable));
+		assert!(!is_transport(&*refused));
+		assert!(!is_transport(&*other));
+	}
+
+	#[test]
+	fn metrics_count_calls_hits_and_errors() {
+		let metrics: Metrics = Metrics::default();
+		assert_eq!(metrics.snapshot().calls, 0);
+		metrics.record(true, true, std::time::Duration::from_micros(10));
+		metrics.record(false, false, std::time::Duration::from_micros(30));
+		let snapshot: Snapshot = metrics.snapshot();
+		assert_eq!((snapshot.calls, snapshot.hits, snapshot.errors), (2, 1, 1));
+		assert!((snapshot.hit_rate - 0.5).abs() < f64::EPSILON);
+		assert_eq!(snapshot.mean_latency, std::time::Duration::from_micros(20));
+	}
+}
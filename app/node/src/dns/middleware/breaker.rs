@@ -0,0 +1,189 @@
+use super::*;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+enum State {
+	Closed {
+		failures: u32
+	},
+	Open {
+		until: std::time::Instant
+	},
+	// one trial call is out, everything else fails fast until it answers or
+	// a cooldown passes without an answer
+	HalfOpen {
+		since: std::time::Instant
+	}
+}
+
+/// Fails fast with [`Error::CircuitOpen`] once `threshold` calls in a row
+/// could not reach the chain, so a down chain costs callers nothing for
+/// `cooldown`. An answer from the chain, a refusal included, counts as the
+/// chain being up. After the cooldown a single trial call goes through;
+/// reaching the chain closes the circuit, failing to opens it again. Hits
+/// are calls failed fast.
+#[derive(Debug)]
+pub struct Breaker {
+	threshold: u32,
+	cooldown: std::time::Duration,
+	state: std::sync::Mutex<State>,
+	metrics: std::sync::Arc<Metrics>
+}
+
+#[bon::bon]
+impl Breaker {
+	#[builder]
+	pub fn new(threshold: u32, cooldown: std::time::Duration) -> Self {
+		Self {
+			threshold: threshold.max(1),
+			cooldown,
+			state: std::sync::Mutex::new(State::Closed {
+				failures: 0
+			}),
+			metrics: std::sync::Arc::default()
+		}
+	}
+
+	/// `Err` with the time left when the call has to fail fast.
+	fn admit(&self) -> std::result::Result<(), std::time::Duration> {
+		let Ok(mut state) = self.state.lock() else {
+			return Ok(())
+		};
+		match *state {
+			State::Closed { .. } => Ok(()),
+			State::Open {
+				until
+			} => {
+				let now: std::time::Instant = std::time::Instant::now();
+				if now < until {
+					return Err(until - now)
+				}
+				*state = State::HalfOpen {
+					since: now
+				};
+				Ok(())
+			},
+			State::HalfOpen {
+				since
+			} => {
+				let now: std::time::Instant = std::time::Instant::now();
+				let until: std::time::Instant = since + self.cooldown;
+				if now < until {
+					return Err(until - now)
+				}
+				*state = State::HalfOpen {
+					since: now
+				};
+				Ok(())
+			}
+		}
+	}
+
+	/// `reached` is whether the call got an answer from the chain, see
+	/// [`is_transport`].
+	fn settle(&self, reached: bool) {
+		let Ok(mut state) = self.state.lock() else {
+			return
+		};
+		*state = match (*state, reached) {
+			(_, true) => State::Closed {
+				failures: 0
+			},
+			(State::Closed {
+				failures
+			}, false) if failures + 1 < self.threshold => State::Closed {
+				failures: failures + 1
+			},
+			(_, false) => {
+				log::warn!("dns circuit open for {:?}", self.cooldown);
+				State::Open {
+					until: std::time::Instant::now() + self.cooldown
+				}
+			}
+		};
+	}
+}
+
+#[async_trait::async_trait]
+impl Layer for Breaker {
+	async fn call(&self, _: &Call, next: Next<'_>) -> Result<Value> {
+		let started_at: std::time::Instant = std::time::Instant::now();
+		if let Err(retry_in) = self.admit() {
+			self.metrics.record(true, false, started_at.elapsed());
+			return Err(Error::CircuitOpen {
+				failures: self.threshold,
+				retry_in
+			}.into())
+		}
+		let answer: Result<Value> = next().await;
+		self.settle(answer.as_ref().map_or_else(|error| !is_transport(&**error), |_| true));
+		self.metrics.record(false, answer.is_ok(), started_at.elapsed());
+		answer
+	}
+
+	fn metrics(&self) -> std::sync::Arc<Metrics> {
+		self.metrics.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn breaker() -> Breaker {
+		Breaker::builder()
+			.threshold(2)
+			.cooldown(std::time::Duration::from_millis(30))
+			.build()
+	}
+
+	#[tokio::test]
+	async fn opens_then_half_opens_then_closes() -> Result {
+		let breaker: Breaker = breaker();
+		let script: Script = Script::new([Outcome::Unreachable, Outcome::Unreachable, Outcome::Answer(1)]);
+		let next = || script.next();
+		let call: Call = Call::read("fee", bytes::Bytes::new());
+		assert!(breaker.call(&call, &next).await.is_err());
+		assert!(breaker.call(&call, &next).await.is_err());
+		// open, fails fast without reaching the chain
+		let error: Box<dyn std::error::Error> = breaker.call(&call, &next).await.err().ok_or("circuit closed")?;
+		assert!(matches!(error.downcast_ref::<Error>(), Some(Error::CircuitOpen { .. })));
+		assert_eq!(script.calls(), 2);
+		assert_eq!(breaker.metrics().snapshot().hits, 1);
+		// half open, the trial call closes it
+		tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+		assert_eq!(answer_of(&breaker.call(&call, &next).await?), Some(1));
+		breaker.call(&call, &next).await?;
+		assert_eq!(script.calls(), 4);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn failed_trial_opens_again() {
+		let breaker: Breaker = breaker();
+		let script: Script = Script::new([Outcome::Unreachable; 3]);
+		let next = || script.next();
+		let call: Call = Call::read("fee", bytes::Bytes::new());
+		breaker.call(&call, &next).await.ok();
+		breaker.call(&call, &next).await.ok();
+		tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+		assert!(breaker.call(&call, &next).await.is_err());
+		assert!(breaker.call(&call, &next).await.is_err());
+		assert_eq!(script.calls(), 3);
+	}
+
+	#[tokio::test]
+	async fn refusals_keep_it_closed() {
+		let breaker: Breaker = breaker();
+		let script: Script = Script::new([Outcome::Refused; 3]);
+		let next = || script.next();
+		let call: Call = Call::read("attestation", bytes::Bytes::new());
+		for _ in 0..3 {
+			assert!(breaker.call(&call, &next).await.is_err());
+		}
+		assert_eq!(script.calls(), 3);
+		assert_eq!(breaker.metrics().snapshot().hits, 0);
+	}
+}
@@ -0,0 +1,151 @@
+use super::*;
+
+/// Answers reads from memory for `ttl`, keyed per method and account,
+/// public key or domain. A write that goes through clears every entry, as
+/// balances, locks and attestations may all have moved with it, and a read
+/// in flight meanwhile is not stored, as it may predate the write. Hits are
+/// reads served from memory.
+#[derive(Debug)]
+pub struct Cache {
+	ttl: std::time::Duration,
+	entries: std::sync::Mutex<Entries>,
+	metrics: std::sync::Arc<Metrics>
+}
+
+#[derive(Debug)]
+#[derive(Default)]
+struct Entries {
+	// writes gone through so far, bumped on every clear
+	generation: u64,
+	values: std::collections::HashMap<Call, (Value, std::time::Instant)>
+}
+
+impl Cache {
+	pub fn new(ttl: std::time::Duration) -> Self {
+		Self {
+			ttl,
+			entries: std::sync::Mutex::default(),
+			metrics: std::sync::Arc::default()
+		}
+	}
+
+	fn get(&self, call: &Call) -> Option<Value> {
+		let entries = self.entries.lock().ok()?;
+		let (value, stored_at) = entries.values.get(call)?;
+		if stored_at.elapsed() > self.ttl {
+			return None
+		}
+		Some(value.to_owned())
+	}
+
+	fn generation(&self) -> Option<u64> {
+		let entries = self.entries.lock().ok()?;
+		Some(entries.generation)
+	}
+
+	/// Stores `value` unless a write cleared the cache since `generation`.
+	fn insert(&self, call: &Call, value: &Value, generation: u64) {
+		if let Ok(mut entries) = self.entries.lock()
+		&& entries.generation == generation {
+			let now: std::time::Instant = std::time::Instant::now();
+			entries.values.retain(|_, (_, stored_at)| now.duration_since(*stored_at) <= self.ttl);
+			entries.values.insert(call.to_owned(), (value.to_owned(), now));
+		}
+	}
+
+	fn clear(&self) {
+		if let Ok(mut entries) = self.entries.lock() {
+			entries.generation = entries.generation.wrapping_add(1);
+			entries.values.clear();
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Layer for Cache {
+	async fn call(&self, call: &Call, next: Next<'_>) -> Result<Value> {
+		let started_at: std::time::Instant = std::time::Instant::now();
+		if call.kind == Kind::Read
+		&& let Some(value) = self.get(call) {
+			self.metrics.record(true, true, started_at.elapsed());
+			return Ok(value)
+		}
+		let generation: Option<u64> = self.generation();
+		let answer: Result<Value> = next().await;
+		if let Ok(value) = &answer {
+			match (call.kind, generation) {
+				(Kind::Read, Some(generation)) => self.insert(call, value, generation),
+				(Kind::Read, None) => (),
+				(Kind::Write, _) => self.clear()
+			}
+		}
+		self.metrics.record(false, answer.is_ok(), started_at.elapsed());
+		answer
+	}
+
+	fn metrics(&self) -> std::sync::Arc<Metrics> {
+		self.metrics.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[tokio::test]
+	async fn reads_are_served_from_memory_until_expiry() -> Result {
+		let cache: Cache = Cache::new(std::time::Duration::from_millis(50));
+		let script: Script = Script::new([Outcome::Answer(1), Outcome::Answer(2)]);
+		let next = || script.next();
+		let call: Call = Call::read("fee", bytes::Bytes::new());
+		assert_eq!(answer_of(&cache.call(&call, &next).await?), Some(1));
+		assert_eq!(answer_of(&cache.call(&call, &next).await?), Some(1));
+		assert_eq!(script.calls(), 1);
+		tokio::time::sleep(std::time::Duration::from_millis(60)).await;
+		assert_eq!(answer_of(&cache.call(&call, &next).await?), Some(2));
+		assert_eq!(script.calls(), 2);
+		let snapshot: Snapshot = cache.metrics().snapshot();
+		assert_eq!((snapshot.calls, snapshot.hits), (3, 1));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn writes_clear_every_entry() -> Result {
+		let cache: Cache = Cache::new(std::time::Duration::from_secs(60));
+		let script: Script = Script::new([Outcome::Answer(1), Outcome::Answer(0), Outcome::Answer(2)]);
+		let next = || script.next();
+		let read: Call = Call::read("fee", bytes::Bytes::new());
+		cache.call(&read, &next).await?;
+		cache.call(&Call::write("lock", bytes::Bytes::new()), &next).await?;
+		assert_eq!(answer_of(&cache.call(&read, &next).await?), Some(2));
+		assert_eq!(script.calls(), 3);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn reads_in_flight_during_a_write_are_not_stored() -> Result {
+		let cache: Cache = Cache::new(std::time::Duration::from_secs(60));
+		let script: Script = Script::new([Outcome::Answer(1), Outcome::Answer(0), Outcome::Answer(2)]);
+		let next = || slow(&script, std::time::Duration::from_millis(20));
+		let now = || script.next();
+		let read: Call = Call::read("fee", bytes::Bytes::new());
+		let (stale, written) = tokio::join!(cache.call(&read, &next), cache.call(&Call::write("lock", bytes::Bytes::new()), &now));
+		assert_eq!(answer_of(&stale?), Some(1));
+		written?;
+		assert_eq!(answer_of(&cache.call(&read, &next).await?), Some(2));
+		assert_eq!(script.calls(), 3);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn failures_are_not_cached() -> Result {
+		let cache: Cache = Cache::new(std::time::Duration::from_secs(60));
+		let script: Script = Script::new([Outcome::Refused, Outcome::Answer(1)]);
+		let next = || script.next();
+		let call: Call = Call::read("fee", bytes::Bytes::new());
+		assert!(cache.call(&call, &next).await.is_err());
+		assert_eq!(answer_of(&cache.call(&call, &next).await?), Some(1));
+		Ok(())
+	}
+}
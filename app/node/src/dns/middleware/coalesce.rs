@@ -0,0 +1,177 @@
+use super::*;
+
+type Waiters = Vec<tokio::sync::oneshot::Sender<std::result::Result<Value, String>>>;
+
+/// Lets concurrent identical reads share one trip to the chain: the first
+/// caller goes through, later ones wait for its answer. Followers get a
+/// failure as [`Error::Coalesced`], carrying its message. Hits are reads
+/// that waited on another caller's trip. A read never joins one that was
+/// in flight when a write finished, which may predate the write.
+#[derive(Debug)]
+#[derive(Default)]
+pub struct Coalesce {
+	// writes finished so far, telling reads before and after them apart
+	generation: std::sync::atomic::AtomicU64,
+	in_flight: std::sync::Mutex<std::collections::HashMap<(Call, u64), Waiters>>,
+	metrics: std::sync::Arc<Metrics>
+}
+
+impl Coalesce {
+	/// Receiver of the answer when `call` is already in flight in
+	/// `generation`, otherwise marks it in flight for the caller to lead.
+	fn join(&self, call: &Call, generation: u64) -> Option<tokio::sync::oneshot::Receiver<std::result::Result<Value, String>>> {
+		let mut in_flight = self.in_flight.lock().ok()?;
+		match in_flight.get_mut(&(call.to_owned(), generation)) {
+			Some(waiters) => {
+				let (sx, rx) = tokio::sync::oneshot::channel();
+				waiters.push(sx);
+				Some(rx)
+			},
+			None => {
+				in_flight.insert((call.to_owned(), generation), Vec::new());
+				None
+			}
+		}
+	}
+
+	fn finish(&self, call: &Call, generation: u64, answer: &Result<Value>) {
+		let waiters: Waiters = match self.in_flight.lock() {
+			Ok(mut in_flight) => in_flight.remove(&(call.to_owned(), generation)).unwrap_or_default(),
+			Err(_) => return
+		};
+		for waiter in waiters {
+			let answer: std::result::Result<Value, String> = match answer {
+				Ok(value) => Ok(value.to_owned()),
+				Err(error) => Err(error.to_string())
+			};
+			// a waiter that gave up is fine
+			waiter.send(answer).ok();
+		}
+	}
+}
+
+/// Lead of an in flight call. Dropped before [`Lead::finish`], as when the
+/// leading caller gives up, it clears the call so followers fail instead of
+/// waiting forever. Once finished the entry is gone, and may already belong
+/// to a new lead.
+struct Lead<'a> {
+	coalesce: &'a Coalesce,
+	call: &'a Call,
+	generation: u64,
+	finished: bool
+}
+
+impl Lead<'_> {
+	fn finish(mut self, answer: &Result<Value>) {
+		self.coalesce.finish(self.call, self.generation, answer);
+		self.finished = true;
+	}
+}
+
+impl Drop for Lead<'_> {
+	fn drop(&mut self) {
+		if self.finished {
+			return
+		}
+		if let Ok(mut in_flight) = self.coalesce.in_flight.lock() {
+			in_flight.remove(&(self.call.to_owned(), self.generation));
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Layer for Coalesce {
+	async fn call(&self, call: &Call, next: Next<'_>) -> Result<Value> {
+		let started_at: std::time::Instant = std::time::Instant::now();
+		if call.kind == Kind::Write {
+			let answer: Result<Value> = next().await;
+			self.generation.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+			self.metrics.record(false, answer.is_ok(), started_at.elapsed());
+			return answer
+		}
+		let generation: u64 = self.generation.load(std::sync::atomic::Ordering::SeqCst);
+		if let Some(rx) = self.join(call, generation) {
+			let answer: std::result::Result<Value, String> = rx.await.unwrap_or_else(|_| Err(String::from("coalesced dns call dropped")));
+			self.metrics.record(true, answer.is_ok(), started_at.elapsed());
+			return answer.map_err(|error| Error::Coalesced(error).into())
+		}
+		let lead: Lead<'_> = Lead {
+			coalesce: self,
+			call,
+			generation,
+			finished: false
+		};
+		let answer: Result<Value> = next().await;
+		lead.finish(&answer);
+		self.metrics.record(false, answer.is_ok(), started_at.elapsed());
+		answer
+	}
+
+	fn metrics(&self) -> std::sync::Arc<Metrics> {
+		self.metrics.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	#[tokio::test]
+	async fn concurrent_reads_share_one_trip() -> Result {
+		let coalesce: Coalesce = Coalesce::default();
+		let script: Script = Script::new([Outcome::Answer(1), Outcome::Answer(2)]);
+		let next = || slow(&script, std::time::Duration::from_millis(20));
+		let call: Call = Call::read("fee", bytes::Bytes::new());
+		let (first, second) = tokio::join!(coalesce.call(&call, &next), coalesce.call(&call, &next));
+		assert_eq!((answer_of(&first?), answer_of(&second?)), (Some(1), Some(1)));
+		assert_eq!(script.calls(), 1);
+		assert_eq!(coalesce.metrics().snapshot().hits, 1);
+		// the finished call no longer holds followers back
+		assert_eq!(answer_of(&coalesce.call(&call, &next).await?), Some(2));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn followers_share_the_failure() {
+		let coalesce: Coalesce = Coalesce::default();
+		let script: Script = Script::new([Outcome::Refused]);
+		let next = || slow(&script, std::time::Duration::from_millis(20));
+		let call: Call = Call::read("attestation", bytes::Bytes::new());
+		let (first, second) = tokio::join!(coalesce.call(&call, &next), coalesce.call(&call, &next));
+		assert!(first.is_err());
+		let error: Option<Box<dyn std::error::Error>> = second.err();
+		assert!(matches!(error.as_deref().and_then(|error| error.downcast_ref::<Error>()), Some(Error::Coalesced(_))));
+		assert_eq!(script.calls(), 1);
+	}
+
+	#[tokio::test]
+	async fn reads_after_a_write_do_not_join_reads_before_it() -> Result {
+		let coalesce: Coalesce = Coalesce::default();
+		let script: Script = Script::new([Outcome::Answer(1), Outcome::Answer(0), Outcome::Answer(2)]);
+		let next = || slow(&script, std::time::Duration::from_millis(20));
+		let now = || script.next();
+		let read: Call = Call::read("fee", bytes::Bytes::new());
+		let write: Call = Call::write("lock", bytes::Bytes::new());
+		let (before, after) = tokio::join!(coalesce.call(&read, &next), async {
+			coalesce.call(&write, &now).await?;
+			coalesce.call(&read, &next).await
+		});
+		assert_eq!((answer_of(&before?), answer_of(&after?)), (Some(1), Some(2)));
+		assert_eq!(script.calls(), 3);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn writes_are_not_coalesced() -> Result {
+		let coalesce: Coalesce = Coalesce::default();
+		let script: Script = Script::new([]);
+		let next = || slow(&script, std::time::Duration::from_millis(20));
+		let call: Call = Call::write("lock", bytes::Bytes::new());
+		let (first, second) = tokio::join!(coalesce.call(&call, &next), coalesce.call(&call, &next));
+		first?;
+		second?;
+		assert_eq!(script.calls(), 2);
+		Ok(())
+	}
+}
@@ -0,0 +1,122 @@
+use super::*;
+
+/// Retries reads that could not reach the chain up to `attempts` times in
+/// total, doubling the delay from `base_delay` up to `max_delay` between
+/// tries; a refusal from the chain is returned as is. Writes go through
+/// once, a write that timed out may still land and must not land twice.
+/// Hits are reads that failed at first and were saved by a retry.
+#[derive(Debug)]
+pub struct Retry {
+	attempts: u32,
+	base_delay: std::time::Duration,
+	max_delay: std::time::Duration,
+	metrics: std::sync::Arc<Metrics>
+}
+
+#[bon::bon]
+impl Retry {
+	#[builder]
+	pub fn new(
+		attempts: u32,
+		base_delay: std::time::Duration,
+		max_delay: std::time::Duration
+	) -> Self {
+		Self {
+			attempts: attempts.max(1),
+			base_delay,
+			max_delay,
+			metrics: std::sync::Arc::default()
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Layer for Retry {
+	async fn call(&self, call: &Call, next: Next<'_>) -> Result<Value> {
+		let started_at: std::time::Instant = std::time::Instant::now();
+		let attempts: u32 = match call.kind {
+			Kind::Read => self.attempts,
+			Kind::Write => 1
+		};
+		let mut delay: std::time::Duration = self.base_delay;
+		let mut attempt: u32 = 1;
+		loop {
+			let failure: String = match next().await {
+				Ok(value) => {
+					self.metrics.record(attempt > 1, true, started_at.elapsed());
+					return Ok(value)
+				},
+				Err(error) if attempt >= attempts || !is_transport(&*error) => {
+					self.metrics.record(false, false, started_at.elapsed());
+					return Err(error)
+				},
+				Err(error) => error.to_string()
+			};
+			log::debug!("dns {} failed on attempt {} of {}, retrying in {:?}: {}", call.method, attempt, attempts, delay, failure);
+			tokio::time::sleep(delay).await;
+			delay = delay.saturating_mul(2).min(self.max_delay);
+			attempt += 1;
+		}
+	}
+
+	fn metrics(&self) -> std::sync::Arc<Metrics> {
+		self.metrics.to_owned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use super::super::tests::*;
+
+	fn retry() -> Retry {
+		Retry::builder()
+			.attempts(3)
+			.base_delay(std::time::Duration::from_millis(10))
+			.max_delay(std::time::Duration::from_millis(15))
+			.build()
+	}
+
+	#[tokio::test]
+	async fn unreachable_reads_are_retried_with_backoff() -> Result {
+		let retry: Retry = retry();
+		let script: Script = Script::new([Outcome::Unreachable, Outcome::Unreachable, Outcome::Answer(1)]);
+		let next = || script.next();
+		let started_at: std::time::Instant = std::time::Instant::now();
+		let value: Value = retry.call(&Call::read("fee", bytes::Bytes::new()), &next).await?;
+		// 10ms, then doubled but capped at 15ms
+		assert!(started_at.elapsed() >= std::time::Duration::from_millis(25));
+		assert_eq!(answer_of(&value), Some(1));
+		assert_eq!(script.calls(), 3);
+		assert_eq!(retry.metrics().snapshot().hits, 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn gives_up_after_every_attempt() {
+		let retry: Retry = retry();
+		let script: Script = Script::new([Outcome::Unreachable; 4]);
+		let next = || script.next();
+		assert!(retry.call(&Call::read("fee", bytes::Bytes::new()), &next).await.is_err());
+		assert_eq!(script.calls(), 3);
+		assert_eq!(retry.metrics().snapshot().errors, 1);
+	}
+
+	#[tokio::test]
+	async fn refusals_are_not_retried() {
+		let retry: Retry = retry();
+		let script: Script = Script::new([Outcome::Refused, Outcome::Answer(1)]);
+		let next = || script.next();
+		assert!(retry.call(&Call::read("attestation", bytes::Bytes::new()), &next).await.is_err());
+		assert_eq!(script.calls(), 1);
+	}
+
+	#[tokio::test]
+	async fn writes_go_through_once() {
+		let retry: Retry = retry();
+		let script: Script = Script::new([Outcome::Unreachable, Outcome::Answer(1)]);
+		let next = || script.next();
+		assert!(retry.call(&Call::write("lock", bytes::Bytes::new()), &next).await.is_err());
+		assert_eq!(script.calls(), 1);
+	}
+}
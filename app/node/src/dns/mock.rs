@@ -0,0 +1,244 @@
+use super::*;
+
+/// [`Dns`] over an in process [`lib_mock_chain::MockChain`], acting as
+/// `account`. Clones share the chain, so every node of a test sees the same
+/// economic state.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Mock {
+	chain: std::sync::Arc<std::sync::Mutex<lib_mock_chain::MockChain>>,
+	account: lib_mock_chain::Account
+}
+
+impl Mock {
+	pub fn new(chain: std::sync::Arc<std::sync::Mutex<lib_mock_chain::MockChain>>, account: Address) -> Self {
+		let Address(account) = account;
+		Self {
+			chain,
+			account: account.into()
+		}
+	}
+
+	/// Same chain, acting as `account`.
+	pub fn acting_as(&self, account: Address) -> Self {
+		Self::new(self.chain.to_owned(), account)
+	}
+
+	fn with<T>(&self, f: impl FnOnce(&mut lib_mock_chain::MockChain) -> lib_mock_chain::Result<T>) -> Result<T> {
+		let mut chain: std::sync::MutexGuard<_> = self.chain.lock().map_err(|_| "mock chain poisoned")?;
+		Ok(f(&mut chain)?)
+	}
+
+	fn account_of(&self, pk: &identity::PublicKey) -> Result<lib_mock_chain::Account> {
+		let key: lib_mock_chain::Key = key_of(pk);
+		self.with(|chain| chain.account_of(&key))
+	}
+}
+
+#[async_trait::async_trait]
+impl Dns for Mock {
+	async fn receive_attestation(
+		&self,
+		pk: identity::PublicKey,
+		sg: identity::Signature
+	) -> Result {
+		let key: lib_mock_chain::Key = key_of(&pk);
+		let sg: lib_bytes::NonEmpty = sg.into();
+		let sg: bytes::Bytes = sg.into();
+		self.with(|chain| chain.attest(&self.account, &key, &sg))
+	}
+
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>> {
+		Ok(transit_of(transit).message()?)
+	}
+
+	async fn receive_proof(&self, proof: Proof) -> Result {
+		let (src_sig, relay_sigs, dst_sig) = proof.signatures()?;
+		let proof: lib_mock_chain::Proof = lib_mock_chain::Proof {
+			transit: transit_of(&proof.transit),
+			header: proof.header().to_vec(),
+			src_sig: signature_of(src_sig),
+			dst_sig: signature_of(dst_sig),
+			relay_sigs: relay_sigs.iter().map(signature_of).collect()
+		};
+		self.with(|chain| chain.submit_proof(&proof))
+	}
+
+	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
+		let lib_mock_chain::Account(account) = self.account_of(&pk)?;
+		Ok(account.into())
+	}
+
+	async fn foreign_attestation(&self) -> Result<identity::PublicKey> {
+		let lib_mock_chain::Key(key) = self.with(|chain| chain.foreign_attestation(&self.account).cloned().ok_or(lib_mock_chain::Error::NotAttested))?;
+		let key: bytes::Bytes = key.into();
+		let key: lib_bytes::NonEmpty = key.try_into()?;
+		Ok(key.into())
+	}
+
+	async fn locked_balance_of(&self, owner: identity::PublicKey) -> Result<Balance> {
+		let owner: lib_mock_chain::Account = self.account_of(&owner)?;
+		self.with(|chain| Ok(Balance(chain.locked_balance_of(&owner))))
+	}
+
+	async fn locked_balance_timeout_of(&self, owner: identity::PublicKey) -> Result<std::time::Instant> {
+		let owner: lib_mock_chain::Account = self.account_of(&owner)?;
+		let (now, until) = self.with(|chain| Ok((chain.now(), chain.lock_expiry_of(&owner))))?;
+		let until: u64 = until.ok_or("no locked balance")?;
+		let remaining: std::time::Duration = std::time::Duration::from_secs(until.saturating_sub(now));
+		Ok(std::time::Instant::now() + remaining)
+	}
+
+	async fn open_commitment(&self, account: identity::PublicKey) -> Result {
+		let account: lib_mock_chain::Account = self.account_of(&account)?;
+		self.with(|chain| chain.open_commitment(&self.account, &account))
+	}
+
+	async fn accept_commitment(&self) -> Result {
+		self.with(|chain| chain.accept_commitment(&self.account))
+	}
+
+	async fn account_has_sufficient_balance(&self, account: identity::PublicKey) -> Result<bool> {
+		let account: lib_mock_chain::Account = self.account_of(&account)?;
+		self.with(|chain| Ok(chain.has_sufficient_balance(&account)))
+	}
+
+	async fn lock(&self, amount: Balance, duration: Duration) -> Result {
+		let Balance(amount) = amount;
+		let Duration(duration) = duration;
+		self.with(|chain| chain.lock(&self.account, amount, duration))
+	}
+
+	async fn renew(&self, domain: Domain) -> Result {
+		self.with(|chain| chain.renew(&self.account, &domain.0))
+	}
+
+	async fn mint(&self, domain: Domain) -> Result {
+		self.with(|chain| chain.mint(&self.account, &domain.0))
+	}
+
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result {
+		let key: bytes::Bytes = key.into();
+		self.with(|chain| chain.publish_agreement_key(&self.account, &domain.0, &key))
+	}
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty> {
+		let key: Vec<u8> = self.with(|chain| {
+			chain.agreement_key_of(&domain.0)
+				.map(<[u8]>::to_vec)
+				.ok_or_else(|| lib_mock_chain::Error::NoAgreementKey(domain.0.to_owned()))
+		})?;
+		let key: bytes::Bytes = key.into();
+		let key: lib_bytes::NonEmpty = key.try_into()?;
+		Ok(key)
+	}
+
+	async fn congestion_charge(&self) -> Result<Fee> {
+		self.with(|chain| Ok(Fee(chain.congestion_charge())))
+	}
+
+	async fn fee(&self) -> Result<Fee> {
+		self.with(|chain| Ok(Fee(chain.fee())))
+	}
+
+	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
+		self.with(|chain| Ok(Traffic(chain.traffic(&domain.0))))
+	}
+
+	async fn total_spend(&self) -> Result<Balance> {
+		self.with(|chain| Ok(Balance(chain.total_spend(&self.account))))
+	}
+
+	async fn total_claim(&self) -> Result<Balance> {
+		self.with(|chain| Ok(Balance(chain.total_claim(&self.account))))
+	}
+
+	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
+		let account: lib_mock_chain::Account = self.account_of(&pk)?;
+		self.with(|chain| Ok(chain.age(&account).map(|age| Age(std::time::Duration::from_secs(age)))))
+	}
+}
+
+fn key_of(pk: &identity::PublicKey) -> lib_mock_chain::Key {
+	let pk: lib_bytes::NonEmpty = (**pk).to_owned();
+	let pk: bytes::Bytes = pk.into();
+	pk.to_vec().into()
+}
+
+/// `transit` as the mock chain settles it, shared with the `mchain` backend
+/// whose service signs over the same message.
+pub fn transit_of(transit: &Transit) -> lib_mock_chain::Transit {
+	lib_mock_chain::Transit {
+		domain: transit.domain.0.to_owned(),
+		src: key_of(&transit.src),
+		dst: key_of(&transit.dst),
+		relays: transit.relays.iter().map(key_of).collect(),
+		nonce: transit.nonce
+	}
+}
+
+fn signature_of(sg: &identity::Signature) -> Vec<u8> {
+	let sg: lib_bytes::NonEmpty = sg.to_owned().into();
+	let sg: bytes::Bytes = sg.into();
+	sg.to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Party acting as `name` with an identity attested to it.
+	async fn party(dns: &Mock, name: &str) -> Result<(Mock, identity::PublicKey, identity::SecretKey)> {
+		let account: Address = Address(name.as_bytes().to_vec());
+		let dns: Mock = dns.acting_as(account.to_owned());
+		let (public_key, secret_key) = identity::generate(identity::DEFAULT_ALGORITHM)?.into();
+		let message: bytes::Bytes = account.0.into();
+		let message: lib_bytes::NonEmpty = message.try_into()?;
+		let signature: identity::Signature = <identity::Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message.into())?;
+		dns.receive_attestation(public_key.to_owned(), signature).await?;
+		Ok((dns, public_key, secret_key))
+	}
+
+	#[tokio::test]
+	async fn transits_signed_by_every_party_settle() -> Result {
+		let chain: std::sync::Arc<std::sync::Mutex<_>> = std::sync::Arc::new(std::sync::Mutex::new(lib_mock_chain::MockChain::new(0)));
+		let dns: Mock = Mock::new(chain.to_owned(), Address(b"faucet".to_vec()));
+		let (src, src_pk, src_sk) = party(&dns, "client").await?;
+		let (dst, dst_pk, dst_sk) = party(&dns, "server").await?;
+		let (_, relay_pk, relay_sk) = party(&dns, "relay").await?;
+		let domain: Domain = Domain("hello.an".to_owned());
+		{
+			let mut chain: std::sync::MutexGuard<_> = chain.lock().map_err(|_| "mock chain poisoned")?;
+			chain.fund(&b"client".to_vec().into(), 10)?;
+			let fee: u64 = chain.mint_fee(&domain.0);
+			chain.fund(&b"server".to_vec().into(), fee)?;
+		}
+		src.lock(Balance(10), Duration(60)).await?;
+		dst.mint(domain.to_owned()).await?;
+		let transit: Transit = Transit {
+			domain: domain.to_owned(),
+			nonce: 0,
+			src: src_pk.to_owned(),
+			dst: dst_pk,
+			relays: vec![relay_pk]
+		};
+		let mut packet: TransitPacket = transit.packet(&dns)?;
+		packet.sign(&src_sk)?;
+		packet.sign(&dst_sk)?;
+		// the relay has yet to sign
+		assert!(Proof::new(&dns, transit.to_owned(), packet.to_owned()).is_err());
+		packet.sign(&relay_sk)?;
+		// a packet signed over another transit
+		let other: Transit = Transit {
+			nonce: 1,
+			..transit.to_owned()
+		};
+		assert!(Proof::new(&dns, other, packet.to_owned()).is_err());
+		let proof: Proof = Proof::new(&dns, transit, packet)?;
+		dns.receive_proof(proof.to_owned()).await?;
+		assert!(dns.receive_proof(proof).await.is_err());
+		assert_eq!(dns.traffic(domain).await?, Traffic(1));
+		assert_eq!(dns.locked_balance_of(src_pk).await?, Balance(9));
+		Ok(())
+	}
+}
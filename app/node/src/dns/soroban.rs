@@ -0,0 +1,313 @@
+use super::*;
+
+use lib_soroban_rpc::xdr;
+use lib_soroban_rpc::xdr::WriteXdr as _;
+
+/// Label of the transit content, as `mock_dns`'s `TRANSIT_LABEL`.
+const TRANSIT_LABEL: &[u8] = b"an-mock-dns-transit-v1";
+
+/// [`Dns`] over the `mock_dns` Soroban contract, invoked through the JSON-RPC
+/// endpoint as the account of the chain secret key.
+///
+/// Addresses travel in the contract's own encoding, the XDR of an address
+/// value, which is also what `sign_attestation` checks the node's signature
+/// against; transits are signed in a packet over the contract's address and
+/// the XDR of its `Transit`, so they settle on one deployment only. The
+/// contract verifies plain Ed25519, so only identities of the
+/// [`lib_cryptography_algorithm_agile::Tag::Ed25519`] algorithm attest or
+/// take part in a settled transit.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Soroban {
+	client: lib_soroban_rpc::Client,
+	contract: xdr::ContractId
+}
+
+impl Soroban {
+	pub fn new(client: lib_soroban_rpc::Client, contract: xdr::ContractId) -> Self {
+		Self {
+			client,
+			contract
+		}
+	}
+
+	/// Account the node acts as, in the contract's address encoding.
+	pub fn account(&self) -> Result<Address> {
+		address_of(&self.client.address())
+	}
+
+	fn owner(&self) -> xdr::ScVal {
+		xdr::ScVal::Address(self.client.address())
+	}
+
+	async fn invoke(&self, function: &str, args: Vec<xdr::ScVal>) -> Result<lib_soroban_rpc::Outcome> {
+		let outcome: lib_soroban_rpc::Outcome = self.client.invoke(&self.contract, function, args).await?;
+		log::debug!("soroban {} settled in {}", function, outcome.hash);
+		Ok(outcome)
+	}
+
+	async fn simulate_u64(&self, function: &str, args: Vec<xdr::ScVal>) -> Result<u64> {
+		match self.client.simulate(&self.contract, function, args).await? {
+			xdr::ScVal::U64(value) => Ok(value),
+			_ => Err(format!("{} returned an unexpected value", function).into())
+		}
+	}
+
+	/// Account `pk` is attested to, as an address value.
+	async fn account_of(&self, pk: &identity::PublicKey) -> Result<xdr::ScVal> {
+		let key: xdr::ScVal = ed25519_of(pk)?;
+		match self.client.simulate(&self.contract, "attestation", vec![key]).await? {
+			address @ xdr::ScVal::Address(_) => Ok(address),
+			xdr::ScVal::Void => Err("key is not attested to any account".into()),
+			_ => Err("attestation returned an unexpected value".into())
+		}
+	}
+
+	/// Field `name` of the contract's pricing.
+	async fn pricing(&self, name: &str) -> Result<u64> {
+		let xdr::ScVal::Map(Some(pricing)) = self.client.simulate(&self.contract, "pricing", Vec::new()).await? else {
+			return Err("pricing returned an unexpected value".into())
+		};
+		let key: xdr::ScVal = symbol(name)?;
+		match pricing.iter().find(|entry| entry.key == key).map(|entry| &entry.val) {
+			Some(xdr::ScVal::U64(value)) => Ok(*value),
+			_ => Err(format!("pricing has no {}", name).into())
+		}
+	}
+}
+
+#[async_trait::async_trait]
+impl Dns for Soroban {
+	async fn receive_attestation(
+		&self,
+		pk: identity::PublicKey,
+		sg: identity::Signature
+	) -> Result {
+		let key: xdr::ScVal = ed25519_of(&pk)?;
+		let sg: xdr::ScVal = ed25519_signature_of(sg)?;
+		// contract types with unnamed fields encode as vectors
+		let key: xdr::ScVal = xdr::ScVal::Vec(Some(vec![key].try_into()?));
+		let sg: xdr::ScVal = xdr::ScVal::Vec(Some(vec![sg].try_into()?));
+		self.invoke("sign_attestation", vec![self.owner(), key, sg]).await?;
+		Ok(())
+	}
+
+	/// `TRANSIT_LABEL || contract || transit`, both as XDR, as the
+	/// contract's `Transit::message` rebuilds it.
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>> {
+		let contract: xdr::ScVal = xdr::ScVal::Address(xdr::ScAddress::Contract(self.contract.to_owned()));
+		let mut message: Vec<u8> = TRANSIT_LABEL.to_vec();
+		message.extend_from_slice(&contract.to_xdr(xdr::Limits::none())?);
+		message.extend_from_slice(&transit_of(transit)?.to_xdr(xdr::Limits::none())?);
+		Ok(message)
+	}
+
+	async fn receive_proof(&self, proof: Proof) -> Result {
+		let (src_sig, relay_sigs, dst_sig) = proof.signatures()?;
+		let relay_sigs: Vec<xdr::ScVal> = relay_sigs
+			.iter()
+			.cloned()
+			.map(ed25519_signature_of)
+			.collect::<Result<_>>()?;
+		let proof: xdr::ScVal = map(vec![
+			("dst_sig", ed25519_signature_of(dst_sig.to_owned())?),
+			("header", xdr::ScVal::Bytes(proof.header().to_vec().try_into()?)),
+			("relay_sigs", xdr::ScVal::Vec(Some(relay_sigs.try_into()?))),
+			("src_sig", ed25519_signature_of(src_sig.to_owned())?),
+			("transit", transit_of(&proof.transit)?)
+		])?;
+		self.invoke("submit_proof", vec![proof]).await?;
+		Ok(())
+	}
+
+	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
+		match self.account_of(&pk).await? {
+			xdr::ScVal::Address(address) => address_of(&address),
+			_ => Err("attestation returned an unexpected value".into())
+		}
+	}
+
+	async fn foreign_attestation(&self) -> Result<identity::PublicKey> {
+		match self.client.simulate(&self.contract, "foreign_attestation", vec![self.owner()]).await? {
+			xdr::ScVal::Bytes(key) => {
+				let tag: u16 = lib_cryptography_algorithm_agile::Tag::Ed25519.into();
+				let key: bytes::Bytes = [tag.to_be_bytes().as_slice(), key.as_slice()].concat().into();
+				let key: lib_bytes::NonEmpty = key.try_into()?;
+				Ok(key.into())
+			},
+			xdr::ScVal::Void => Err("account is not attested by any key".into()),
+			_ => Err("foreign_attestation returned an unexpected value".into())
+		}
+	}
+
+	async fn locked_balance_of(&self, owner: identity::PublicKey) -> Result<Balance> {
+		let owner: xdr::ScVal = self.account_of(&owner).await?;
+		let amount: u64 = self.simulate_u64("locked_balance_of", vec![owner]).await?;
+		Ok(Balance(amount))
+	}
+
+	/// Ledger timestamps are measured against the local clock.
+	async fn locked_balance_timeout_of(&self, owner: identity::PublicKey) -> Result<std::time::Instant> {
+		let owner: xdr::ScVal = self.account_of(&owner).await?;
+		let until: u64 = self.simulate_u64("lock_timeout_of", vec![owner]).await?;
+		let now: u64 = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)?.as_secs();
+		let remaining: std::time::Duration = std::time::Duration::from_secs(until.saturating_sub(now));
+		Ok(std::time::Instant::now() + remaining)
+	}
+
+	async fn open_commitment(&self, account: identity::PublicKey) -> Result {
+		let account: xdr::ScVal = self.account_of(&account).await?;
+		self.invoke("open_commitment", vec![self.owner(), account]).await?;
+		Ok(())
+	}
+
+	async fn accept_commitment(&self) -> Result {
+		self.invoke("accept_commitment", vec![self.owner()]).await?;
+		Ok(())
+	}
+
+	async fn account_has_sufficient_balance(&self, account: identity::PublicKey) -> Result<bool> {
+		let Balance(locked) = self.locked_balance_of(account).await?;
+		let fee: u64 = self.pricing("fee").await?;
+		Ok(locked >= fee)
+	}
+
+	async fn lock(&self, amount: Balance, duration: Duration) -> Result {
+		let Balance(amount) = amount;
+		let Duration(duration) = duration;
+		self.invoke("lock", vec![self.owner(), xdr::ScVal::U64(amount), xdr::ScVal::U64(duration)]).await?;
+		Ok(())
+	}
+
+	async fn renew(&self, domain: Domain) -> Result {
+		self.invoke("renew", vec![self.owner(), string(&domain)?]).await?;
+		Ok(())
+	}
+
+	async fn mint(&self, domain: Domain) -> Result {
+		self.invoke("mint", vec![self.owner(), string(&domain)?]).await?;
+		Ok(())
+	}
+
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result {
+		let key: bytes::Bytes = key.into();
+		let key: xdr::ScVal = xdr::ScVal::Bytes(key.to_vec().try_into()?);
+		self.invoke("publish_agreement_key", vec![self.owner(), string(&domain)?, key]).await?;
+		Ok(())
+	}
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty> {
+		match self.client.simulate(&self.contract, "agreement_key_of", vec![string(&domain)?]).await? {
+			xdr::ScVal::Bytes(key) => {
+				let key: bytes::Bytes = key.to_vec().into();
+				let key: lib_bytes::NonEmpty = key.try_into()?;
+				Ok(key)
+			},
+			xdr::ScVal::Void => Err("domain has no agreement key".into()),
+			_ => Err("agreement_key_of returned an unexpected value".into())
+		}
+	}
+
+	async fn congestion_charge(&self) -> Result<Fee> {
+		let charge: u64 = self.pricing("congestion_charge").await?;
+		Ok(Fee(charge))
+	}
+
+	async fn fee(&self) -> Result<Fee> {
+		let fee: u64 = self.pricing("fee").await?;
+		Ok(Fee(fee))
+	}
+
+	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
+		let traffic: u64 = self.simulate_u64("traffic", vec![string(&domain)?]).await?;
+		Ok(Traffic(traffic))
+	}
+
+	async fn total_spend(&self) -> Result<Balance> {
+		let spent: u64 = self.simulate_u64("total_spend", vec![self.owner()]).await?;
+		Ok(Balance(spent))
+	}
+
+	async fn total_claim(&self) -> Result<Balance> {
+		let claimed: u64 = self.simulate_u64("total_claim", vec![self.owner()]).await?;
+		Ok(Balance(claimed))
+	}
+
+	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
+		let account: xdr::ScVal = self.account_of(&pk).await?;
+		match self.client.simulate(&self.contract, "age", vec![account]).await? {
+			xdr::ScVal::U64(age) => Ok(Some(Age(std::time::Duration::from_secs(age)))),
+			xdr::ScVal::Void => Ok(None),
+			_ => Err("age returned an unexpected value".into())
+		}
+	}
+}
+
+fn address_of(address: &xdr::ScAddress) -> Result<Address> {
+	let address: xdr::ScVal = xdr::ScVal::Address(address.to_owned());
+	let address: Vec<u8> = address.to_xdr(xdr::Limits::none())?;
+	Ok(address.into())
+}
+
+fn symbol(name: &str) -> Result<xdr::ScVal> {
+	Ok(xdr::ScVal::Symbol(xdr::ScSymbol(name.try_into()?)))
+}
+
+fn string(domain: &Domain) -> Result<xdr::ScVal> {
+	Ok(xdr::ScVal::String(xdr::ScString(domain.0.as_str().try_into()?)))
+}
+
+/// Untagged Ed25519 key of `pk`, as the contract's `BytesN<32>`.
+fn ed25519_of(pk: &identity::PublicKey) -> Result<xdr::ScVal> {
+	let pk: bytes::Bytes = untag_ed25519((**pk).to_owned(), lib_cryptography_algorithm_agile::Tag::Ed25519.public_key_len())?;
+	Ok(xdr::ScVal::Bytes(pk.to_vec().try_into()?))
+}
+
+/// Signature of `sg` without its tag, as the contract's `BytesN<64>`.
+fn ed25519_signature_of(sg: identity::Signature) -> Result<xdr::ScVal> {
+	let sg: bytes::Bytes = untag_ed25519(sg.into(), lib_cryptography_algorithm_agile::Tag::Ed25519.signature_len())?;
+	Ok(xdr::ScVal::Bytes(sg.to_vec().try_into()?))
+}
+
+/// `value` past its agile tag, which has to be Ed25519 and followed by
+/// exactly `len` bytes.
+fn untag_ed25519(value: lib_bytes::NonEmpty, len: usize) -> Result<bytes::Bytes> {
+	let mut reader: lib_bytes::Reader = value.into();
+	let tag: lib_cryptography_algorithm_agile::Tag = reader.read_u16()?.try_into()?;
+	if tag != lib_cryptography_algorithm_agile::Tag::Ed25519 {
+		return Err(format!("soroban verifies ed25519 only, not {:?}", tag).into())
+	}
+	if reader.remaining() != len {
+		return Err(format!("ed25519 value of {} bytes, not {}", reader.remaining(), len).into())
+	}
+	Ok(reader.rest())
+}
+
+/// `transit` as the contract's `Transit`, the value every party signs the
+/// XDR of.
+fn transit_of(transit: &Transit) -> Result<xdr::ScVal> {
+	let relays: Vec<xdr::ScVal> = transit.relays
+		.iter()
+		.map(ed25519_of)
+		.collect::<Result<_>>()?;
+	map(vec![
+		("domain", string(&transit.domain)?),
+		("dst", ed25519_of(&transit.dst)?),
+		("nonce", xdr::ScVal::U64(transit.nonce)),
+		("relays", xdr::ScVal::Vec(Some(relays.try_into()?))),
+		("src", ed25519_of(&transit.src)?)
+	])
+}
+
+/// Contract types with named fields encode as maps keyed by field name, in
+/// name order, which `fields` has to be in.
+fn map(fields: Vec<(&str, xdr::ScVal)>) -> Result<xdr::ScVal> {
+	let entries: Vec<xdr::ScMapEntry> = fields
+		.into_iter()
+		.map(|(key, val)| Ok(xdr::ScMapEntry {
+			key: symbol(key)?,
+			val
+		}))
+		.collect::<Result<_>>()?;
+	Ok(xdr::ScVal::Map(Some(xdr::ScMap(entries.try_into()?))))
+}
@@ -1,4 +1,5 @@
 pub const PUBLIC_KEY: &str = "PUBLIC_KEY";
 pub const SECRET_KEY: &str = "SECRET_KEY";
 pub const NODE_PUBLIC_KEY: &str = "NODE_PUBLIC_KEY";
-pub const NODE_SECRET_KEY: &str = "NODE_SECRET_KEY";
\ No newline at end of file
+pub const NODE_SECRET_KEY: &str = "NODE_SECRET_KEY";
+pub const CHAIN_SECRET_KEY: &str = "CHAIN_SECRET_KEY";
\ No newline at end of file
@@ -1,6 +1,4 @@
 use super::*;
-use ed25519_dalek::Signer as _;
-use ed25519::signature::Keypair as _;
 
 pub mod commitment {
 	use super::*;
@@ -20,259 +18,325 @@ pub mod commitment {
 	}
 }
 
+/// Packets signed once by their sender, shared by every protocol a node
+/// signs for; `P` is the [`lib_packet::Protocol`] stamped in the header so a
+/// packet signed for one protocol is rejected by every other.
+pub mod single_party {
+	use super::*;
+	
+	pub type Unsigned<T, P> = lib_packet::Unsigned<T, P>;
+	pub type SignedUnverified<T, P> = lib_packet::MarkedSignedUnverified<T, Algorithm, P>;
+	pub type SignedVerified<T, P> = lib_packet::MarkedSignedVerified<T, Algorithm, P>;
+}
+
+/// Packets every one of an ordered set of nodes signs, see
+/// [`lib_packet::multi_party`].
 pub mod multi_party {
 	use super::*;
-		
-	pub struct CompleteVerified(Vec<u8>);
 	
-	impl TryFrom<Partial> for CompleteVerified {
-		type Error = Box<dyn std::error::Error>;
-		
-		fn try_from(value: Partial) -> std::result::Result<Self, Self::Error> {
-			
+	pub type Partial<T, P> = lib_packet::multi_party::Partial<T, Algorithm, P>;
+	pub type Complete<T, P> = lib_packet::multi_party::Complete<T, Algorithm, P>;
+}
+
+/// Signature algorithm of node identities, tagged per key so ed25519,
+/// dilithium3 and composite nodes verify one another.
+pub type Algorithm = lib_cryptography_algorithm_agile::AgileAlgorithm;
+
+/// Algorithm a node generates its identity with unless configured otherwise,
+/// the one every chain backend can attest.
+pub const DEFAULT_ALGORITHM: lib_cryptography_algorithm_agile::Tag = lib_cryptography_algorithm_agile::Tag::Ed25519;
+
+/// File the identity secret key is kept in unless configured otherwise,
+/// relative to the working directory like `an.toml`.
+pub const DEFAULT_PATH: &str = "an.key";
+
+pub type PublicKey = lib_cryptography::public_key::PublicKey<Algorithm>;
+pub type SecretKey = lib_cryptography::secret_key::SecretKey<Algorithm>;
+pub type Signature = lib_cryptography::signature::Signature<Algorithm>;
+pub type Pair = lib_cryptography::pair::Pair<Algorithm>;
+
+pub fn generate(algorithm: lib_cryptography_algorithm_agile::Tag) -> Result<Pair> {
+	Algorithm::generate_with(algorithm)
+}
+
+/// Loads the identity kept at `path`, generating one with `algorithm` and
+/// storing it there the first time, so a node keeps its identity and every
+/// attestation made for it across restarts.
+pub fn load_or_generate(path: &std::path::Path, algorithm: lib_cryptography_algorithm_agile::Tag) -> Result<Pair> {
+	if path.exists() {
+		let secret_key: SecretKey = read_secret(path)?.try_into()?;
+		let public_key: PublicKey = secret_key.public_key()?;
+		let stored: lib_cryptography_algorithm_agile::Tag = Algorithm::tag(&public_key)?;
+		if stored != algorithm {
+			return Err(format!("identity at {} is {:?}, configured for {:?}", path.display(), stored, algorithm).into())
 		}
+		return Ok((public_key, secret_key).into())
 	}
-	
-	// all parties have signed
-	pub struct CompleteUnverified(Vec<u8>);
-	
-	pub struct CompleteVerified {
-		signers: Vec<PublicKey>,
-		content: Vec<u8>
-	}
-	
-	#[derive(Debug)]
-	#[derive(Clone)]
-	#[derive(PartialEq)]
-	#[derive(Eq)]
-	pub struct Partial {
-		mg: Vec<u8>,
-		pk_to_sg: std::collections::HashMap<PublicKey, Signature>,
-		signers: Vec<PublicKey>
+	let (public_key, secret_key) = generate(algorithm)?.into();
+	let mut options: std::fs::OpenOptions = std::fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+	let mut file: std::fs::File = options.open(path)?;
+	std::io::Write::write_all(&mut file, secret_key.expose_secret())?;
+	Ok((public_key, secret_key).into())
+}
+
+/// Reads a secret key file into a buffer the key takes over and wipes, so no
+/// unwiped copy of it is left behind.
+pub fn read_secret(path: &std::path::Path) -> Result<bytes::BytesMut> {
+	let mut file: std::fs::File = std::fs::File::open(path)?;
+	let len: usize = file.metadata()?.len().try_into()?;
+	let mut out: bytes::BytesMut = bytes::BytesMut::zeroed(len);
+	std::io::Read::read_exact(&mut file, &mut out)?;
+	Ok(out)
+}
+
+/// Path named by the `[identity]` section of `conf`, if any.
+pub fn path_of(conf: Option<&config::Config>) -> std::path::PathBuf {
+	if let Some(conf) = conf
+	&& let Some(identity) = &conf.identity
+	&& let Some(path) = &identity.path {
+		path.to_owned()
+	} else {
+		DEFAULT_PATH.into()
 	}
-	
-	impl Partial {
-		pub fn sign(self, pk: PublicKey, sg: Signature) -> Result<kore::Or<Self, CompleteVerified>> {
-			sg.verify(mg, &pk)?;
-			
-			self.pk_to_sg.insert(pk, sg);
-			Ok(kore::Or::Lhs(self))
-		}
+}
+
+/// Algorithm named by the `[identity]` section of `conf`, if any.
+pub fn algorithm_of(conf: Option<&config::Config>) -> lib_cryptography_algorithm_agile::Tag {
+	if let Some(conf) = conf
+	&& let Some(identity) = &conf.identity
+	&& let Some(algorithm) = identity.algorithm {
+		algorithm.into()
+	} else {
+		DEFAULT_ALGORITHM
 	}
-	
-	impl TryFrom<bytes::Bytes> for Partial {
-		type Error = Box<dyn std::error::Error>;
-		
-		fn try_from(value: bytes::Bytes) -> std::result::Result<Self, Self::Error> {
-			
-		}
+}
+
+/// Signature policy named by the `[relay]` section of `conf`, accepting every
+/// algorithm unless it lists some.
+pub fn policy_of(conf: Option<&config::Config>) -> lib_packet::policy::Policy {
+	if let Some(conf) = conf
+	&& let Some(relay) = &conf.relay
+	&& let Some(accepted) = &relay.accepted_algorithms {
+		lib_packet::policy::Policy::new(accepted.iter().map(|algorithm| lib_cryptography_algorithm_agile::Tag::from(*algorithm).id()))
+	} else {
+		lib_packet::policy::Policy::default()
 	}
-	
-	pub struct Unsigned(Vec<u8>);
 }
 
-pub mod single_party {
+/// Certificate tying a node identity to the libp2p key of the connection it
+/// speaks on, so records signed by the identity can be held against the peer
+/// that delivered them.
+///
+/// Both keys sign `LABEL || peer id || public key`; the libp2p key vouches
+/// for the node identity and the node identity vouches for the peer id.
+/// Encoded as `len (2) || libp2p key || len (2) || libp2p signature || len (2)
+/// || public key || signature`, lengths big endian.
+pub mod binding {
 	use super::*;
 	
-	#[derive(Debug)]
-	#[derive(Clone)]
-	#[derive(PartialEq)]
-	#[derive(Eq)]
-	#[derive(derive_more::Deref)]
-	#[derive(derive_more::DerefMut)]
-	pub struct SignedVerified {
-		pk: PublicKey,
-		#[deref]
-		#[deref_mut]
-		content: Vec<u8>
-	}
+	pub const LABEL: &[u8] = b"an-binding-v1";
 	
-	impl SignedVerified {
-		pub fn pk(&self) -> &PublicKey {
-			&self.pk
-		}
+	#[derive(Debug)]
+	#[derive(thiserror::Error)]
+	pub enum Error {
+		#[error("binding is for {bound}, not {peer}")]
+		Peer {
+			bound: libp2p::PeerId,
+			peer: libp2p::PeerId
+		},
 		
-		pub fn content(&self) -> &[u8] {
-			&self.content
-		}
-	}
-	
-	impl kore::Unpack<(PublicKey, Unsigned)> for SignedVerified {
-		fn unpack(self) -> (PublicKey, Unsigned) {
-			let mg: Unsigned = self.content.into();
-			(self.pk, mg)
-		}
-	}
-	
-	impl TryFrom<SignedUnverified> for SignedVerified {
-		type Error = Box<dyn std::error::Error>;
+		#[error("libp2p signature does not verify")]
+		PeerSignature,
 		
-		fn try_from(value: SignedUnverified) -> std::result::Result<Self, Self::Error> {
-			let (sg, pk, mg) = value.unpack();
-	
-			sg.verify(&mg, &pk)?;
-			
-			let Unsigned(content) = mg;
-			Ok(Self {
-				pk: pk.to_owned(),
-				content
-			})
-		}
+		#[error("identity signature does not verify")]
+		Signature,
+		
+		#[error("field longer than {}", u16::MAX)]
+		Length
 	}
 	
 	#[derive(Debug)]
 	#[derive(Clone)]
 	#[derive(PartialEq)]
-	#[derive(Eq)]
-	#[derive(serde::Serialize)]
-	#[derive(serde::Deserialize)]
-	#[derive(derive_more::Deref)]
-	#[derive(derive_more::DerefMut)]
-	#[serde(try_from = "Vec<u8>")]
-	pub struct SignedUnverified(Vec<u8>);
-	
-	impl SignedUnverified {
-		pub fn verify(self) -> Result<SignedVerified> {
-			self.try_into()
-		}
+	pub struct Binding {
+		peer_public_key: libp2p::identity::PublicKey,
+		peer_signature: bytes::Bytes,
+		public_key: PublicKey,
+		signature: Signature
 	}
 	
-	impl Unpack<(Signature, PublicKey, Unsigned)> for SignedUnverified {
-		fn unpack(self) -> (Signature, PublicKey, Unsigned) {
-			let Self(mut bytes) = self;				
-			let mut mgpk: Vec<_> = bytes.split_off(64);
-			let mg: Vec<_> = mgpk.split_off(32);
-			let mg: Unsigned = mg.try_into().expect("unsigned content slice is guaranteed to be non-empty via constructors");
-			let pk: [_; _] = mgpk.as_slice().try_into().expect("public key state corruption; must equal 32 bytes");
-			let pk: PublicKey = pk.into();
-			let sg: [_; _] = bytes.as_slice().try_into().expect("signature state corruption; remaining bytes must equal 64; guaranteed via constructors");
-			let sg: Signature = sg.into();
-			(sg, pk, mg)
+	impl Binding {
+		pub fn new(public_key: &PublicKey, secret_key: &SecretKey, keypair: &libp2p::identity::Keypair) -> Result<Self> {
+			let peer_public_key: libp2p::identity::PublicKey = keypair.public();
+			let message: bytes::Bytes = message(&peer_public_key.to_peer_id(), public_key);
+			let peer_signature: Vec<u8> = keypair.sign(&message)?;
+			let message: lib_bytes::NonEmpty = message.try_into()?;
+			let message: lib_cryptography::message::Message = message.into();
+			let signature: Signature = <Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(secret_key, &message)?;
+			Ok(Self {
+				peer_public_key,
+				peer_signature: peer_signature.into(),
+				public_key: public_key.to_owned(),
+				signature
+			})
 		}
-	}
-	
-	impl From<(&Unsigned, &SecretKey)> for SignedUnverified {
-		fn from(value: (&Unsigned, &SecretKey)) -> Self {
-			let (Unsigned(mg), SecretKey(sk)) = value;
-			let sk: ed25519_dalek::SigningKey = ed25519_dalek::SigningKey::from_bytes(&sk);
-			let pk: [_; _] = sk.verifying_key().to_bytes();
-			let sg: ed25519_dalek::Signature = sk.sign(&mg);
-			let sg: [_; _] = sg.to_bytes();
-			let capacity: usize = 64 + 32 + mg.len();
-			
-			let mut bytes: Vec<_> = Vec::with_capacity(capacity);
-			bytes.extend_from_slice(&sg);
-			bytes.extend_from_slice(&pk);
-			bytes.extend_from_slice(&mg);
-			
-			Self(bytes)
+		
+		pub fn peer(&self) -> libp2p::PeerId {
+			self.peer_public_key.to_peer_id()
+		}
+		
+		pub fn public_key(&self) -> &PublicKey {
+			&self.public_key
 		}
-	}
-	
-	impl TryFrom<Vec<u8>> for SignedUnverified {
-		type Error = Box<dyn std::error::Error>;
 		
-		fn try_from(value: Vec<u8>) -> std::result::Result<Self, Self::Error> {
-			if value.len() <= 96 {
-				return Err(<Box<dyn std::error::Error>>::from(String::from("signed message packet is too short")))
+		/// Checks both signatures and that the binding names `peer`, the peer
+		/// the binding arrived from.
+		pub fn verify(&self, peer: &libp2p::PeerId) -> Result {
+			let bound: libp2p::PeerId = self.peer();
+			if &bound != peer {
+				return Err(Error::Peer {
+					bound,
+					peer: peer.to_owned()
+				}.into())
+			}
+			let message: bytes::Bytes = message(&bound, &self.public_key);
+			if !self.peer_public_key.verify(&message, &self.peer_signature) {
+				return Err(Error::PeerSignature.into())
 			}
-			let content: &[_] = &value[96..];
-			if content.is_empty() {
-				return Err(<Box<dyn std::error::Error>>::from(String::from("signed message content cannot be empty")))
+			let message: lib_bytes::NonEmpty = message.try_into()?;
+			let message: lib_cryptography::message::Message = message.into();
+			if !<Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&self.public_key, &message, &self.signature)? {
+				return Err(Error::Signature.into())
 			}
-			Ok(Self(value))
+			Ok(())
 		}
 	}
 	
-	#[derive(Debug)]
-	#[derive(Clone)]
-	#[derive(PartialEq)]
-	#[derive(Eq)]
-	#[derive(serde::Serialize)]
-	#[derive(serde::Deserialize)]
-	#[derive(derive_more::Deref)]
-	#[derive(derive_more::DerefMut)]
-	#[serde(try_from = "Vec<u8>")]
-	pub struct Unsigned(Vec<u8>);
-	
-	impl Unsigned {
-		pub fn sign(&self, sk: &SecretKey) -> SignedUnverified {
-			(self, sk).into()
+	impl TryFrom<Binding> for bytes::Bytes {
+		type Error = Box<dyn std::error::Error>;
+		
+		fn try_from(value: Binding) -> std::result::Result<Self, Self::Error> {
+			let Binding {
+				peer_public_key,
+				peer_signature,
+				public_key,
+				signature
+			} = value;
+			let peer_public_key: Vec<u8> = peer_public_key.encode_protobuf();
+			let public_key: lib_bytes::NonEmpty = public_key.into();
+			let public_key: bytes::Bytes = public_key.into();
+			let signature: lib_bytes::NonEmpty = signature.into();
+			let signature: bytes::Bytes = signature.into();
+			
+			let mut bytes: Vec<u8> = Vec::new();
+			for field in [peer_public_key.as_slice(), &peer_signature, &public_key] {
+				let len: u16 = field.len().try_into().map_err(|_| Error::Length)?;
+				bytes.extend_from_slice(&len.to_be_bytes());
+				bytes.extend_from_slice(field);
+			}
+			bytes.extend_from_slice(&signature);
+			
+			Ok(bytes.into())
 		}
 	}
 	
-	impl TryFrom<Vec<u8>> for Unsigned {
+	impl TryFrom<bytes::Bytes> for Binding {
 		type Error = Box<dyn std::error::Error>;
 		
-		fn try_from(value: Vec<u8>) -> std::result::Result<Self, Self::Error> {
-			if value.len() == 0 {
-				return Err(<Box<dyn std::error::Error>>::from(String::from("message too short")))
-			}
-			Ok(Self(value))
+		fn try_from(value: bytes::Bytes) -> std::result::Result<Self, Self::Error> {
+			let mut reader: lib_bytes::Reader = value.into();
+			let len: u16 = reader.read_u16()?;
+			let peer_public_key: bytes::Bytes = reader.read(len.into())?;
+			let peer_public_key: libp2p::identity::PublicKey = libp2p::identity::PublicKey::try_decode_protobuf(&peer_public_key)?;
+			let len: u16 = reader.read_u16()?;
+			let peer_signature: bytes::Bytes = reader.read(len.into())?;
+			let len: u16 = reader.read_u16()?;
+			let public_key: bytes::Bytes = reader.read(len.into())?;
+			let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+			let signature: lib_bytes::NonEmpty = reader.rest().try_into()?;
+			Ok(Self {
+				peer_public_key,
+				peer_signature,
+				public_key: public_key.into(),
+				signature: signature.into()
+			})
 		}
-	}	
+	}
+	
+	fn message(peer: &libp2p::PeerId, public_key: &PublicKey) -> bytes::Bytes {
+		let public_key: lib_bytes::NonEmpty = (**public_key).to_owned();
+		let public_key: bytes::Bytes = public_key.into();
+		let mut message: Vec<u8> = LABEL.to_vec();
+		message.extend_from_slice(&peer.to_bytes());
+		message.extend_from_slice(&public_key);
+		message.into()
+	}
 }
 
-#[derive(Debug)]
-#[derive(Clone)]
-#[derive(PartialEq)]
-#[derive(Eq)]
-#[derive(derive_more::From)]
-pub struct PublicKey([u8; 32]);
+#[cfg(test)]
+mod tests {
+	use super::*;
 
-#[derive(Debug)]
-#[derive(Clone)]
-#[derive(PartialEq)]
-#[derive(Eq)]
-#[derive(derive_more::From)]
-pub struct SecretKey([u8; 32]);
+	fn path(name: &str) -> std::path::PathBuf {
+		std::env::temp_dir().join(format!("an-identity-{}-{}", name, std::process::id()))
+	}
 
-#[derive(Debug)]
-#[derive(Clone)]
-#[derive(PartialEq)]
-#[derive(Eq)]
-#[derive(derive_more::From)]
-pub struct Signature([u8; 64]);
+	#[test]
+	fn identity_survives_restart() -> Result {
+		let path: std::path::PathBuf = path("restart");
+		let first: Pair = load_or_generate(&path, lib_cryptography_algorithm_agile::Tag::Ed25519)?;
+		let second: Pair = load_or_generate(&path, lib_cryptography_algorithm_agile::Tag::Ed25519)?;
+		let other: Result<Pair> = load_or_generate(&path, lib_cryptography_algorithm_agile::Tag::Dilithium3);
+		std::fs::remove_file(&path)?;
+		assert_eq!(first, second);
+		assert!(other.is_err());
+		Ok(())
+	}
 
-impl Signature {
-	pub fn verify(&self, mg: &[u8], pk: &PublicKey) -> Result {
-		let pk: ed25519_dalek::VerifyingKey = ed25519_dalek::VerifyingKey::from_bytes(&pk.0)?;
-		let sg: ed25519_dalek::Signature = ed25519_dalek::Signature::from_bytes(&self.0);
-		
-		pk.verify(mg, &sg)
+	fn binding() -> Result<(libp2p::identity::Keypair, PublicKey, binding::Binding)> {
+		let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::generate_ed25519();
+		let (public_key, secret_key) = generate(DEFAULT_ALGORITHM)?.into();
+		let binding: binding::Binding = binding::Binding::new(&public_key, &secret_key, &keypair)?;
+		Ok((keypair, public_key, binding))
 	}
-}
 
-#[derive(Debug)]
-#[derive(Clone)]
-#[derive(PartialEq)]
-#[derive(Eq)]
-#[derive(derive_more::From)]
-pub struct Pair((PublicKey, SecretKey));
+	#[test]
+	fn binding_round_trips() -> Result {
+		let (keypair, public_key, binding) = binding()?;
+		let encoded: bytes::Bytes = binding.to_owned().try_into()?;
+		let decoded: binding::Binding = encoded.try_into()?;
+		assert_eq!(decoded, binding);
+		assert_eq!(decoded.public_key(), &public_key);
+		decoded.verify(&keypair.public().to_peer_id())
+	}
 
-impl Default for Pair {
-	fn default() -> Self {
-		let sk: ed25519_dalek::SigningKey = ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng);
-		let vk: ed25519_dalek::VerifyingKey = sk.verifying_key();
-		let vk: [_; _] = vk.to_bytes();
-		let pk: PublicKey = PublicKey::from(vk);
-		let sk: [_; _] = sk.to_bytes();
-		let sk: SecretKey = SecretKey::from(sk);
-		
-		Self::from((pk, sk))
+	#[test]
+	fn binding_rejects_other_peer() -> Result {
+		let (_, _, binding) = binding()?;
+		let error: Option<String> = binding.verify(&libp2p::PeerId::random()).err().map(|error| error.to_string());
+		assert!(error.is_some_and(|error| error.starts_with("binding is for")));
+		Ok(())
 	}
-}
 
-impl From<SecretKey> for Pair {
-	fn from(value: SecretKey) -> Self {
-		let SecretKey(bytes) = value;
-		let sk: ed25519_dalek::SigningKey = ed25519_dalek::SigningKey::from_bytes(&bytes);
-		let vk: ed25519_dalek::VerifyingKey = sk.verifying_key();
-		let vk: [_; _] = vk.to_bytes();
-		let pk: PublicKey = PublicKey::from(vk);
-		let sk: [_; _] = sk.to_bytes();
-		let sk: SecretKey = SecretKey::from(sk);
-		
-		Self::from((pk, sk))
+	#[test]
+	fn binding_rejects_tampered_signatures() -> Result {
+		let (keypair, _, binding) = binding()?;
+		let peer: libp2p::PeerId = keypair.public().to_peer_id();
+		let encoded: bytes::Bytes = binding.try_into()?;
+		// the identity signature closes the encoding, the libp2p signature
+		// follows the length prefixed libp2p key
+		let peer_public_key_len: usize = u16::from_be_bytes([encoded[0], encoded[1]]).into();
+		let peer_signature_at: usize = 2 + peer_public_key_len + 2;
+		for (at, expected) in [(encoded.len() - 1, "identity signature does not verify"), (peer_signature_at, "libp2p signature does not verify")] {
+			let mut tampered: Vec<u8> = encoded.to_vec();
+			tampered[at] ^= 1;
+			let tampered: binding::Binding = bytes::Bytes::from(tampered).try_into()?;
+			let error: Option<String> = tampered.verify(&peer).err().map(|error| error.to_string());
+			assert_eq!(error.as_deref(), Some(expected));
+		}
+		Ok(())
 	}
-}
\ No newline at end of file
+}
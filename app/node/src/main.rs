@@ -106,6 +106,7 @@ use num::ToPrimitive as _;
 
 mod cmn;
 mod config;
+mod dns;
 mod identity;
 mod env_key;
 mod grpc;
@@ -172,30 +173,117 @@ struct Traffic(u64);
 #[derive(derive_more::Sub)]
 struct Age(std::time::Duration);
 
+/// Delivery of a session to `domain` from src to dst through relays in path
+/// order; `nonce` tells transits over the same path apart. Every party signs
+/// it in a [`TransitPacket`] whose content is [`Dns::transit_message`].
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(PartialEq)]
 #[derive(Eq)]
-struct Proof {
+struct Transit {
+	domain: Domain,
+	nonce: u64,
 	src: identity::PublicKey,
-	src_sig: identity::Signature,
 	dst: identity::PublicKey,
-	dst_sig: identity::Signature,
 	// relays involved in the delivery of this session
-	relays: Vec<(identity::PublicKey, identity::Signature)>
+	relays: Vec<identity::PublicKey>
+}
+
+impl Transit {
+	/// Parties in the order they sign: src, relays in path order, then dst.
+	fn signers(&self) -> Vec<identity::PublicKey> {
+		std::iter::once(&self.src)
+			.chain(&self.relays)
+			.chain(std::iter::once(&self.dst))
+			.cloned()
+			.collect()
+	}
+
+	/// Unsigned packet over the transit, passed from party to party until
+	/// every one has signed it, then settled through [`Proof::new`].
+	fn packet(&self, dns: &dyn Dns) -> Result<TransitPacket> {
+		Ok(TransitPacket::new(self.content(dns)?, self.signers())?)
+	}
+
+	fn content(&self, dns: &dyn Dns) -> Result<lib_bytes::NonEmpty> {
+		let content: bytes::Bytes = dns.transit_message(self)?.into();
+		Ok(content.try_into()?)
+	}
+}
+
+/// Protocol id carried in the header of transit packets.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct TransitProtocol;
+
+impl lib_packet::Protocol for TransitProtocol {
+	const ID: u16 = 0x0002;
+}
+
+type TransitPacket = identity::multi_party::Partial<lib_bytes::NonEmpty, TransitProtocol>;
+
+/// [`Transit`] with the signature of every party over it.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Proof {
+	transit: Transit,
+	packet: identity::multi_party::Complete<lib_bytes::NonEmpty, TransitProtocol>
+}
+
+impl Proof {
+	/// Completes `packet`, checking it was signed over `transit` by its
+	/// parties so a proof the chain would refuse is never submitted.
+	fn new(dns: &dyn Dns, transit: Transit, packet: TransitPacket) -> Result<Self> {
+		if *packet.content() != transit.content(dns)? || packet.signers() != transit.signers().as_slice() {
+			return Err("packet is not over this transit".into())
+		}
+		let packet: identity::multi_party::Complete<lib_bytes::NonEmpty, TransitProtocol> = packet
+			.complete()
+			.map_err(|packet| lib_packet::multi_party::Error::Incomplete(packet.missing().count()))?;
+		packet.verify()?;
+		Ok(Self {
+			transit,
+			packet
+		})
+	}
+
+	/// Header of the packet, part of what every party signed.
+	fn header(&self) -> [u8; lib_packet::header::LEN] {
+		self.packet.header().encode()
+	}
+
+	/// Signatures of src, of the relays in path order and of dst.
+	fn signatures(&self) -> Result<(&identity::Signature, &[identity::Signature], &identity::Signature)> {
+		let (src_sig, rest) = self.packet.signatures().split_first().ok_or("proof has no signatures")?;
+		let (dst_sig, relay_sigs) = rest.split_last().ok_or("proof has no destination signature")?;
+		Ok((src_sig, relay_sigs, dst_sig))
+	}
 }
 
 /// External dns source of truth provider, may be swapped and implemented by
 /// other chains or networks, the nodes rely on this sytem for value transfer
 /// and cryptographic proofs
+///
+/// Subsystems never name a backend, they share the [`dns::Handle`] picked by
+/// the `[chain]` section of the config.
 #[async_trait::async_trait]
 trait Dns {
+	/// Records that `pk` speaks for the backend's own account; `sg` is `pk`'s
+	/// signature over that account's address in the chain's own encoding.
 	async fn receive_attestation(
 		&self,
 		pk: identity::PublicKey,
 		sg: identity::Signature
 	) -> Result;
 
+	/// Content of the [`TransitPacket`] every party of `transit` signs, in
+	/// the backend's own encoding.
+	fn transit_message(&self, transit: &Transit) -> Result<Vec<u8>>;
+
 	/// Receives a proof of trasit from src to dst through possible relays.
 	async fn receive_proof(&self, proof: Proof) -> Result;
 
@@ -223,6 +311,12 @@ trait Dns {
 	async fn renew(&self, domain: Domain) -> Result;
 	
 	async fn mint(&self, domain: Domain) -> Result;
+
+	/// Publishes `key` in the record of `domain`, which the backend's account
+	/// has to own, for clients to seal their sessions to its server with.
+	async fn publish_agreement_key(&self, domain: Domain, key: lib_bytes::NonEmpty) -> Result;
+
+	async fn agreement_key(&self, domain: Domain) -> Result<lib_bytes::NonEmpty>;
 	
 	
 	async fn congestion_charge(&self) -> Result<Fee>;
@@ -246,61 +340,6 @@ trait Dns {
 	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>>;
 }
 
-#[derive(Debug)]
-struct StellarTestnet;
-
-#[async_trait::async_trait]
-impl Dns for StellarTestnet {
-	async fn receive_attestation(
-		&self,
-		pk: identity::PublicKey,
-		sg: identity::Signature
-	) -> Result {
-		
-	}
-	
-	async fn receive_proof(&self, proof: Proof) -> Result {
-		
-	}
-	
-	async fn attestation(&self, pk: identity::PublicKey) -> Result<Address> {
-		
-	}
-	
-	async fn renew(&self, domain: Domain) -> Result {
-		todo!()
-	}
-	
-	async fn mint(&self, domain: Domain) -> Result {
-		todo!()
-	}
-	
-	async fn congestion_charge(&self) -> Result<Fee> {
-		todo!()
-	}
-	
-	async fn fee(&self) -> Result<Fee> {
-		todo!()
-	}
-	
-	async fn traffic(&self, domain: Domain) -> Result<Traffic> {
-		todo!()
-	}
-	
-	async fn total_spend(&self) -> Result<Balance> {
-		todo!()
-	}
-	
-	async fn total_claim(&self) -> Result<Balance> {
-		todo!()
-	}
-	
-	async fn age(&self, pk: identity::PublicKey) -> Result<Option<Age>> {
-		todo!()
-	}
-}
-
-
 type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 type Swarm = swarm::Swarm<Behaviour>;
@@ -475,6 +514,23 @@ async fn main() -> Result<()> {
     	None
     };
 
+    let algorithm: lib_cryptography_algorithm_agile::Tag = identity::algorithm_of(conf.as_ref());
+    let node_path: std::path::PathBuf = identity::path_of(conf.as_ref());
+    let node_pair: identity::Pair = identity::load_or_generate(&node_path, algorithm)?;
+    let (node_public_key, node_secret_key) = node_pair.into();
+
+    log::info!("node identity algorithm: {:?}, kept at {}", algorithm, node_path.display());
+
+    let account: Option<Address> = if let Some(conf) = &conf
+    && let Some(chain) = &conf.chain
+    && let Some(account) = &chain.account {
+        Some(hex::decode(account)?.into())
+    } else {
+        None
+    };
+
+    let dns: dns::Handle = dns::Handle::from_config(conf.as_ref().and_then(|conf| conf.chain.as_ref()), account.to_owned(), &node_secret_key)?;
+
     let version: &str = env!("CARGO_PKG_VERSION");
     let protocol_version: String = format!("/an/{}", version);
     let protocol_name: libp2p::StreamProtocol = libp2p::StreamProtocol::new("/an");
@@ -543,6 +599,8 @@ async fn main() -> Result<()> {
 
     log::info!("peer identity initialized: {:?}", local_peer_id);
 
+    let binding: identity::binding::Binding = identity::binding::Binding::new(&node_public_key, &node_secret_key, &local_keypair)?;
+
     let mut quic_config: quic::Config = quic::Config::new(&local_keypair);
     quic_config.handshake_timeout = std::time::Duration::from_millis(3000);
     quic_config.keep_alive_interval = std::time::Duration::from_secs(10);
@@ -923,6 +981,17 @@ async fn main() -> Result<()> {
         .interval(std::time::Duration::from_secs(5))
         .build();
 
+    let peer_registry: sub_system::peer_registry::PeerRegistry = sub_system::peer_registry::PeerRegistry::default();
+    let attestations: sub_system::attestation::Attestations = sub_system::attestation::Attestations::new(std::time::Duration::from_mins(10));
+
+    let attestation: sub_system::attestation::Attestation = sub_system::attestation::Attestation::builder()
+        .dns(dns.to_owned())
+        .maybe_account(dns.account().cloned())
+        .public_key(node_public_key.to_owned())
+        .secret_key(node_secret_key.to_owned())
+        .attestations(attestations.to_owned())
+        .build();
+
     let mut sub_system_bus: sub_system::Bus = sub_system::Bus::default();
     sub_system_bus.add_system(bootstrap);
     // sub_system_bus.add_system(connection_manager);
@@ -932,6 +1001,35 @@ async fn main() -> Result<()> {
     sub_system_bus.add_system(sub_system::metadata::Metadata);
     sub_system_bus.add_system(sub_system::monitor::Monitor);
     sub_system_bus.add_system(sub_system::session_manager::SessionManager::default());
+    sub_system_bus.add_system(sub_system::stream::Stream::<sub_system::handshake::Protocol>::default());
+    sub_system_bus.add_system(sub_system::handshake::Handshake::new(binding, peer_registry.to_owned())?);
+    sub_system_bus.add_system(attestation);
+
+    #[cfg(any(feature = "client", feature = "malicious_client"))]
+    {
+        sub_system_bus.add_system(sub_system::stream::Stream::<sub_system::broker::An>::default());
+        sub_system_bus.add_system(sub_system::seal::Seal::<sub_system::broker::An>::client(dns.to_owned()));
+    }
+
+    #[cfg(any(feature = "server", feature = "malicious_server"))]
+    {
+        let agreement_secret_key: sub_system::seal::SecretKey = sub_system::seal::load_or_generate(&sub_system::seal::path_of(&node_path))?;
+
+        sub_system_bus.add_system(sub_system::stream::Stream::<sub_system::broker::An>::default());
+
+        if let Some(conf) = &conf
+        && let Some(server) = &conf.server
+        && let Some(domain) = &server.domain {
+            sub_system_bus.add_system(sub_system::seal::Seal::<sub_system::broker::An>::server(dns.to_owned(), Domain(domain.to_owned()), agreement_secret_key));
+        } else {
+            log::warn!("no server domain configured, serving without end to end sessions");
+        }
+    }
+
+    #[cfg(feature = "relay")]
+    {
+        sub_system_bus.add_system(sub_system::broker::Broker::new(dns.to_owned(), peer_registry.to_owned(), identity::policy_of(conf.as_ref())));
+    }
 
     cfg_if::cfg_if!(
         if #[cfg(feature = "malicious_relay")] {
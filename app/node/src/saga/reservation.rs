@@ -8,6 +8,16 @@ struct Request {
 	
 }
 
+/// Protocol id carried in the header of reservation packets, so a signed
+/// reservation cannot be passed off as any other packet. Requests are signed
+/// with the node [`identity::Algorithm`]; which tags are taken is up to the
+/// relay's [`lib_packet::policy::Policy`].
+struct Protocol;
+
+impl lib_packet::Protocol for Protocol {
+	const ID: u16 = 0x0001;
+}
+
 impl TryFrom<lib_packet::Unsigned> for Request {
 	type Error = Box<dyn std::error::Error>;
 	
@@ -16,17 +26,22 @@ impl TryFrom<lib_packet::Unsigned> for Request {
 	}
 }
 
-#[derive(serde::Serialize)]
-#[derive(serde::Deserialize)]
-pub enum Reservation<T> {
-	Parse(bytes::Bytes),
+pub enum Reservation {
+	/// `replay` is shared by every reservation the relay parses, so one
+	/// signed request only ever reserves once.
+	Parse {
+		content: bytes::Bytes,
+		dns: dns::Handle,
+		policy: lib_packet::policy::Policy,
+		replay: std::sync::Arc<std::sync::Mutex<lib_packet::replay::Memory>>
+	},
 	Validation {
 		key: String,
 		pk: identity::PublicKey,
 		src: libp2p::Multiaddr,
 		dst: libp2p::Multiaddr,
 		ttl: std::time::Instant,
-		dns: T
+		dns: dns::Handle
 	},
 	Connection,
 	ConnectionOngoing {
@@ -36,8 +51,11 @@ pub enum Reservation<T> {
 		signer: identity::PublicKey
 	},
 	ProofInbound,
+	/// Every party of `transit` signed `packet`, see [`Transit::packet`].
 	ProofSubmission {
-		dns: T
+		dns: dns::Handle,
+		transit: Transit,
+		packet: TransitPacket
 	},
 	Renewal,
 	Expiration,
@@ -51,15 +69,13 @@ pub enum Reservation<T> {
 	}
 }
 
-impl<T> Unique for Reservation<T> {
+impl Unique for Reservation {
 	fn key(&self) -> &str {
     	""
 	}
 }
 
-impl<T> Saga for Reservation<T>
-where
-	T: Dns {	
+impl Saga for Reservation {
 	fn next(
 		self,
 		swarm: &mut Swarm,
@@ -67,24 +83,49 @@ where
 		queue: &mut dyn FnMut(Event)
 	) -> Self {
 		match self {
-			Self::Parse(bytes) => {
-				let content: Vec<_> = bytes.to_vec();
+			Self::Parse {
+				content,
+				dns,
+				policy,
+				replay
+			} => {
+				let content: Vec<_> = content.to_vec();
 				let content: bytes::Bytes = content.into();
-				let content: lib_bytes::NonEmpty = content.try_into().unwrap();
-				let content: lib_packet::Packet<
-					Request, 
-					lib_cryptography_algorithm_ed25519::Ed25519Algorithm, 
-					lib_packet::IsUnsetProtocol,
-					lib_cryptography::public_key::PublicKey<
-						lib_cryptography_algorithm_ed25519::Ed25519Algorithm
-					>,
-					lib_cryptography::signature::Signature<
-						lib_cryptography_algorithm_ed25519::Ed25519Algorithm
-					>
-				> = content.try_into().unwrap();
+				let content: lib_bytes::NonEmpty = match content.try_into() {
+					Ok(content) => content,
+					Err(error) => {
+						log::warn!("dropping empty reservation: {}", error);
+						return Self::Invalid
+					}
+				};
+				let content: identity::single_party::SignedUnverified<Request, Protocol> = match content.try_into() {
+					Ok(content) => content,
+					Err(error) => {
+						log::warn!("dropping malformed reservation: {}", error);
+						return Self::Invalid
+					}
+				};
+				let now: u64 = lib_packet::header::now();
+				let content: identity::single_party::SignedVerified<Request, Protocol> = match content.verify_with_at(&policy, now) {
+					Ok(content) => content,
+					Err(error) => {
+						log::warn!("dropping unverified reservation: {}", error);
+						return Self::Invalid
+					}
+				};
+				let content: identity::single_party::SignedVerified<Request, Protocol> = match replay.lock() {
+					Ok(mut replay) => match content.check_replay(&mut *replay) {
+						Ok(content) => content,
+						Err(error) => {
+							log::warn!("dropping replayed reservation: {}", error);
+							return Self::Invalid
+						}
+					},
+					Err(_) => return Self::Invalid
+				};
 				
 	
-				let (_, public_key, message) = content.into();
+				let (_, public_key, message, _) = content.into();
 				let message: lib_bytes::NonEmpty = message.into();
 				let message: bytes::Bytes = message.into();
 				let segments: std::iter::Filter<_, _> = message.split(|byte| byte.is_ascii_whitespace()).filter(|chunk| !chunk.is_empty());
@@ -111,7 +152,7 @@ where
 					src,
 					dst,
 					ttl,
-					dns: T::default()
+					dns
 				}
 			},
 			Self::Validation {
@@ -129,7 +170,7 @@ where
 				self
 			},
 			Self::Connection => {
-				let event: sub_system::forward::Route<T> = sub_system::forward::Route {
+				let event: sub_system::forward::Route<sub_system::broker::An> = sub_system::forward::Route {
 					src,
 					dst
 				};
@@ -166,10 +207,13 @@ where
 				
 			},
 			Self::ProofSubmission {
-				dns
+				dns,
+				transit,
+				packet
 			} => {
-				Proof {
-					
+				let proof: Proof = match Proof::new(&dns, transit, packet) {
+					Ok(proof) => proof,
+					Err(_) => return Self::Invalid
 				};
 				
 				tokio::runtime::Handle::current().block_on(async move {
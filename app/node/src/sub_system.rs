@@ -1,5 +1,6 @@
 use super::*;
 
+pub mod attestation;
 pub mod bootstrap;
 pub mod broker;
 pub mod connection_manager;
@@ -18,6 +19,7 @@ pub mod peer_registry;
 pub mod relay_killer;
 pub mod routing_monitor;
 pub mod search_engine;
+pub mod seal;
 pub mod self_destruct;
 pub mod session_manager;
 pub mod slug;
@@ -0,0 +1,275 @@
+use super::*;
+
+/// Looks up the on chain account attested by `public_key`, answered with
+/// [`Resolved`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(derive_more::From)]
+pub struct Resolve(pub identity::PublicKey);
+
+/// `account` is `None` when `public_key` attests to no account, or the
+/// lookup failed.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Resolved {
+	pub public_key: identity::PublicKey,
+	pub account: Option<Address>
+}
+
+/// A lookup that failed, answered as [`Resolved`] without an account but
+/// never cached, so the next lookup asks the chain again.
+struct Failed(Resolved);
+
+/// Attested accounts by node identity, as last answered by the [`Dns`]
+/// backend. Cloning shares the cache, so relays pricing and trusting clients
+/// read the entries [`Attestation`] writes.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Attestations {
+	ttl: std::time::Duration,
+	accounts: std::sync::Arc<std::sync::RwLock<std::collections::HashMap<bytes::Bytes, (Option<Address>, std::time::Instant)>>>
+}
+
+impl Attestations {
+	pub fn new(ttl: std::time::Duration) -> Self {
+		Self {
+			ttl,
+			accounts: std::sync::Arc::default()
+		}
+	}
+
+	/// `None` on a miss or an expired entry, `Some(None)` when the identity
+	/// is known to attest to no account.
+	pub fn account_of(&self, public_key: &identity::PublicKey) -> Option<Option<Address>> {
+		let accounts = self.accounts.read().ok()?;
+		let (account, resolved_at) = accounts.get(&key_of(public_key))?;
+		if resolved_at.elapsed() > self.ttl {
+			return None
+		}
+		Some(account.to_owned())
+	}
+
+	pub fn insert(&self, public_key: &identity::PublicKey, account: Option<Address>) {
+		if let Ok(mut accounts) = self.accounts.write() {
+			let now: std::time::Instant = std::time::Instant::now();
+			accounts.retain(|_, (_, resolved_at)| now.duration_since(*resolved_at) <= self.ttl);
+			accounts.insert(key_of(public_key), (account, now));
+		}
+	}
+}
+
+/// Links the node identity to its on chain account and resolves the
+/// accounts of peers.
+///
+/// On the first event the node signs the configured account, in the chain's
+/// own address encoding, with its identity key and submits the signature
+/// through [`Dns::receive_attestation`]. Peers are resolved on [`Resolve`]
+/// and as soon as [`handshake::Bound`] proves who they are; answers are
+/// cached in [`Attestations`] so repeated lookups never reach the chain.
+pub struct Attestation {
+	dns: dns::Handle,
+	account: Option<Address>,
+	public_key: identity::PublicKey,
+	secret_key: identity::SecretKey,
+	attestations: Attestations,
+	pending: std::collections::HashSet<bytes::Bytes>,
+	event_sx: tokio::sync::mpsc::Sender<Event>,
+	event_rx: tokio::sync::mpsc::Receiver<Event>,
+	setup_complete: bool
+}
+
+#[bon::bon]
+impl Attestation {
+	#[builder]
+	pub fn new(
+		dns: dns::Handle,
+		account: Option<Address>,
+		public_key: identity::PublicKey,
+		secret_key: identity::SecretKey,
+		attestations: Attestations
+	) -> Self {
+		let (event_sx, event_rx) = tokio::sync::mpsc::channel(1000);
+		Self {
+			dns,
+			account,
+			public_key,
+			secret_key,
+			attestations,
+			pending: std::collections::HashSet::default(),
+			event_sx,
+			event_rx,
+			setup_complete: false
+		}
+	}
+}
+
+impl Attestation {
+	fn submit(&self, account: &Address) -> Result {
+		let message: bytes::Bytes = account.0.to_owned().into();
+		let message: lib_bytes::NonEmpty = message.try_into()?;
+		let message: lib_cryptography::message::Message = message.into();
+		let signature: identity::Signature = <identity::Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&self.secret_key, &message)?;
+		let dns: dns::Handle = self.dns.to_owned();
+		let public_key: identity::PublicKey = self.public_key.to_owned();
+
+		tokio::spawn(async move {
+			match dns.receive_attestation(public_key, signature).await {
+				Ok(()) => log::info!("attested node identity on chain"),
+				Err(error) => log::error!("failed to attest node identity on chain: {}", error)
+			}
+		});
+
+		Ok(())
+	}
+
+	fn resolve(&mut self, public_key: &identity::PublicKey, queue: &mut dyn FnMut(Event)) {
+		if let Some(account) = self.attestations.account_of(public_key) {
+			queue(Event::from_any(Resolved {
+				public_key: public_key.to_owned(),
+				account
+			}));
+			return
+		}
+		if !self.pending.insert(key_of(public_key)) {
+			return
+		}
+		let dns: dns::Handle = self.dns.to_owned();
+		let public_key: identity::PublicKey = public_key.to_owned();
+		let event_sx: tokio::sync::mpsc::Sender<_> = self.event_sx.to_owned();
+
+		tokio::spawn(async move {
+			let event: Event = match dns.attestation(public_key.to_owned()).await {
+				Ok(account) => Event::from_any(Resolved {
+					public_key,
+					account: Some(account)
+				}),
+				Err(error) => {
+					log::warn!("failed to resolve attestation: {}", error);
+					Event::from_any(Failed(Resolved {
+						public_key,
+						account: None
+					}))
+				}
+			};
+			if event_sx.send(event).await.is_err() {
+				log::error!("failed to send resolved attestation");
+			}
+		});
+	}
+
+	/// Caches the answer of a finished lookup and passes it on.
+	fn answer(&mut self, event: Event, queue: &mut dyn FnMut(Event)) {
+		if let Some(Failed(resolved)) = event.downcast_ref() {
+			self.pending.remove(&key_of(&resolved.public_key));
+			queue(Event::from_any(resolved.to_owned()));
+			return
+		}
+		if let Some(Resolved {
+			public_key,
+			account
+		}) = event.downcast_ref() {
+			self.pending.remove(&key_of(public_key));
+			self.attestations.insert(public_key, account.to_owned());
+		}
+		queue(event);
+	}
+}
+
+impl SubSystem for Attestation {
+	fn receive(
+		&mut self,
+		swarm: &mut Swarm,
+		event: &mut Event,
+		queue: &mut dyn FnMut(Event)
+	) {
+		if !self.setup_complete {
+			self.setup_complete = true;
+
+			match self.account.to_owned() {
+				Some(account) => {
+					if let Err(error) = self.submit(&account) {
+						log::error!("failed to sign attestation: {}", error);
+					}
+				},
+				None => {
+					log::warn!("no chain account configured, node identity left unattested");
+				}
+			}
+		}
+
+		while let Ok(event) = self.event_rx.try_recv() {
+			self.answer(event, queue);
+		}
+
+		if let Some(Resolve(public_key)) = event.downcast_ref() {
+			let public_key: identity::PublicKey = public_key.to_owned();
+			self.resolve(&public_key, queue);
+		}
+
+		if let Some(handshake::Bound {
+			public_key,
+			..
+		}) = event.downcast_ref() {
+			let public_key: identity::PublicKey = public_key.to_owned();
+			self.resolve(&public_key, queue);
+		}
+	}
+}
+
+fn key_of(public_key: &identity::PublicKey) -> bytes::Bytes {
+	let public_key: lib_bytes::NonEmpty = (**public_key).to_owned();
+	public_key.into()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn attestation(dns: &dns::Handle) -> Result<(Attestation, identity::PublicKey, identity::SecretKey)> {
+		let (public_key, secret_key) = identity::generate(identity::DEFAULT_ALGORITHM)?.into();
+		let attestation: Attestation = Attestation::builder()
+			.dns(dns.to_owned())
+			.public_key(public_key.to_owned())
+			.secret_key(secret_key.to_owned())
+			.attestations(Attestations::new(std::time::Duration::from_mins(10)))
+			.build();
+		Ok((attestation, public_key, secret_key))
+	}
+
+	/// Resolves `public_key` and waits for the lookup to finish, returning
+	/// the accounts it answered with.
+	async fn resolve(attestation: &mut Attestation, public_key: &identity::PublicKey) -> Result<Vec<Option<Address>>> {
+		let mut answered: Vec<Option<Address>> = Vec::new();
+		let mut queue = |event: Event| {
+			if let Some(Resolved { account, .. }) = event.downcast_ref() {
+				answered.push(account.to_owned());
+			}
+		};
+		attestation.resolve(public_key, &mut queue);
+		let event: Event = attestation.event_rx.recv().await.ok_or("lookup never finished")?;
+		attestation.answer(event, &mut queue);
+		Ok(answered)
+	}
+
+	#[tokio::test]
+	async fn failed_lookup_is_answered_but_not_cached() -> Result {
+		let chain: std::sync::Arc<std::sync::Mutex<_>> = std::sync::Arc::new(std::sync::Mutex::new(lib_mock_chain::MockChain::new(0)));
+		let account: Address = Address(b"account".to_vec());
+		let dns: dns::Handle = dns::Handle::new(dns::mock::Mock::new(chain, account.to_owned()));
+		let (mut attestation, public_key, secret_key) = attestation(&dns)?;
+		assert_eq!(resolve(&mut attestation, &public_key).await?, vec![None]);
+		assert_eq!(attestation.attestations.account_of(&public_key), None);
+		assert!(attestation.pending.is_empty());
+		let message: bytes::Bytes = account.0.to_owned().into();
+		let message: lib_bytes::NonEmpty = message.try_into()?;
+		let signature: identity::Signature = <identity::Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message.into())?;
+		dns.receive_attestation(public_key.to_owned(), signature).await?;
+		assert_eq!(resolve(&mut attestation, &public_key).await?, vec![Some(account.to_owned())]);
+		assert_eq!(attestation.attestations.account_of(&public_key), Some(Some(account)));
+		Ok(())
+	}
+}
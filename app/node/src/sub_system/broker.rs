@@ -60,21 +60,40 @@ pub struct PendingReservation {
 	dst_stream_established: bool
 }
 
-pub struct Broker<T> {
-	dns: T,
+/// Unexpired reservations a relay remembers to reject replays of.
+const REPLAY_CAPACITY: usize = 65536;
+
+pub struct Broker {
+	dns: dns::Handle,
 	res: Vec<Reservation>,
 	
+	// signature algorithms reservations are accepted from, read from an.toml
+	policy: lib_packet::policy::Policy,
+	
+	// shared with every reservation saga so a signed request reserves once
+	replay: std::sync::Arc<std::sync::Mutex<lib_packet::replay::Memory>>,
+	
+	// reservations are only taken from the peer bound to their owner
+	registry: peer_registry::PeerRegistry,
+	
 	// cache visited ones
 	domain_to_addrs: std::collections::HashMap<Domain, Found>
 }
 
-impl<T> Broker<T> {
-	
+impl Broker {
+	pub fn new(dns: dns::Handle, registry: peer_registry::PeerRegistry, policy: lib_packet::policy::Policy) -> Self {
+		Self {
+			dns,
+			res: Vec::new(),
+			policy,
+			replay: std::sync::Arc::new(std::sync::Mutex::new(lib_packet::replay::Memory::new(REPLAY_CAPACITY))),
+			registry,
+			domain_to_addrs: std::collections::HashMap::new()
+		}
+	}
 }
 
-impl<T> SubSystem for Broker<T> 
-where
-	T: Dns {
+impl SubSystem for Broker {
 	fn receive(
 		&mut self, 
 		swarm: &mut Swarm, 
@@ -132,7 +151,10 @@ where
 					dst_public_key,
 					ttl
 				}) = content {
-					
+					if !self.registry.is_bound(peer, &owner) {
+						log::warn!("dropping reservation from {} not bound to its owner", peer);
+						return
+					}
 					
 					tokio::runtime::Handle::current().block_on(async move {
 						match self.dns.locked_balance_of(owner).await {
@@ -0,0 +1,149 @@
+use super::*;
+
+pub struct Protocol;
+
+impl stream::Protocol for Protocol {
+	fn protocol() -> libp2p::StreamProtocol {
+		libp2p::StreamProtocol::new("/an/handshake/1")
+	}
+}
+
+/// `peer` proved it holds the node identity `public_key`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Bound {
+	pub peer: libp2p::PeerId,
+	pub public_key: identity::PublicKey
+}
+
+/// Sends the local [`identity::binding::Binding`] to every peer on connect
+/// and records the bindings peers send back in the [`peer_registry::PeerRegistry`].
+///
+/// A binding that does not verify against the connection it arrived on is
+/// dropped, leaving the peer unbound; entries go when the last connection to
+/// the peer closes.
+pub struct Handshake {
+	binding: bytes::Bytes,
+	registry: peer_registry::PeerRegistry
+}
+
+impl Handshake {
+	pub fn new(binding: identity::binding::Binding, registry: peer_registry::PeerRegistry) -> Result<Self> {
+		Ok(Self {
+			binding: binding.try_into()?,
+			registry
+		})
+	}
+	
+	/// Verifies the binding `peer` sent against `peer` itself and registers
+	/// the identity it proves.
+	fn bind(&self, peer: &libp2p::PeerId, content: &bytes::Bytes) -> Result<Bound> {
+		let binding: identity::binding::Binding = content.to_owned().try_into()?;
+		binding.verify(peer)?;
+		let public_key: identity::PublicKey = binding.public_key().to_owned();
+		self.registry.insert(peer.to_owned(), public_key.to_owned());
+		Ok(Bound {
+			peer: peer.to_owned(),
+			public_key
+		})
+	}
+}
+
+impl SubSystem for Handshake {
+	fn receive(
+		&mut self,
+		swarm: &mut Swarm,
+		event: &mut Event,
+		queue: &mut dyn FnMut(Event)
+	) {
+		if let Some(SwarmEvent::ConnectionEstablished {
+			peer_id,
+			num_established,
+			..
+		}) = event.downcast_ref() && num_established.get() == 1 {
+			let packet: stream::Packet<Protocol> = stream::Packet::from((peer_id.to_owned(), self.binding.to_owned()));
+			queue(Event::from_any(stream::Outbound::<Protocol>::from(packet)));
+		}
+		
+		if let Some(SwarmEvent::ConnectionClosed {
+			peer_id,
+			num_established: 0,
+			..
+		}) = event.downcast_ref() {
+			self.registry.remove(peer_id);
+		}
+		
+		if let Some(stream::Inbound::<Protocol>(stream::Packet {
+			peer,
+			content,
+			..
+		})) = event.downcast_ref() {
+			match self.bind(peer, content) {
+				Ok(bound) => {
+					queue(Event::from_any(bound));
+				},
+				Err(error) => {
+					log::warn!("rejected identity binding from {}: {}", peer, error);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn binding(keypair: &libp2p::identity::Keypair) -> Result<(identity::PublicKey, bytes::Bytes)> {
+		let (public_key, secret_key) = identity::generate(identity::DEFAULT_ALGORITHM)?.into();
+		let binding: identity::binding::Binding = identity::binding::Binding::new(&public_key, &secret_key, keypair)?;
+		Ok((public_key, binding.try_into()?))
+	}
+
+	#[test]
+	fn verified_binding_registers_peer() -> Result {
+		let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::generate_ed25519();
+		let peer: libp2p::PeerId = keypair.public().to_peer_id();
+		let (public_key, binding) = binding(&keypair)?;
+		let registry: peer_registry::PeerRegistry = peer_registry::PeerRegistry::default();
+		let handshake: Handshake = Handshake::new(binding.to_owned().try_into()?, registry.to_owned())?;
+		let bound: Bound = handshake.bind(&peer, &binding)?;
+		assert_eq!(bound, Bound {
+			peer,
+			public_key: public_key.to_owned()
+		});
+		assert!(registry.is_bound(&peer, &public_key));
+		Ok(())
+	}
+
+	#[test]
+	fn binding_from_other_peer_is_not_registered() -> Result {
+		let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::generate_ed25519();
+		let (public_key, binding) = binding(&keypair)?;
+		let registry: peer_registry::PeerRegistry = peer_registry::PeerRegistry::default();
+		let handshake: Handshake = Handshake::new(binding.to_owned().try_into()?, registry.to_owned())?;
+		let relayed_by: libp2p::PeerId = libp2p::PeerId::random();
+		assert!(handshake.bind(&relayed_by, &binding).is_err());
+		assert!(!registry.is_bound(&relayed_by, &public_key));
+		Ok(())
+	}
+
+	#[test]
+	fn tampered_binding_is_not_registered() -> Result {
+		let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::generate_ed25519();
+		let peer: libp2p::PeerId = keypair.public().to_peer_id();
+		let (public_key, binding) = binding(&keypair)?;
+		let registry: peer_registry::PeerRegistry = peer_registry::PeerRegistry::default();
+		let handshake: Handshake = Handshake::new(binding.to_owned().try_into()?, registry.to_owned())?;
+		let mut tampered: Vec<u8> = binding.to_vec();
+		if let Some(last) = tampered.last_mut() {
+			*last ^= 1;
+		}
+		assert!(handshake.bind(&peer, &tampered.into()).is_err());
+		assert!(registry.public_key_of(&peer).is_none());
+		assert!(!registry.is_bound(&peer, &public_key));
+		Ok(())
+	}
+}
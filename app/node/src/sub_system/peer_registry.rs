@@ -0,0 +1,36 @@
+use super::*;
+
+/// Node identities of connected peers, as proven by their
+/// [`identity::binding::Binding`]. Cloning shares the table, so sub systems
+/// checking records against the connection they arrived on read the same
+/// entries [`handshake::Handshake`] writes.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+pub struct PeerRegistry {
+	peers: std::sync::Arc<std::sync::RwLock<std::collections::HashMap<libp2p::PeerId, identity::PublicKey>>>
+}
+
+impl PeerRegistry {
+	pub fn insert(&self, peer: libp2p::PeerId, public_key: identity::PublicKey) {
+		if let Ok(mut peers) = self.peers.write() {
+			peers.insert(peer, public_key);
+		}
+	}
+	
+	pub fn remove(&self, peer: &libp2p::PeerId) {
+		if let Ok(mut peers) = self.peers.write() {
+			peers.remove(peer);
+		}
+	}
+	
+	pub fn public_key_of(&self, peer: &libp2p::PeerId) -> Option<identity::PublicKey> {
+		self.peers.read().ok()?.get(peer).cloned()
+	}
+	
+	/// Whether `peer` proved it speaks for `public_key`; unbound peers speak
+	/// for no one.
+	pub fn is_bound(&self, peer: &libp2p::PeerId, public_key: &identity::PublicKey) -> bool {
+		self.public_key_of(peer).is_some_and(|bound| &bound == public_key)
+	}
+}
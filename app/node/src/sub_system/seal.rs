@@ -0,0 +1,453 @@
+use super::*;
+
+pub type Agreement = lib_cryptography_algorithm_x25519_kyber1024::X25519Kyber1024Algorithm;
+pub type Aead = lib_cryptography_algorithm_chacha20poly1305::ChaCha20Poly1305Algorithm;
+pub type Session = lib_session::Session<Aead>;
+pub type Pending = lib_session::Pending<Aead>;
+pub type PublicKey = lib_cryptography::public_key::PublicKey<Agreement>;
+pub type SecretKey = lib_cryptography::secret_key::SecretKey<Agreement>;
+
+/// Agreement key as carried in a domain record. The length is checked here
+/// so a garbled record fails the lookup instead of the handshake.
+pub fn decode(key: lib_bytes::NonEmpty) -> Result<PublicKey> {
+	let key: PublicKey = key.into();
+	key.to_fixed::<{ lib_cryptography_algorithm_x25519_kyber1024::PUBLIC_KEY_LEN }>()?;
+	Ok(key)
+}
+
+/// Where a server keeps its agreement secret key, next to the identity at
+/// `identity_path`.
+pub fn path_of(identity_path: &std::path::Path) -> std::path::PathBuf {
+	identity_path.with_extension("agreement.key")
+}
+
+/// Loads the agreement key kept at `path`, generating and storing one the
+/// first time, so the key published for a domain survives restarts.
+pub fn load_or_generate(path: &std::path::Path) -> Result<SecretKey> {
+	if path.exists() {
+		let secret_key: SecretKey = identity::read_secret(path)?.try_into()?;
+		<Agreement as lib_cryptography::AsymmetricKeyDerivationAlgorithm>::public_key(&secret_key)?;
+		return Ok(secret_key)
+	}
+	let pair: lib_cryptography::pair::Pair<Agreement> = <Agreement as lib_cryptography::AsymmetricKeyGenAlgorithm>::generate()?;
+	let (_, secret_key) = pair.into();
+	let mut options: std::fs::OpenOptions = std::fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+	let mut file: std::fs::File = options.open(path)?;
+	std::io::Write::write_all(&mut file, secret_key.expose_secret())?;
+	Ok(secret_key)
+}
+
+/// Asks for an end to end session with the server behind `peer`, sealed to
+/// the agreement key [`Dns::agreement_key`] answers for `domain`.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Open<T> {
+	pub peer: stream::Peer<T>,
+	pub domain: Domain
+}
+
+/// Agreement key of `domain` as looked up for [`Open`], `None` when the
+/// lookup failed.
+#[derive(Debug)]
+pub struct Resolved<T> {
+	pub peer: stream::Peer<T>,
+	pub domain: Domain,
+	pub public_key: Option<PublicKey>
+}
+
+/// Plaintext the application wants delivered to `peer`, sealed into a frame
+/// and sent out as [`stream::Outbound`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(derive_more::Deref)]
+#[derive(derive_more::DerefMut)]
+#[derive(derive_more::From)]
+pub struct Outgoing<T>(pub stream::Packet<T>);
+
+/// Plaintext opened from a [`stream::Inbound`] frame.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(derive_more::Deref)]
+#[derive(derive_more::DerefMut)]
+#[derive(derive_more::From)]
+pub struct Incoming<T>(pub stream::Packet<T>);
+
+/// Both ends hold the session keys; on the client only once the server
+/// confirmed them.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(derive_more::From)]
+pub struct Established<T>(pub stream::Peer<T>);
+
+/// The session with the peer was dropped, either because a frame failed to
+/// open or the handshake was rejected.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(derive_more::From)]
+pub struct Rejected<T>(pub stream::Peer<T>);
+
+/// Seals the application payloads of `T` end to end between client and server.
+///
+/// Only the two endpoints run this sub system; a relay keeps parroting
+/// [`stream::Inbound`] through [`forward::Forward`] and sees nothing but
+/// ciphertext and frame lengths. A server publishes its agreement key in the
+/// record of its domain on the first event. A client looks that key up on
+/// [`Open`] and sends the handshake as its first packet, the server answers
+/// with the accept, and every packet after that in either direction is a
+/// [`lib_session::Session`] frame. Payloads only leave the client once the
+/// accept confirmed the keys.
+pub struct Seal<T> {
+	phantom_data: std::marker::PhantomData<T>,
+	dns: dns::Handle,
+	// set on servers only, clients never accept a handshake
+	identity: Option<(Domain, SecretKey)>,
+	pending: std::collections::HashMap<libp2p::PeerId, Pending>,
+	sessions: std::collections::HashMap<libp2p::PeerId, Session>,
+	event_sx: tokio::sync::mpsc::Sender<Event>,
+	event_rx: tokio::sync::mpsc::Receiver<Event>,
+	setup_complete: bool
+}
+
+impl<T> Seal<T> {
+	pub fn client(dns: dns::Handle) -> Self {
+		Self::new(dns, None)
+	}
+
+	pub fn server(dns: dns::Handle, domain: Domain, secret_key: SecretKey) -> Self {
+		Self::new(dns, Some((domain, secret_key)))
+	}
+
+	fn new(dns: dns::Handle, identity: Option<(Domain, SecretKey)>) -> Self {
+		let (event_sx, event_rx) = tokio::sync::mpsc::channel(1000);
+		Self {
+			phantom_data: std::marker::PhantomData,
+			dns,
+			identity,
+			pending: std::collections::HashMap::default(),
+			sessions: std::collections::HashMap::default(),
+			event_sx,
+			event_rx,
+			setup_complete: false
+		}
+	}
+}
+
+impl<T> Seal<T>
+where
+	T: 'static,
+	T: Send,
+	T: stream::Protocol {
+	fn publish(&self) -> Result {
+		let Some((domain, secret_key)) = &self.identity else {
+			return Ok(())
+		};
+		let public_key: PublicKey = <Agreement as lib_cryptography::AsymmetricKeyDerivationAlgorithm>::public_key(secret_key)?;
+		let public_key: lib_bytes::NonEmpty = public_key.into();
+		let dns: dns::Handle = self.dns.to_owned();
+		let domain: Domain = domain.to_owned();
+
+		tokio::spawn(async move {
+			match dns.publish_agreement_key(domain.to_owned(), public_key).await {
+				Ok(()) => log::info!("published agreement key of {}", domain.0),
+				Err(error) => log::error!("failed to publish agreement key of {}: {}", domain.0, error)
+			}
+		});
+
+		Ok(())
+	}
+
+	fn resolve(&self, peer: &stream::Peer<T>, domain: &Domain) {
+		let dns: dns::Handle = self.dns.to_owned();
+		let peer: stream::Peer<T> = stream::Peer::from(peer.peer);
+		let domain: Domain = domain.to_owned();
+		let event_sx: tokio::sync::mpsc::Sender<_> = self.event_sx.to_owned();
+
+		tokio::spawn(async move {
+			let public_key: Option<PublicKey> = match dns.agreement_key(domain.to_owned()).await.and_then(decode) {
+				Ok(public_key) => Some(public_key),
+				Err(error) => {
+					log::warn!("failed to look up agreement key of {}: {}", domain.0, error);
+					None
+				}
+			};
+			let event: Event = Event::from_any(Resolved {
+				peer,
+				domain,
+				public_key
+			});
+			if event_sx.send(event).await.is_err() {
+				log::error!("failed to send resolved agreement key");
+			}
+		});
+	}
+
+	fn initiate(&mut self, peer: &stream::Peer<T>, domain: &Domain, public_key: Option<&PublicKey>, queue: &mut dyn FnMut(Event)) {
+		let initiated: Option<lib_session::Result<_>> = public_key.map(|public_key| Session::initiate(public_key, &domain.0));
+		match initiated {
+			Some(Ok((encapsulated, pending))) => {
+				let encapsulated: lib_bytes::NonEmpty = encapsulated.into();
+				let encapsulated: bytes::Bytes = encapsulated.into();
+				self.sessions.remove(&peer.peer);
+				self.pending.insert(peer.peer.to_owned(), pending);
+				queue(Event::from_any(stream::Outbound::<T>::from(stream::Packet::from((peer.peer.to_owned(), encapsulated)))));
+			},
+			Some(Err(error)) => {
+				log::error!("failed to initiate session with {}: {}", peer.peer, error);
+				queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.peer))));
+			},
+			None => {
+				queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.peer))));
+			}
+		}
+	}
+
+	fn seal(&mut self, peer: &libp2p::PeerId, content: &bytes::Bytes, queue: &mut dyn FnMut(Event)) {
+		match self.sessions.get_mut(peer).map(|session| session.seal(content)) {
+			Some(Ok(frame)) => {
+				queue(Event::from_any(stream::Outbound::<T>::from(stream::Packet::from((peer.to_owned(), frame)))));
+			},
+			Some(Err(error)) => {
+				log::error!("failed to seal frame for {}: {}", peer, error);
+				self.sessions.remove(peer);
+				queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.to_owned()))));
+			},
+			None => {
+				// never send plaintext, the application has to open a session first
+				log::warn!("dropping outgoing packet for {} without a session", peer);
+			}
+		}
+	}
+
+	fn open(&mut self, peer: &libp2p::PeerId, content: &bytes::Bytes, queue: &mut dyn FnMut(Event)) {
+		if let Some(session) = self.sessions.get_mut(peer) {
+			match session.open(content) {
+				Ok(content) => {
+					queue(Event::from_any(Incoming::<T>::from(stream::Packet::from((peer.to_owned(), content)))));
+				},
+				Err(error) => {
+					log::warn!("dropping session with {} after a frame failed to open: {}", peer, error);
+					self.sessions.remove(peer);
+					queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.to_owned()))));
+				}
+			}
+		} else if let Some(pending) = self.pending.remove(peer) {
+			match pending.confirm(content) {
+				Ok(session) => {
+					self.sessions.insert(peer.to_owned(), session);
+					queue(Event::from_any(Established::<T>::from(stream::Peer::from(peer.to_owned()))));
+				},
+				Err(error) => {
+					log::warn!("server {} did not confirm the session: {}", peer, error);
+					queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.to_owned()))));
+				}
+			}
+		} else if let Some((domain, secret_key)) = &self.identity {
+			let encapsulated: Option<lib_bytes::NonEmpty> = content.to_owned().try_into().ok();
+			let responded: Option<lib_session::Result<(bytes::Bytes, Session)>> = encapsulated.map(|encapsulated| {
+				let encapsulated: lib_cryptography::encapsulated::Encapsulated<Agreement> = encapsulated.into();
+				Session::respond(secret_key, &domain.0, &encapsulated)
+			});
+			match responded {
+				Some(Ok((accept, session))) => {
+					self.sessions.insert(peer.to_owned(), session);
+					queue(Event::from_any(stream::Outbound::<T>::from(stream::Packet::from((peer.to_owned(), accept)))));
+					queue(Event::from_any(Established::<T>::from(stream::Peer::from(peer.to_owned()))));
+				},
+				_ => {
+					log::warn!("rejected handshake from {}", peer);
+					queue(Event::from_any(Rejected::<T>::from(stream::Peer::from(peer.to_owned()))));
+				}
+			}
+		}
+	}
+}
+
+impl<T> SubSystem for Seal<T>
+where
+	T: 'static,
+	T: Send,
+	T: stream::Protocol {
+	fn receive(
+		&mut self,
+		swarm: &mut Swarm,
+		event: &mut Event,
+		queue: &mut dyn FnMut(Event)
+	) {
+		if !self.setup_complete {
+			self.setup_complete = true;
+
+			if let Err(error) = self.publish() {
+				log::error!("failed to derive agreement key: {}", error);
+			}
+		}
+
+		while let Ok(event) = self.event_rx.try_recv() {
+			if let Some(Resolved::<T> {
+				peer,
+				domain,
+				public_key
+			}) = event.downcast_ref() {
+				self.initiate(peer, domain, public_key.as_ref(), queue);
+			}
+		}
+
+		if let Some(Open::<T> {
+			peer,
+			domain
+		}) = event.downcast_ref() {
+			self.resolve(peer, domain);
+		}
+
+		if let Some(Outgoing::<T>(stream::Packet {
+			peer,
+			content,
+			..
+		})) = event.downcast_ref() {
+			self.seal(peer, content, queue);
+		}
+
+		if let Some(stream::Inbound::<T>(stream::Packet {
+			peer,
+			content,
+			..
+		})) = event.downcast_ref() {
+			self.open(peer, content, queue);
+		}
+
+		if let Some(stream::Disconnect::<T>(peer)) = event.downcast_ref() {
+			self.pending.remove(&peer.peer);
+			self.sessions.remove(&peer.peer);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Test;
+
+	impl stream::Protocol for Test {
+		fn protocol() -> libp2p::StreamProtocol {
+			libp2p::StreamProtocol::new("/an/test")
+		}
+	}
+
+	const DOMAIN: &str = "example.an";
+
+	fn dns() -> dns::Handle {
+		let chain: std::sync::Arc<std::sync::Mutex<_>> = std::sync::Arc::new(std::sync::Mutex::new(lib_mock_chain::MockChain::new(0)));
+		dns::Handle::new(dns::mock::Mock::new(chain, Address(Vec::new())))
+	}
+
+	fn pair() -> Result<(PublicKey, SecretKey)> {
+		let pair: lib_cryptography::pair::Pair<Agreement> = <Agreement as lib_cryptography::AsymmetricKeyGenAlgorithm>::generate()?;
+		Ok(pair.into())
+	}
+
+	/// Every packet `f` queued as [`stream::Outbound`], and whether it queued
+	/// [`Established`] or [`Rejected`].
+	fn run(f: impl FnOnce(&mut dyn FnMut(Event))) -> (Vec<bytes::Bytes>, bool, bool) {
+		let mut outbound: Vec<bytes::Bytes> = Vec::new();
+		let mut established: bool = false;
+		let mut rejected: bool = false;
+		f(&mut |event: Event| {
+			if let Some(stream::Outbound::<Test>(packet)) = event.downcast_ref() {
+				outbound.push(packet.content.to_owned());
+			}
+			established |= event.downcast_ref::<Established<Test>>().is_some();
+			rejected |= event.downcast_ref::<Rejected<Test>>().is_some();
+		});
+		(outbound, established, rejected)
+	}
+
+	/// Client and server seals after the handshake, with the accept.
+	fn handshake(public_key: &PublicKey, secret_key: SecretKey) -> (Seal<Test>, Seal<Test>, bytes::Bytes) {
+		let server_peer: libp2p::PeerId = libp2p::PeerId::random();
+		let client_peer: libp2p::PeerId = libp2p::PeerId::random();
+		let mut client: Seal<Test> = Seal::client(dns());
+		let mut server: Seal<Test> = Seal::server(dns(), Domain(DOMAIN.to_owned()), secret_key);
+		let (encapsulated, established, _) = run(|queue| client.initiate(&stream::Peer::from(server_peer), &Domain(DOMAIN.to_owned()), Some(public_key), queue));
+		assert_eq!(encapsulated.len(), 1);
+		assert!(!established);
+		let (accept, established, _) = run(|queue| server.open(&client_peer, &encapsulated[0], queue));
+		assert_eq!(accept.len(), 1);
+		assert!(established);
+		(client, server, accept[0].to_owned())
+	}
+
+	#[test]
+	fn client_is_established_only_after_confirmation() -> Result {
+		let (public_key, secret_key) = pair()?;
+		let (mut client, _, accept) = handshake(&public_key, secret_key);
+		let server_peer: libp2p::PeerId = client.pending.keys().next().copied().ok_or("no pending session")?;
+		let (sealed, ..) = run(|queue| client.seal(&server_peer, &bytes::Bytes::from_static(b"hello"), queue));
+		assert!(sealed.is_empty());
+		let (_, established, rejected) = run(|queue| client.open(&server_peer, &accept, queue));
+		assert!(established);
+		assert!(!rejected);
+		let (sealed, ..) = run(|queue| client.seal(&server_peer, &bytes::Bytes::from_static(b"hello"), queue));
+		assert_eq!(sealed.len(), 1);
+		Ok(())
+	}
+
+	#[test]
+	fn tampered_accept_is_rejected() -> Result {
+		let (public_key, secret_key) = pair()?;
+		let (mut client, _, accept) = handshake(&public_key, secret_key);
+		let server_peer: libp2p::PeerId = client.pending.keys().next().copied().ok_or("no pending session")?;
+		let mut tampered: Vec<u8> = accept.to_vec();
+		if let Some(last) = tampered.last_mut() {
+			*last ^= 1;
+		}
+		let (_, established, rejected) = run(|queue| client.open(&server_peer, &tampered.into(), queue));
+		assert!(!established);
+		assert!(rejected);
+		assert!(client.sessions.is_empty());
+		Ok(())
+	}
+
+	#[test]
+	fn impostor_server_is_rejected() -> Result {
+		let (public_key, _) = pair()?;
+		let (_, impostor) = pair()?;
+		let (mut client, _, accept) = handshake(&public_key, impostor);
+		let server_peer: libp2p::PeerId = client.pending.keys().next().copied().ok_or("no pending session")?;
+		let (_, established, rejected) = run(|queue| client.open(&server_peer, &accept, queue));
+		assert!(!established);
+		assert!(rejected);
+		Ok(())
+	}
+
+	#[test]
+	fn agreement_key_survives_restart() -> Result {
+		let path: std::path::PathBuf = path_of(&std::env::temp_dir().join(format!("an-seal-{}.key", std::process::id())));
+		let first: SecretKey = load_or_generate(&path)?;
+		let second: SecretKey = load_or_generate(&path)?;
+		std::fs::remove_file(&path)?;
+		assert_eq!(first, second);
+		Ok(())
+	}
+
+	#[test]
+	fn garbled_record_fails_to_decode() -> Result {
+		let (public_key, _) = pair()?;
+		let encoded: lib_bytes::NonEmpty = public_key.to_owned().into();
+		assert_eq!(decode(encoded)?, public_key);
+		let garbled: bytes::Bytes = bytes::Bytes::from_static(&[1; 32]);
+		assert!(decode(garbled.try_into()?).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn missing_agreement_key_is_rejected() {
+		let mut client: Seal<Test> = Seal::client(dns());
+		let (outbound, established, rejected) = run(|queue| client.initiate(&stream::Peer::from(libp2p::PeerId::random()), &Domain(DOMAIN.to_owned()), None, queue));
+		assert!(outbound.is_empty());
+		assert!(!established);
+		assert!(rejected);
+	}
+}
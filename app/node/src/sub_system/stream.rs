@@ -98,7 +98,7 @@ pub struct StreamConnectionSuccess<T>(pub Peer<T>);
 #[derive(PartialEq)]
 #[derive(Eq)]
 #[derive(derive_more::From)]
-pub struct Disconnect<T>(Peer<T>);
+pub struct Disconnect<T>(pub Peer<T>);
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -171,6 +171,21 @@ pub struct Stream<T> {
 	setup_complete: bool
 }
 
+impl<T> Default for Stream<T> {
+	fn default() -> Self {
+		let (event_sx, event_rx) = tokio::sync::mpsc::channel(1000);
+		Self {
+			phantom_data: std::marker::PhantomData,
+			src_to_dsts: std::collections::HashMap::default(),
+			peer_to_bytes_sx: std::collections::HashMap::default(),
+			pending: std::collections::HashSet::default(),
+			event_sx,
+			event_rx,
+			setup_complete: false
+		}
+	}
+}
+
 impl<T> SubSystem for Stream<T> 
 where
 	T: 'static,
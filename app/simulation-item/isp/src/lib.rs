@@ -0,0 +1,58 @@
+pub use netfilter::Cidr;
+pub use netfilter::Error;
+pub use netfilter::Port;
+pub use netfilter::Protocol;
+pub use netfilter::Rule;
+
+use netfilter::rule;
+
+/// Censorship applied to traffic the isp forwards between the lans it
+/// connects.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Policy {
+    pub blocked_ips: Vec<Cidr>,
+    pub blocked_ports: Vec<Port>,
+
+    /// Udp packets per second allowed for each source and destination pair,
+    /// anything above is dropped.
+    pub udp_packets_per_sec: Option<u32>
+}
+
+/// Iptables invocations, without the leading `iptables`, that enforce
+/// `policy` on forwarded traffic. Drops are evaluated before the final
+/// accept so an empty policy forwards everything.
+pub fn rules(policy: &Policy) -> Vec<Rule> {
+    let mut out: Vec<Rule> = vec![
+        rule(&["-P", "FORWARD", "DROP"])
+    ];
+    for cidr in &policy.blocked_ips {
+        let cidr: String = cidr.to_string();
+        out.push(rule(&["-A", "FORWARD", "-s", &cidr, "-j", "DROP"]));
+        out.push(rule(&["-A", "FORWARD", "-d", &cidr, "-j", "DROP"]));
+    }
+    for port in &policy.blocked_ports {
+        let protocol: String = port.protocol.to_string();
+        let port: String = port.port.to_string();
+        out.push(rule(&["-A", "FORWARD", "-p", &protocol, "--dport", &port, "-j", "DROP"]));
+        out.push(rule(&["-A", "FORWARD", "-p", &protocol, "--sport", &port, "-j", "DROP"]));
+    }
+    if let Some(rate) = policy.udp_packets_per_sec {
+        let above: String = format!("{}/sec", rate);
+        let burst: String = rate.max(1).to_string();
+        out.push(rule(&[
+            "-A", "FORWARD", "-p", "udp",
+            "-m", "hashlimit",
+            "--hashlimit-name", "udp_throttle",
+            "--hashlimit-mode", "srcip,dstip",
+            "--hashlimit-above", &above,
+            "--hashlimit-burst", &burst,
+            "-j", "DROP"
+        ]));
+    }
+    out.push(rule(&["-A", "FORWARD", "-j", "ACCEPT"]));
+    out
+}
@@ -1,4 +1,39 @@
+use std::time::Duration;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::exit;
+use std::fs::write;
+use std::thread::sleep;
+use clap::Parser as _;
 
-fn main() {
+#[derive(clap::Parser)]
+#[command(about)]
+struct Cli {
+    #[arg(long)]
+    pub block_ip: Vec<isp::Cidr>,
+    #[arg(long)]
+    pub block_port: Vec<isp::Port>,
+    #[arg(long)]
+    pub throttle_udp: Option<u32>
+}
 
-}
\ No newline at end of file
+fn main() -> ! {
+    let cli: Cli = Cli::parse();
+    write("/proc/sys/net/ipv4/ip_forward", "1").unwrap();
+    let policy: isp::Policy = isp::Policy {
+        blocked_ips: cli.block_ip,
+        blocked_ports: cli.block_port,
+        udp_packets_per_sec: cli.throttle_udp
+    };
+    for rule in isp::rules(&policy) {
+        let exit_status: ExitStatus = Command::new("iptables").args(&rule).status().unwrap();
+        if !exit_status.success() {
+            eprintln!("Unable to apply rule: {:?}", rule);
+            exit(1);
+        }
+    }
+    println!("Isp online");
+    loop {
+        sleep(Duration::from_secs(3600));
+    }
+}
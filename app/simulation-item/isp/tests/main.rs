@@ -0,0 +1,55 @@
+fn contains(rules: &[isp::Rule], tokens: &[&str]) -> bool {
+    rules.iter().any(|rule| rule.iter().map(String::as_str).eq(tokens.iter().copied()))
+}
+
+#[test]
+fn empty_policy_forwards_everything() {
+    let rules: Vec<isp::Rule> = isp::rules(&isp::Policy::default());
+    assert_eq!(rules.last().map(Vec::as_slice), Some(["-A", "FORWARD", "-j", "ACCEPT"].map(String::from).as_slice()));
+    assert!(!rules.iter().flatten().any(|token| token == "hashlimit"));
+}
+
+#[test]
+fn blocks_ip_in_both_directions() -> Result<(), isp::Error> {
+    let policy: isp::Policy = isp::Policy {
+        blocked_ips: vec!["10.1.0.0/16".parse()?],
+        ..Default::default()
+    };
+    let rules: Vec<isp::Rule> = isp::rules(&policy);
+    assert!(contains(&rules, &["-A", "FORWARD", "-s", "10.1.0.0/16", "-j", "DROP"]));
+    assert!(contains(&rules, &["-A", "FORWARD", "-d", "10.1.0.0/16", "-j", "DROP"]));
+    Ok(())
+}
+
+#[test]
+fn blocks_port() -> Result<(), isp::Error> {
+    let policy: isp::Policy = isp::Policy {
+        blocked_ports: vec!["udp/4001".parse()?],
+        ..Default::default()
+    };
+    let rules: Vec<isp::Rule> = isp::rules(&policy);
+    assert!(contains(&rules, &["-A", "FORWARD", "-p", "udp", "--dport", "4001", "-j", "DROP"]));
+    Ok(())
+}
+
+#[test]
+fn throttles_udp_before_accept() {
+    let policy: isp::Policy = isp::Policy {
+        udp_packets_per_sec: Some(50),
+        ..Default::default()
+    };
+    let rules: Vec<isp::Rule> = isp::rules(&policy);
+    let throttle: Option<usize> = rules.iter().position(|rule| rule.iter().any(|token| token == "hashlimit"));
+    let accept: Option<usize> = rules.iter().position(|rule| rule.iter().map(String::as_str).eq(["-A", "FORWARD", "-j", "ACCEPT"]));
+    assert!(throttle.is_some());
+    assert!(throttle < accept);
+}
+
+#[test]
+fn rejects_malformed_input() {
+    assert!("10.0.0.0/33".parse::<isp::Cidr>().is_err());
+    assert!("nope".parse::<isp::Cidr>().is_err());
+    assert!("sctp/80".parse::<isp::Port>().is_err());
+    assert!("udp".parse::<isp::Port>().is_err());
+    assert_eq!("10.0.0.1".parse::<isp::Cidr>().map(|cidr| cidr.mask()), Ok(32));
+}
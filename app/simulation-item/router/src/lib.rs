@@ -0,0 +1,126 @@
+pub use netfilter::Rule;
+
+use netfilter::rule;
+
+const HAIRPIN_MARK: &str = "0x1";
+
+/// NAT behaviour emulated by the router, named after the RFC 3489 / RFC 4787
+/// classification so that scenarios can request the exact mapping and
+/// filtering combination a hole punching strategy must survive.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Default)]
+#[derive(clap::ValueEnum)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Profile {
+    /// Endpoint independent mapping and filtering. Every inbound packet on
+    /// the wan is forwarded to the exposed host.
+    FullCone,
+
+    /// Endpoint independent mapping, inbound packets are accepted from any
+    /// port of an address the lan has previously contacted.
+    AddressRestricted,
+
+    /// Endpoint independent mapping, inbound packets are only accepted from
+    /// the exact address and port the lan has contacted.
+    #[default]
+    PortRestricted,
+
+    /// Mapping depends on the destination, hole punching is expected to fail.
+    Symmetric,
+
+    /// Port restricted behaviour where lan hosts cannot reach each other
+    /// through the router's public address. Every other profile loops lan
+    /// traffic to the public address back to the exposed host.
+    NoHairpin
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Interfaces {
+    pub lan: String,
+    pub wan: String,
+
+    /// Address of the router on the wan, the public address lan hosts are
+    /// mapped to.
+    pub public: std::net::Ipv4Addr
+}
+
+impl Profile {
+    pub const fn hairpin(&self) -> bool {
+        !matches!(self, Self::NoHairpin)
+    }
+}
+
+/// Iptables invocations, without the leading `iptables`, that configure the
+/// router for `profile`. `exposed` is the lan host unsolicited inbound
+/// traffic is forwarded to. Profiles that drop such traffic from the wan
+/// still forward it from the lan unless they disable hairpinning.
+pub fn rules(profile: Profile, interfaces: &Interfaces, exposed: Option<std::net::Ipv4Addr>) -> Vec<Rule> {
+    let lan: &str = &interfaces.lan;
+    let wan: &str = &interfaces.wan;
+    let public: String = interfaces.public.to_string();
+    let mut out: Vec<Rule> = vec![
+        rule(&["-P", "INPUT", "DROP"]),
+        rule(&["-P", "FORWARD", "DROP"]),
+        rule(&["-A", "INPUT", "-i", "lo", "-j", "ACCEPT"]),
+        rule(&["-A", "INPUT", "-m", "conntrack", "--ctstate", "ESTABLISHED,RELATED", "-j", "ACCEPT"]),
+        rule(&["-A", "FORWARD", "-m", "conntrack", "--ctstate", "ESTABLISHED,RELATED", "-j", "ACCEPT"])
+    ];
+    match profile {
+        Profile::Symmetric => {
+            out.push(rule(&["-t", "nat", "-A", "POSTROUTING", "-o", wan, "-j", "MASQUERADE", "--random-fully"]));
+        },
+        Profile::FullCone | Profile::AddressRestricted | Profile::PortRestricted | Profile::NoHairpin => {
+            out.push(rule(&["-t", "nat", "-A", "POSTROUTING", "-o", wan, "-j", "MASQUERADE"]));
+        }
+    }
+    match profile {
+        Profile::AddressRestricted => {
+            // remembers every remote address the lan talks to so that replies
+            // from other ports of that address are let through
+            out.push(rule(&["-A", "FORWARD", "-i", lan, "-o", wan, "-m", "recent", "--name", "contacted", "--rdest", "--set", "-j", "ACCEPT"]));
+        },
+        Profile::FullCone | Profile::PortRestricted | Profile::Symmetric | Profile::NoHairpin => {
+            out.push(rule(&["-A", "FORWARD", "-i", lan, "-o", wan, "-j", "ACCEPT"]));
+        }
+    }
+    if let Some(exposed) = exposed {
+        let exposed: String = exposed.to_string();
+        match profile {
+            Profile::FullCone => {
+                out.push(rule(&["-t", "nat", "-A", "PREROUTING", "-i", wan, "-m", "conntrack", "--ctstate", "NEW", "-j", "DNAT", "--to-destination", &exposed]));
+                out.push(rule(&["-A", "FORWARD", "-i", wan, "-o", lan, "-d", &exposed, "-j", "ACCEPT"]));
+            },
+            Profile::AddressRestricted => {
+                out.push(rule(&["-t", "nat", "-A", "PREROUTING", "-i", wan, "-m", "recent", "--name", "contacted", "--rsource", "--rcheck", "-j", "DNAT", "--to-destination", &exposed]));
+                out.push(rule(&["-A", "FORWARD", "-i", wan, "-o", lan, "-d", &exposed, "-m", "recent", "--name", "contacted", "--rsource", "--rcheck", "-j", "ACCEPT"]));
+            },
+            Profile::PortRestricted | Profile::Symmetric | Profile::NoHairpin => {}
+        }
+    }
+    if profile.hairpin() {
+        if let Some(exposed) = exposed {
+            // lan traffic to the public address is sent back into the lan and
+            // masqueraded behind the router so replies return the same way
+            let exposed: String = exposed.to_string();
+            out.push(rule(&["-t", "mangle", "-A", "PREROUTING", "-i", lan, "-d", &public, "-j", "MARK", "--set-mark", HAIRPIN_MARK]));
+            out.push(rule(&["-t", "nat", "-A", "PREROUTING", "-i", lan, "-d", &public, "-j", "DNAT", "--to-destination", &exposed]));
+            out.push(rule(&["-t", "nat", "-A", "POSTROUTING", "-o", lan, "-m", "mark", "--mark", HAIRPIN_MARK, "-j", "MASQUERADE"]));
+        }
+        out.push(rule(&["-A", "FORWARD", "-i", lan, "-o", lan, "-j", "ACCEPT"]));
+    } else {
+        // explicit rather than left to the policy so that lan to lan traffic
+        // stays dropped when callers append accepting rules afterwards
+        out.push(rule(&["-A", "FORWARD", "-i", lan, "-o", lan, "-j", "DROP"]));
+    }
+    out
+}
+
@@ -4,17 +4,32 @@ use std::process::ExitStatus;
 use std::process::exit;
 use std::fs::write;
 use std::thread::sleep;
+use clap::Parser as _;
 
-type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
-
-type Rules = Vec<Vec<&'static str>>;
+#[derive(clap::Parser)]
+#[command(about)]
+struct Cli {
+    #[arg(long, value_enum, default_value_t)]
+    pub profile: router::Profile,
+    #[arg(long, default_value = "eth1")]
+    pub lan: String,
+    #[arg(long, default_value = "eth0")]
+    pub wan: String,
+    #[arg(long)]
+    pub public: std::net::Ipv4Addr,
+    #[arg(long)]
+    pub exposed: Option<std::net::Ipv4Addr>
+}
 
 fn main() -> ! {
+    let cli: Cli = Cli::parse();
     write("/proc/sys/net/ipv4/ip_forward", "1").unwrap();
-    let mut rules: Rules = vec![];
-    rules.extend(nat());
-    rules.extend(firewall());
-    for rule in rules {
+    let interfaces: router::Interfaces = router::Interfaces {
+        lan: cli.lan,
+        wan: cli.wan,
+        public: cli.public
+    };
+    for rule in router::rules(cli.profile, &interfaces, cli.exposed) {
         let exit_status: ExitStatus = Command::new("iptables").args(&rule).status().unwrap();
         if !exit_status.success() {
             eprintln!("Unable to apply rule: {:?}", rule);
@@ -26,16 +41,3 @@ fn main() -> ! {
         sleep(Duration::from_secs(3600));
     }
 }
-
-fn nat() -> Rules {
-    vec![
-        vec!["-t", "nat", "-A", "POSTROUTING", "-o", "eth0", "-j", "MASQUERADE"],
-        vec!["-P", "INPUT", "DROP"]
-    ]
-}
-
-fn firewall() -> Rules {
-    vec![
-        vec!["-P", "INPUT", "DROP"]
-    ]
-}
\ No newline at end of file
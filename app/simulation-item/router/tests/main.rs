@@ -0,0 +1,37 @@
+fn interfaces() -> router::Interfaces {
+    router::Interfaces {
+        lan: String::from("eth0"),
+        wan: String::from("eth1"),
+        public: std::net::Ipv4Addr::new(172, 18, 0, 2)
+    }
+}
+
+fn position(rules: &[router::Rule], tokens: &[&str]) -> Option<usize> {
+    let wanted: router::Rule = netfilter::rule(tokens);
+    rules.iter().position(|rule| *rule == wanted)
+}
+
+#[test]
+fn no_hairpin_drops_lan_to_lan_in_forward() {
+    let rules: Vec<router::Rule> = router::rules(router::Profile::NoHairpin, &interfaces(), Some(std::net::Ipv4Addr::new(10, 0, 0, 2)));
+    assert!(position(&rules, &["-A", "FORWARD", "-i", "eth0", "-o", "eth0", "-j", "DROP"]).is_some());
+    assert!(position(&rules, &["-A", "FORWARD", "-i", "eth0", "-o", "eth0", "-j", "ACCEPT"]).is_none());
+    assert!(rules.iter().all(|rule| !rule.contains(&String::from("addrtype"))));
+    assert!(rules.iter().all(|rule| !rule.contains(&String::from("--set-mark"))));
+}
+
+#[test]
+fn hairpin_loops_the_public_address_back_to_the_exposed_host() {
+    let rules: Vec<router::Rule> = router::rules(router::Profile::PortRestricted, &interfaces(), Some(std::net::Ipv4Addr::new(10, 0, 0, 2)));
+    assert!(position(&rules, &["-t", "nat", "-A", "PREROUTING", "-i", "eth0", "-d", "172.18.0.2", "-j", "DNAT", "--to-destination", "10.0.0.2"]).is_some());
+    assert!(position(&rules, &["-A", "FORWARD", "-i", "eth0", "-o", "eth0", "-j", "ACCEPT"]).is_some());
+}
+
+#[test]
+fn policies_come_before_appended_rules() {
+    for profile in [router::Profile::FullCone, router::Profile::AddressRestricted, router::Profile::PortRestricted, router::Profile::Symmetric, router::Profile::NoHairpin] {
+        let rules: Vec<router::Rule> = router::rules(profile, &interfaces(), None);
+        assert_eq!(position(&rules, &["-P", "INPUT", "DROP"]), Some(0));
+        assert_eq!(position(&rules, &["-P", "FORWARD", "DROP"]), Some(1));
+    }
+}
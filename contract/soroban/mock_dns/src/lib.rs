@@ -6,6 +6,22 @@ use alloc::borrow::ToOwned as _;
 use soroban_sdk::FromVal as _;
 use soroban_sdk::xdr::ToXdr as _;
 
+/// Longest domain name, as in DNS.
+pub const MAX_DOMAIN_LEN: usize = 253;
+
+/// Label of the node's multi party packets, which parties sign transits in.
+pub const MULTI_PARTY_LABEL: &[u8] = b"an-packet-multi-party-v1";
+
+/// Label of the transit content parties sign, ahead of the contract address
+/// so a signature settles on this contract only.
+pub const TRANSIT_LABEL: &[u8] = b"an-mock-dns-transit-v1";
+
+/// Length of a multi party packet header.
+pub const HEADER_LEN: u32 = 37;
+
+/// Tag the node prefixes Ed25519 keys with.
+pub const ED25519_TAG: [u8; 2] = [0x00, 0x01];
+
 #[soroban_sdk::contracttype]
 pub struct ForeignPublicKey(pub soroban_sdk::BytesN<32>);
 
@@ -15,21 +31,205 @@ pub struct ForeignSignature(pub soroban_sdk::BytesN<64>);
 #[soroban_sdk::contracttype]
 pub struct PublicKey(pub soroban_sdk::Address);
 
+/// Prices and terms of the contract, the same as the mock chains' so every
+/// backend charges alike. Fees are in token units, see `lib_economics` for
+/// how they combine.
+#[soroban_sdk::contracttype]
+#[derive(Clone)]
+pub struct Pricing {
+	/// Burnt to mint a domain carrying at least `target_information` bits.
+	pub mint_fee: u64,
+	pub target_information: u64,
+	/// Renewal fee of a domain at or above `target_traffic`.
+	pub min_renew_fee: u64,
+	/// Renewal fee of a domain without traffic.
+	pub max_renew_fee: u64,
+	pub target_traffic: u64,
+	/// Basis points of a domain's mint fee charged a year on renewal.
+	pub harberger_tax_rate: u64,
+	/// Paid by the source of a proof to every relay on its path.
+	pub fee: u64,
+	/// Charged on top of `fee` while relays are congested.
+	pub congestion_charge: u64,
+	/// Seconds a mint or renewal registers a domain for.
+	pub term: u64
+}
+
+#[soroban_sdk::contracttype]
+#[derive(Clone)]
+pub struct Registration {
+	pub owner: soroban_sdk::Address,
+	pub expires_at: u64,
+	/// Mint fee paid, the value the Harberger tax is assessed on.
+	pub value: u64,
+	pub taxed_at: u64
+}
+
+/// Tokens escrowed by an account to pay the relays of its transits.
+#[soroban_sdk::contracttype]
+#[derive(Clone)]
+pub struct Lock {
+	pub amount: u64,
+	pub unlock_at: u64
+}
+
+/// Delivery of a session to `domain`, from `src` to `dst` over `relays` in
+/// path order. Every party signs [`Transit::message`], `nonce` tells
+/// transits over the same path apart.
+#[soroban_sdk::contracttype]
+#[derive(Clone)]
+pub struct Transit {
+	pub domain: soroban_sdk::String,
+	pub src: soroban_sdk::BytesN<32>,
+	pub dst: soroban_sdk::BytesN<32>,
+	pub relays: soroban_sdk::Vec<soroban_sdk::BytesN<32>>,
+	pub nonce: u64
+}
+
+/// Transit with the Ed25519 signatures of its parties over
+/// [`Proof::message`], `relay_sigs` in the order of `transit.relays`.
 #[soroban_sdk::contracttype]
+#[derive(Clone)]
 pub struct Proof {
-	src: soroban_sdk::Bytes,
-	dst: soroban_sdk::Bytes
+	pub transit: Transit,
+	/// Header of the multi party packet the signatures were collected in.
+	pub header: soroban_sdk::Bytes,
+	pub src_sig: soroban_sdk::BytesN<64>,
+	pub dst_sig: soroban_sdk::BytesN<64>,
+	pub relay_sigs: soroban_sdk::Vec<soroban_sdk::BytesN<64>>
+}
+
+impl Transit {
+	/// `TRANSIT_LABEL || contract || transit`, both as XDR: the content of
+	/// the multi party packet parties sign the transit in.
+	pub fn message(&self, environment: &soroban_sdk::Env, contract: &soroban_sdk::Address) -> soroban_sdk::Bytes {
+		let mut out: soroban_sdk::Bytes = soroban_sdk::Bytes::from_slice(environment, TRANSIT_LABEL);
+		out.append(&Clone::clone(contract).to_xdr(environment));
+		out.append(&Clone::clone(self).to_xdr(environment));
+		out
+	}
+}
+
+impl Proof {
+	/// `MULTI_PARTY_LABEL || header || signer_count (2) || signers ||
+	/// content`, signers src, relays in path order, then dst, each tagged as
+	/// the node tags its keys, and the content [`Transit::message`] for
+	/// `contract`.
+	pub fn message(&self, environment: &soroban_sdk::Env, contract: &soroban_sdk::Address) -> soroban_sdk::Bytes {
+		let transit: &Transit = &self.transit;
+		let count: u16 = u16::try_from(transit.relays.len() + 2).expect("overflow");
+		let mut out: soroban_sdk::Bytes = soroban_sdk::Bytes::from_slice(environment, MULTI_PARTY_LABEL);
+		out.append(&self.header);
+		out.extend_from_slice(&count.to_be_bytes());
+		for signer in core::iter::once(Clone::clone(&transit.src)).chain(transit.relays.iter()).chain(core::iter::once(Clone::clone(&transit.dst))) {
+			out.extend_from_slice(&ED25519_TAG);
+			out.append(&signer.into());
+		}
+		out.append(&transit.message(environment, contract));
+		out
+	}
+}
+
+#[soroban_sdk::contractevent(topics = ["attest"], data_format = "single-value")]
+pub struct Attested {
+	#[topic]
+	pub owner: soroban_sdk::Address,
+	pub foreign_public_key: soroban_sdk::BytesN<32>
+}
+
+#[soroban_sdk::contractevent(topics = ["commitment_open"], data_format = "single-value")]
+pub struct CommitmentOpened {
+	#[topic]
+	pub relay: soroban_sdk::Address,
+	pub account: soroban_sdk::Address
+}
+
+#[soroban_sdk::contractevent(topics = ["commitment_accept"], data_format = "single-value")]
+pub struct CommitmentAccepted {
+	#[topic]
+	pub relay: soroban_sdk::Address,
+	pub account: soroban_sdk::Address
+}
+
+#[soroban_sdk::contractevent(topics = ["mint"])]
+pub struct Minted {
+	#[topic]
+	pub account: soroban_sdk::Address,
+	#[topic]
+	pub domain: soroban_sdk::String,
+	pub fee: u64,
+	pub expires_at: u64
+}
+
+#[soroban_sdk::contractevent(topics = ["renew"])]
+pub struct Renewed {
+	#[topic]
+	pub account: soroban_sdk::Address,
+	#[topic]
+	pub domain: soroban_sdk::String,
+	pub fee: u64,
+	pub expires_at: u64
+}
+
+#[soroban_sdk::contractevent(topics = ["agreement_key"], data_format = "single-value")]
+pub struct AgreementKeyPublished {
+	#[topic]
+	pub domain: soroban_sdk::String,
+	pub key: soroban_sdk::Bytes
+}
+
+#[soroban_sdk::contractevent(topics = ["lock"])]
+pub struct Locked {
+	#[topic]
+	pub owner: soroban_sdk::Address,
+	pub amount: u64,
+	pub unlock_at: u64
+}
+
+#[soroban_sdk::contractevent(topics = ["unlock"], data_format = "single-value")]
+pub struct Unlocked {
+	#[topic]
+	pub owner: soroban_sdk::Address,
+	pub amount: u64
+}
+
+#[soroban_sdk::contractevent(topics = ["proof"], data_format = "single-value")]
+pub struct ProofSubmitted {
+	#[topic]
+	pub src: soroban_sdk::Address,
+	#[topic]
+	pub id: soroban_sdk::BytesN<32>,
+	pub paid: u64
+}
+
+#[soroban_sdk::contractevent(topics = ["claim"], data_format = "single-value")]
+pub struct Claimed {
+	#[topic]
+	pub account: soroban_sdk::Address,
+	pub amount: u64
 }
 
 #[soroban_sdk::contracttype]
 pub enum MemoryStoreKey {
 	Tkn,
 	Nft,
-	RenewMinFee,
-	RenewMaxFee,
-	RenewTargetTraffic,
+	Pricing,
 	Attestation(soroban_sdk::Address),
-	AttestationOwner(soroban_sdk::BytesN<32>)
+	AttestationOwner(soroban_sdk::BytesN<32>),
+	Domain(soroban_sdk::String),
+	AgreementKey(soroban_sdk::String),
+	Traffic(soroban_sdk::String),
+	Lock(soroban_sdk::Address),
+	Claimable(soroban_sdk::Address),
+	// relays waiting on an account to accept their commitment
+	PendingCommitments(soroban_sdk::Address),
+	// (relay, account) to whether the account accepted
+	Commitment(soroban_sdk::Address, soroban_sdk::Address),
+	Spent(soroban_sdk::Address),
+	Claimed(soroban_sdk::Address),
+	FirstSeen(soroban_sdk::Address),
+	// sha256 of a submitted transit
+	Proof(soroban_sdk::BytesN<32>)
 }
 
 #[soroban_sdk::contract]
@@ -37,125 +237,477 @@ pub struct Main;
 
 #[soroban_sdk::contractimpl]
 impl Main {
+	/// `tkn` has to be owned by this contract, which burns fees from it.
 	pub fn wake(
 		environment: soroban_sdk::Env,
 		tkn: soroban_sdk::Address,
 		nft: soroban_sdk::Address,
-		min_fee: soroban_sdk::U256,
-		max_fee: soroban_sdk::U256,
-		harberger_tax_rate: soroban_sdk::U256,
-		target_traffic: soroban_sdk::U256
+		pricing: Pricing
 	) {
-		if environment.storage().persistent().has(&MemoryStoreKey::Tkn) 
+		if environment.storage().persistent().has(&MemoryStoreKey::Tkn)
 		|| environment.storage().persistent().has(&MemoryStoreKey::Nft) {
 			panic!("awoken")
 		}
 
 		environment.storage().persistent().set(&MemoryStoreKey::Tkn, &tkn);
 		environment.storage().persistent().set(&MemoryStoreKey::Nft, &nft);
-		environment.storage().persistent().set(&MemoryStoreKey::RenewMinFee, &min_fee);
-		environment.storage().persistent().set(&MemoryStoreKey::RenewMaxFee, &max_fee);
-		environment.storage().persistent().set(&MemoryStoreKey::RenewTargetTraffic, &target_traffic);
+		environment.storage().persistent().set(&MemoryStoreKey::Pricing, &pricing);
+	}
+
+	pub fn pricing(environment: soroban_sdk::Env) -> Pricing {
+		environment.storage().persistent().get(&MemoryStoreKey::Pricing).expect("set on awakening")
 	}
-	
-	pub fn attestation(environment: soroban_sdk::Env, foreign_public_key: soroban_sdk::BytesN<32>) -> Option<soroban_sdk::Address> {		
+
+	pub fn attestation(environment: soroban_sdk::Env, foreign_public_key: soroban_sdk::BytesN<32>) -> Option<soroban_sdk::Address> {
 		environment.storage().persistent().get(&MemoryStoreKey::AttestationOwner(foreign_public_key))
 	}
-	
+
+	/// Key attested to `owner`, without its tag.
+	pub fn foreign_attestation(environment: soroban_sdk::Env, owner: soroban_sdk::Address) -> Option<soroban_sdk::BytesN<32>> {
+		environment.storage().persistent()
+			.get(&MemoryStoreKey::Attestation(owner))
+			.map(|foreign_public_key: ForeignPublicKey| foreign_public_key.0)
+	}
+
 	pub fn sign_attestation(
-		environment: soroban_sdk::Env, 
-		owner: soroban_sdk::Address, 
+		environment: soroban_sdk::Env,
+		owner: soroban_sdk::Address,
 		foreign_public_key: ForeignPublicKey,
 		foreign_signature: ForeignSignature
 	) {
 		owner.require_auth();
-		
+
 		let message: soroban_sdk::Bytes = owner.to_owned().to_xdr(&environment);
 		let raw_pub_key: &soroban_sdk::BytesN<32> = &foreign_public_key.0;
 		let raw_sig: &soroban_sdk::BytesN<64> = &foreign_signature.0;
-		
+
 		environment.crypto().ed25519_verify(raw_pub_key, &message, raw_sig);
 		environment.storage().persistent().set(&MemoryStoreKey::Attestation(Clone::clone(&owner)), &foreign_public_key);
-		environment.storage().persistent().set(&MemoryStoreKey::AttestationOwner(Clone::clone(&foreign_public_key)), &owner);
-		environment.events().publish((soroban_sdk::symbol_short!("attest"), owner), foreign_public_key);
+		environment.storage().persistent().set(&MemoryStoreKey::AttestationOwner(Clone::clone(&foreign_public_key.0)), &owner);
+		Self::seen(&environment, &owner);
+		Attested {
+			owner,
+			foreign_public_key: foreign_public_key.0
+		}.publish(&environment);
+	}
+
+	/// `relay` offers to serve `account`, which it takes once
+	/// [`Main::accept_commitment`] is called.
+	pub fn open_commitment(environment: soroban_sdk::Env, relay: soroban_sdk::Address, account: soroban_sdk::Address) {
+		relay.require_auth();
+
+		let mut pending: soroban_sdk::Vec<soroban_sdk::Address> = environment.storage().persistent()
+			.get(&MemoryStoreKey::PendingCommitments(Clone::clone(&account)))
+			.unwrap_or(soroban_sdk::Vec::new(&environment));
+
+		if !pending.contains(&relay) {
+			pending.push_back(Clone::clone(&relay));
+		}
+
+		environment.storage().persistent().set(&MemoryStoreKey::PendingCommitments(Clone::clone(&account)), &pending);
+		environment.storage().persistent().set(&MemoryStoreKey::Commitment(Clone::clone(&relay), Clone::clone(&account)), &false);
+		CommitmentOpened {
+			relay,
+			account
+		}.publish(&environment);
 	}
-	
-	pub fn mint(environment: soroban_sdk::Env, account: soroban_sdk::Address) {
+
+	/// Accepts every commitment opened to `account` since the last call.
+	pub fn accept_commitment(environment: soroban_sdk::Env, account: soroban_sdk::Address) {
 		account.require_auth();
-	
-		let event: soroban_sdk::events::Events = environment.events();
-		
-		let token_address: soroban_sdk::Address = environment.storage().persistent().get(&MemoryStoreKey::Tkn).unwrap();
-
-	    environment.invoke_contract::<()>(
-	        &token_address, 
-	        &soroban_sdk::symbol_short!("burn"),
-	        soroban_sdk::vec![
-		        &environment, 
-		        soroban_sdk::Val::from_val(&environment, &account),
-				soroban_sdk::Val::from_val(&environment, &299)
-		    ]
-	    );
-		
-		environment.events().publish((soroban_sdk::symbol_short!("mint"), account), ());
-	}
-	
-	pub fn renew(environment: soroban_sdk::Env) {
-		Self::fee_rational(min_fee, max_fee, traffic, target_traffic);
-		
-	    environment.invoke_contract(
-	        &token_address, 
-	        &soroban_sdk::symbol_short!("burn"),
-	        soroban_sdk::vec![
-		        &environment, 
-		        soroban_sdk::Val::from_val(&environment, &account),
-				soroban_sdk::Val::from_val(&environment, &)
-		    ]
-	    );
-	}
-
-	pub fn lock(environment: soroban_sdk::Env, owner: soroban_sdk::Address, amount: soroban_sdk::U256) {
+
+		let pending: soroban_sdk::Vec<soroban_sdk::Address> = environment.storage().persistent()
+			.get(&MemoryStoreKey::PendingCommitments(Clone::clone(&account)))
+			.unwrap_or(soroban_sdk::Vec::new(&environment));
+
+		if pending.is_empty() {
+			panic!("no pending commitment")
+		}
+
+		environment.storage().persistent().remove(&MemoryStoreKey::PendingCommitments(Clone::clone(&account)));
+
+		for relay in pending.iter() {
+			environment.storage().persistent().set(&MemoryStoreKey::Commitment(Clone::clone(&relay), Clone::clone(&account)), &true);
+			CommitmentAccepted {
+				relay,
+				account: Clone::clone(&account)
+			}.publish(&environment);
+		}
+	}
+
+	pub fn is_committed(environment: soroban_sdk::Env, relay: soroban_sdk::Address, account: soroban_sdk::Address) -> bool {
+		environment.storage().persistent().get(&MemoryStoreKey::Commitment(relay, account)).unwrap_or(false)
+	}
+
+	/// Registers an unowned or expired `domain` to `account` for one term,
+	/// burning its [`Main::mint_fee`].
+	pub fn mint(environment: soroban_sdk::Env, account: soroban_sdk::Address, domain: soroban_sdk::String) {
+		account.require_auth();
+
+		if Self::owner_of(Clone::clone(&environment), Clone::clone(&domain)).is_some() {
+			panic!("domain taken")
+		}
+
+		let pricing: Pricing = Self::pricing(Clone::clone(&environment));
+		let fee: u64 = Self::mint_fee(Clone::clone(&environment), Clone::clone(&domain));
+		let now: u64 = environment.ledger().timestamp();
+		let registration: Registration = Registration {
+			owner: Clone::clone(&account),
+			expires_at: now.saturating_add(pricing.term),
+			value: fee,
+			taxed_at: now
+		};
+
+		Self::burn(&environment, &account, fee);
+		environment.storage().persistent().set(&MemoryStoreKey::Domain(Clone::clone(&domain)), &registration);
+		// a new owner never inherits the server key of the last one
+		environment.storage().persistent().remove(&MemoryStoreKey::AgreementKey(Clone::clone(&domain)));
+		Minted {
+			account,
+			domain,
+			fee,
+			expires_at: registration.expires_at
+		}.publish(&environment);
+	}
+
+	/// Extends `domain` by one term from its current expiry, burning its
+	/// [`Main::renew_fee`].
+	pub fn renew(environment: soroban_sdk::Env, account: soroban_sdk::Address, domain: soroban_sdk::String) {
+		account.require_auth();
+
+		let now: u64 = environment.ledger().timestamp();
+		let registration: Registration = environment.storage().persistent()
+			.get(&MemoryStoreKey::Domain(Clone::clone(&domain)))
+			.filter(|registration: &Registration| registration.expires_at > now)
+			.expect("unknown domain");
+
+		if registration.owner != account {
+			panic!("not owner")
+		}
+
+		let pricing: Pricing = Self::pricing(Clone::clone(&environment));
+		let fee: u64 = Self::renew_fee(Clone::clone(&environment), Clone::clone(&domain));
+		let registration: Registration = Registration {
+			expires_at: registration.expires_at.saturating_add(pricing.term),
+			taxed_at: now,
+			..registration
+		};
+
+		Self::burn(&environment, &account, fee);
+		environment.storage().persistent().set(&MemoryStoreKey::Domain(Clone::clone(&domain)), &registration);
+		Renewed {
+			account,
+			domain,
+			fee,
+			expires_at: registration.expires_at
+		}.publish(&environment);
+	}
+
+	pub fn owner_of(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> Option<soroban_sdk::Address> {
+		let now: u64 = environment.ledger().timestamp();
+		environment.storage().persistent()
+			.get(&MemoryStoreKey::Domain(domain))
+			.filter(|registration: &Registration| registration.expires_at > now)
+			.map(|registration: Registration| registration.owner)
+	}
+
+	/// Publishes `key` as the agreement key clients seal their sessions to
+	/// the server of `domain` with, replacing the one before.
+	pub fn publish_agreement_key(environment: soroban_sdk::Env, account: soroban_sdk::Address, domain: soroban_sdk::String, key: soroban_sdk::Bytes) {
+		account.require_auth();
+
+		if Self::owner_of(Clone::clone(&environment), Clone::clone(&domain)) != Some(account) {
+			panic!("not owner")
+		}
+
+		environment.storage().persistent().set(&MemoryStoreKey::AgreementKey(Clone::clone(&domain)), &key);
+		AgreementKeyPublished {
+			domain,
+			key
+		}.publish(&environment);
+	}
+
+	pub fn agreement_key_of(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> Option<soroban_sdk::Bytes> {
+		Self::owner_of(Clone::clone(&environment), Clone::clone(&domain))?;
+		environment.storage().persistent().get(&MemoryStoreKey::AgreementKey(domain))
+	}
+
+	pub fn expiry_of(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> Option<u64> {
+		environment.storage().persistent()
+			.get(&MemoryStoreKey::Domain(domain))
+			.map(|registration: Registration| registration.expires_at)
+	}
+
+	/// Proven transits to `domain`, which lower its renewal fee.
+	pub fn traffic(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> u64 {
+		environment.storage().persistent().get(&MemoryStoreKey::Traffic(domain)).unwrap_or(0)
+	}
+
+	/// Mint fee of `domain`, higher for short or low entropy names.
+	pub fn mint_fee(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> u64 {
+		let pricing: Pricing = Self::pricing(Clone::clone(&environment));
+		let mut name: [u8; MAX_DOMAIN_LEN] = [0; MAX_DOMAIN_LEN];
+		let len: usize = domain.len() as usize;
+
+		if len > MAX_DOMAIN_LEN {
+			panic!("domain too long")
+		}
+
+		domain.copy_into_slice(&mut name[..len]);
+		lib_economics::name_fee(pricing.mint_fee, &name[..len], pricing.target_information)
+	}
+
+	/// Renewal fee of `domain` by its traffic, plus the Harberger tax it
+	/// accrued since minted or last renewed.
+	pub fn renew_fee(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> u64 {
+		let pricing: Pricing = Self::pricing(Clone::clone(&environment));
+		let traffic: u64 = Self::traffic(Clone::clone(&environment), Clone::clone(&domain));
+		let fee: u64 = lib_economics::renew_fee(pricing.min_renew_fee, pricing.max_renew_fee, traffic, pricing.target_traffic);
+		let tax: u64 = match environment.storage().persistent().get::<_, Registration>(&MemoryStoreKey::Domain(domain)) {
+			Some(registration) => {
+				let elapsed: u64 = environment.ledger().timestamp().saturating_sub(registration.taxed_at);
+				lib_economics::harberger_tax(registration.value, pricing.harberger_tax_rate, elapsed)
+			},
+			None => 0
+		};
+		fee.saturating_add(tax)
+	}
+
+	/// Escrows `amount` of `owner`'s tokens to pay the relays of its transits,
+	/// until at least `duration` seconds from now. Locking again adds to the
+	/// escrow and never brings its unlock timestamp forward.
+	pub fn lock(environment: soroban_sdk::Env, owner: soroban_sdk::Address, amount: u64, duration: u64) {
 		owner.require_auth();
-		
-		
+
+		if amount == 0 {
+			panic!("amount must be greater than zero")
+		}
+
+		let lock: Lock = Self::lock_of(&environment, &owner);
+		let lock: Lock = Lock {
+			amount: lock.amount.checked_add(amount).expect("overflow"),
+			unlock_at: lock.unlock_at.max(environment.ledger().timestamp().saturating_add(duration))
+		};
+
+		Self::transfer(&environment, &owner, &environment.current_contract_address(), amount);
+		environment.storage().persistent().set(&MemoryStoreKey::Lock(Clone::clone(&owner)), &lock);
+		Locked {
+			owner,
+			amount,
+			unlock_at: lock.unlock_at
+		}.publish(&environment);
 	}
-	
+
+	/// Hands what is left of `owner`'s escrow back once it unlocked.
+	pub fn unlock(environment: soroban_sdk::Env, owner: soroban_sdk::Address) -> u64 {
+		owner.require_auth();
+
+		let lock: Lock = Self::lock_of(&environment, &owner);
+
+		if lock.unlock_at > environment.ledger().timestamp() {
+			panic!("locked")
+		}
+
+		environment.storage().persistent().remove(&MemoryStoreKey::Lock(Clone::clone(&owner)));
+
+		if lock.amount > 0 {
+			Self::transfer(&environment, &environment.current_contract_address(), &owner, lock.amount);
+		}
+
+		Unlocked {
+			owner,
+			amount: lock.amount
+		}.publish(&environment);
+		lock.amount
+	}
+
+	pub fn locked_balance_of(environment: soroban_sdk::Env, owner: soroban_sdk::Address) -> u64 {
+		Self::lock_of(&environment, &owner).amount
+	}
+
+	pub fn lock_timeout_of(environment: soroban_sdk::Env, owner: soroban_sdk::Address) -> u64 {
+		Self::lock_of(&environment, &owner).unlock_at
+	}
+
+	/// Settles a transit once: checks every party's signature, pays every
+	/// relay the fee out of the locked pool of the source's account and
+	/// counts the transit towards the domain's traffic. Parties are known by
+	/// the keys attested to their accounts. A transit needs a relay and a
+	/// registered domain, so traffic always costs its source something and
+	/// only counts towards domains that exist.
 	pub fn submit_proof(environment: soroban_sdk::Env, proof: Proof) {
-		
+		let transit: &Transit = &proof.transit;
+
+		if transit.relays.is_empty() {
+			panic!("transit without relays")
+		}
+
+		if transit.relays.len() != proof.relay_sigs.len() {
+			panic!("relay signatures do not match relays")
+		}
+
+		// a relay listed twice would be paid twice for one hop
+		for (index, relay) in transit.relays.iter().enumerate() {
+			if transit.relays.iter().skip(index + 1).any(|other| other == relay) {
+				panic!("duplicate relay")
+			}
+		}
+
+		if Self::owner_of(Clone::clone(&environment), Clone::clone(&transit.domain)).is_none() {
+			panic!("domain not registered")
+		}
+
+		if proof.header.len() != HEADER_LEN {
+			panic!("malformed header")
+		}
+
+		let message: soroban_sdk::Bytes = Clone::clone(transit).to_xdr(&environment);
+		let id: soroban_sdk::BytesN<32> = environment.crypto().sha256(&message).into();
+
+		if environment.storage().persistent().has(&MemoryStoreKey::Proof(Clone::clone(&id))) {
+			panic!("proof already submitted")
+		}
+
+		let message: soroban_sdk::Bytes = proof.message(&environment, &environment.current_contract_address());
+
+		environment.crypto().ed25519_verify(&transit.src, &message, &proof.src_sig);
+		environment.crypto().ed25519_verify(&transit.dst, &message, &proof.dst_sig);
+
+		for (relay, relay_sig) in transit.relays.iter().zip(proof.relay_sigs.iter()) {
+			environment.crypto().ed25519_verify(&relay, &message, &relay_sig);
+		}
+
+		let pricing: Pricing = Self::pricing(Clone::clone(&environment));
+		let src: soroban_sdk::Address = Self::attestation(Clone::clone(&environment), Clone::clone(&transit.src)).expect("source not attested");
+		let paid: u64 = pricing.fee.checked_mul(u64::from(transit.relays.len())).expect("overflow");
+		let lock: Lock = Self::lock_of(&environment, &src);
+
+		if lock.amount < paid {
+			panic!("insufficient locked balance")
+		}
+
+		let lock: Lock = Lock {
+			amount: lock.amount - paid,
+			..lock
+		};
+
+		environment.storage().persistent().set(&MemoryStoreKey::Lock(Clone::clone(&src)), &lock);
+		Self::add_spent(&environment, &src, paid);
+
+		for relay in transit.relays.iter() {
+			let relay: soroban_sdk::Address = Self::attestation(Clone::clone(&environment), relay).expect("relay not attested");
+			let claimable: u64 = Self::claimable_of(Clone::clone(&environment), Clone::clone(&relay));
+
+			environment.storage().persistent().set(&MemoryStoreKey::Claimable(relay), &claimable.saturating_add(pricing.fee));
+		}
+
+		let traffic: u64 = Self::traffic(Clone::clone(&environment), Clone::clone(&transit.domain));
+
+		environment.storage().persistent().set(&MemoryStoreKey::Traffic(Clone::clone(&transit.domain)), &traffic.saturating_add(1));
+		environment.storage().persistent().set(&MemoryStoreKey::Proof(Clone::clone(&id)), &true);
+		ProofSubmitted {
+			src,
+			id,
+			paid
+		}.publish(&environment);
 	}
-	
-	pub fn claim(environment: soroban_sdk::Env) {
-		
+
+	pub fn claimable_of(environment: soroban_sdk::Env, account: soroban_sdk::Address) -> u64 {
+		environment.storage().persistent().get(&MemoryStoreKey::Claimable(account)).unwrap_or(0)
 	}
-	
-	fn fee_rational(
-		environment: soroban_sdk::Env,
-		min_fee: soroban_sdk::U256,
-		max_fee: soroban_sdk::U256,
-		traffic: soroban_sdk::U256,
-		target_traffic: soroban_sdk::U256
-	) -> soroban_sdk::U256 {
-		let n_0: soroban_sdk::U256 = soroban_sdk::U256::from_u32(&environment, 0);
-		let n_1: soroban_sdk::U256 = soroban_sdk::U256::from_u32(&environment, 1);
-		
-		if traffic <= n_0 {
-			return max_fee
-		}
-		
-		
-
-		()
-	}
-	
-	fn harberger_tax(environment: soroban_sdk::Env, last_mint: soroban_sdk::U256, tax_rate: soroban_sdk::U256) -> soroban_sdk::U256 {
-		let n_100: soroban_sdk::U256 = soroban_sdk::U256::from_u32(&environment, 100);
-		let out: soroban_sdk::U256 = last_mint.div(&n_100);
-		let out: soroban_sdk::U256 = out.mul(&tax_rate);
-		out
+
+	/// Pays `account` every relay reward it earned, out of the locked pools
+	/// held by the contract, returning the amount.
+	pub fn claim(environment: soroban_sdk::Env, account: soroban_sdk::Address) -> u64 {
+		account.require_auth();
+
+		let amount: u64 = Self::claimable_of(Clone::clone(&environment), Clone::clone(&account));
+
+		if amount == 0 {
+			panic!("nothing to claim")
+		}
+
+		let claimed: u64 = Self::total_claim(Clone::clone(&environment), Clone::clone(&account));
+
+		environment.storage().persistent().remove(&MemoryStoreKey::Claimable(Clone::clone(&account)));
+		environment.storage().persistent().set(&MemoryStoreKey::Claimed(Clone::clone(&account)), &claimed.saturating_add(amount));
+		Self::transfer(&environment, &environment.current_contract_address(), &account, amount);
+		Claimed {
+			account,
+			amount
+		}.publish(&environment);
+		amount
 	}
-	
-	// algorithm to measure complexity of domains
-	fn shannon_entropy(environment: soroban_sdk::Env, domain: soroban_sdk::String) -> soroban_sdk::U256 {
-		soroban_sdk::U256::from_u32(&environment, 1)
+
+	/// Fees `account` burnt on domains and paid relays.
+	pub fn total_spend(environment: soroban_sdk::Env, account: soroban_sdk::Address) -> u64 {
+		environment.storage().persistent().get(&MemoryStoreKey::Spent(account)).unwrap_or(0)
+	}
+
+	/// Relay rewards `account` claimed.
+	pub fn total_claim(environment: soroban_sdk::Env, account: soroban_sdk::Address) -> u64 {
+		environment.storage().persistent().get(&MemoryStoreKey::Claimed(account)).unwrap_or(0)
+	}
+
+	/// Seconds since `account` first attested a key.
+	pub fn age(environment: soroban_sdk::Env, account: soroban_sdk::Address) -> Option<u64> {
+		let first_seen: u64 = environment.storage().persistent().get(&MemoryStoreKey::FirstSeen(account))?;
+		Some(environment.ledger().timestamp().saturating_sub(first_seen))
+	}
+}
+
+impl Main {
+	fn seen(environment: &soroban_sdk::Env, account: &soroban_sdk::Address) {
+		if !environment.storage().persistent().has(&MemoryStoreKey::FirstSeen(Clone::clone(account))) {
+			environment.storage().persistent().set(&MemoryStoreKey::FirstSeen(Clone::clone(account)), &environment.ledger().timestamp());
+		}
+	}
+
+	fn add_spent(environment: &soroban_sdk::Env, account: &soroban_sdk::Address, amount: u64) {
+		let spent: u64 = Self::total_spend(Clone::clone(environment), Clone::clone(account));
+
+		environment.storage().persistent().set(&MemoryStoreKey::Spent(Clone::clone(account)), &spent.saturating_add(amount));
+	}
+
+	fn lock_of(environment: &soroban_sdk::Env, owner: &soroban_sdk::Address) -> Lock {
+		environment.storage().persistent().get(&MemoryStoreKey::Lock(Clone::clone(owner))).unwrap_or(Lock {
+			amount: 0,
+			unlock_at: 0
+		})
+	}
+
+	fn tkn(environment: &soroban_sdk::Env) -> soroban_sdk::Address {
+		environment.storage().persistent().get(&MemoryStoreKey::Tkn).expect("set on awakening")
+	}
+
+	fn burn(environment: &soroban_sdk::Env, account: &soroban_sdk::Address, amount: u64) {
+		Self::add_spent(environment, account, amount);
+
+		let amount: soroban_sdk::U256 = soroban_sdk::U256::from_u128(environment, u128::from(amount));
+
+		environment.invoke_contract::<()>(
+			&Self::tkn(environment),
+			&soroban_sdk::symbol_short!("burn"),
+			soroban_sdk::vec![
+				environment,
+				soroban_sdk::Val::from_val(environment, account),
+				soroban_sdk::Val::from_val(environment, &amount)
+			]
+		);
+	}
+
+	fn transfer(environment: &soroban_sdk::Env, sender: &soroban_sdk::Address, recipient: &soroban_sdk::Address, amount: u64) {
+		let amount: soroban_sdk::U256 = soroban_sdk::U256::from_u128(environment, u128::from(amount));
+
+		environment.invoke_contract::<()>(
+			&Self::tkn(environment),
+			&soroban_sdk::symbol_short!("transfer"),
+			soroban_sdk::vec![
+				environment,
+				soroban_sdk::Val::from_val(environment, sender),
+				soroban_sdk::Val::from_val(environment, recipient),
+				soroban_sdk::Val::from_val(environment, &amount)
+			]
+		);
 	}
 }
@@ -0,0 +1,371 @@
+use soroban_mock_dns::MainClient;
+use soroban_mock_dns::Pricing;
+use soroban_mock_dns::Proof;
+use soroban_mock_dns::Transit;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::testutils::Ledger as _;
+use soroban_sdk::xdr::ToXdr as _;
+
+use ed25519_dalek::Signer as _;
+
+const YEAR: u64 = 365 * 24 * 60 * 60;
+
+struct Setup {
+	environment: soroban_sdk::Env,
+	dns: soroban_sdk::Address,
+	tkn: soroban_sdk::Address
+}
+
+impl Setup {
+	fn new() -> Self {
+		let environment: soroban_sdk::Env = soroban_sdk::Env::default();
+		environment.mock_all_auths();
+		let dns: soroban_sdk::Address = environment.register(soroban_mock_dns::Main, ());
+		let tkn: soroban_sdk::Address = environment.register(soroban_mock_erc_20::Main, ());
+		let nft: soroban_sdk::Address = soroban_sdk::Address::generate(&environment);
+		soroban_mock_erc_20::MainClient::new(&environment, &tkn).wake(
+			&dns,
+			&soroban_sdk::String::from_str(&environment, "an"),
+			&soroban_sdk::String::from_str(&environment, "AN"),
+			&soroban_sdk::U256::from_u32(&environment, 0),
+			&soroban_sdk::U256::from_u32(&environment, 0)
+		);
+		let pricing: Pricing = Pricing {
+			mint_fee: 300,
+			target_information: 16,
+			min_renew_fee: 10,
+			max_renew_fee: 100,
+			target_traffic: 2,
+			harberger_tax_rate: 500,
+			fee: 1,
+			congestion_charge: 2,
+			term: YEAR
+		};
+		MainClient::new(&environment, &dns).wake(&tkn, &nft, &pricing);
+		Self {
+			environment,
+			dns,
+			tkn
+		}
+	}
+
+	fn dns(&self) -> MainClient<'_> {
+		MainClient::new(&self.environment, &self.dns)
+	}
+
+	fn tkn(&self) -> soroban_mock_erc_20::MainClient<'_> {
+		soroban_mock_erc_20::MainClient::new(&self.environment, &self.tkn)
+	}
+
+	fn funded(&self, amount: u32) -> soroban_sdk::Address {
+		let account: soroban_sdk::Address = soroban_sdk::Address::generate(&self.environment);
+		self.tkn().mint(&account, &soroban_sdk::U256::from_u32(&self.environment, amount));
+		account
+	}
+
+	fn balance_of(&self, account: &soroban_sdk::Address) -> soroban_sdk::U256 {
+		self.tkn().balance_of(account)
+	}
+
+	fn u256(&self, amount: u32) -> soroban_sdk::U256 {
+		soroban_sdk::U256::from_u32(&self.environment, amount)
+	}
+
+	fn domain(&self, domain: &str) -> soroban_sdk::String {
+		soroban_sdk::String::from_str(&self.environment, domain)
+	}
+
+	fn advance(&self, seconds: u64) {
+		self.environment.ledger().with_mut(|ledger| ledger.timestamp += seconds);
+	}
+
+	/// Funded account with a fresh Ed25519 key attested to it.
+	fn party(&self, seed: u8) -> (soroban_sdk::Address, ed25519_dalek::SigningKey) {
+		let account: soroban_sdk::Address = self.funded(1000);
+		let key: ed25519_dalek::SigningKey = ed25519_dalek::SigningKey::from_bytes(&[seed; 32]);
+		let message: soroban_sdk::Bytes = account.clone().to_xdr(&self.environment);
+		let message: std::vec::Vec<u8> = message.iter().collect();
+		let signature: ed25519_dalek::Signature = key.sign(&message);
+		self.dns().sign_attestation(
+			&account,
+			&soroban_mock_dns::ForeignPublicKey(self.public_key(&key)),
+			&soroban_mock_dns::ForeignSignature(soroban_sdk::BytesN::from_array(&self.environment, &signature.to_bytes()))
+		);
+		(account, key)
+	}
+
+	fn public_key(&self, key: &ed25519_dalek::SigningKey) -> soroban_sdk::BytesN<32> {
+		soroban_sdk::BytesN::from_array(&self.environment, &key.verifying_key().to_bytes())
+	}
+
+	fn sign(&self, proof: &Proof, key: &ed25519_dalek::SigningKey) -> soroban_sdk::BytesN<64> {
+		let message: soroban_sdk::Bytes = proof.message(&self.environment, &self.dns);
+		let message: std::vec::Vec<u8> = message.iter().collect();
+		soroban_sdk::BytesN::from_array(&self.environment, &key.sign(&message).to_bytes())
+	}
+
+	fn proof(&self, domain: &str, src: &ed25519_dalek::SigningKey, dst: &ed25519_dalek::SigningKey, relays: &[&ed25519_dalek::SigningKey], nonce: u64) -> Proof {
+		let mut keys: soroban_sdk::Vec<soroban_sdk::BytesN<32>> = soroban_sdk::Vec::new(&self.environment);
+		for relay in relays {
+			keys.push_back(self.public_key(relay));
+		}
+		let transit: Transit = Transit {
+			domain: self.domain(domain),
+			src: self.public_key(src),
+			dst: self.public_key(dst),
+			relays: keys,
+			nonce
+		};
+		let unsigned: soroban_sdk::BytesN<64> = soroban_sdk::BytesN::from_array(&self.environment, &[0; 64]);
+		let mut proof: Proof = Proof {
+			transit,
+			header: soroban_sdk::Bytes::from_array(&self.environment, &[0; soroban_mock_dns::HEADER_LEN as usize]),
+			src_sig: unsigned.clone(),
+			dst_sig: unsigned,
+			relay_sigs: soroban_sdk::Vec::new(&self.environment)
+		};
+		for relay in relays {
+			let relay_sig: soroban_sdk::BytesN<64> = self.sign(&proof, relay);
+			proof.relay_sigs.push_back(relay_sig);
+		}
+		proof.src_sig = self.sign(&proof, src);
+		proof.dst_sig = self.sign(&proof, dst);
+		proof
+	}
+}
+
+#[test]
+fn mint_burns_the_name_fee() {
+	let setup: Setup = Setup::new();
+	let alice: soroban_sdk::Address = setup.funded(10_000);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	assert_eq!(setup.dns().mint_fee(&domain), 300);
+	assert_eq!(setup.dns().mint_fee(&setup.domain("aa.an")), lib_economics::name_fee(300, b"aa.an", 16));
+	setup.dns().mint(&alice, &domain);
+	assert_eq!(setup.dns().owner_of(&domain), Some(alice.clone()));
+	assert_eq!(setup.dns().expiry_of(&domain), Some(YEAR));
+	assert_eq!(setup.balance_of(&alice), setup.u256(10_000 - 300));
+	let bob: soroban_sdk::Address = setup.funded(10_000);
+	assert!(setup.dns().try_mint(&bob, &domain).is_err());
+}
+
+#[test]
+fn renew_charges_traffic_fee_and_harberger_tax_and_extends_expiry() {
+	let setup: Setup = Setup::new();
+	let alice: soroban_sdk::Address = setup.funded(10_000);
+	let bob: soroban_sdk::Address = setup.funded(10_000);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	setup.dns().mint(&alice, &domain);
+	assert!(setup.dns().try_renew(&bob, &domain).is_err());
+	assert!(setup.dns().try_renew(&alice, &setup.domain("other.an")).is_err());
+	setup.advance(YEAR / 2);
+	// no traffic, the maximum fee plus half a year of 5% on the mint fee
+	assert_eq!(setup.dns().renew_fee(&domain), 100 + 7);
+	setup.dns().renew(&alice, &domain);
+	assert_eq!(setup.balance_of(&alice), setup.u256(10_000 - 300 - 107));
+	assert_eq!(setup.dns().expiry_of(&domain), Some(2 * YEAR));
+	assert_eq!(setup.dns().renew_fee(&domain), 100);
+	setup.advance(2 * YEAR);
+	assert_eq!(setup.dns().owner_of(&domain), None);
+	assert!(setup.dns().try_renew(&alice, &domain).is_err());
+}
+
+#[test]
+fn fees_follow_lib_economics() {
+	let setup: Setup = Setup::new();
+	let (src, src_key) = setup.party(1);
+	let (_, dst_key) = setup.party(2);
+	let (_, relay_key) = setup.party(3);
+	for name in ["a.an", "aaaa.an", "hello.an", "q7x-f2.an"] {
+		assert_eq!(setup.dns().mint_fee(&setup.domain(name)), lib_economics::name_fee(300, name.as_bytes(), 16));
+	}
+	assert!(setup.dns().try_mint_fee(&setup.domain(&"a".repeat(1024))).is_err());
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	setup.dns().mint(&src, &domain);
+	setup.dns().lock(&src, &10, &60);
+	assert_eq!(setup.dns().renew_fee(&domain), lib_economics::renew_fee(10, 100, 0, 2));
+	setup.dns().submit_proof(&setup.proof("hello.an", &src_key, &dst_key, &[&relay_key], 0));
+	setup.advance(YEAR / 4);
+	let tax: u64 = lib_economics::harberger_tax(300, 500, YEAR / 4);
+	assert_eq!(setup.dns().renew_fee(&domain), lib_economics::renew_fee(10, 100, 1, 2) + tax);
+}
+
+#[test]
+fn agreement_key_is_published_by_the_owner_until_expiry() {
+	let setup: Setup = Setup::new();
+	let alice: soroban_sdk::Address = setup.funded(10_000);
+	let bob: soroban_sdk::Address = setup.funded(10_000);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	let key: soroban_sdk::Bytes = soroban_sdk::Bytes::from_array(&setup.environment, &[1; 32]);
+	assert!(setup.dns().try_publish_agreement_key(&alice, &domain, &key).is_err());
+	setup.dns().mint(&alice, &domain);
+	assert_eq!(setup.dns().agreement_key_of(&domain), None);
+	assert!(setup.dns().try_publish_agreement_key(&bob, &domain, &key).is_err());
+	setup.dns().publish_agreement_key(&alice, &domain, &key);
+	assert_eq!(setup.dns().agreement_key_of(&domain), Some(key.clone()));
+	setup.advance(YEAR);
+	assert_eq!(setup.dns().agreement_key_of(&domain), None);
+	setup.dns().mint(&bob, &domain);
+	assert_eq!(setup.dns().agreement_key_of(&domain), None);
+}
+
+#[test]
+fn lock_escrows_until_unlock_timestamp() {
+	let setup: Setup = Setup::new();
+	let alice: soroban_sdk::Address = setup.funded(1000);
+	setup.dns().lock(&alice, &400, &60);
+	assert_eq!(setup.dns().locked_balance_of(&alice), 400);
+	assert_eq!(setup.dns().lock_timeout_of(&alice), 60);
+	assert_eq!(setup.balance_of(&alice), setup.u256(600));
+	assert_eq!(setup.balance_of(&setup.dns), setup.u256(400));
+	assert!(setup.dns().try_lock(&alice, &0, &60).is_err());
+	setup.advance(30);
+	// a shorter lock keeps the later unlock timestamp
+	setup.dns().lock(&alice, &100, &10);
+	assert_eq!(setup.dns().locked_balance_of(&alice), 500);
+	assert_eq!(setup.dns().lock_timeout_of(&alice), 60);
+	assert!(setup.dns().try_unlock(&alice).is_err());
+	setup.advance(30);
+	assert_eq!(setup.dns().unlock(&alice), 500);
+	assert_eq!(setup.dns().locked_balance_of(&alice), 0);
+	assert_eq!(setup.balance_of(&alice), setup.u256(1000));
+}
+
+#[test]
+fn submit_proof_verifies_every_party_once_and_claim_pays_relays() {
+	let setup: Setup = Setup::new();
+	let (src, src_key) = setup.party(1);
+	let (_, dst_key) = setup.party(2);
+	let (relay_a, relay_a_key) = setup.party(3);
+	let (relay_b, relay_b_key) = setup.party(4);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	setup.dns().mint(&src, &domain);
+	setup.dns().lock(&src, &10, &60);
+	let proof: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&relay_a_key, &relay_b_key], 0);
+	setup.dns().submit_proof(&proof);
+	assert_eq!(setup.dns().locked_balance_of(&src), 8);
+	assert_eq!(setup.dns().claimable_of(&relay_a), 1);
+	assert_eq!(setup.dns().claimable_of(&relay_b), 1);
+	assert_eq!(setup.dns().traffic(&domain), 1);
+	// recorded once
+	assert!(setup.dns().try_submit_proof(&proof).is_err());
+	// the same path again under a new nonce is a new transit
+	setup.dns().submit_proof(&setup.proof("hello.an", &src_key, &dst_key, &[&relay_a_key], 1));
+	assert_eq!(setup.dns().claimable_of(&relay_a), 2);
+	assert_eq!(setup.dns().traffic(&domain), 2);
+	assert_eq!(setup.dns().renew_fee(&domain), 10);
+	assert_eq!(setup.dns().claim(&relay_a), 2);
+	assert_eq!(setup.balance_of(&relay_a), setup.u256(1002));
+	assert_eq!(setup.dns().claimable_of(&relay_a), 0);
+	assert!(setup.dns().try_claim(&relay_a).is_err());
+	assert_eq!(setup.balance_of(&setup.dns), setup.u256(10 - 2));
+	assert_eq!(setup.dns().total_spend(&src), setup.dns().mint_fee(&domain) + 3);
+	assert_eq!(setup.dns().total_claim(&relay_a), 2);
+	assert_eq!(setup.dns().total_claim(&relay_b), 0);
+}
+
+#[test]
+fn commitments_wait_for_the_account() {
+	let setup: Setup = Setup::new();
+	let relay_a: soroban_sdk::Address = setup.funded(0);
+	let relay_b: soroban_sdk::Address = setup.funded(0);
+	let account: soroban_sdk::Address = setup.funded(0);
+	assert!(setup.dns().try_accept_commitment(&account).is_err());
+	setup.dns().open_commitment(&relay_a, &account);
+	setup.dns().open_commitment(&relay_b, &account);
+	assert!(!setup.dns().is_committed(&relay_a, &account));
+	setup.dns().accept_commitment(&account);
+	assert!(setup.dns().is_committed(&relay_a, &account));
+	assert!(setup.dns().is_committed(&relay_b, &account));
+	assert!(setup.dns().try_accept_commitment(&account).is_err());
+}
+
+#[test]
+fn attestation_starts_the_age_of_an_account() {
+	let setup: Setup = Setup::new();
+	let stranger: soroban_sdk::Address = setup.funded(0);
+	assert_eq!(setup.dns().age(&stranger), None);
+	assert_eq!(setup.dns().foreign_attestation(&stranger), None);
+	setup.advance(10);
+	let (account, key) = setup.party(1);
+	assert_eq!(setup.dns().foreign_attestation(&account), Some(setup.public_key(&key)));
+	assert_eq!(setup.dns().age(&account), Some(0));
+	setup.advance(60);
+	assert_eq!(setup.dns().age(&account), Some(60));
+}
+
+#[test]
+fn submit_proof_rejects_bad_signatures_and_unfunded_sources() {
+	let setup: Setup = Setup::new();
+	let (src, src_key) = setup.party(1);
+	let (_, dst_key) = setup.party(2);
+	let (_, relay_key) = setup.party(3);
+	let outsider_key: ed25519_dalek::SigningKey = ed25519_dalek::SigningKey::from_bytes(&[9; 32]);
+	// no lock to pay the relay from
+	let proof: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&relay_key], 0);
+	assert!(setup.dns().try_submit_proof(&proof).is_err());
+	setup.dns().lock(&src, &10, &60);
+	setup.dns().mint(&src, &setup.domain("hello.an"));
+	// a relay signature by someone else
+	let mut forged: Proof = proof.clone();
+	forged.relay_sigs = soroban_sdk::vec![&setup.environment, setup.sign(&proof, &outsider_key)];
+	assert!(setup.dns().try_submit_proof(&forged).is_err());
+	// signatures collected under another header
+	let mut rewrapped: Proof = proof.clone();
+	rewrapped.header = soroban_sdk::Bytes::from_array(&setup.environment, &[1; soroban_mock_dns::HEADER_LEN as usize]);
+	assert!(setup.dns().try_submit_proof(&rewrapped).is_err());
+	// a relay dropped from the signatures
+	let mut short: Proof = proof.clone();
+	short.relay_sigs = soroban_sdk::Vec::new(&setup.environment);
+	assert!(setup.dns().try_submit_proof(&short).is_err());
+	// a relay whose key is attested to no account
+	let unattested: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&outsider_key], 0);
+	assert!(setup.dns().try_submit_proof(&unattested).is_err());
+	setup.dns().submit_proof(&proof);
+	assert_eq!(setup.dns().locked_balance_of(&src), 9);
+}
+
+#[test]
+fn submit_proof_needs_a_relay_and_a_registered_domain() {
+	let setup: Setup = Setup::new();
+	let (src, src_key) = setup.party(1);
+	let (_, dst_key) = setup.party(2);
+	let (_, relay_key) = setup.party(3);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	setup.dns().lock(&src, &10, &60);
+	// free traffic, nothing paid for it
+	let direct: Proof = setup.proof("hello.an", &src_key, &dst_key, &[], 0);
+	setup.dns().mint(&src, &domain);
+	assert!(setup.dns().try_submit_proof(&direct).is_err());
+	// traffic towards a name nobody holds
+	let unknown: Proof = setup.proof("other.an", &src_key, &dst_key, &[&relay_key], 0);
+	assert!(setup.dns().try_submit_proof(&unknown).is_err());
+	setup.advance(YEAR);
+	let expired: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&relay_key], 0);
+	assert!(setup.dns().try_submit_proof(&expired).is_err());
+	assert_eq!(setup.dns().traffic(&domain), 0);
+	assert_eq!(setup.dns().locked_balance_of(&src), 10);
+}
+
+#[test]
+fn submit_proof_rejects_duplicate_relays_and_other_contracts() {
+	let setup: Setup = Setup::new();
+	let (src, src_key) = setup.party(1);
+	let (_, dst_key) = setup.party(2);
+	let (_, relay_key) = setup.party(3);
+	let domain: soroban_sdk::String = setup.domain("hello.an");
+	setup.dns().mint(&src, &domain);
+	setup.dns().lock(&src, &10, &60);
+	let twice: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&relay_key, &relay_key], 0);
+	assert!(setup.dns().try_submit_proof(&twice).is_err());
+	// signed for a deployment elsewhere
+	let mut elsewhere: Proof = setup.proof("hello.an", &src_key, &dst_key, &[&relay_key], 0);
+	let other: soroban_sdk::Address = soroban_sdk::Address::generate(&setup.environment);
+	let message: std::vec::Vec<u8> = elsewhere.message(&setup.environment, &other).iter().collect();
+	let sign = |key: &ed25519_dalek::SigningKey| soroban_sdk::BytesN::from_array(&setup.environment, &key.sign(&message).to_bytes());
+	elsewhere.src_sig = sign(&src_key);
+	elsewhere.dst_sig = sign(&dst_key);
+	elsewhere.relay_sigs = soroban_sdk::vec![&setup.environment, sign(&relay_key)];
+	assert!(setup.dns().try_submit_proof(&elsewhere).is_err());
+	assert_eq!(setup.dns().locked_balance_of(&src), 10);
+}
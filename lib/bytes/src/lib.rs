@@ -19,4 +19,268 @@ impl Into<bytes::Bytes> for NonEmpty {
 	fn into(self) -> bytes::Bytes {
 		self.0
 	}
-}
\ No newline at end of file
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("expected {expected} bytes, received {received}")]
+	Length {
+		expected: usize,
+		received: usize
+	},
+
+	#[error("expected between {min} and {max} bytes, received {received}")]
+	Bounds {
+		min: usize,
+		max: usize,
+		received: usize
+	},
+
+	#[error("needed {needed} bytes, {remaining} remaining")]
+	Eof {
+		needed: usize,
+		remaining: usize
+	},
+
+	#[error("{0} trailing bytes")]
+	Trailing(usize)
+}
+
+/// Exactly `N` bytes, for keys, signatures and other fixed width fields.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub struct Fixed<const N: usize>([u8; N]);
+
+impl<const N: usize> Fixed<N> {
+	pub const LEN: usize = N;
+
+	pub const fn as_array(&self) -> &[u8; N] {
+		&self.0
+	}
+
+	pub const fn into_array(self) -> [u8; N] {
+		self.0
+	}
+}
+
+impl<const N: usize> AsRef<[u8]> for Fixed<N> {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl<const N: usize> AsMut<[u8]> for Fixed<N> {
+	fn as_mut(&mut self) -> &mut [u8] {
+		&mut self.0
+	}
+}
+
+/// So secret fields can be held in a [`zeroize::Zeroizing`].
+impl<const N: usize> zeroize::Zeroize for Fixed<N> {
+	fn zeroize(&mut self) {
+		self.0.zeroize();
+	}
+}
+
+impl<const N: usize> From<[u8; N]> for Fixed<N> {
+	fn from(value: [u8; N]) -> Self {
+		Self(value)
+	}
+}
+
+impl<const N: usize> From<Fixed<N>> for [u8; N] {
+	fn from(value: Fixed<N>) -> Self {
+		value.0
+	}
+}
+
+impl<const N: usize> From<Fixed<N>> for bytes::Bytes {
+	fn from(value: Fixed<N>) -> Self {
+		Self::copy_from_slice(&value.0)
+	}
+}
+
+impl<const N: usize> TryFrom<&[u8]> for Fixed<N> {
+	type Error = Error;
+
+	fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+		let out: [u8; N] = value.try_into().map_err(|_| Error::Length {
+			expected: N,
+			received: value.len()
+		})?;
+		Ok(Self(out))
+	}
+}
+
+impl<const N: usize> TryFrom<bytes::Bytes> for Fixed<N> {
+	type Error = Error;
+
+	fn try_from(value: bytes::Bytes) -> Result<Self, Self::Error> {
+		value.as_ref().try_into()
+	}
+}
+
+impl<const N: usize> TryFrom<NonEmpty> for Fixed<N> {
+	type Error = Error;
+
+	fn try_from(value: NonEmpty) -> Result<Self, Self::Error> {
+		value.0.try_into()
+	}
+}
+
+/// Between `MIN` and `MAX` bytes inclusive, sharing the buffer it was cut
+/// from.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub struct Bounded<const MIN: usize, const MAX: usize>(bytes::Bytes);
+
+impl<const MIN: usize, const MAX: usize> Bounded<MIN, MAX> {
+	pub fn len(&self) -> usize {
+		self.0.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+}
+
+impl<const MIN: usize, const MAX: usize> AsRef<[u8]> for Bounded<MIN, MAX> {
+	fn as_ref(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl<const MIN: usize, const MAX: usize> TryFrom<bytes::Bytes> for Bounded<MIN, MAX> {
+	type Error = Error;
+
+	fn try_from(value: bytes::Bytes) -> Result<Self, Self::Error> {
+		if !(MIN..=MAX).contains(&value.len()) {
+			return Err(Error::Bounds {
+				min: MIN,
+				max: MAX,
+				received: value.len()
+			})
+		}
+		Ok(Self(value))
+	}
+}
+
+impl<const MIN: usize, const MAX: usize> TryFrom<NonEmpty> for Bounded<MIN, MAX> {
+	type Error = Error;
+
+	fn try_from(value: NonEmpty) -> Result<Self, Self::Error> {
+		value.0.try_into()
+	}
+}
+
+/// Only for `MIN` of at least one, anything else fails to compile.
+impl<const MIN: usize, const MAX: usize> From<Bounded<MIN, MAX>> for NonEmpty {
+	fn from(value: Bounded<MIN, MAX>) -> Self {
+		const {
+			assert!(MIN >= 1, "bounded bytes may be empty");
+		}
+		Self(value.0)
+	}
+}
+
+impl<const MIN: usize, const MAX: usize> From<Bounded<MIN, MAX>> for bytes::Bytes {
+	fn from(value: Bounded<MIN, MAX>) -> Self {
+		value.0
+	}
+}
+
+/// Cursor over wire input. Every read either yields exactly what was asked
+/// for or an [`Error`], and slices share the underlying buffer instead of
+/// copying it.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Reader(bytes::Bytes);
+
+impl Reader {
+	pub fn remaining(&self) -> usize {
+		self.0.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	pub fn read(&mut self, len: usize) -> Result<bytes::Bytes, Error> {
+		if self.0.len() < len {
+			return Err(Error::Eof {
+				needed: len,
+				remaining: self.0.len()
+			})
+		}
+		Ok(self.0.split_to(len))
+	}
+
+	pub fn read_fixed<const N: usize>(&mut self) -> Result<Fixed<N>, Error> {
+		let out: bytes::Bytes = self.read(N)?;
+		out.try_into()
+	}
+
+	pub fn read_bounded<const MIN: usize, const MAX: usize>(&mut self, len: usize) -> Result<Bounded<MIN, MAX>, Error> {
+		if !(MIN..=MAX).contains(&len) {
+			return Err(Error::Bounds {
+				min: MIN,
+				max: MAX,
+				received: len
+			})
+		}
+		let out: bytes::Bytes = self.read(len)?;
+		out.try_into()
+	}
+
+	pub fn read_u8(&mut self) -> Result<u8, Error> {
+		let [out] = self.read_fixed::<1>()?.into_array();
+		Ok(out)
+	}
+
+	pub fn read_u16(&mut self) -> Result<u16, Error> {
+		Ok(u16::from_be_bytes(self.read_fixed()?.into_array()))
+	}
+
+	pub fn read_u64(&mut self) -> Result<u64, Error> {
+		Ok(u64::from_be_bytes(self.read_fixed()?.into_array()))
+	}
+
+	/// Everything left, leaving the reader empty.
+	pub fn rest(&mut self) -> bytes::Bytes {
+		std::mem::take(&mut self.0)
+	}
+
+	/// Fails if anything is left unread.
+	pub fn finish(self) -> Result<(), Error> {
+		if !self.0.is_empty() {
+			return Err(Error::Trailing(self.0.len()))
+		}
+		Ok(())
+	}
+}
+
+impl From<bytes::Bytes> for Reader {
+	fn from(value: bytes::Bytes) -> Self {
+		Self(value)
+	}
+}
+
+impl From<NonEmpty> for Reader {
+	fn from(value: NonEmpty) -> Self {
+		Self(value.0)
+	}
+}
+
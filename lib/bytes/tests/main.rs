@@ -0,0 +1,49 @@
+use lib_bytes::Bounded;
+use lib_bytes::Error;
+use lib_bytes::Fixed;
+use lib_bytes::Reader;
+
+#[test]
+fn fixed_takes_exactly_n_bytes() {
+	let out: Result<Fixed<4>, Error> = [1, 2, 3, 4].as_slice().try_into();
+	assert_eq!(out.map(Fixed::into_array), Ok([1, 2, 3, 4]));
+	let out: Result<Fixed<4>, Error> = [1, 2, 3].as_slice().try_into();
+	assert_eq!(out, Err(Error::Length { expected: 4, received: 3 }));
+	let out: Result<Fixed<4>, Error> = bytes::Bytes::from_static(&[0; 5]).try_into();
+	assert_eq!(out, Err(Error::Length { expected: 4, received: 5 }));
+}
+
+#[test]
+fn bounded_takes_lengths_within_bounds() {
+	for len in 2..=4 {
+		let out: Result<Bounded<2, 4>, Error> = bytes::Bytes::from(vec![0; len]).try_into();
+		assert_eq!(out.map(|out| out.len()), Ok(len));
+	}
+	let out: Result<Bounded<2, 4>, Error> = bytes::Bytes::from(vec![0; 1]).try_into();
+	assert_eq!(out, Err(Error::Bounds { min: 2, max: 4, received: 1 }));
+	let out: Result<Bounded<2, 4>, Error> = bytes::Bytes::from(vec![0; 5]).try_into();
+	assert_eq!(out, Err(Error::Bounds { min: 2, max: 4, received: 5 }));
+}
+
+#[test]
+fn reader_reads_fields_in_order() -> Result<(), Error> {
+	let mut reader: Reader = bytes::Bytes::from_static(&[1, 0, 2, 0, 0, 0, 0, 0, 0, 0, 3, 4, 5, 6]).into();
+	assert_eq!(reader.read_u8()?, 1);
+	assert_eq!(reader.read_u16()?, 2);
+	assert_eq!(reader.read_u64()?, 3);
+	assert_eq!(reader.read_fixed::<1>()?.into_array(), [4]);
+	assert_eq!(reader.read_bounded::<1, 2>(1)?.as_ref(), [5]);
+	assert_eq!(reader.remaining(), 1);
+	assert_eq!(reader.rest().as_ref(), [6]);
+	assert!(reader.is_empty());
+	reader.finish()
+}
+
+#[test]
+fn reader_fails_without_consuming_on_short_input() {
+	let mut reader: Reader = bytes::Bytes::from_static(&[1, 2, 3]).into();
+	assert_eq!(reader.read_u64(), Err(Error::Eof { needed: 8, remaining: 3 }));
+	assert_eq!(reader.read_bounded::<1, 2>(3), Err(Error::Bounds { min: 1, max: 2, received: 3 }));
+	assert_eq!(reader.remaining(), 3);
+	assert_eq!(reader.finish(), Err(Error::Trailing(3)));
+}
@@ -0,0 +1,102 @@
+/// Output of a [`crate::transport::Transport`]. Byte oriented outputs (tcp, memory)
+/// are metered and reset once the scenario budget is spent; multiplexed
+/// outputs such as quic pass through untouched.
+#[derive(Debug)]
+#[derive(derive_more::Deref)]
+#[derive(derive_more::DerefMut)]
+pub struct Connection<T> {
+	#[deref]
+	#[deref_mut]
+	inner: T,
+	remaining: Option<u64>
+}
+
+impl<T> Connection<T> {
+	pub fn new(inner: T, reset_after: Option<u64>) -> Self {
+		Self {
+			inner,
+			remaining: reset_after
+		}
+	}
+
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+
+	// clamps a read or write to the remaining budget
+	fn budget(&self, len: usize) -> std::io::Result<usize> {
+		let Some(remaining) = self.remaining else {
+			return Ok(len)
+		};
+		if remaining == 0 {
+			return Err(std::io::Error::from(std::io::ErrorKind::ConnectionReset))
+		}
+		let remaining: usize = usize::try_from(remaining).unwrap_or(usize::MAX);
+		Ok(len.min(remaining))
+	}
+
+	fn spend(&mut self, len: usize) {
+		if let Some(remaining) = &mut self.remaining {
+			let len: u64 = u64::try_from(len).unwrap_or(u64::MAX);
+			*remaining = remaining.saturating_sub(len);
+		}
+	}
+}
+
+impl<T> futures::AsyncRead for Connection<T>
+where
+	T: futures::AsyncRead,
+	T: Unpin {
+	fn poll_read(
+		self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context<'_>,
+		buf: &mut [u8]
+	) -> std::task::Poll<std::io::Result<usize>> {
+		let this: &mut Self = self.get_mut();
+		let len: usize = match this.budget(buf.len()) {
+			Ok(len) => len,
+			Err(error) => return std::task::Poll::Ready(Err(error))
+		};
+		let Some(buf) = buf.get_mut(..len) else {
+			return std::task::Poll::Ready(Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)))
+		};
+		let out: std::task::Poll<_> = std::pin::Pin::new(&mut this.inner).poll_read(cx, buf);
+		if let std::task::Poll::Ready(Ok(read)) = out {
+			this.spend(read);
+		}
+		out
+	}
+}
+
+impl<T> futures::AsyncWrite for Connection<T>
+where
+	T: futures::AsyncWrite,
+	T: Unpin {
+	fn poll_write(
+		self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context<'_>,
+		buf: &[u8]
+	) -> std::task::Poll<std::io::Result<usize>> {
+		let this: &mut Self = self.get_mut();
+		let len: usize = match this.budget(buf.len()) {
+			Ok(len) => len,
+			Err(error) => return std::task::Poll::Ready(Err(error))
+		};
+		let Some(buf) = buf.get(..len) else {
+			return std::task::Poll::Ready(Err(std::io::Error::from(std::io::ErrorKind::InvalidInput)))
+		};
+		let out: std::task::Poll<_> = std::pin::Pin::new(&mut this.inner).poll_write(cx, buf);
+		if let std::task::Poll::Ready(Ok(written)) = out {
+			this.spend(written);
+		}
+		out
+	}
+
+	fn poll_flush(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+		std::pin::Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+	}
+
+	fn poll_close(self: std::pin::Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> std::task::Poll<std::io::Result<()>> {
+		std::pin::Pin::new(&mut self.get_mut().inner).poll_close(cx)
+	}
+}
@@ -0,0 +1,141 @@
+use super::*;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Evaluates connection attempts against a [`scenario::Scenario`].
+///
+/// Randomness is never shared between attempts: every attempt draws from its
+/// own generator seeded by the scenario seed, the direction, the remote
+/// address and how many times that address was seen before. Verdicts are
+/// therefore independent of task scheduling and reproducible bit-for-bit.
+#[derive(Debug)]
+pub struct Filter {
+	scenario: scenario::Scenario,
+	attempts: std::collections::HashMap<(Direction, libp2p::Multiaddr), u64>
+}
+
+impl From<scenario::Scenario> for Filter {
+	fn from(value: scenario::Scenario) -> Self {
+		Self {
+			scenario: value,
+			attempts: std::collections::HashMap::default()
+		}
+	}
+}
+
+impl Filter {
+	pub fn scenario(&self) -> &scenario::Scenario {
+		&self.scenario
+	}
+
+	pub fn inspect(&mut self, addr: &libp2p::Multiaddr, direction: Direction) -> policy::Verdict {
+		let attempt: &mut u64 = self.attempts.entry((direction, addr.to_owned())).or_default();
+		let seed: u64 = mix(self.scenario.seed, direction, addr, *attempt);
+		*attempt += 1;
+		let mut rng: rand_chacha::ChaCha8Rng = rand_chacha::ChaCha8Rng::seed_from_u64(seed);
+		let ip: Option<std::net::IpAddr> = ip_of(addr);
+		let port: Option<u16> = port_of(addr);
+		let fingerprint: Option<Fingerprint> = Fingerprint::of(addr);
+		let mut latency: std::time::Duration = std::time::Duration::ZERO;
+		let mut reset_after: Option<u64> = None;
+		for policy in &self.scenario.policies {
+			match policy {
+				policy::Policy::BlockIp {
+					cidr,
+					direction: scope
+				} => {
+					if policy::Policy::applies(*scope, direction)
+					&& let Some(ip) = ip
+					&& cidr.contains(&ip) {
+						return policy::Verdict::Drop(policy::Reason::Ip(ip))
+					}
+				},
+				policy::Policy::BlockPort {
+					port: blocked,
+					fingerprint: only,
+					direction: scope
+				} => {
+					if policy::Policy::applies(*scope, direction)
+					&& port == Some(*blocked)
+					&& only.is_none_or(|only| fingerprint == Some(only)) {
+						return policy::Verdict::Drop(policy::Reason::Port(*blocked))
+					}
+				},
+				policy::Policy::BlockFingerprint {
+					fingerprint: blocked,
+					direction: scope
+				} => {
+					if policy::Policy::applies(*scope, direction) && fingerprint == Some(*blocked) {
+						return policy::Verdict::Drop(policy::Reason::Fingerprint(*blocked))
+					}
+				},
+				policy::Policy::ResetAfter {
+					bytes,
+					fingerprint: only
+				} => {
+					// quic outputs are multiplexed and never metered
+					if fingerprint != Some(Fingerprint::Quic)
+					&& only.is_none_or(|only| fingerprint == Some(only)) {
+						let bytes: u64 = reset_after.map_or(*bytes, |current| current.min(*bytes));
+						reset_after = Some(bytes);
+					}
+				},
+				policy::Policy::Latency {
+					min_ms,
+					max_ms
+				} => {
+					// checked on the way in, but the fields are public
+					let ms: u64 = rng.random_range(*min_ms..=(*max_ms).max(*min_ms));
+					latency += std::time::Duration::from_millis(ms);
+				},
+				policy::Policy::Loss {
+					per_mille
+				} => {
+					let roll: u16 = rng.random_range(0..1000);
+					if roll < *per_mille {
+						return policy::Verdict::Drop(policy::Reason::Loss)
+					}
+				}
+			}
+		}
+		policy::Verdict::Allow {
+			latency,
+			reset_after
+		}
+	}
+}
+
+fn ip_of(addr: &libp2p::Multiaddr) -> Option<std::net::IpAddr> {
+	addr.iter().find_map(|protocol| match protocol {
+		libp2p::multiaddr::Protocol::Ip4(ip) => Some(std::net::IpAddr::V4(ip)),
+		libp2p::multiaddr::Protocol::Ip6(ip) => Some(std::net::IpAddr::V6(ip)),
+		_ => None
+	})
+}
+
+fn port_of(addr: &libp2p::Multiaddr) -> Option<u16> {
+	addr.iter().find_map(|protocol| match protocol {
+		libp2p::multiaddr::Protocol::Tcp(port) | libp2p::multiaddr::Protocol::Udp(port) => Some(port),
+		_ => None
+	})
+}
+
+// fnv-1a, chosen over `DefaultHasher` because its output is stable across
+// compiler releases which the reproducibility guarantee depends on
+fn mix(seed: u64, direction: Direction, addr: &libp2p::Multiaddr, attempt: u64) -> u64 {
+	let direction: u8 = match direction {
+		Direction::Inbound => 0,
+		Direction::Outbound => 1
+	};
+	let mut out: u64 = FNV_OFFSET;
+	let bytes = seed.to_be_bytes().into_iter()
+		.chain(std::iter::once(direction))
+		.chain(addr.as_ref().iter().copied())
+		.chain(attempt.to_be_bytes());
+	for byte in bytes {
+		out ^= u64::from(byte);
+		out = out.wrapping_mul(FNV_PRIME);
+	}
+	out
+}
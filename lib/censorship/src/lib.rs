@@ -0,0 +1,62 @@
+use rand::Rng as _;
+use rand::SeedableRng as _;
+use futures::FutureExt as _;
+
+pub mod connection;
+pub mod filter;
+pub mod policy;
+pub mod scenario;
+pub mod transport;
+
+pub use netfilter::Cidr;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+	Inbound,
+	Outbound
+}
+
+/// Transport fingerprint observable by a censor without decrypting
+/// anything, derived from the shape of the dialed or accepted address.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fingerprint {
+	Tcp,
+	Udp,
+	Quic,
+	Memory
+}
+
+impl Fingerprint {
+	pub fn of(addr: &libp2p::Multiaddr) -> Option<Self> {
+		let mut out: Option<Self> = None;
+		for protocol in addr.iter() {
+			match protocol {
+				libp2p::multiaddr::Protocol::Tcp(_) => out = Some(Self::Tcp),
+				libp2p::multiaddr::Protocol::Udp(_) => out = Some(Self::Udp),
+				libp2p::multiaddr::Protocol::Quic | libp2p::multiaddr::Protocol::QuicV1 => out = Some(Self::Quic),
+				libp2p::multiaddr::Protocol::Memory(_) => out = Some(Self::Memory),
+				libp2p::multiaddr::Protocol::P2pCircuit => break,
+				_ => {}
+			}
+		}
+		out
+	}
+}
@@ -0,0 +1,120 @@
+use super::*;
+
+/// A single censorship technique. Policies are evaluated in scenario order;
+/// the first blocking policy wins, shaping policies accumulate.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind")]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+	/// Refuses any connection whose remote address falls within `cidr`.
+	BlockIp {
+		cidr: Cidr,
+		direction: Option<Direction>
+	},
+
+	/// Refuses any connection to or from `port`, optionally only for one
+	/// transport fingerprint (e.g. udp/443 but not tcp/443).
+	BlockPort {
+		port: u16,
+		fingerprint: Option<Fingerprint>,
+		direction: Option<Direction>
+	},
+
+	/// Refuses every connection carrying the given fingerprint, e.g. drop
+	/// QUIC while leaving TCP untouched.
+	BlockFingerprint {
+		fingerprint: Fingerprint,
+		direction: Option<Direction>
+	},
+
+	/// Resets the connection once `bytes` have crossed it in either direction.
+	/// Only byte oriented outputs (tcp, memory) are metered: QUIC connections
+	/// are multiplexed, so this policy never applies to them and a scenario
+	/// scoping it to the `quic` fingerprint is rejected.
+	ResetAfter {
+		bytes: u64,
+		fingerprint: Option<Fingerprint>
+	},
+
+	/// Delays connection establishment by a value drawn uniformly from
+	/// `min_ms..=max_ms`.
+	Latency {
+		min_ms: u64,
+		max_ms: u64
+	},
+
+	/// Drops connection attempts with probability `per_mille / 1000`, the
+	/// connection level equivalent of a lost handshake.
+	Loss {
+		per_mille: u16
+	}
+}
+
+impl Policy {
+	/// Rejects parameters the filter cannot draw from or meter.
+	pub fn check(&self) -> Result {
+		match self {
+			Self::Latency {
+				min_ms,
+				max_ms
+			} if min_ms > max_ms => Err("latency min_ms must not exceed max_ms".into()),
+			Self::Loss {
+				per_mille
+			} if *per_mille > 1000 => Err("loss per_mille must not exceed 1000".into()),
+			Self::ResetAfter {
+				fingerprint: Some(Fingerprint::Quic),
+				..
+			} => Err("reset-after cannot meter quic connections".into()),
+			_ => Ok(())
+		}
+	}
+
+	pub(crate) fn applies(direction: Option<Direction>, other: Direction) -> bool {
+		direction.is_none_or(|direction| direction == other)
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum Reason {
+	Ip(std::net::IpAddr),
+	Port(u16),
+	Fingerprint(Fingerprint),
+	Loss
+}
+
+impl std::fmt::Display for Reason {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Ip(addr) => write!(f, "ip {} is blocked", addr),
+			Self::Port(port) => write!(f, "port {} is blocked", port),
+			Self::Fingerprint(fingerprint) => write!(f, "{:?} is blocked", fingerprint),
+			Self::Loss => write!(f, "connection attempt lost")
+		}
+	}
+}
+
+/// Outcome of running a connection attempt through a scenario.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum Verdict {
+	Allow {
+		latency: std::time::Duration,
+		reset_after: Option<u64>
+	},
+	Drop(Reason)
+}
+
+impl Verdict {
+	pub fn is_allowed(&self) -> bool {
+		matches!(self, Self::Allow { .. })
+	}
+}
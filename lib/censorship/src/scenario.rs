@@ -0,0 +1,73 @@
+use super::*;
+
+/// Seeded, declarative description of a censor. The same scenario applied to
+/// the same sequence of connection attempts always yields the same verdicts.
+///
+/// ```toml
+/// seed = 7
+///
+/// [[policy]]
+/// kind = "block-fingerprint"
+/// fingerprint = "quic"
+///
+/// [[policy]]
+/// kind = "latency"
+/// min_ms = 20
+/// max_ms = 80
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Default)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Scenario {
+	pub seed: u64,
+	#[serde(default)]
+	#[serde(rename = "policy")]
+	#[serde(deserialize_with = "checked")]
+	pub policies: Vec<policy::Policy>
+}
+
+#[bon::bon]
+impl Scenario {
+	#[builder]
+	pub fn new(
+		seed: u64,
+		#[builder(default)]
+		policies: Vec<policy::Policy>
+	) -> Result<Self> {
+		for policy in &policies {
+			policy.check()?;
+		}
+		Ok(Self {
+			seed,
+			policies
+		})
+	}
+}
+
+impl Scenario {
+	pub fn from_toml_at(path: &std::path::Path) -> Result<Self> {
+		let content: String = std::fs::read_to_string(path)?;
+		content.parse()
+	}
+}
+
+impl std::str::FromStr for Scenario {
+	type Err = Box<dyn std::error::Error>;
+
+	fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+		Ok(toml::from_str(s)?)
+	}
+}
+
+fn checked<'de, D>(deserializer: D) -> std::result::Result<Vec<policy::Policy>, D::Error>
+where
+	D: serde::Deserializer<'de> {
+	let policies: Vec<policy::Policy> = serde::Deserialize::deserialize(deserializer)?;
+	for policy in &policies {
+		policy.check().map_err(serde::de::Error::custom)?;
+	}
+	Ok(policies)
+}
@@ -0,0 +1,138 @@
+use super::*;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error<T> {
+	#[error("censored: {0}")]
+	Censored(policy::Reason),
+	#[error("{0}")]
+	Transport(T)
+}
+
+type Upgrade<T, E> = futures::future::BoxFuture<'static, std::result::Result<connection::Connection<T>, Error<E>>>;
+
+/// Wraps any libp2p transport and runs every dial and every accepted
+/// connection through a [`filter::Filter`] before handing it to the swarm.
+#[derive(Debug)]
+pub struct Transport<T> {
+	inner: T,
+	filter: filter::Filter
+}
+
+impl<T> Transport<T> {
+	pub fn new(inner: T, scenario: scenario::Scenario) -> Self {
+		let filter: filter::Filter = scenario.into();
+		Self {
+			inner,
+			filter
+		}
+	}
+
+	pub fn filter(&self) -> &filter::Filter {
+		&self.filter
+	}
+}
+
+impl<T> libp2p::core::Transport for Transport<T>
+where
+	T: libp2p::core::Transport,
+	T: Unpin,
+	T::Output: Send,
+	T::Output: 'static,
+	T::Error: Send,
+	T::Error: 'static,
+	T::Dial: Send,
+	T::Dial: 'static,
+	T::ListenerUpgrade: Send,
+	T::ListenerUpgrade: 'static {
+	type Output = connection::Connection<T::Output>;
+	type Error = Error<T::Error>;
+	type ListenerUpgrade = Upgrade<T::Output, T::Error>;
+	type Dial = Upgrade<T::Output, T::Error>;
+
+	fn listen_on(
+		&mut self,
+		id: libp2p::core::transport::ListenerId,
+		addr: libp2p::Multiaddr
+	) -> std::result::Result<(), libp2p::core::transport::TransportError<Self::Error>> {
+		self.inner.listen_on(id, addr).map_err(|error| error.map(Error::Transport))
+	}
+
+	fn remove_listener(&mut self, id: libp2p::core::transport::ListenerId) -> bool {
+		self.inner.remove_listener(id)
+	}
+
+	fn dial(
+		&mut self,
+		addr: libp2p::Multiaddr,
+		opts: libp2p::core::transport::DialOpts
+	) -> std::result::Result<Self::Dial, libp2p::core::transport::TransportError<Self::Error>> {
+		let (latency, reset_after) = match self.filter.inspect(&addr, Direction::Outbound) {
+			policy::Verdict::Allow {
+				latency,
+				reset_after
+			} => (latency, reset_after),
+			policy::Verdict::Drop(reason) => {
+				return Ok(futures::future::ready(Err(Error::Censored(reason))).boxed())
+			}
+		};
+		let dial: T::Dial = self.inner.dial(addr, opts).map_err(|error| error.map(Error::Transport))?;
+		Ok(delayed(dial, latency, reset_after))
+	}
+
+	fn poll(
+		self: std::pin::Pin<&mut Self>,
+		cx: &mut std::task::Context<'_>
+	) -> std::task::Poll<libp2p::core::transport::TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+		let this: &mut Self = self.get_mut();
+		loop {
+			let event: libp2p::core::transport::TransportEvent<_, _> = match std::pin::Pin::new(&mut this.inner).poll(cx) {
+				std::task::Poll::Ready(event) => event,
+				std::task::Poll::Pending => return std::task::Poll::Pending
+			};
+			let libp2p::core::transport::TransportEvent::Incoming {
+				listener_id,
+				upgrade,
+				local_addr,
+				send_back_addr
+			} = event else {
+				let event: libp2p::core::transport::TransportEvent<_, _> = event
+					.map_upgrade(|upgrade| delayed(upgrade, std::time::Duration::ZERO, None))
+					.map_err(Error::Transport);
+				return std::task::Poll::Ready(event)
+			};
+			let policy::Verdict::Allow {
+				latency,
+				reset_after
+			} = this.filter.inspect(&send_back_addr, Direction::Inbound) else {
+				// dropping the upgrade closes the socket before any handshake
+				continue
+			};
+			return std::task::Poll::Ready(libp2p::core::transport::TransportEvent::Incoming {
+				listener_id,
+				upgrade: delayed(upgrade, latency, reset_after),
+				local_addr,
+				send_back_addr
+			})
+		}
+	}
+}
+
+fn delayed<F, O, E>(future: F, latency: std::time::Duration, reset_after: Option<u64>) -> Upgrade<O, E>
+where
+	F: std::future::Future<Output = std::result::Result<O, E>>,
+	F: Send,
+	F: 'static,
+	O: Send,
+	O: 'static,
+	E: Send,
+	E: 'static {
+	async move {
+		if !latency.is_zero() {
+			futures_timer::Delay::new(latency).await;
+		}
+		let output: O = future.await.map_err(Error::Transport)?;
+		Ok(connection::Connection::new(output, reset_after))
+	}
+	.boxed()
+}
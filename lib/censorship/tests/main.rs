@@ -0,0 +1,258 @@
+use futures::AsyncReadExt as _;
+use futures::AsyncWriteExt as _;
+use libp2p::core::Transport as _;
+
+fn addr(s: &str) -> censorship::Result<libp2p::Multiaddr> {
+	Ok(s.parse()?)
+}
+
+#[test]
+fn scenario_parses_from_toml() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = r#"
+		seed = 42
+
+		[[policy]]
+		kind = "block-ip"
+		cidr = "10.0.0.0/8"
+
+		[[policy]]
+		kind = "block-port"
+		port = 443
+		fingerprint = "udp"
+		direction = "outbound"
+
+		[[policy]]
+		kind = "block-fingerprint"
+		fingerprint = "quic"
+
+		[[policy]]
+		kind = "reset-after"
+		bytes = 1024
+
+		[[policy]]
+		kind = "latency"
+		min_ms = 10
+		max_ms = 20
+
+		[[policy]]
+		kind = "loss"
+		per_mille = 5
+	"#.parse()?;
+	assert_eq!(scenario.seed, 42);
+	assert_eq!(scenario.policies.len(), 6);
+	Ok(())
+}
+
+#[test]
+fn scenario_rejects_inverted_latency() {
+	let scenario: censorship::Result<censorship::scenario::Scenario> = r#"
+		seed = 0
+
+		[[policy]]
+		kind = "latency"
+		min_ms = 20
+		max_ms = 10
+	"#.parse();
+	assert!(scenario.is_err());
+}
+
+#[test]
+fn every_constructor_checks_policies() -> censorship::Result {
+	let inverted: censorship::policy::Policy = censorship::policy::Policy::Latency {
+		min_ms: 20,
+		max_ms: 10
+	};
+	let certain: censorship::policy::Policy = censorship::policy::Policy::Loss {
+		per_mille: 1001
+	};
+	for policy in [inverted, certain] {
+		let built: censorship::Result<censorship::scenario::Scenario> = censorship::scenario::Scenario::builder()
+			.seed(0)
+			.policies(vec![policy.to_owned()])
+			.build();
+		assert!(built.is_err());
+		let unchecked: censorship::scenario::Scenario = censorship::scenario::Scenario {
+			seed: 0,
+			policies: vec![policy]
+		};
+		let encoded: String = toml::to_string(&unchecked)?;
+		let decoded: Result<censorship::scenario::Scenario, toml::de::Error> = toml::from_str(&encoded);
+		assert!(decoded.is_err());
+	}
+	Ok(())
+}
+
+#[test]
+fn inverted_latency_built_by_hand_does_not_panic() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario {
+		seed: 0,
+		policies: vec![censorship::policy::Policy::Latency {
+			min_ms: 20,
+			max_ms: 10
+		}]
+	};
+	let mut filter: censorship::filter::Filter = scenario.into();
+	let verdict: censorship::policy::Verdict = filter.inspect(&addr("/ip4/1.2.3.4/tcp/4001")?, censorship::Direction::Outbound);
+	assert_eq!(verdict, censorship::policy::Verdict::Allow {
+		latency: std::time::Duration::from_millis(20),
+		reset_after: None
+	});
+	Ok(())
+}
+
+#[test]
+fn blocks_quic_but_allows_tcp() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario::builder()
+		.seed(0)
+		.policies(vec![
+			censorship::policy::Policy::BlockFingerprint {
+				fingerprint: censorship::Fingerprint::Quic,
+				direction: None
+			}
+		])
+		.build()?;
+	let mut filter: censorship::filter::Filter = scenario.into();
+	let quic: censorship::policy::Verdict = filter.inspect(&addr("/ip4/1.2.3.4/udp/4001/quic-v1")?, censorship::Direction::Outbound);
+	let tcp: censorship::policy::Verdict = filter.inspect(&addr("/ip4/1.2.3.4/tcp/4001")?, censorship::Direction::Outbound);
+	assert_eq!(quic, censorship::policy::Verdict::Drop(censorship::policy::Reason::Fingerprint(censorship::Fingerprint::Quic)));
+	assert!(tcp.is_allowed());
+	Ok(())
+}
+
+#[test]
+fn blocks_ip_range_and_port() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario::builder()
+		.seed(0)
+		.policies(vec![
+			censorship::policy::Policy::BlockIp {
+				cidr: "192.168.0.0/16".parse()?,
+				direction: Some(censorship::Direction::Inbound)
+			},
+			censorship::policy::Policy::BlockPort {
+				port: 4001,
+				fingerprint: Some(censorship::Fingerprint::Udp),
+				direction: None
+			}
+		])
+		.build()?;
+	let mut filter: censorship::filter::Filter = scenario.into();
+	let inbound: censorship::policy::Verdict = filter.inspect(&addr("/ip4/192.168.4.2/tcp/80")?, censorship::Direction::Inbound);
+	let outbound: censorship::policy::Verdict = filter.inspect(&addr("/ip4/192.168.4.2/tcp/80")?, censorship::Direction::Outbound);
+	let udp: censorship::policy::Verdict = filter.inspect(&addr("/ip4/8.8.8.8/udp/4001")?, censorship::Direction::Outbound);
+	let tcp: censorship::policy::Verdict = filter.inspect(&addr("/ip4/8.8.8.8/tcp/4001")?, censorship::Direction::Outbound);
+	assert!(!inbound.is_allowed());
+	assert!(outbound.is_allowed());
+	assert_eq!(udp, censorship::policy::Verdict::Drop(censorship::policy::Reason::Port(4001)));
+	assert!(tcp.is_allowed());
+	Ok(())
+}
+
+#[test]
+fn verdicts_are_reproducible_for_a_seed() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario::builder()
+		.seed(7)
+		.policies(vec![
+			censorship::policy::Policy::Latency {
+				min_ms: 0,
+				max_ms: 500
+			},
+			censorship::policy::Policy::Loss {
+				per_mille: 300
+			}
+		])
+		.build()?;
+	let addrs: Vec<libp2p::Multiaddr> = (0..64)
+		.map(|i| addr(&format!("/ip4/10.0.0.{}/tcp/4001", i % 8)))
+		.collect::<censorship::Result<_>>()?;
+	let run = |scenario: censorship::scenario::Scenario| -> Vec<censorship::policy::Verdict> {
+		let mut filter: censorship::filter::Filter = scenario.into();
+		addrs.iter().map(|addr| filter.inspect(addr, censorship::Direction::Outbound)).collect()
+	};
+	let lhs: Vec<_> = run(scenario.to_owned());
+	let rhs: Vec<_> = run(scenario.to_owned());
+	assert_eq!(lhs, rhs);
+	assert!(lhs.iter().any(censorship::policy::Verdict::is_allowed));
+	assert!(lhs.iter().any(|verdict| !verdict.is_allowed()));
+	let reseeded: Vec<_> = run(censorship::scenario::Scenario {
+		seed: 8,
+		..scenario
+	});
+	assert_ne!(lhs, reseeded);
+	Ok(())
+}
+
+#[test]
+fn reset_after_never_meters_quic() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario::builder()
+		.seed(0)
+		.policies(vec![
+			censorship::policy::Policy::ResetAfter {
+				bytes: 1024,
+				fingerprint: None
+			}
+		])
+		.build()?;
+	let mut filter: censorship::filter::Filter = scenario.into();
+	let quic: censorship::policy::Verdict = filter.inspect(&addr("/ip4/1.2.3.4/udp/4001/quic-v1")?, censorship::Direction::Outbound);
+	let tcp: censorship::policy::Verdict = filter.inspect(&addr("/ip4/1.2.3.4/tcp/4001")?, censorship::Direction::Outbound);
+	assert_eq!(quic, censorship::policy::Verdict::Allow {
+		latency: std::time::Duration::ZERO,
+		reset_after: None
+	});
+	assert_eq!(tcp, censorship::policy::Verdict::Allow {
+		latency: std::time::Duration::ZERO,
+		reset_after: Some(1024)
+	});
+	let scoped: censorship::Result<censorship::scenario::Scenario> = r#"
+		seed = 0
+
+		[[policy]]
+		kind = "reset-after"
+		bytes = 1024
+		fingerprint = "quic"
+	"#.parse();
+	assert!(scoped.is_err());
+	Ok(())
+}
+
+#[tokio::test]
+async fn connection_resets_after_byte_budget() -> censorship::Result {
+	let inner: futures::io::Cursor<Vec<u8>> = futures::io::Cursor::new(vec![0; 64]);
+	let mut connection: censorship::connection::Connection<_> = censorship::connection::Connection::new(inner, Some(10));
+	let mut buf: [u8; 8] = [0; 8];
+	assert_eq!(connection.read(&mut buf).await?, 8);
+	assert_eq!(connection.write(&buf).await?, 2);
+	let error: std::io::Error = connection.read(&mut buf).await.err().ok_or("expected reset")?;
+	assert_eq!(error.kind(), std::io::ErrorKind::ConnectionReset);
+	Ok(())
+}
+
+#[tokio::test]
+async fn censored_dial_fails_on_wrapped_transport() -> censorship::Result {
+	let scenario: censorship::scenario::Scenario = censorship::scenario::Scenario::builder()
+		.seed(0)
+		.policies(vec![
+			censorship::policy::Policy::Loss {
+				per_mille: 1000
+			}
+		])
+		.build()?;
+	let mut transport: censorship::transport::Transport<_> = censorship::transport::Transport::new(libp2p::core::transport::MemoryTransport::default(), scenario);
+	let opts: libp2p::core::transport::DialOpts = libp2p::core::transport::DialOpts {
+		role: libp2p::core::Endpoint::Dialer,
+		port_use: libp2p::core::transport::PortUse::New
+	};
+	let outcome = transport.dial(addr("/memory/1")?, opts)?.await;
+	assert!(matches!(outcome, Err(censorship::transport::Error::Censored(censorship::policy::Reason::Loss))));
+	Ok(())
+}
+
+#[test]
+fn bundled_scenarios_parse() -> censorship::Result {
+	let dir: std::path::PathBuf = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("scenario");
+	for entry in std::fs::read_dir(dir)? {
+		let path: std::path::PathBuf = entry?.path();
+		censorship::scenario::Scenario::from_toml_at(&path)?;
+	}
+	Ok(())
+}
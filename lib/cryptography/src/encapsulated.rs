@@ -0,0 +1,39 @@
+/// Output of [`crate::AsymmetricKeyAgreementAlgorithm::initiate`] that the
+/// initiator sends to the responder, such as an ephemeral public key or a kem
+/// ciphertext.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(derive_more::Deref)]
+#[derive(derive_more::DerefMut)]
+pub struct Encapsulated<T> {
+    phantom_data: std::marker::PhantomData<T>,
+    #[deref]
+    #[deref_mut]
+    content: lib_bytes::NonEmpty
+}
+
+impl<T> Encapsulated<T> {
+	/// Exactly `N` bytes, failing on any other length.
+	pub fn to_fixed<const N: usize>(&self) -> std::result::Result<lib_bytes::Fixed<N>, lib_bytes::Error> {
+		let content: bytes::Bytes = self.content.to_owned().into();
+		content.try_into()
+	}
+}
+
+impl<T> From<lib_bytes::NonEmpty> for Encapsulated<T> {
+	fn from(value: lib_bytes::NonEmpty) -> Self {
+		let content: lib_bytes::NonEmpty = value;
+		Self {
+			phantom_data: std::marker::PhantomData,
+			content
+		}
+	}
+}
+
+impl<T> From<Encapsulated<T>> for lib_bytes::NonEmpty {
+	fn from(value: Encapsulated<T>) -> Self {
+		value.content
+	}
+}
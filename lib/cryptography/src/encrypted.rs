@@ -12,4 +12,20 @@ pub struct Encrypted<T> {
     #[deref]
     #[deref_mut]
     content: message::Message
-}
\ No newline at end of file
+}
+
+impl<T> From<lib_bytes::NonEmpty> for Encrypted<T> {
+	fn from(value: lib_bytes::NonEmpty) -> Self {
+		let content: message::Message = value.into();
+		Self {
+			phantom_data: std::marker::PhantomData,
+			content
+		}
+	}
+}
+
+impl<T> Into<lib_bytes::NonEmpty> for Encrypted<T> {
+	fn into(self) -> lib_bytes::NonEmpty {
+		self.content.into()
+	}
+}
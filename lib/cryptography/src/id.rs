@@ -0,0 +1,18 @@
+//! Registry of the wire identifiers behind [`crate::Algorithm::ID`]. Values
+//! are never reused, a retired algorithm keeps its number.
+
+/// Never on the wire. Taken by algorithms that pick the concrete algorithm at
+/// runtime from a tag carried in every key and signature, that tag being one
+/// of the ids below.
+pub const TAGGED: u16 = 0x0000;
+
+pub const ED25519: u16 = 0x0001;
+pub const DILITHIUM3: u16 = 0x0002;
+pub const ED25519_DILITHIUM3: u16 = 0x0003;
+
+pub const X25519: u16 = 0x0101;
+pub const KYBER1024: u16 = 0x0102;
+pub const X25519_KYBER1024: u16 = 0x0103;
+
+pub const CHACHA20POLY1305: u16 = 0x0201;
+pub const AES256GCM: u16 = 0x0202;
@@ -0,0 +1,13 @@
+use super::*;
+
+/// Expands shared key material into a [`key::Key`] of `S::KEY_LEN` bytes with
+/// HKDF-SHA256. `salt` should bind the transcript, `info` the purpose of the
+/// key, so that the same shared secret never yields the same key twice.
+pub fn derive<S>(shared_secret: &[u8], salt: &[u8], info: &[u8]) -> Result<key::Key<S>>
+where
+	S: SymmetricSetLayout {
+	let hkdf: hkdf::Hkdf<sha2::Sha256> = hkdf::Hkdf::new(Some(salt), shared_secret);
+	let mut out: Vec<u8> = vec![0; S::KEY_LEN];
+	hkdf.expand(info, &mut out).map_err(|_| String::from("requested key length exceeds hkdf output"))?;
+	out.try_into()
+}
@@ -1,16 +1,20 @@
 use super::*;
 
+/// Wiped on drop, redacted in `Debug` and compared in constant time through
+/// [`secret::Secret`]; [`Key::expose_secret`] is the only way out.
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(PartialEq)]
 #[derive(Eq)]
-#[derive(derive_more::Deref)]
-#[derive(derive_more::DerefMut)]
 pub struct Key<T> {
     phantom_data: std::marker::PhantomData<T>,
-    #[deref]
-    #[deref_mut]
-    content: lib_bytes::NonEmpty
+    content: secret::Secret
+}
+
+impl<T> Key<T> {
+    pub fn expose_secret(&self) -> &[u8] {
+        self.content.expose_secret()
+    }
 }
 
 impl<T> Key<T> 
@@ -31,4 +35,54 @@ where
     pub fn decrypt(&self, message: &encrypted::Encrypted<T>) -> Result<message::Message> {
         T::decrypt(&self, &message)
     }
-}
\ No newline at end of file
+}
+
+impl<T> Key<T>
+where
+    T: SymmetricAeadAlgorithm {
+    pub fn encrypt_with_associated_data(&self, message: &message::Message, associated_data: &[u8]) -> Result<encrypted::Encrypted<T>> {
+        T::encrypt_with_associated_data(self, message, associated_data)
+    }
+
+    pub fn encrypt_with_nonce(&self, nonce: &[u8], message: &message::Message, associated_data: &[u8]) -> Result<encrypted::Encrypted<T>> {
+        T::encrypt_with_nonce(self, nonce, message, associated_data)
+    }
+
+    pub fn decrypt_with_associated_data(&self, message: &encrypted::Encrypted<T>, associated_data: &[u8]) -> Result<message::Message> {
+        T::decrypt_with_associated_data(self, message, associated_data)
+    }
+}
+
+impl<T> From<secret::Secret> for Key<T> {
+	fn from(value: secret::Secret) -> Self {
+		Self {
+			phantom_data: std::marker::PhantomData,
+			content: value
+		}
+	}
+}
+
+impl<T> From<lib_bytes::NonEmpty> for Key<T> {
+	fn from(value: lib_bytes::NonEmpty) -> Self {
+		let content: secret::Secret = value.into();
+		content.into()
+	}
+}
+
+impl<T> TryFrom<bytes::BytesMut> for Key<T> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: bytes::BytesMut) -> std::result::Result<Self, Self::Error> {
+		let content: secret::Secret = value.try_into()?;
+		Ok(content.into())
+	}
+}
+
+impl<T> TryFrom<Vec<u8>> for Key<T> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: Vec<u8>) -> std::result::Result<Self, Self::Error> {
+		let content: secret::Secret = value.try_into()?;
+		Ok(content.into())
+	}
+}
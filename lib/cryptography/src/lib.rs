@@ -1,8 +1,13 @@
+pub mod encapsulated;
 pub mod encrypted;
+pub mod id;
+pub mod kdf;
 pub mod key;
 pub mod message;
+pub mod nonce;
 pub mod pair;
 pub mod public_key;
+pub mod secret;
 pub mod secret_key;
 pub mod signature;
 
@@ -10,7 +15,10 @@ pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 pub trait Algorithm 
 where
-	Self: Sized {}
+	Self: Sized {
+	/// Wire identifier, see [`id`] for the registry.
+	const ID: u16;
+}
 
 pub trait AsymmetricSetLayout {
 	const PUBLIC_KEY_LEN: usize;
@@ -18,10 +26,47 @@ pub trait AsymmetricSetLayout {
 	const SIGNATURE_LEN: usize;
 }
 
+/// Key and signature lengths looked up by wire id at runtime, so a packet can
+/// be framed once its algorithm tag is read. Every [`AsymmetricSetLayout`]
+/// algorithm has one answering for its own [`Algorithm::ID`] only, algorithms
+/// dispatching at runtime answer for each algorithm they carry.
+pub trait AsymmetricTaggedLayout
+where
+	Self: Algorithm {
+	fn public_key_len(algorithm: u16) -> Option<usize>;
+	fn signature_len(algorithm: u16) -> Option<usize>;
+	/// Wire id of the concrete algorithm `public_key` belongs to.
+	fn algorithm_of(public_key: &public_key::PublicKey<Self>) -> Option<u16>;
+}
+
+impl<T> AsymmetricTaggedLayout for T
+where
+	T: Algorithm,
+	T: AsymmetricSetLayout {
+	fn public_key_len(algorithm: u16) -> Option<usize> {
+		(algorithm == T::ID).then_some(T::PUBLIC_KEY_LEN)
+	}
+
+	fn signature_len(algorithm: u16) -> Option<usize> {
+		(algorithm == T::ID).then_some(T::SIGNATURE_LEN)
+	}
+
+	fn algorithm_of(_: &public_key::PublicKey<Self>) -> Option<u16> {
+		Some(T::ID)
+	}
+}
+
+pub trait SymmetricSetLayout {
+	const KEY_LEN: usize;
+	const NONCE_LEN: usize;
+	const TAG_LEN: usize;
+}
+
 pub trait AsymmetricKeyDerivationAlgorithm 
 where
 	Self: Algorithm {
-	fn public_key(secret_key: &secret_key::SecretKey<Self>) -> public_key::PublicKey<Self>;
+	/// Fails on a secret key of the wrong shape, without echoing any of it.
+	fn public_key(secret_key: &secret_key::SecretKey<Self>) -> Result<public_key::PublicKey<Self>>;
 }
 
 pub trait AsymmetricKeyGenAlgorithm 
@@ -55,4 +100,32 @@ where
 	Self: Algorithm {
     fn encrypt(key: &key::Key<Self>, message: &message::Message) -> Result<encrypted::Encrypted<Self>>;
     fn decrypt(key: &key::Key<Self>, message: &encrypted::Encrypted<Self>) -> Result<message::Message>;
-}
\ No newline at end of file
+}
+
+/// Authenticated encryption where `associated_data` is bound to the
+/// ciphertext without being encrypted, decryption fails unless the exact same
+/// bytes are supplied again.
+pub trait SymmetricAeadAlgorithm
+where
+	Self: Algorithm {
+    fn encrypt_with_associated_data(key: &key::Key<Self>, message: &message::Message, associated_data: &[u8]) -> Result<encrypted::Encrypted<Self>>;
+    /// Encrypts under a caller chosen nonce, which must never repeat under
+    /// one key; see [`nonce::Sequence`].
+    fn encrypt_with_nonce(key: &key::Key<Self>, nonce: &[u8], message: &message::Message, associated_data: &[u8]) -> Result<encrypted::Encrypted<Self>>;
+    fn decrypt_with_associated_data(key: &key::Key<Self>, message: &encrypted::Encrypted<Self>, associated_data: &[u8]) -> Result<message::Message>;
+}
+
+/// Key agreement in encapsulation form: the initiator derives a key from the
+/// responder's public key and sends the resulting [`encapsulated::Encapsulated`]
+/// over, from which the responder derives the same key with its secret key.
+/// Both sides pass the same `info`, binding the key to its purpose.
+pub trait AsymmetricKeyAgreementAlgorithm
+where
+	Self: Algorithm {
+    fn initiate<S>(public_key: &public_key::PublicKey<Self>, info: &[u8]) -> Result<(encapsulated::Encapsulated<Self>, key::Key<S>)>
+    where
+        S: SymmetricSetLayout;
+    fn respond<S>(secret_key: &secret_key::SecretKey<Self>, encapsulated: &encapsulated::Encapsulated<Self>, info: &[u8]) -> Result<key::Key<S>>
+    where
+        S: SymmetricSetLayout;
+}
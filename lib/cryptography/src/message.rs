@@ -1,5 +1,3 @@
-use super::*;
-
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(PartialEq)]
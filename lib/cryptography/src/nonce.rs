@@ -0,0 +1,40 @@
+/// Counter based nonces for a single key.
+///
+/// A nonce is a fixed `prefix` followed by a big endian counter. Two peers
+/// sharing a key must use distinct prefixes, after which nonces never repeat
+/// until the counter is exhausted; at that point `advance` fails and the key
+/// must be rotated.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Sequence {
+    prefix: [u8; 4],
+    counter: u64,
+    exhausted: bool
+}
+
+impl Sequence {
+    pub const fn new(prefix: [u8; 4]) -> Self {
+        Self {
+            prefix,
+            counter: 0,
+            exhausted: false
+        }
+    }
+
+    pub fn advance(&mut self) -> crate::Result<[u8; 12]> {
+        if self.exhausted {
+            return Err(String::from("nonce sequence exhausted").into())
+        }
+        let mut out: [u8; 12] = [0; 12];
+        let (prefix, counter) = out.split_at_mut(4);
+        prefix.copy_from_slice(&self.prefix);
+        counter.copy_from_slice(&self.counter.to_be_bytes());
+        match self.counter.checked_add(1) {
+            Some(counter) => self.counter = counter,
+            None => self.exhausted = true
+        }
+        Ok(out)
+    }
+}
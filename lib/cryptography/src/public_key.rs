@@ -11,6 +11,14 @@ pub struct PublicKey<T> {
     content: lib_bytes::NonEmpty
 }
 
+impl<T> PublicKey<T> {
+	/// Exactly `N` bytes, failing on any other length.
+	pub fn to_fixed<const N: usize>(&self) -> std::result::Result<lib_bytes::Fixed<N>, lib_bytes::Error> {
+		let content: bytes::Bytes = self.content.to_owned().into();
+		content.try_into()
+	}
+}
+
 impl<T> From<lib_bytes::NonEmpty> for PublicKey<T> {
 	fn from(value: lib_bytes::NonEmpty) -> Self {
 		let content: lib_bytes::NonEmpty = value;
@@ -0,0 +1,74 @@
+use subtle::ConstantTimeEq as _;
+
+/// Non empty secret bytes, the storage behind [`crate::secret_key::SecretKey`]
+/// and [`crate::key::Key`].
+///
+/// The bytes are wiped when dropped, never show up in `Debug` output and are
+/// compared in constant time. [`Secret::expose_secret`] is the one way to read
+/// them back out, so every export is greppable.
+#[derive(Clone)]
+pub struct Secret(zeroize::Zeroizing<Vec<u8>>);
+
+impl Secret {
+	/// Borrows the raw secret bytes. Callers should not copy them into storage
+	/// that outlives the call unless that storage is wiped as well.
+	pub fn expose_secret(&self) -> &[u8] {
+		&self.0
+	}
+}
+
+impl std::fmt::Debug for Secret {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("<redacted>")
+	}
+}
+
+impl PartialEq for Secret {
+	fn eq(&self, other: &Self) -> bool {
+		// the length is not secret, only the content is
+		self.0.ct_eq(&other.0).into()
+	}
+}
+
+impl Eq for Secret {}
+
+impl TryFrom<Vec<u8>> for Secret {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+		let value: zeroize::Zeroizing<Vec<u8>> = value.into();
+		if value.is_empty() {
+			return Err(<Box<dyn std::error::Error>>::from(String::from("empty")))
+		}
+		Ok(Self(value))
+	}
+}
+
+impl TryFrom<&[u8]> for Secret {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+		value.to_vec().try_into()
+	}
+}
+
+impl TryFrom<bytes::BytesMut> for Secret {
+	type Error = Box<dyn std::error::Error>;
+
+	/// Moves the bytes out of `value` and wipes it, so the only copy left is
+	/// the one wiped on drop. Prefer this over [`lib_bytes::NonEmpty`] when
+	/// reading secrets from files or the network.
+	fn try_from(mut value: bytes::BytesMut) -> Result<Self, Self::Error> {
+		let out: Result<Self, Self::Error> = value.as_ref().try_into();
+		zeroize::Zeroize::zeroize(value.as_mut());
+		out
+	}
+}
+
+impl From<lib_bytes::NonEmpty> for Secret {
+	fn from(value: lib_bytes::NonEmpty) -> Self {
+		// the source buffer may be shared and cannot be wiped, only this copy is
+		let value: bytes::Bytes = value.into();
+		Self(value.to_vec().into())
+	}
+}
@@ -1,44 +1,60 @@
 use super::*;
 
+/// Wiped on drop, redacted in `Debug` and compared in constant time through
+/// [`secret::Secret`]; [`SecretKey::expose_secret`] is the only way out.
 #[derive(Debug)]
 #[derive(Clone)]
 #[derive(PartialEq)]
 #[derive(Eq)]
-#[derive(derive_more::Deref)]
-#[derive(derive_more::DerefMut)]
 pub struct SecretKey<T> {
     phantom_data: std::marker::PhantomData<T>,
-    #[deref]
-    #[deref_mut]
-    content: lib_bytes::NonEmpty
+    content: secret::Secret
+}
+
+impl<T> SecretKey<T> {
+	pub fn expose_secret(&self) -> &[u8] {
+		self.content.expose_secret()
+	}
 }
 
 impl<T> SecretKey<T> 
 where
 	T: AsymmetricKeyDerivationAlgorithm {
-	pub fn public_key(&self) -> public_key::PublicKey<T> {
+	pub fn public_key(&self) -> Result<public_key::PublicKey<T>> {
 		T::public_key(&self)
 	}
 }
 
-impl<T> From<lib_bytes::NonEmpty> for SecretKey<T> {
-	fn from(value: lib_bytes::NonEmpty) -> Self {
-		let content: lib_bytes::NonEmpty = value;
+impl<T> From<secret::Secret> for SecretKey<T> {
+	fn from(value: secret::Secret) -> Self {
 		Self {
 			phantom_data: std::marker::PhantomData,
-			content
+			content: value
 		}
 	}
 }
 
-impl<T> Into<lib_bytes::NonEmpty> for SecretKey<T> {
-	fn into(self) -> lib_bytes::NonEmpty {
-		self.content
+impl<T> From<lib_bytes::NonEmpty> for SecretKey<T> {
+	fn from(value: lib_bytes::NonEmpty) -> Self {
+		let content: secret::Secret = value.into();
+		content.into()
 	}
 }
 
-impl<T> Into<lib_bytes::NonEmpty> for &SecretKey<T> {
-	fn into(self) -> lib_bytes::NonEmpty {
-		self.content.to_owned()
+impl<T> TryFrom<bytes::BytesMut> for SecretKey<T> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: bytes::BytesMut) -> std::result::Result<Self, Self::Error> {
+		let content: secret::Secret = value.try_into()?;
+		Ok(content.into())
+	}
+}
+
+impl<T> TryFrom<Vec<u8>> for SecretKey<T> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: Vec<u8>) -> std::result::Result<Self, Self::Error> {
+		let content: secret::Secret = value.try_into()?;
+		Ok(content.into())
 	}
-}
\ No newline at end of file
+}
@@ -11,6 +11,14 @@ pub struct Signature<T> {
     content: lib_bytes::NonEmpty
 }
 
+impl<T> Signature<T> {
+	/// Exactly `N` bytes, failing on any other length.
+	pub fn to_fixed<const N: usize>(&self) -> std::result::Result<lib_bytes::Fixed<N>, lib_bytes::Error> {
+		let content: bytes::Bytes = self.content.to_owned().into();
+		content.try_into()
+	}
+}
+
 impl<T> From<lib_bytes::NonEmpty> for Signature<T> {
 	fn from(value: lib_bytes::NonEmpty) -> Self {
 		let content: lib_bytes::NonEmpty = value;
@@ -0,0 +1,77 @@
+#[derive(Debug)]
+#[derive(PartialEq)]
+struct Algorithm;
+
+// distinctive enough that any formatting of it, decimal or hex, is caught
+const SECRET: [u8; 8] = [0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe, 0xba, 0xbe];
+
+fn leaks(formatted: &str) -> bool {
+	let decimal: String = SECRET.iter().map(u8::to_string).collect::<Vec<_>>().join(", ");
+	let hex: String = SECRET.iter().map(|byte| format!("{byte:02x}")).collect();
+	formatted.contains(&decimal) || formatted.to_lowercase().contains(&hex)
+}
+
+#[test]
+fn secret_key_debug_is_redacted() -> cryptography::Result<()> {
+	let secret_key: cryptography::secret_key::SecretKey<Algorithm> = SECRET.to_vec().try_into()?;
+	assert!(!leaks(&format!("{secret_key:?}")));
+	assert!(!leaks(&format!("{secret_key:#?}")));
+	Ok(())
+}
+
+#[test]
+fn key_debug_is_redacted() -> cryptography::Result<()> {
+	let key: cryptography::key::Key<Algorithm> = SECRET.to_vec().try_into()?;
+	assert!(!leaks(&format!("{key:?}")));
+	assert!(!leaks(&format!("{key:#?}")));
+	Ok(())
+}
+
+#[test]
+fn secret_key_from_bytes_is_redacted() -> cryptography::Result<()> {
+	let secret_key: bytes::Bytes = bytes::Bytes::copy_from_slice(&SECRET);
+	let secret_key: lib_bytes::NonEmpty = secret_key.try_into()?;
+	let secret_key: cryptography::secret_key::SecretKey<Algorithm> = secret_key.into();
+	assert!(!leaks(&format!("{secret_key:?}")));
+	Ok(())
+}
+
+#[test]
+fn export_is_explicit() -> cryptography::Result<()> {
+	let secret_key: cryptography::secret_key::SecretKey<Algorithm> = SECRET.to_vec().try_into()?;
+	let key: cryptography::key::Key<Algorithm> = SECRET.to_vec().try_into()?;
+	assert_eq!(secret_key.expose_secret(), SECRET);
+	assert_eq!(key.expose_secret(), SECRET);
+	Ok(())
+}
+
+#[test]
+fn equality_compares_content() -> cryptography::Result<()> {
+	let lhs: cryptography::key::Key<Algorithm> = SECRET.to_vec().try_into()?;
+	let rhs: cryptography::key::Key<Algorithm> = SECRET.to_vec().try_into()?;
+	let mut flipped: Vec<u8> = SECRET.to_vec();
+	if let Some(last) = flipped.last_mut() {
+		*last ^= 1;
+	}
+	let flipped: cryptography::key::Key<Algorithm> = flipped.try_into()?;
+	let shorter: cryptography::key::Key<Algorithm> = SECRET.get(..4).unwrap_or_default().to_vec().try_into()?;
+	assert!(lhs == rhs);
+	assert!(lhs != flipped);
+	assert!(lhs != shorter);
+	Ok(())
+}
+
+#[test]
+fn secret_key_from_owned_buffer() -> cryptography::Result<()> {
+	let buffer: bytes::BytesMut = bytes::BytesMut::from(SECRET.as_slice());
+	let secret_key: cryptography::secret_key::SecretKey<Algorithm> = buffer.try_into()?;
+	assert_eq!(secret_key.expose_secret(), SECRET);
+	assert!(cryptography::secret_key::SecretKey::<Algorithm>::try_from(bytes::BytesMut::new()).is_err());
+	Ok(())
+}
+
+#[test]
+fn empty_secret_is_rejected() {
+	assert!(cryptography::key::Key::<Algorithm>::try_from(Vec::new()).is_err());
+	assert!(cryptography::secret_key::SecretKey::<Algorithm>::try_from(Vec::new()).is_err());
+}
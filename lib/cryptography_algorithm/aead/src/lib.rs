@@ -0,0 +1,110 @@
+//! Sealing shared by the aead algorithms. Ciphertexts are laid out as
+//! `nonce || ciphertext || tag`; the algorithm crates only pick the cipher.
+
+use rand::RngCore as _;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("invalid key")]
+	InvalidKey,
+
+	#[error("invalid nonce")]
+	InvalidNonce,
+
+	#[error("encryption failed")]
+	Encrypt,
+
+	#[error("malformed ciphertext")]
+	Malformed,
+
+	#[error("authentication failed")]
+	Unauthentic
+}
+
+pub fn generate<A>() -> lib_cryptography::Result<lib_cryptography::key::Key<A>>
+where
+	A: lib_cryptography::SymmetricSetLayout {
+	let mut key: Vec<u8> = vec![0; A::KEY_LEN];
+	rand::rngs::OsRng.try_fill_bytes(&mut key)?;
+	key.try_into()
+}
+
+pub fn encrypt_with_nonce<C, A>(
+	key: &lib_cryptography::key::Key<A>,
+	nonce: &[u8],
+	message: &lib_cryptography::message::Message,
+	associated_data: &[u8]
+) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<A>>
+where
+	C: aead::KeyInit + aead::Aead,
+	A: lib_cryptography::SymmetricSetLayout {
+	let cipher: C = cipher(key)?;
+	let nonce: aead::Nonce<C> = aead::Nonce::<C>::from_exact_iter(nonce.iter().copied()).ok_or(Error::InvalidNonce)?;
+	let message: lib_bytes::NonEmpty = message.to_owned().into();
+	let message: bytes::Bytes = message.into();
+	let payload: aead::Payload<'_, '_> = aead::Payload {
+		msg: message.as_ref(),
+		aad: associated_data
+	};
+	let sealed: Vec<u8> = cipher
+		.encrypt(&nonce, payload)
+		.map_err(|_| Error::Encrypt)?;
+	let mut out: Vec<u8> = Vec::with_capacity(nonce.len() + sealed.len());
+	out.extend_from_slice(&nonce);
+	out.extend_from_slice(&sealed);
+	let out: bytes::Bytes = out.into();
+	let out: lib_bytes::NonEmpty = out.try_into()?;
+	let out: lib_cryptography::encrypted::Encrypted<A> = out.into();
+	Ok(out)
+}
+
+/// Draws a random nonce, which is safe for up to 2^32 messages under one key.
+pub fn encrypt_with_associated_data<C, A>(
+	key: &lib_cryptography::key::Key<A>,
+	message: &lib_cryptography::message::Message,
+	associated_data: &[u8]
+) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<A>>
+where
+	C: aead::KeyInit + aead::Aead,
+	A: lib_cryptography::SymmetricSetLayout {
+	let mut nonce: Vec<u8> = vec![0; A::NONCE_LEN];
+	rand::rngs::OsRng.try_fill_bytes(&mut nonce)?;
+	encrypt_with_nonce::<C, A>(key, &nonce, message, associated_data)
+}
+
+pub fn decrypt_with_associated_data<C, A>(
+	key: &lib_cryptography::key::Key<A>,
+	message: &lib_cryptography::encrypted::Encrypted<A>,
+	associated_data: &[u8]
+) -> lib_cryptography::Result<lib_cryptography::message::Message>
+where
+	C: aead::KeyInit + aead::Aead,
+	A: lib_cryptography::SymmetricSetLayout,
+	A: Clone {
+	let cipher: C = cipher(key)?;
+	let message: lib_bytes::NonEmpty = message.to_owned().into();
+	let message: bytes::Bytes = message.into();
+	if message.len() < A::NONCE_LEN + A::TAG_LEN {
+		return Err(Error::Malformed.into())
+	}
+	let (nonce, sealed) = message.split_at(A::NONCE_LEN);
+	let nonce: aead::Nonce<C> = aead::Nonce::<C>::from_exact_iter(nonce.iter().copied()).ok_or(Error::InvalidNonce)?;
+	let payload: aead::Payload<'_, '_> = aead::Payload {
+		msg: sealed,
+		aad: associated_data
+	};
+	let out: Vec<u8> = cipher
+		.decrypt(&nonce, payload)
+		.map_err(|_| Error::Unauthentic)?;
+	let out: bytes::Bytes = out.into();
+	let out: lib_bytes::NonEmpty = out.try_into()?;
+	let out: lib_cryptography::message::Message = out.into();
+	Ok(out)
+}
+
+fn cipher<C, A>(key: &lib_cryptography::key::Key<A>) -> Result<C, Error>
+where
+	C: aead::KeyInit {
+	C::new_from_slice(key.expose_secret()).map_err(|_| Error::InvalidKey)
+}
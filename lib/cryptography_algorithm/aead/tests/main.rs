@@ -0,0 +1,51 @@
+#[derive(Clone)]
+struct Layout;
+
+impl lib_cryptography::SymmetricSetLayout for Layout {
+	const KEY_LEN: usize = 32;
+	const NONCE_LEN: usize = 12;
+	const TAG_LEN: usize = 16;
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+	let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+	let content: lib_bytes::NonEmpty = content.try_into()?;
+	Ok(content.into())
+}
+
+#[test]
+fn round_trip_carries_the_nonce() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Layout> = cryptography_algorithm_aead::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"payload")?;
+	let encrypted: lib_cryptography::encrypted::Encrypted<Layout> = cryptography_algorithm_aead::encrypt_with_nonce::<chacha20poly1305::ChaCha20Poly1305, _>(&key, &[7; 12], &plaintext, b"aad")?;
+	let encoded: lib_bytes::NonEmpty = encrypted.to_owned().into();
+	let encoded: bytes::Bytes = encoded.into();
+	assert_eq!(encoded.get(..12), Some([7; 12].as_slice()));
+	let decrypted: lib_cryptography::message::Message = cryptography_algorithm_aead::decrypt_with_associated_data::<chacha20poly1305::ChaCha20Poly1305, _>(&key, &encrypted, b"aad")?;
+	assert_eq!(decrypted, plaintext);
+	Ok(())
+}
+
+#[test]
+fn rejects_a_nonce_of_the_wrong_length() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Layout> = cryptography_algorithm_aead::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"payload")?;
+	let error: Box<dyn std::error::Error> = cryptography_algorithm_aead::encrypt_with_nonce::<chacha20poly1305::ChaCha20Poly1305, _>(&key, &[7; 8], &plaintext, &[])
+		.err()
+		.ok_or("short nonce accepted")?;
+	assert!(matches!(error.downcast_ref(), Some(cryptography_algorithm_aead::Error::InvalidNonce)));
+	Ok(())
+}
+
+#[test]
+fn rejects_a_truncated_ciphertext() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Layout> = cryptography_algorithm_aead::generate()?;
+	let truncated: bytes::Bytes = bytes::Bytes::from_static(&[0; 27]);
+	let truncated: lib_bytes::NonEmpty = truncated.try_into()?;
+	let truncated: lib_cryptography::encrypted::Encrypted<Layout> = truncated.into();
+	let error: Box<dyn std::error::Error> = cryptography_algorithm_aead::decrypt_with_associated_data::<chacha20poly1305::ChaCha20Poly1305, _>(&key, &truncated, &[])
+		.err()
+		.ok_or("truncated ciphertext accepted")?;
+	assert!(matches!(error.downcast_ref(), Some(cryptography_algorithm_aead::Error::Malformed)));
+	Ok(())
+}
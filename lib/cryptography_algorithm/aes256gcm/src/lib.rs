@@ -0,0 +1,71 @@
+pub use cryptography_algorithm_aead::Error;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// AES-256 in Galois/Counter mode as specified by NIST SP 800-38D.
+///
+/// Ciphertexts are laid out as `nonce || ciphertext || tag`. The trait entry
+/// points draw a random nonce for every message, which is safe for up to
+/// 2^32 messages under one key; callers encrypting more than that, or
+/// wanting deterministic nonces, should drive [`lib_cryptography::SymmetricAeadAlgorithm::encrypt_with_nonce`]
+/// from a [`lib_cryptography::nonce::Sequence`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Aes256GcmAlgorithm;
+
+impl lib_cryptography::Algorithm for Aes256GcmAlgorithm {
+	const ID: u16 = lib_cryptography::id::AES256GCM;
+}
+
+impl lib_cryptography::SymmetricSetLayout for Aes256GcmAlgorithm {
+	const KEY_LEN: usize = KEY_LEN;
+	const NONCE_LEN: usize = NONCE_LEN;
+	const TAG_LEN: usize = TAG_LEN;
+}
+
+impl lib_cryptography::SymmetricKeyGenAlgorithm for Aes256GcmAlgorithm {
+	fn generate() -> lib_cryptography::Result<lib_cryptography::key::Key<Self>> {
+		cryptography_algorithm_aead::generate::<Self>()
+	}
+}
+
+impl lib_cryptography::SymmetricAeadAlgorithm for Aes256GcmAlgorithm {
+	fn encrypt_with_associated_data(
+		key: &lib_cryptography::key::Key<Self>,
+		message: &lib_cryptography::message::Message,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		cryptography_algorithm_aead::encrypt_with_associated_data::<aes_gcm::Aes256Gcm, Self>(key, message, associated_data)
+	}
+
+	fn encrypt_with_nonce(
+		key: &lib_cryptography::key::Key<Self>,
+		nonce: &[u8],
+		message: &lib_cryptography::message::Message,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		cryptography_algorithm_aead::encrypt_with_nonce::<aes_gcm::Aes256Gcm, Self>(key, nonce, message, associated_data)
+	}
+
+	fn decrypt_with_associated_data(
+		key: &lib_cryptography::key::Key<Self>,
+		message: &lib_cryptography::encrypted::Encrypted<Self>,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+		cryptography_algorithm_aead::decrypt_with_associated_data::<aes_gcm::Aes256Gcm, Self>(key, message, associated_data)
+	}
+}
+
+impl lib_cryptography::SymmetricEncryptionAlgorithm for Aes256GcmAlgorithm {
+	fn encrypt(key: &lib_cryptography::key::Key<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		<Self as lib_cryptography::SymmetricAeadAlgorithm>::encrypt_with_associated_data(key, message, &[])
+	}
+
+	fn decrypt(key: &lib_cryptography::key::Key<Self>, message: &lib_cryptography::encrypted::Encrypted<Self>) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+		<Self as lib_cryptography::SymmetricAeadAlgorithm>::decrypt_with_associated_data(key, message, &[])
+	}
+}
@@ -0,0 +1,73 @@
+use cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm;
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len())
+		.step_by(2)
+		.filter_map(|i| hex.get(i..i + 2))
+		.filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+		.collect()
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+	let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+	let content: lib_bytes::NonEmpty = content.try_into()?;
+	Ok(content.into())
+}
+
+fn encoded<T>(encrypted: lib_cryptography::encrypted::Encrypted<T>) -> Vec<u8> {
+	let encrypted: lib_bytes::NonEmpty = encrypted.into();
+	let encrypted: bytes::Bytes = encrypted.into();
+	encrypted.to_vec()
+}
+
+// McGrew & Viega, "The Galois/Counter Mode of Operation", test case 16
+#[test]
+fn gcm_spec_vector() -> lib_cryptography::Result<()> {
+	let key: bytes::Bytes = bytes("feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308").into();
+	let key: lib_bytes::NonEmpty = key.try_into()?;
+	let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = key.into();
+	let nonce: [u8; 12] = bytes("cafebabefacedbaddecaf888").as_slice().try_into()?;
+	let aad: Vec<u8> = bytes("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+	let plaintext: lib_cryptography::message::Message = message(&bytes(concat!(
+		"d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+		"1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b39"
+	)))?;
+	let encrypted = key.encrypt_with_nonce(&nonce, &plaintext, &aad)?;
+	let expected: Vec<u8> = bytes(concat!(
+		"cafebabefacedbaddecaf888",
+		"522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa",
+		"8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+		"76fc6ece0f4e1768cddf8853bb2d551b"
+	));
+	assert_eq!(encoded(encrypted.to_owned()), expected);
+	assert_eq!(key.decrypt_with_associated_data(&encrypted, &aad)?, plaintext);
+	Ok(())
+}
+
+#[test]
+fn round_trip() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let encrypted = key.encrypt(&plaintext)?;
+	assert_eq!(key.decrypt(&encrypted)?, plaintext);
+	Ok(())
+}
+
+#[test]
+fn rejects_wrong_associated_data() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let encrypted = key.encrypt_with_associated_data(&plaintext, b"header")?;
+	assert!(key.decrypt_with_associated_data(&encrypted, b"tampered").is_err());
+	Ok(())
+}
+
+#[test]
+fn rejects_wrong_key() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = lib_cryptography::key::Key::generate()?;
+	let other: lib_cryptography::key::Key<Aes256GcmAlgorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let encrypted = key.encrypt(&plaintext)?;
+	assert!(other.decrypt(&encrypted).is_err());
+	Ok(())
+}
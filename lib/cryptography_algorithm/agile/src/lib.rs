@@ -0,0 +1,255 @@
+use lib_cryptography::Algorithm as _;
+use lib_cryptography::AsymmetricSetLayout as _;
+use cryptography_algorithm_ed25519::Ed25519Algorithm;
+use cryptography_algorithm_dilithium3::Dilithium3;
+use cryptography_algorithm_ed25519_dilithium3::Ed25519Dilithium3Algorithm;
+
+/// Length of the big endian algorithm id prefixing every key and signature.
+pub const TAG_LEN: usize = 2;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("unknown algorithm tag {0:#06x}")]
+	UnknownTag(u16),
+
+	#[error("missing algorithm tag")]
+	MissingTag
+}
+
+/// Concrete algorithm behind an [`AgileAlgorithm`] key or signature. Its wire
+/// value is the algorithm's [`lib_cryptography::Algorithm::ID`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Hash)]
+pub enum Tag {
+	Ed25519,
+	Dilithium3,
+	Ed25519Dilithium3
+}
+
+impl Tag {
+	pub const ALL: [Self; 3] = [Self::Ed25519, Self::Dilithium3, Self::Ed25519Dilithium3];
+
+	pub const fn id(self) -> u16 {
+		match self {
+			Self::Ed25519 => Ed25519Algorithm::ID,
+			Self::Dilithium3 => Dilithium3::ID,
+			Self::Ed25519Dilithium3 => Ed25519Dilithium3Algorithm::ID
+		}
+	}
+
+	pub const fn public_key_len(self) -> usize {
+		match self {
+			Self::Ed25519 => Ed25519Algorithm::PUBLIC_KEY_LEN,
+			Self::Dilithium3 => Dilithium3::PUBLIC_KEY_LEN,
+			Self::Ed25519Dilithium3 => Ed25519Dilithium3Algorithm::PUBLIC_KEY_LEN
+		}
+	}
+
+	pub const fn signature_len(self) -> usize {
+		match self {
+			Self::Ed25519 => Ed25519Algorithm::SIGNATURE_LEN,
+			Self::Dilithium3 => Dilithium3::SIGNATURE_LEN,
+			Self::Ed25519Dilithium3 => Ed25519Dilithium3Algorithm::SIGNATURE_LEN
+		}
+	}
+}
+
+impl TryFrom<u16> for Tag {
+	type Error = Error;
+
+	fn try_from(value: u16) -> Result<Self, Self::Error> {
+		Self::ALL.into_iter().find(|tag| tag.id() == value).ok_or(Error::UnknownTag(value))
+	}
+}
+
+impl From<Tag> for u16 {
+	fn from(value: Tag) -> Self {
+		value.id()
+	}
+}
+
+/// Signature algorithm chosen at runtime.
+///
+/// Keys and signatures are `tag (2, big endian) || inner`, where `inner` is
+/// the key or signature of the algorithm [`Tag`] names, so one verifier can
+/// take ed25519, dilithium3 and composite signers side by side. A signature
+/// only verifies under a public key carrying the same tag.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct AgileAlgorithm;
+
+impl AgileAlgorithm {
+	pub fn generate_with(tag: Tag) -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+		match tag {
+			Tag::Ed25519 => generate_as::<Ed25519Algorithm>(),
+			Tag::Dilithium3 => generate_as::<Dilithium3>(),
+			Tag::Ed25519Dilithium3 => generate_as::<Ed25519Dilithium3Algorithm>()
+		}
+	}
+
+	pub fn tag(public_key: &lib_cryptography::public_key::PublicKey<Self>) -> lib_cryptography::Result<Tag> {
+		let (tag, _) = untag((**public_key).to_owned())?;
+		Ok(tag)
+	}
+
+	/// Tags the public key of a concrete algorithm.
+	pub fn public_key_from<A>(public_key: lib_cryptography::public_key::PublicKey<A>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>>
+	where
+		A: lib_cryptography::Algorithm {
+		let tag: Tag = A::ID.try_into()?;
+		let out: lib_bytes::NonEmpty = tagged(tag, public_key)?;
+		Ok(out.into())
+	}
+
+	/// Tags the secret key of a concrete algorithm.
+	pub fn secret_key_from<A>(secret_key: &lib_cryptography::secret_key::SecretKey<A>) -> lib_cryptography::Result<lib_cryptography::secret_key::SecretKey<Self>>
+	where
+		A: lib_cryptography::Algorithm {
+		let tag: Tag = A::ID.try_into()?;
+		tagged_secret(tag, secret_key.expose_secret())
+	}
+}
+
+impl lib_cryptography::Algorithm for AgileAlgorithm {
+	const ID: u16 = lib_cryptography::id::TAGGED;
+}
+
+impl lib_cryptography::AsymmetricTaggedLayout for AgileAlgorithm {
+	fn public_key_len(algorithm: u16) -> Option<usize> {
+		let tag: Tag = algorithm.try_into().ok()?;
+		Some(TAG_LEN + tag.public_key_len())
+	}
+
+	fn signature_len(algorithm: u16) -> Option<usize> {
+		let tag: Tag = algorithm.try_into().ok()?;
+		Some(TAG_LEN + tag.signature_len())
+	}
+
+	fn algorithm_of(public_key: &lib_cryptography::public_key::PublicKey<Self>) -> Option<u16> {
+		Self::tag(public_key).ok().map(Tag::id)
+	}
+}
+
+/// Generates a composite [`Tag::Ed25519Dilithium3`] pair, the choice that
+/// stays secure while either component is unbroken.
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for AgileAlgorithm {
+	fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+		Self::generate_with(Tag::Ed25519Dilithium3)
+	}
+}
+
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for AgileAlgorithm {
+	fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+		let (tag, secret_key) = untag_secret(secret_key.expose_secret())?;
+		let public_key: lib_bytes::NonEmpty = match tag {
+			Tag::Ed25519 => public_key_as::<Ed25519Algorithm>(secret_key)?,
+			Tag::Dilithium3 => public_key_as::<Dilithium3>(secret_key)?,
+			Tag::Ed25519Dilithium3 => public_key_as::<Ed25519Dilithium3Algorithm>(secret_key)?
+		};
+		let out: lib_bytes::NonEmpty = tagged(tag, public_key)?;
+		Ok(out.into())
+	}
+}
+
+impl lib_cryptography::AsymmetricSignatureAlgorithm for AgileAlgorithm {
+	fn sign(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::signature::Signature<Self>> {
+		let (tag, secret_key) = untag_secret(secret_key.expose_secret())?;
+		let out: lib_bytes::NonEmpty = match tag {
+			Tag::Ed25519 => sign_as::<Ed25519Algorithm>(secret_key, message)?,
+			Tag::Dilithium3 => sign_as::<Dilithium3>(secret_key, message)?,
+			Tag::Ed25519Dilithium3 => sign_as::<Ed25519Dilithium3Algorithm>(secret_key, message)?
+		};
+		let out: lib_cryptography::signature::Signature<Self> = tagged(tag, out)?.into();
+		Ok(out)
+	}
+
+	fn verify(public_key: &lib_cryptography::public_key::PublicKey<Self>, message: &lib_cryptography::message::Message, signature: &lib_cryptography::signature::Signature<Self>) -> lib_cryptography::Result<bool> {
+		let (tag, public_key) = untag((**public_key).to_owned())?;
+		let (signature_tag, signature) = untag((**signature).to_owned())?;
+		if tag != signature_tag {
+			return Ok(false)
+		}
+		match tag {
+			Tag::Ed25519 => verify_as::<Ed25519Algorithm>(public_key, message, signature),
+			Tag::Dilithium3 => verify_as::<Dilithium3>(public_key, message, signature),
+			Tag::Ed25519Dilithium3 => verify_as::<Ed25519Dilithium3Algorithm>(public_key, message, signature)
+		}
+	}
+}
+
+fn generate_as<A>() -> lib_cryptography::Result<lib_cryptography::pair::Pair<AgileAlgorithm>>
+where
+	A: lib_cryptography::AsymmetricKeyGenAlgorithm {
+	let (public_key, secret_key) = A::generate()?.into();
+	let public_key: lib_cryptography::public_key::PublicKey<AgileAlgorithm> = AgileAlgorithm::public_key_from(public_key)?;
+	let secret_key: lib_cryptography::secret_key::SecretKey<AgileAlgorithm> = AgileAlgorithm::secret_key_from(&secret_key)?;
+	let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+	Ok(out)
+}
+
+fn public_key_as<A>(secret_key: &[u8]) -> lib_cryptography::Result<lib_bytes::NonEmpty>
+where
+	A: lib_cryptography::AsymmetricKeyDerivationAlgorithm {
+	let secret_key: lib_cryptography::secret_key::SecretKey<A> = secret_key.to_vec().try_into()?;
+	Ok(A::public_key(&secret_key)?.into())
+}
+
+fn sign_as<A>(secret_key: &[u8], message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_bytes::NonEmpty>
+where
+	A: lib_cryptography::AsymmetricSignatureAlgorithm {
+	let secret_key: lib_cryptography::secret_key::SecretKey<A> = secret_key.to_vec().try_into()?;
+	let out: lib_cryptography::signature::Signature<A> = A::sign(&secret_key, message)?;
+	Ok(out.into())
+}
+
+fn verify_as<A>(public_key: bytes::Bytes, message: &lib_cryptography::message::Message, signature: bytes::Bytes) -> lib_cryptography::Result<bool>
+where
+	A: lib_cryptography::AsymmetricSignatureAlgorithm {
+	let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+	let public_key: lib_cryptography::public_key::PublicKey<A> = public_key.into();
+	let signature: lib_bytes::NonEmpty = signature.try_into()?;
+	let signature: lib_cryptography::signature::Signature<A> = signature.into();
+	A::verify(&public_key, message, &signature)
+}
+
+fn tagged<T>(tag: Tag, value: T) -> lib_cryptography::Result<lib_bytes::NonEmpty>
+where
+	T: Into<lib_bytes::NonEmpty> {
+	let value: lib_bytes::NonEmpty = value.into();
+	let value: bytes::Bytes = value.into();
+	let mut out: Vec<u8> = Vec::with_capacity(TAG_LEN + value.len());
+	out.extend_from_slice(&tag.id().to_be_bytes());
+	out.extend_from_slice(&value);
+	let out: bytes::Bytes = out.into();
+	out.try_into()
+}
+
+fn tagged_secret(tag: Tag, secret_key: &[u8]) -> lib_cryptography::Result<lib_cryptography::secret_key::SecretKey<AgileAlgorithm>> {
+	let mut out: Vec<u8> = Vec::with_capacity(TAG_LEN + secret_key.len());
+	out.extend_from_slice(&tag.id().to_be_bytes());
+	out.extend_from_slice(secret_key);
+	out.try_into()
+}
+
+fn untag(value: lib_bytes::NonEmpty) -> lib_cryptography::Result<(Tag, bytes::Bytes)> {
+	let mut reader: lib_bytes::Reader = value.into();
+	let tag: u16 = reader.read_u16().map_err(|_| Error::MissingTag)?;
+	let tag: Tag = tag.try_into()?;
+	Ok((tag, reader.rest()))
+}
+
+// secret keys are split in place rather than copied into a reader, which
+// would leave a copy behind that is not wiped on drop
+fn untag_secret(value: &[u8]) -> lib_cryptography::Result<(Tag, &[u8])> {
+	let (tag, rest) = value.split_at_checked(TAG_LEN).ok_or(Error::MissingTag)?;
+	let tag: lib_bytes::Fixed<TAG_LEN> = tag.try_into()?;
+	let tag: Tag = u16::from_be_bytes(tag.into_array()).try_into()?;
+	Ok((tag, rest))
+}
@@ -0,0 +1,145 @@
+use cryptography_algorithm_agile::AgileAlgorithm;
+use cryptography_algorithm_agile::Tag;
+use cryptography_algorithm_ed25519::Ed25519Algorithm;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Payload(lib_bytes::NonEmpty);
+
+impl TryFrom<lib_bytes::NonEmpty> for Payload {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: lib_bytes::NonEmpty) -> Result<Self, Self::Error> {
+		Ok(Self(value))
+	}
+}
+
+impl From<Payload> for lib_bytes::NonEmpty {
+	fn from(value: Payload) -> Self {
+		value.0
+	}
+}
+
+struct Test;
+
+impl lib_packet::Protocol for Test {
+	const ID: u16 = 0;
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+	let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+	let content: lib_bytes::NonEmpty = content.try_into()?;
+	Ok(content.into())
+}
+
+fn pair(tag: Tag) -> lib_cryptography::Result<(lib_cryptography::public_key::PublicKey<AgileAlgorithm>, lib_cryptography::secret_key::SecretKey<AgileAlgorithm>)> {
+	let pair: lib_cryptography::pair::Pair<AgileAlgorithm> = AgileAlgorithm::generate_with(tag)?;
+	Ok(pair.into())
+}
+
+fn packet(tag: Tag) -> lib_cryptography::Result<lib_bytes::NonEmpty> {
+	let (_, secret_key) = pair(tag)?;
+	let content: Payload = Payload(message(b"agile packet")?.into());
+	let packet: lib_packet::MarkedSignedVerified<Payload, AgileAlgorithm, Test> = lib_packet::Unsigned::<Payload, Test>::new(content).sign(&secret_key)?;
+	assert_eq!(packet.header().algorithm(), tag.id());
+	packet.try_into()
+}
+
+#[test]
+fn sign_and_verify_every_tag() -> lib_cryptography::Result<()> {
+	for tag in Tag::ALL {
+		let (public_key, secret_key) = pair(tag)?;
+		assert_eq!(AgileAlgorithm::tag(&public_key)?, tag);
+		assert_eq!(secret_key.public_key()?, public_key);
+		let message: lib_cryptography::message::Message = message(b"agile")?;
+		let signature = <AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message)?;
+		assert!(<AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message, &signature)?);
+	}
+	Ok(())
+}
+
+#[test]
+fn rejects_signature_of_other_tag() -> lib_cryptography::Result<()> {
+	let (public_key, _) = pair(Tag::Ed25519)?;
+	let (_, secret_key) = pair(Tag::Ed25519Dilithium3)?;
+	let message: lib_cryptography::message::Message = message(b"agile")?;
+	let signature = <AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message)?;
+	assert!(!<AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message, &signature)?);
+	Ok(())
+}
+
+#[test]
+fn tags_concrete_keys() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = lib_cryptography::pair::Pair::<Ed25519Algorithm>::generate()?.into();
+	let public_key: lib_cryptography::public_key::PublicKey<AgileAlgorithm> = AgileAlgorithm::public_key_from(public_key)?;
+	let secret_key: lib_cryptography::secret_key::SecretKey<AgileAlgorithm> = AgileAlgorithm::secret_key_from(&secret_key)?;
+	assert_eq!(AgileAlgorithm::tag(&public_key)?, Tag::Ed25519);
+	assert_eq!(secret_key.public_key()?, public_key);
+	Ok(())
+}
+
+#[test]
+fn rejects_unknown_tag() {
+	assert!(Tag::try_from(lib_cryptography::id::X25519).is_err());
+	assert!(Tag::try_from(lib_cryptography::id::TAGGED).is_err());
+}
+
+#[test]
+fn decodes_packets_of_every_tag() -> lib_cryptography::Result<()> {
+	for tag in Tag::ALL {
+		let packet: lib_packet::MarkedSignedVerified<Payload, AgileAlgorithm, Test> = packet(tag)?.try_into()?;
+		assert_eq!(AgileAlgorithm::tag(packet.signer())?, tag);
+	}
+	Ok(())
+}
+
+#[test]
+fn policy_controls_accepted_tags() -> lib_cryptography::Result<()> {
+	let policy: lib_packet::policy::Policy = lib_packet::policy::Policy::default().reject(Tag::Ed25519.id());
+	let classical: lib_packet::MarkedSignedUnverified<Payload, AgileAlgorithm, Test> = packet(Tag::Ed25519)?.try_into()?;
+	assert!(classical.verify_with(&policy).is_err());
+	let composite: lib_packet::MarkedSignedUnverified<Payload, AgileAlgorithm, Test> = packet(Tag::Ed25519Dilithium3)?.try_into()?;
+	assert!(composite.verify_with(&policy).is_ok());
+	Ok(())
+}
+
+#[test]
+fn concrete_verifier_rejects_other_algorithm() -> lib_cryptography::Result<()> {
+	let packet: lib_cryptography::Result<lib_packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Test>> = packet(Tag::Dilithium3)?.try_into();
+	assert!(packet.is_err());
+	Ok(())
+}
+
+#[test]
+fn malformed_secret_key_has_no_public_key() -> lib_cryptography::Result<()> {
+	for tag in Tag::ALL {
+		let mut secret_key: Vec<u8> = tag.id().to_be_bytes().to_vec();
+		secret_key.extend_from_slice(&[7; 16]);
+		let secret_key: lib_cryptography::secret_key::SecretKey<AgileAlgorithm> = secret_key.try_into()?;
+		assert!(secret_key.public_key().is_err());
+	}
+	let secret_key: lib_cryptography::secret_key::SecretKey<AgileAlgorithm> = vec![0xff; 64].try_into()?;
+	assert!(secret_key.public_key().is_err());
+	Ok(())
+}
+
+#[test]
+fn rejects_key_without_tag_or_inner_key() -> lib_cryptography::Result<()> {
+	let (_, secret_key) = pair(Tag::Ed25519)?;
+	let message: lib_cryptography::message::Message = message(b"agile")?;
+	let signature = <AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message)?;
+	let untagged: bytes::Bytes = vec![0].into();
+	let untagged: lib_bytes::NonEmpty = untagged.try_into()?;
+	let untagged: lib_cryptography::public_key::PublicKey<AgileAlgorithm> = untagged.into();
+	let error: Option<String> = AgileAlgorithm::tag(&untagged).err().map(|error| error.to_string());
+	assert_eq!(error.as_deref(), Some("missing algorithm tag"));
+	assert!(<AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&untagged, &message, &signature).is_err());
+	let bare: bytes::Bytes = Tag::Ed25519.id().to_be_bytes().to_vec().into();
+	let bare: lib_bytes::NonEmpty = bare.try_into()?;
+	let bare: lib_cryptography::public_key::PublicKey<AgileAlgorithm> = bare.into();
+	assert_eq!(AgileAlgorithm::tag(&bare)?, Tag::Ed25519);
+	assert!(<AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&bare, &message, &signature).is_err());
+	Ok(())
+}
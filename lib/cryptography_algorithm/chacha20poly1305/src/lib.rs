@@ -0,0 +1,71 @@
+pub use cryptography_algorithm_aead::Error;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// ChaCha20-Poly1305 as specified by RFC 8439.
+///
+/// Ciphertexts are laid out as `nonce || ciphertext || tag`. The trait entry
+/// points draw a random nonce for every message, which is safe for up to
+/// 2^32 messages under one key; callers encrypting more than that, or
+/// wanting deterministic nonces, should drive [`lib_cryptography::SymmetricAeadAlgorithm::encrypt_with_nonce`]
+/// from a [`lib_cryptography::nonce::Sequence`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct ChaCha20Poly1305Algorithm;
+
+impl lib_cryptography::Algorithm for ChaCha20Poly1305Algorithm {
+	const ID: u16 = lib_cryptography::id::CHACHA20POLY1305;
+}
+
+impl lib_cryptography::SymmetricSetLayout for ChaCha20Poly1305Algorithm {
+	const KEY_LEN: usize = KEY_LEN;
+	const NONCE_LEN: usize = NONCE_LEN;
+	const TAG_LEN: usize = TAG_LEN;
+}
+
+impl lib_cryptography::SymmetricKeyGenAlgorithm for ChaCha20Poly1305Algorithm {
+	fn generate() -> lib_cryptography::Result<lib_cryptography::key::Key<Self>> {
+		cryptography_algorithm_aead::generate::<Self>()
+	}
+}
+
+impl lib_cryptography::SymmetricAeadAlgorithm for ChaCha20Poly1305Algorithm {
+	fn encrypt_with_associated_data(
+		key: &lib_cryptography::key::Key<Self>,
+		message: &lib_cryptography::message::Message,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		cryptography_algorithm_aead::encrypt_with_associated_data::<chacha20poly1305::ChaCha20Poly1305, Self>(key, message, associated_data)
+	}
+
+	fn encrypt_with_nonce(
+		key: &lib_cryptography::key::Key<Self>,
+		nonce: &[u8],
+		message: &lib_cryptography::message::Message,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		cryptography_algorithm_aead::encrypt_with_nonce::<chacha20poly1305::ChaCha20Poly1305, Self>(key, nonce, message, associated_data)
+	}
+
+	fn decrypt_with_associated_data(
+		key: &lib_cryptography::key::Key<Self>,
+		message: &lib_cryptography::encrypted::Encrypted<Self>,
+		associated_data: &[u8]
+	) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+		cryptography_algorithm_aead::decrypt_with_associated_data::<chacha20poly1305::ChaCha20Poly1305, Self>(key, message, associated_data)
+	}
+}
+
+impl lib_cryptography::SymmetricEncryptionAlgorithm for ChaCha20Poly1305Algorithm {
+	fn encrypt(key: &lib_cryptography::key::Key<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+		<Self as lib_cryptography::SymmetricAeadAlgorithm>::encrypt_with_associated_data(key, message, &[])
+	}
+
+	fn decrypt(key: &lib_cryptography::key::Key<Self>, message: &lib_cryptography::encrypted::Encrypted<Self>) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+		<Self as lib_cryptography::SymmetricAeadAlgorithm>::decrypt_with_associated_data(key, message, &[])
+	}
+}
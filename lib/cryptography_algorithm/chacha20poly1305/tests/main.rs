@@ -0,0 +1,106 @@
+use cryptography_algorithm_chacha20poly1305::ChaCha20Poly1305Algorithm;
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len())
+		.step_by(2)
+		.filter_map(|i| hex.get(i..i + 2))
+		.filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+		.collect()
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+	let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+	let content: lib_bytes::NonEmpty = content.try_into()?;
+	Ok(content.into())
+}
+
+fn encoded<T>(encrypted: lib_cryptography::encrypted::Encrypted<T>) -> Vec<u8> {
+	let encrypted: lib_bytes::NonEmpty = encrypted.into();
+	let encrypted: bytes::Bytes = encrypted.into();
+	encrypted.to_vec()
+}
+
+// RFC 8439 section 2.8.2
+#[test]
+fn rfc8439_vector() -> lib_cryptography::Result<()> {
+	let key: bytes::Bytes = (0x80..=0x9f).collect::<Vec<u8>>().into();
+	let key: lib_bytes::NonEmpty = key.try_into()?;
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = key.into();
+	let nonce: [u8; 12] = bytes("070000004041424344454647").as_slice().try_into()?;
+	let aad: Vec<u8> = bytes("50515253c0c1c2c3c4c5c6c7");
+	let plaintext: lib_cryptography::message::Message = message(b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for the future, sunscreen would be it.")?;
+	let encrypted = key.encrypt_with_nonce(&nonce, &plaintext, &aad)?;
+	let expected: Vec<u8> = bytes(concat!(
+		"070000004041424344454647",
+		"d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6",
+		"3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36",
+		"92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc",
+		"3ff4def08e4b7a9de576d26586cec64b6116",
+		"1ae10b594f09e26a7e902ecbd0600691"
+	));
+	assert_eq!(encoded(encrypted.to_owned()), expected);
+	assert_eq!(key.decrypt_with_associated_data(&encrypted, &aad)?, plaintext);
+	Ok(())
+}
+
+#[test]
+fn round_trip() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let encrypted = key.encrypt(&plaintext)?;
+	assert_eq!(key.decrypt(&encrypted)?, plaintext);
+	Ok(())
+}
+
+#[test]
+fn nonces_are_not_reused() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	assert_ne!(key.encrypt(&plaintext)?, key.encrypt(&plaintext)?);
+	Ok(())
+}
+
+#[test]
+fn rejects_wrong_associated_data() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let encrypted = key.encrypt_with_associated_data(&plaintext, b"header")?;
+	assert!(key.decrypt_with_associated_data(&encrypted, b"tampered").is_err());
+	assert!(key.decrypt(&encrypted).is_err());
+	Ok(())
+}
+
+#[test]
+fn rejects_tampered_ciphertext() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let mut encrypted: Vec<u8> = encoded(key.encrypt(&plaintext)?);
+	if let Some(byte) = encrypted.last_mut() {
+		*byte ^= 1;
+	}
+	let encrypted: bytes::Bytes = encrypted.into();
+	let encrypted: lib_bytes::NonEmpty = encrypted.try_into()?;
+	assert!(key.decrypt(&encrypted.into()).is_err());
+	Ok(())
+}
+
+#[test]
+fn rejects_truncated_ciphertext() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let encrypted: bytes::Bytes = bytes::Bytes::from_static(&[0; 27]);
+	let encrypted: lib_bytes::NonEmpty = encrypted.try_into()?;
+	assert!(key.decrypt(&encrypted.into()).is_err());
+	Ok(())
+}
+
+#[test]
+fn sequence_drives_unique_nonces() -> lib_cryptography::Result<()> {
+	let key: lib_cryptography::key::Key<ChaCha20Poly1305Algorithm> = lib_cryptography::key::Key::generate()?;
+	let plaintext: lib_cryptography::message::Message = message(b"session payload")?;
+	let mut sequence: lib_cryptography::nonce::Sequence = lib_cryptography::nonce::Sequence::new([1, 2, 3, 4]);
+	let first = key.encrypt_with_nonce(&sequence.advance()?, &plaintext, &[])?;
+	let second = key.encrypt_with_nonce(&sequence.advance()?, &plaintext, &[])?;
+	assert_ne!(first, second);
+	assert_eq!(encoded(second).get(..12), Some([1, 2, 3, 4, 0, 0, 0, 0, 0, 0, 0, 1].as_slice()));
+	Ok(())
+}
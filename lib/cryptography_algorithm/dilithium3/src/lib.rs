@@ -3,13 +3,20 @@ use pqcrypto_traits::sign::PublicKey as _;
 use pqcrypto_traits::sign::SecretKey as _;
 use pqcrypto_dilithium::dilithium3;
 
-pub type Bytes = Box<[u8]>;
+const PUBLIC_KEY_LEN: usize = 1952;
+const SIGNATURE_LEN: usize = 3309;
 
-pub type Result<T> = std::result::Result<T, Error>;
+// dilithium secret keys do not carry the full public key, so the public key
+// is appended to make it derivable: `dilithium3_secret_key || public_key`
+const INNER_SECRET_KEY_LEN: usize = 4032;
+const SECRET_KEY_LEN: usize = INNER_SECRET_KEY_LEN + PUBLIC_KEY_LEN;
 
 #[derive(Debug)]
 #[derive(thiserror::Error)]
 pub enum Error {
+    #[error("invalid public key")]
+    InvalidPublicKey,
+
     #[error("invalid secret key")]
     InvalidSecretKey,
 
@@ -17,41 +24,69 @@ pub enum Error {
     InvalidSignature
 }
 
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
 pub struct Dilithium3;
 
-impl cryptography::AsymmetricKeyGenAlgorithm for Dilithium3 {
-    fn generate() -> cryptography::Result<cryptography::Pair> {
-        let pair: (_, _) = dilithium3::keypair();
-        let pk = pair.0;
-        let pk: Bytes = pk.as_bytes().to_vec().into();
-        let sk = pair.1;
-        let sk: Bytes = sk.as_bytes().to_vec().into();
-        Ok((pk, sk))
+impl lib_cryptography::Algorithm for Dilithium3 {
+    const ID: u16 = lib_cryptography::id::DILITHIUM3;
+}
+
+impl lib_cryptography::AsymmetricSetLayout for Dilithium3 {
+    const PUBLIC_KEY_LEN: usize = PUBLIC_KEY_LEN;
+    const SECRET_KEY_LEN: usize = SECRET_KEY_LEN;
+    const SIGNATURE_LEN: usize = SIGNATURE_LEN;
+}
+
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for Dilithium3 {
+    fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+        let (public_key, secret_key) = dilithium3::keypair();
+        let public_key: &[u8] = public_key.as_bytes();
+        let mut out: Vec<u8> = Vec::with_capacity(SECRET_KEY_LEN);
+        out.extend_from_slice(secret_key.as_bytes());
+        out.extend_from_slice(public_key);
+        let secret_key: lib_cryptography::secret_key::SecretKey<_> = out.try_into()?;
+        let public_key: bytes::Bytes = bytes::Bytes::copy_from_slice(public_key);
+        let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+        let public_key: lib_cryptography::public_key::PublicKey<_> = public_key.into();
+        let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+        Ok(out)
     }
 }
 
-impl cryptography::AsymmetricSignatureAlgorithm for Dilithium3 {
-    fn sign(sk: &cryptography::SecretKey, msg: &cryptography::Message) -> cryptography::Result<cryptography::Signature> {
-        let sk: dilithium3::SecretKey = dilithium3::SecretKey::from_bytes(sk)
-            .ok()
-            .ok_or(Error::InvalidSecretKey)?;
-        let sig: dilithium3::DetachedSignature = dilithium3::detached_sign(msg, &sk);
-        let sig: Bytes = sig.as_bytes().to_owned().into();
-        Ok(sig)
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for Dilithium3 {
+    fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+        let public_key: &[u8] = secret_key.expose_secret().get(INNER_SECRET_KEY_LEN..).ok_or(Error::InvalidSecretKey)?;
+        let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.try_into().map_err(|_| Error::InvalidSecretKey)?;
+        let out: bytes::Bytes = public_key.into();
+        let out: lib_bytes::NonEmpty = out.try_into()?;
+        Ok(out.into())
     }
+}
 
-    fn verify(
-        pk: &cryptography::PublicKey,
-        msg: &cryptography::Message,
-        sig: &cryptography::Signature
-    ) -> cryptography::Result<bool> {
-        let pk: dilithium3::PublicKey = dilithium3::PublicKey::from_bytes(pk)
-            .ok()
-            .ok_or(Error::InvalidSecretKey)?;
-        let sig: dilithium3::DetachedSignature = dilithium3::DetachedSignature::from_bytes(sig)
-            .ok()
-            .ok_or(Error::InvalidSignature)?;
-        let ret: bool = dilithium3::verify_detached_signature(&sig, msg, &pk).is_ok();
-        Ok(ret)
+impl lib_cryptography::AsymmetricSignatureAlgorithm for Dilithium3 {
+    fn sign(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::signature::Signature<Self>> {
+        let message: lib_bytes::NonEmpty = message.to_owned().into();
+        let message: bytes::Bytes = message.into();
+        let secret_key: &[u8] = secret_key.expose_secret().get(..INNER_SECRET_KEY_LEN).ok_or(Error::InvalidSecretKey)?;
+        let secret_key: dilithium3::SecretKey = dilithium3::SecretKey::from_bytes(secret_key).map_err(|_| Error::InvalidSecretKey)?;
+        let out: dilithium3::DetachedSignature = dilithium3::detached_sign(&message, &secret_key);
+        let out: bytes::Bytes = bytes::Bytes::copy_from_slice(out.as_bytes());
+        let out: lib_bytes::NonEmpty = out.try_into()?;
+        let out: lib_cryptography::signature::Signature<Self> = out.into();
+        Ok(out)
     }
-}
\ No newline at end of file
+
+    fn verify(public_key: &lib_cryptography::public_key::PublicKey<Self>, message: &lib_cryptography::message::Message, signature: &lib_cryptography::signature::Signature<Self>) -> lib_cryptography::Result<bool> {
+        let message: lib_bytes::NonEmpty = message.to_owned().into();
+        let message: bytes::Bytes = message.into();
+        let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.to_fixed().map_err(|_| Error::InvalidPublicKey)?;
+        let public_key: dilithium3::PublicKey = dilithium3::PublicKey::from_bytes(public_key.as_array()).map_err(|_| Error::InvalidPublicKey)?;
+        let signature: lib_bytes::Fixed<SIGNATURE_LEN> = signature.to_fixed().map_err(|_| Error::InvalidSignature)?;
+        let signature: dilithium3::DetachedSignature = dilithium3::DetachedSignature::from_bytes(signature.as_array()).map_err(|_| Error::InvalidSignature)?;
+        let out: bool = dilithium3::verify_detached_signature(&signature, &message, &public_key).is_ok();
+        Ok(out)
+    }
+}
@@ -0,0 +1,121 @@
+use cryptography_algorithm_dilithium3::Dilithium3;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Payload(lib_bytes::NonEmpty);
+
+impl TryFrom<lib_bytes::NonEmpty> for Payload {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: lib_bytes::NonEmpty) -> Result<Self, Self::Error> {
+        Ok(Self(value))
+    }
+}
+
+impl From<Payload> for lib_bytes::NonEmpty {
+    fn from(value: Payload) -> Self {
+        value.0
+    }
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+    let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+    let content: lib_bytes::NonEmpty = content.try_into()?;
+    Ok(content.into())
+}
+
+fn pair() -> lib_cryptography::Result<(lib_cryptography::public_key::PublicKey<Dilithium3>, lib_cryptography::secret_key::SecretKey<Dilithium3>)> {
+    let pair: lib_cryptography::pair::Pair<Dilithium3> = lib_cryptography::pair::Pair::generate()?;
+    Ok(pair.into())
+}
+
+fn bytes_of<T: Into<lib_bytes::NonEmpty>>(value: T) -> bytes::Bytes {
+    let value: lib_bytes::NonEmpty = value.into();
+    value.into()
+}
+
+#[test]
+fn sign_and_verify() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"post quantum")?;
+    let signature = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message)?;
+    assert!(<Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message, &signature)?);
+    Ok(())
+}
+
+#[test]
+fn rejects_other_message() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let signature = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message(b"post quantum")?)?;
+    assert!(!<Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message(b"pre quantum")?, &signature)?);
+    Ok(())
+}
+
+#[test]
+fn layout() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let signature = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message(b"post quantum")?)?;
+    assert_eq!(bytes_of(public_key).len(), <Dilithium3 as lib_cryptography::AsymmetricSetLayout>::PUBLIC_KEY_LEN);
+    assert_eq!(secret_key.expose_secret().len(), <Dilithium3 as lib_cryptography::AsymmetricSetLayout>::SECRET_KEY_LEN);
+    assert_eq!(bytes_of(signature).len(), <Dilithium3 as lib_cryptography::AsymmetricSetLayout>::SIGNATURE_LEN);
+    Ok(())
+}
+
+#[test]
+fn public_key_is_derivable() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    assert_eq!(secret_key.public_key()?, public_key);
+    Ok(())
+}
+
+struct Test;
+
+impl lib_packet::Protocol for Test {
+    const ID: u16 = 0;
+}
+
+#[test]
+fn frames_signed_packet() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let content: lib_cryptography::message::Message = message(b"post quantum packet")?;
+    let packet: lib_packet::MarkedSignedVerified<Payload, Dilithium3, Test> = lib_packet::Unsigned::<Payload, Test>::new(Payload(content.to_owned().into())).sign(&secret_key)?;
+    let framed: lib_bytes::NonEmpty = packet.try_into()?;
+    let packet: lib_packet::MarkedSignedVerified<Payload, Dilithium3, Test> = framed.to_owned().try_into()?;
+    assert_eq!(packet.signer(), &public_key);
+    assert_eq!(packet.content().to_owned(), Payload(content.into()));
+    let mut tampered: Vec<u8> = bytes_of(framed).to_vec();
+    if let Some(byte) = tampered.last_mut() {
+        *byte ^= 1;
+    }
+    let tampered: bytes::Bytes = tampered.into();
+    let tampered: lib_bytes::NonEmpty = tampered.try_into()?;
+    let tampered: lib_packet::Result<lib_packet::MarkedSignedVerified<Payload, Dilithium3, Test>> = tampered.try_into();
+    assert!(tampered.is_err());
+    Ok(())
+}
+
+#[test]
+fn malformed_secret_key_has_no_public_key() -> lib_cryptography::Result<()> {
+    let secret_key: lib_cryptography::secret_key::SecretKey<Dilithium3> = vec![7; 100].try_into()?;
+    let error: Option<String> = secret_key.public_key().err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid secret key"));
+    Ok(())
+}
+
+#[test]
+fn rejects_public_key_and_signature_of_other_length() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"post quantum")?;
+    let signature = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&secret_key, &message)?;
+    let truncated: bytes::Bytes = bytes_of(public_key.to_owned()).slice(1..);
+    let truncated: lib_bytes::NonEmpty = truncated.try_into()?;
+    let error: Option<String> = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&truncated.into(), &message, &signature).err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid public key"));
+    let truncated: bytes::Bytes = bytes_of(signature).slice(1..);
+    let truncated: lib_bytes::NonEmpty = truncated.try_into()?;
+    let error: Option<String> = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message, &truncated.into()).err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid signature"));
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use lib_cryptography::AsymmetricSetLayout as _;
+use cryptography_algorithm_ed25519::Ed25519Algorithm;
+use cryptography_algorithm_dilithium3::Dilithium3;
+
+const PUBLIC_KEY_LEN: usize = Ed25519Algorithm::PUBLIC_KEY_LEN + Dilithium3::PUBLIC_KEY_LEN;
+const SECRET_KEY_LEN: usize = Ed25519Algorithm::SECRET_KEY_LEN + Dilithium3::SECRET_KEY_LEN;
+const SIGNATURE_LEN: usize = Ed25519Algorithm::SIGNATURE_LEN + Dilithium3::SIGNATURE_LEN;
+
+/// Prefixed to every message before either component signs it, so that a
+/// component signature lifted out of a composite one does not verify as a
+/// plain ed25519 or dilithium3 signature over the same message.
+pub const DOMAIN: &[u8] = b"ed25519-dilithium3-composite-v1";
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+    #[error("invalid public key")]
+    InvalidPublicKey,
+
+    #[error("invalid secret key")]
+    InvalidSecretKey,
+
+    #[error("invalid signature")]
+    InvalidSignature
+}
+
+/// Composite of [`Ed25519Algorithm`] and [`Dilithium3`].
+///
+/// Keys and signatures are the ed25519 component followed by the dilithium3
+/// component. A signature is valid only if both components verify, so the
+/// scheme stays secure as long as either one is unbroken.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Ed25519Dilithium3Algorithm;
+
+impl lib_cryptography::Algorithm for Ed25519Dilithium3Algorithm {
+    const ID: u16 = lib_cryptography::id::ED25519_DILITHIUM3;
+}
+
+impl lib_cryptography::AsymmetricSetLayout for Ed25519Dilithium3Algorithm {
+    const PUBLIC_KEY_LEN: usize = PUBLIC_KEY_LEN;
+    const SECRET_KEY_LEN: usize = SECRET_KEY_LEN;
+    const SIGNATURE_LEN: usize = SIGNATURE_LEN;
+}
+
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for Ed25519Dilithium3Algorithm {
+    fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+        let (ed25519_public_key, ed25519_secret_key) = lib_cryptography::pair::Pair::<Ed25519Algorithm>::generate()?.into();
+        let (dilithium3_public_key, dilithium3_secret_key) = lib_cryptography::pair::Pair::<Dilithium3>::generate()?.into();
+        let public_key: lib_cryptography::public_key::PublicKey<Self> = join(ed25519_public_key, dilithium3_public_key)?.into();
+        let secret_key: lib_cryptography::secret_key::SecretKey<Self> = join_secret(&ed25519_secret_key, &dilithium3_secret_key)?;
+        let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+        Ok(out)
+    }
+}
+
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for Ed25519Dilithium3Algorithm {
+    fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+        let (ed25519_secret_key, dilithium3_secret_key) = split_secret(secret_key).map_err(|_| Error::InvalidSecretKey)?;
+        let out: lib_bytes::NonEmpty = join(ed25519_secret_key.public_key()?, dilithium3_secret_key.public_key()?)?;
+        Ok(out.into())
+    }
+}
+
+impl lib_cryptography::AsymmetricSignatureAlgorithm for Ed25519Dilithium3Algorithm {
+    fn sign(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::signature::Signature<Self>> {
+        let (ed25519_secret_key, dilithium3_secret_key) = split_secret(secret_key).map_err(|_| Error::InvalidSecretKey)?;
+        let message: lib_cryptography::message::Message = domain_separated(message)?;
+        let ed25519_signature = <Ed25519Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&ed25519_secret_key, &message)?;
+        let dilithium3_signature = <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(&dilithium3_secret_key, &message)?;
+        let out: lib_cryptography::signature::Signature<Self> = join(ed25519_signature, dilithium3_signature)?.into();
+        Ok(out)
+    }
+
+    fn verify(public_key: &lib_cryptography::public_key::PublicKey<Self>, message: &lib_cryptography::message::Message, signature: &lib_cryptography::signature::Signature<Self>) -> lib_cryptography::Result<bool> {
+        let public_key: lib_bytes::NonEmpty = public_key.to_owned().into();
+        let (ed25519_public_key, dilithium3_public_key) = split::<{ Ed25519Algorithm::PUBLIC_KEY_LEN }, { Dilithium3::PUBLIC_KEY_LEN }>(public_key)
+            .map_err(|_| Error::InvalidPublicKey)?;
+        let signature: lib_bytes::NonEmpty = signature.to_owned().into();
+        let (ed25519_signature, dilithium3_signature) = split::<{ Ed25519Algorithm::SIGNATURE_LEN }, { Dilithium3::SIGNATURE_LEN }>(signature)
+            .map_err(|_| Error::InvalidSignature)?;
+        let message: lib_cryptography::message::Message = domain_separated(message)?;
+        let ed25519_public_key: lib_cryptography::public_key::PublicKey<Ed25519Algorithm> = ed25519_public_key.into();
+        let ed25519_signature: lib_cryptography::signature::Signature<Ed25519Algorithm> = ed25519_signature.into();
+        if !<Ed25519Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&ed25519_public_key, &message, &ed25519_signature)? {
+            return Ok(false)
+        }
+        let dilithium3_public_key: lib_cryptography::public_key::PublicKey<Dilithium3> = dilithium3_public_key.into();
+        let dilithium3_signature: lib_cryptography::signature::Signature<Dilithium3> = dilithium3_signature.into();
+        <Dilithium3 as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&dilithium3_public_key, &message, &dilithium3_signature)
+    }
+}
+
+fn domain_separated(message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+    let message: lib_bytes::NonEmpty = message.to_owned().into();
+    let message: bytes::Bytes = message.into();
+    let mut out: Vec<u8> = Vec::with_capacity(DOMAIN.len() + message.len());
+    out.extend_from_slice(DOMAIN);
+    out.extend_from_slice(&message);
+    let out: bytes::Bytes = out.into();
+    let out: lib_bytes::NonEmpty = out.try_into()?;
+    Ok(out.into())
+}
+
+fn join<A, B>(lhs: A, rhs: B) -> lib_cryptography::Result<lib_bytes::NonEmpty>
+where
+    A: Into<lib_bytes::NonEmpty>,
+    B: Into<lib_bytes::NonEmpty> {
+    let lhs: lib_bytes::NonEmpty = lhs.into();
+    let lhs: bytes::Bytes = lhs.into();
+    let rhs: lib_bytes::NonEmpty = rhs.into();
+    let rhs: bytes::Bytes = rhs.into();
+    let mut out: Vec<u8> = Vec::with_capacity(lhs.len() + rhs.len());
+    out.extend_from_slice(&lhs);
+    out.extend_from_slice(&rhs);
+    let out: bytes::Bytes = out.into();
+    out.try_into()
+}
+
+fn join_secret(
+    ed25519_secret_key: &lib_cryptography::secret_key::SecretKey<Ed25519Algorithm>,
+    dilithium3_secret_key: &lib_cryptography::secret_key::SecretKey<Dilithium3>
+) -> lib_cryptography::Result<lib_cryptography::secret_key::SecretKey<Ed25519Dilithium3Algorithm>> {
+    let ed25519_secret_key: &[u8] = ed25519_secret_key.expose_secret();
+    let dilithium3_secret_key: &[u8] = dilithium3_secret_key.expose_secret();
+    let mut out: Vec<u8> = Vec::with_capacity(ed25519_secret_key.len() + dilithium3_secret_key.len());
+    out.extend_from_slice(ed25519_secret_key);
+    out.extend_from_slice(dilithium3_secret_key);
+    out.try_into()
+}
+
+fn split_secret(
+    secret_key: &lib_cryptography::secret_key::SecretKey<Ed25519Dilithium3Algorithm>
+) -> lib_cryptography::Result<(lib_cryptography::secret_key::SecretKey<Ed25519Algorithm>, lib_cryptography::secret_key::SecretKey<Dilithium3>)> {
+    let secret_key: &[u8] = secret_key.expose_secret();
+    if secret_key.len() != SECRET_KEY_LEN {
+        return Err(String::from("unexpected length").into())
+    }
+    let (lhs, rhs) = secret_key.split_at(Ed25519Algorithm::SECRET_KEY_LEN);
+    Ok((lhs.to_vec().try_into()?, rhs.to_vec().try_into()?))
+}
+
+/// Exactly `L` bytes of the ed25519 component followed by exactly `R` bytes
+/// of the dilithium3 component.
+fn split<const L: usize, const R: usize>(value: lib_bytes::NonEmpty) -> std::result::Result<(lib_bytes::NonEmpty, lib_bytes::NonEmpty), lib_bytes::Error> {
+    let mut reader: lib_bytes::Reader = value.into();
+    let lhs: lib_bytes::Bounded<L, L> = reader.read_bounded(L)?;
+    let rhs: lib_bytes::Bounded<R, R> = reader.read_bounded(R)?;
+    reader.finish()?;
+    Ok((lhs.into(), rhs.into()))
+}
@@ -0,0 +1,159 @@
+use cryptography_algorithm_ed25519_dilithium3::Ed25519Dilithium3Algorithm;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Payload(lib_bytes::NonEmpty);
+
+impl TryFrom<lib_bytes::NonEmpty> for Payload {
+    type Error = Box<dyn std::error::Error>;
+
+    fn try_from(value: lib_bytes::NonEmpty) -> Result<Self, Self::Error> {
+        Ok(Self(value))
+    }
+}
+
+impl From<Payload> for lib_bytes::NonEmpty {
+    fn from(value: Payload) -> Self {
+        value.0
+    }
+}
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+    let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+    let content: lib_bytes::NonEmpty = content.try_into()?;
+    Ok(content.into())
+}
+
+fn pair() -> lib_cryptography::Result<(lib_cryptography::public_key::PublicKey<Ed25519Dilithium3Algorithm>, lib_cryptography::secret_key::SecretKey<Ed25519Dilithium3Algorithm>)> {
+    let pair: lib_cryptography::pair::Pair<Ed25519Dilithium3Algorithm> = lib_cryptography::pair::Pair::generate()?;
+    Ok(pair.into())
+}
+
+fn bytes_of<T: Into<lib_bytes::NonEmpty>>(value: T) -> Vec<u8> {
+    let value: lib_bytes::NonEmpty = value.into();
+    let value: bytes::Bytes = value.into();
+    value.to_vec()
+}
+
+fn from_vec<T: From<lib_bytes::NonEmpty>>(value: Vec<u8>) -> lib_cryptography::Result<T> {
+    let value: bytes::Bytes = value.into();
+    let value: lib_bytes::NonEmpty = value.try_into()?;
+    Ok(value.into())
+}
+
+fn sign(secret_key: &lib_cryptography::secret_key::SecretKey<Ed25519Dilithium3Algorithm>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::signature::Signature<Ed25519Dilithium3Algorithm>> {
+    <Ed25519Dilithium3Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(secret_key, message)
+}
+
+fn verify(public_key: &lib_cryptography::public_key::PublicKey<Ed25519Dilithium3Algorithm>, message: &lib_cryptography::message::Message, signature: &lib_cryptography::signature::Signature<Ed25519Dilithium3Algorithm>) -> lib_cryptography::Result<bool> {
+    <Ed25519Dilithium3Algorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(public_key, message, signature)
+}
+
+#[test]
+fn sign_and_verify() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"hybrid")?;
+    let signature = sign(&secret_key, &message)?;
+    assert!(verify(&public_key, &message, &signature)?);
+    assert!(!verify(&public_key, &self::message(b"other")?, &signature)?);
+    Ok(())
+}
+
+#[test]
+fn layout() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let signature = sign(&secret_key, &message(b"hybrid")?)?;
+    assert_eq!(bytes_of(public_key).len(), 32 + 1952);
+    assert_eq!(secret_key.expose_secret().len(), 32 + 4032 + 1952);
+    assert_eq!(bytes_of(signature).len(), 64 + 3309);
+    Ok(())
+}
+
+#[test]
+fn public_key_is_derivable() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    assert_eq!(secret_key.public_key()?, public_key);
+    Ok(())
+}
+
+#[test]
+fn malformed_secret_key_has_no_public_key() -> lib_cryptography::Result<()> {
+    let (_, secret_key) = pair()?;
+    let mut truncated: Vec<u8> = secret_key.expose_secret().to_vec();
+    truncated.truncate(32);
+    let truncated: lib_cryptography::secret_key::SecretKey<Ed25519Dilithium3Algorithm> = truncated.try_into()?;
+    let error: Option<String> = truncated.public_key().err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid secret key"));
+    Ok(())
+}
+
+#[test]
+fn rejects_either_component_tampered() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"hybrid")?;
+    let signature: Vec<u8> = bytes_of(sign(&secret_key, &message)?);
+    for index in [0, 64] {
+        let mut tampered: Vec<u8> = signature.to_owned();
+        if let Some(byte) = tampered.get_mut(index) {
+            *byte ^= 1;
+        }
+        assert!(!verify(&public_key, &message, &from_vec(tampered)?)?);
+    }
+    Ok(())
+}
+
+#[test]
+fn rejects_component_swapped_from_other_key() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let (_, other) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"hybrid")?;
+    let signature: Vec<u8> = bytes_of(sign(&secret_key, &message)?);
+    let other: Vec<u8> = bytes_of(sign(&other, &message)?);
+    let mut mixed: Vec<u8> = signature.get(..64).map(<[u8]>::to_vec).unwrap_or_default();
+    mixed.extend_from_slice(other.get(64..).unwrap_or_default());
+    assert!(!verify(&public_key, &message, &from_vec(mixed)?)?);
+    Ok(())
+}
+
+#[test]
+fn rejects_malformed_signature() -> lib_cryptography::Result<()> {
+    let (public_key, _) = pair()?;
+    assert!(verify(&public_key, &message(b"hybrid")?, &from_vec(vec![0; 64])?).is_err());
+    Ok(())
+}
+
+struct Test;
+
+impl lib_packet::Protocol for Test {
+    const ID: u16 = 0;
+}
+
+#[test]
+fn frames_signed_packet() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let content: lib_cryptography::message::Message = message(b"hybrid packet")?;
+    let packet: lib_packet::MarkedSignedVerified<Payload, Ed25519Dilithium3Algorithm, Test> = lib_packet::Unsigned::<Payload, Test>::new(Payload(content.to_owned().into())).sign(&secret_key)?;
+    let framed: lib_bytes::NonEmpty = packet.try_into()?;
+    let packet: lib_packet::MarkedSignedVerified<Payload, Ed25519Dilithium3Algorithm, Test> = framed.try_into()?;
+    assert_eq!(packet.signer(), &public_key);
+    assert_eq!(packet.content().to_owned(), Payload(content.into()));
+    Ok(())
+}
+
+#[test]
+fn rejects_public_key_of_other_length() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let message: lib_cryptography::message::Message = message(b"hybrid")?;
+    let signature = sign(&secret_key, &message)?;
+    let mut extended: Vec<u8> = bytes_of(public_key.to_owned());
+    extended.push(0);
+    let error: Option<String> = verify(&from_vec(extended)?, &message, &signature).err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid public key"));
+    let mut truncated: Vec<u8> = bytes_of(public_key);
+    truncated.pop();
+    let error: Option<String> = verify(&from_vec(truncated)?, &message, &signature).err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid public key"));
+    Ok(())
+}
@@ -1,73 +1,160 @@
-use aes_gcm::KeyInit;
 use pqcrypto_traits::kem::PublicKey as _;
 use pqcrypto_traits::kem::SecretKey as _;
 use pqcrypto_traits::kem::Ciphertext as _;
 use pqcrypto_traits::kem::SharedSecret as _;
 use pqcrypto_kyber::kyber1024;
-use aes_gcm::aead;
-use aes_gcm::aead::generic_array as aead_generic_array;
-use rand_core::RngCore as _;
-use super::*;
+use lib_cryptography::SymmetricAeadAlgorithm as _;
+use cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm;
 
-pub type PublicKey = [u8; 1568];
-pub type SecretKey = [u8; 3168];
-pub type Bytes = Box<[u8]>;
+pub const PUBLIC_KEY_LEN: usize = 1568;
+pub const SECRET_KEY_LEN: usize = 3168;
+pub const KEM_CIPHERTEXT_LEN: usize = 1568;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
 
-pub type Result<T> = std::result::Result<T, Error>;
+/// Label mixed into the key derivation so that a shared secret can never be
+/// reused as a key by another construction.
+pub const HKDF_INFO: &[u8] = b"kyber1024-hkdf-sha256-aes256gcm-v1";
+
+// a kyber secret key embeds its public key after the 1536 byte indcpa secret
+const EMBEDDED_PUBLIC_KEY_OFFSET: usize = 1536;
 
 #[derive(Debug)]
+#[derive(thiserror::Error)]
 pub enum Error {
+    #[error("invalid public key")]
     InvalidPublicKey,
+
+    #[error("invalid secret key")]
     InvalidSecretKey,
+
+    #[error("invalid ciphertext")]
     InvalidCipherText
 }
 
-pub struct Unset;
+/// Kyber1024 key encapsulation followed by AES-256-GCM.
+///
+/// Every message is encrypted under a fresh key derived from its own
+/// encapsulation:
+///
+/// ```text
+/// shared_secret       = kyber1024::encapsulate(public_key)
+/// aead_key            = hkdf_sha256(salt = none, ikm = shared_secret, info = HKDF_INFO)
+/// encrypted           = kem_ciphertext (1568) || nonce (12) || aead_ciphertext || tag (16)
+/// ```
+///
+/// The kem ciphertext is passed to the aead as associated data so that it
+/// cannot be swapped for another encapsulation of the same key.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Kyber1024Algorithm;
 
-pub struct Kyber1024;
+impl lib_cryptography::Algorithm for Kyber1024Algorithm {
+    const ID: u16 = lib_cryptography::id::KYBER1024;
+}
 
-impl cryptography::AsymmetricKeyGenAlgorithm for Kyber1024 {
-    type PublicKey = PublicKey;
-    type SecretKey = SecretKey;
-    type Rng = Unset;
-    type Error = Error;
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for Kyber1024Algorithm {
+    fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+        let (public_key, secret_key) = kyber1024::keypair();
+        let public_key: bytes::Bytes = bytes::Bytes::copy_from_slice(public_key.as_bytes());
+        let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+        let public_key: lib_cryptography::public_key::PublicKey<_> = public_key.into();
+        let secret_key: lib_cryptography::secret_key::SecretKey<_> = secret_key.as_bytes().to_vec().try_into()?;
+        let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+        Ok(out)
+    }
+}
 
-    fn generate(_: &mut Self::Rng) -> std::result::Result<(Self::PublicKey, Self::SecretKey), Self::Error> {
-        let pair: (_, _) = kyber1024::keypair(); 
-        let public_key: kyber1024::PublicKey = pair.0;
-        let public_key: Self::PublicKey = public_key.as_bytes().into();
-        let secret_key: kyber1024::SecretKey = pair.1;
-        let secret_key: Self::SecretKey = secret_key.as_bytes().into();
-        Ok((public_key, secret_key))
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for Kyber1024Algorithm {
+    fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+        let secret_key: &[u8] = secret_key.expose_secret();
+        if secret_key.len() != SECRET_KEY_LEN {
+            return Err(Error::InvalidSecretKey.into())
+        }
+        let public_key: &[u8] = secret_key.get(EMBEDDED_PUBLIC_KEY_OFFSET..EMBEDDED_PUBLIC_KEY_OFFSET + PUBLIC_KEY_LEN).ok_or(Error::InvalidSecretKey)?;
+        let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.try_into().map_err(|_| Error::InvalidSecretKey)?;
+        let out: bytes::Bytes = public_key.into();
+        let out: lib_bytes::NonEmpty = out.try_into()?;
+        Ok(out.into())
     }
 }
 
-impl cryptography::AsymmetricEncryptionAlgorithm for Kyber1024 {
-    type PublicKey = PublicKey;
-    type SecretKey = SecretKey;
-    type Encrypted = Bytes;
-    type Decrypted = Bytes;
-    type Error = Error;
-
-    fn encrypt(public_key: &Self::PublicKey, decrypted: &Self::Decrypted) -> std::result::Result<Self::Encrypted, Self::Error> {
-        let public_key: kyber1024::PublicKey = kyber1024::PublicKey::from_bytes(public_key)
-            .ok()
-            .ok_or(Error::InvalidPublicKey)?;
-        let pair: (_, _) = kyber1024::encapsulate(&public_key);
-        let shared_secret: kyber1024::SharedSecret = pair.0;
-        let cipher_text: kyber1024::Ciphertext = pair.1;
-        let hkdf: hkdf::Hkdf<_> = hkdf::Hkdf::<sha2::Sha256>::new(None, shared_secret.as_bytes());
-        let mut aead_key: [_; _] = [0u8; 32];
-        hkdf.expand(b"Kyber1024-aes256gcm", &mut aead_key)
-            .ok()
-            .ok_or(Error::InvalidCipherText)?;
-        let cipher_generic_array: aead_generic_array::GenericArray<u8, 32> = aead_generic_array::GenericArray::from_slice(&aead_key);
-        let cipher = aes_gcm::Aes256Gcm::new(cipher_generic_array);
-        let mut nonce: [_; _] = [0u8; 12];
-        
+impl lib_cryptography::AsymmetricEncryptionAlgorithm for Kyber1024Algorithm {
+    fn encrypt(public_key: &lib_cryptography::public_key::PublicKey<Self>, message: &lib_cryptography::message::Message) -> lib_cryptography::Result<lib_cryptography::encrypted::Encrypted<Self>> {
+        let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.to_fixed().map_err(|_| Error::InvalidPublicKey)?;
+        let public_key: kyber1024::PublicKey = kyber1024::PublicKey::from_bytes(public_key.as_array()).map_err(|_| Error::InvalidPublicKey)?;
+        let (shared_secret, kem_ciphertext) = kyber1024::encapsulate(&public_key);
+        let kem_ciphertext: &[u8] = kem_ciphertext.as_bytes();
+        let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = derive(shared_secret.as_bytes())?;
+        let sealed: lib_cryptography::encrypted::Encrypted<Aes256GcmAlgorithm> = Aes256GcmAlgorithm::encrypt_with_associated_data(&key, message, kem_ciphertext)?;
+        let sealed: lib_bytes::NonEmpty = sealed.into();
+        let sealed: bytes::Bytes = sealed.into();
+        let mut out: Vec<u8> = Vec::with_capacity(KEM_CIPHERTEXT_LEN + sealed.len());
+        out.extend_from_slice(kem_ciphertext);
+        out.extend_from_slice(&sealed);
+        let out: bytes::Bytes = out.into();
+        let out: lib_bytes::NonEmpty = out.try_into()?;
+        let out: lib_cryptography::encrypted::Encrypted<Self> = out.into();
+        Ok(out)
     }
 
-    fn decrypt(secret_key: &Self::SecretKey, encrypted: &Self::Encrypted) -> std::result::Result<Self::Decrypted, Self::Error> {
-        
+    fn decrypt(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, message: &lib_cryptography::encrypted::Encrypted<Self>) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+        let secret_key: kyber1024::SecretKey = kyber1024::SecretKey::from_bytes(secret_key.expose_secret()).map_err(|_| Error::InvalidSecretKey)?;
+        let message: lib_bytes::NonEmpty = message.to_owned().into();
+        let mut reader: lib_bytes::Reader = message.into();
+        let kem_ciphertext: lib_bytes::Fixed<KEM_CIPHERTEXT_LEN> = reader.read_fixed().map_err(|_| Error::InvalidCipherText)?;
+        let sealed: lib_bytes::Bounded<{ NONCE_LEN + TAG_LEN }, { usize::MAX }> = reader.rest().try_into().map_err(|_| Error::InvalidCipherText)?;
+        let kem_ciphertext_parsed: kyber1024::Ciphertext = kyber1024::Ciphertext::from_bytes(kem_ciphertext.as_array()).map_err(|_| Error::InvalidCipherText)?;
+        let shared_secret: kyber1024::SharedSecret = kyber1024::decapsulate(&kem_ciphertext_parsed, &secret_key);
+        let key: lib_cryptography::key::Key<Aes256GcmAlgorithm> = derive(shared_secret.as_bytes())?;
+        let sealed: lib_bytes::NonEmpty = sealed.into();
+        let sealed: lib_cryptography::encrypted::Encrypted<Aes256GcmAlgorithm> = sealed.into();
+        Aes256GcmAlgorithm::decrypt_with_associated_data(&key, &sealed, kem_ciphertext.as_array())
     }
-}
\ No newline at end of file
+}
+
+/// Plain kyber1024 encapsulation, the key is expanded with
+/// [`lib_cryptography::kdf::derive`] salted with `ciphertext || public_key`.
+impl lib_cryptography::AsymmetricKeyAgreementAlgorithm for Kyber1024Algorithm {
+    fn initiate<S>(public_key: &lib_cryptography::public_key::PublicKey<Self>, info: &[u8]) -> lib_cryptography::Result<(lib_cryptography::encapsulated::Encapsulated<Self>, lib_cryptography::key::Key<S>)>
+    where
+        S: lib_cryptography::SymmetricSetLayout {
+        let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.to_fixed().map_err(|_| Error::InvalidPublicKey)?;
+        let parsed: kyber1024::PublicKey = kyber1024::PublicKey::from_bytes(public_key.as_array()).map_err(|_| Error::InvalidPublicKey)?;
+        let (shared_secret, kem_ciphertext) = kyber1024::encapsulate(&parsed);
+        let kem_ciphertext: bytes::Bytes = bytes::Bytes::copy_from_slice(kem_ciphertext.as_bytes());
+        let key: lib_cryptography::key::Key<S> = lib_cryptography::kdf::derive(shared_secret.as_bytes(), &transcript(&kem_ciphertext, public_key.as_array()), info)?;
+        let encapsulated: lib_bytes::NonEmpty = kem_ciphertext.try_into()?;
+        Ok((encapsulated.into(), key))
+    }
+
+    fn respond<S>(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, encapsulated: &lib_cryptography::encapsulated::Encapsulated<Self>, info: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<S>>
+    where
+        S: lib_cryptography::SymmetricSetLayout {
+        let public_key: lib_bytes::NonEmpty = secret_key.public_key()?.into();
+        let public_key: bytes::Bytes = public_key.into();
+        let secret_key: kyber1024::SecretKey = kyber1024::SecretKey::from_bytes(secret_key.expose_secret()).map_err(|_| Error::InvalidSecretKey)?;
+        let kem_ciphertext: lib_bytes::Fixed<KEM_CIPHERTEXT_LEN> = encapsulated.to_fixed().map_err(|_| Error::InvalidCipherText)?;
+        let parsed: kyber1024::Ciphertext = kyber1024::Ciphertext::from_bytes(kem_ciphertext.as_array()).map_err(|_| Error::InvalidCipherText)?;
+        let shared_secret: kyber1024::SharedSecret = kyber1024::decapsulate(&parsed, &secret_key);
+        lib_cryptography::kdf::derive(shared_secret.as_bytes(), &transcript(kem_ciphertext.as_array(), &public_key), info)
+    }
+}
+
+fn transcript(kem_ciphertext: &[u8], public_key: &[u8]) -> Vec<u8> {
+    let mut out: Vec<u8> = Vec::with_capacity(kem_ciphertext.len() + public_key.len());
+    out.extend_from_slice(kem_ciphertext);
+    out.extend_from_slice(public_key);
+    out
+}
+
+fn derive(shared_secret: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<Aes256GcmAlgorithm>> {
+    let hkdf: hkdf::Hkdf<sha2::Sha256> = hkdf::Hkdf::new(None, shared_secret);
+    let mut out: [u8; 32] = [0; 32];
+    hkdf.expand(HKDF_INFO, &mut out).map_err(|_| Error::InvalidCipherText)?;
+    let out: bytes::Bytes = bytes::Bytes::copy_from_slice(&out);
+    let out: lib_bytes::NonEmpty = out.try_into()?;
+    Ok(out.into())
+}
@@ -0,0 +1,126 @@
+use cryptography_algorithm_kyber1024::Kyber1024Algorithm;
+
+fn message(content: &[u8]) -> lib_cryptography::Result<lib_cryptography::message::Message> {
+    let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+    let content: lib_bytes::NonEmpty = content.try_into()?;
+    Ok(content.into())
+}
+
+fn encoded(encrypted: lib_cryptography::encrypted::Encrypted<Kyber1024Algorithm>) -> bytes::Bytes {
+    let encrypted: lib_bytes::NonEmpty = encrypted.into();
+    encrypted.into()
+}
+
+fn pair() -> lib_cryptography::Result<(lib_cryptography::public_key::PublicKey<Kyber1024Algorithm>, lib_cryptography::secret_key::SecretKey<Kyber1024Algorithm>)> {
+    let pair: lib_cryptography::pair::Pair<Kyber1024Algorithm> = lib_cryptography::pair::Pair::generate()?;
+    Ok(pair.into())
+}
+
+#[test]
+fn round_trip() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let plaintext: lib_cryptography::message::Message = message(b"post quantum payload")?;
+    let encrypted = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::encrypt(&public_key, &plaintext)?;
+    let decrypted = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::decrypt(&secret_key, &encrypted)?;
+    assert_eq!(decrypted, plaintext);
+    Ok(())
+}
+
+#[test]
+fn layout() -> lib_cryptography::Result<()> {
+    let (public_key, _) = pair()?;
+    let plaintext: lib_cryptography::message::Message = message(&[7; 100])?;
+    let encrypted = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::encrypt(&public_key, &plaintext)?;
+    let expected: usize = cryptography_algorithm_kyber1024::KEM_CIPHERTEXT_LEN
+        + cryptography_algorithm_kyber1024::NONCE_LEN
+        + 100
+        + cryptography_algorithm_kyber1024::TAG_LEN;
+    assert_eq!(encoded(encrypted).len(), expected);
+    Ok(())
+}
+
+#[test]
+fn public_key_is_derivable() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    assert_eq!(secret_key.public_key()?, public_key);
+    Ok(())
+}
+
+#[test]
+fn rejects_secret_key_of_other_length() -> lib_cryptography::Result<()> {
+    let (_, secret_key) = pair()?;
+    // long enough to hold the embedded public key, but not a kyber key
+    for len in [100, cryptography_algorithm_kyber1024::SECRET_KEY_LEN - 1, cryptography_algorithm_kyber1024::SECRET_KEY_LEN + 1] {
+        let mut malformed: Vec<u8> = secret_key.expose_secret().to_vec();
+        malformed.resize(len, 0);
+        let malformed: lib_cryptography::secret_key::SecretKey<Kyber1024Algorithm> = malformed.try_into()?;
+        let error: Option<String> = malformed.public_key().err().map(|error| error.to_string());
+        assert_eq!(error.as_deref(), Some("invalid secret key"));
+    }
+    Ok(())
+}
+
+#[test]
+fn rejects_other_secret_key() -> lib_cryptography::Result<()> {
+    let (public_key, _) = pair()?;
+    let (_, other) = pair()?;
+    let plaintext: lib_cryptography::message::Message = message(b"post quantum payload")?;
+    let encrypted = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::encrypt(&public_key, &plaintext)?;
+    assert!(<Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::decrypt(&other, &encrypted).is_err());
+    Ok(())
+}
+
+#[test]
+fn rejects_tampered_kem_ciphertext() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let plaintext: lib_cryptography::message::Message = message(b"post quantum payload")?;
+    let encrypted = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::encrypt(&public_key, &plaintext)?;
+    let mut encrypted: Vec<u8> = encoded(encrypted).to_vec();
+    if let Some(byte) = encrypted.first_mut() {
+        *byte ^= 1;
+    }
+    let encrypted: bytes::Bytes = encrypted.into();
+    let encrypted: lib_bytes::NonEmpty = encrypted.try_into()?;
+    assert!(<Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::decrypt(&secret_key, &encrypted.into()).is_err());
+    Ok(())
+}
+
+#[test]
+fn rejects_truncated_ciphertext() -> lib_cryptography::Result<()> {
+    let (_, secret_key) = pair()?;
+    let encrypted: bytes::Bytes = vec![0; cryptography_algorithm_kyber1024::KEM_CIPHERTEXT_LEN].into();
+    let encrypted: lib_bytes::NonEmpty = encrypted.try_into()?;
+    assert!(<Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::decrypt(&secret_key, &encrypted.into()).is_err());
+    Ok(())
+}
+
+#[test]
+fn key_agreement() -> lib_cryptography::Result<()> {
+    let (public_key, secret_key) = pair()?;
+    let (encapsulated, initiator) = <Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm>(&public_key, b"session")?;
+    let responder = <Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm>(&secret_key, &encapsulated, b"session")?;
+    assert_eq!(initiator, responder);
+    Ok(())
+}
+
+#[test]
+fn rejects_public_key_of_other_length() -> lib_cryptography::Result<()> {
+    let public_key: bytes::Bytes = vec![0; cryptography_algorithm_kyber1024::PUBLIC_KEY_LEN - 1].into();
+    let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+    let public_key: lib_cryptography::public_key::PublicKey<Kyber1024Algorithm> = public_key.into();
+    let error: Option<String> = <Kyber1024Algorithm as lib_cryptography::AsymmetricEncryptionAlgorithm>::encrypt(&public_key, &message(b"hello")?).err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid public key"));
+    let error: Option<String> = <Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm>(&public_key, b"session").err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid public key"));
+    Ok(())
+}
+
+#[test]
+fn rejects_encapsulation_of_other_length() -> lib_cryptography::Result<()> {
+    let (_, secret_key) = pair()?;
+    let encapsulated: bytes::Bytes = vec![0; cryptography_algorithm_kyber1024::KEM_CIPHERTEXT_LEN + 1].into();
+    let encapsulated: lib_bytes::NonEmpty = encapsulated.try_into()?;
+    let error: Option<String> = <Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<cryptography_algorithm_aes256gcm::Aes256GcmAlgorithm>(&secret_key, &encapsulated.into(), b"session").err().map(|error| error.to_string());
+    assert_eq!(error.as_deref(), Some("invalid ciphertext"));
+    Ok(())
+}
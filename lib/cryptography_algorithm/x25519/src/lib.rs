@@ -0,0 +1,118 @@
+use rand::RngCore as _;
+
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SECRET_KEY_LEN: usize = 32;
+pub const ENCAPSULATED_LEN: usize = 32;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("invalid public key")]
+	InvalidPublicKey,
+
+	#[error("invalid secret key")]
+	InvalidSecretKey,
+
+	#[error("shared secret is not contributory")]
+	NonContributory
+}
+
+/// Ephemeral-static X25519 as specified by RFC 7748.
+///
+/// The initiator generates an ephemeral key pair per agreement and sends its
+/// public half as the encapsulation. The shared secret is expanded with
+/// [`lib_cryptography::kdf::derive`], salted with
+/// `ephemeral_public_key || static_public_key` so the key is bound to both
+/// parties.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct X25519Algorithm;
+
+impl lib_cryptography::Algorithm for X25519Algorithm {
+	const ID: u16 = lib_cryptography::id::X25519;
+}
+
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for X25519Algorithm {
+	fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+		let (public_key, secret_key) = keypair()?;
+		let public_key: bytes::Bytes = public_key.into();
+		let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+		let public_key: lib_cryptography::public_key::PublicKey<_> = public_key.into();
+		let secret_key: lib_cryptography::secret_key::SecretKey<_> = secret_key.as_array().to_vec().try_into()?;
+		let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+		Ok(out)
+	}
+}
+
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for X25519Algorithm {
+	fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+		let secret_key: zeroize::Zeroizing<lib_bytes::Fixed<SECRET_KEY_LEN>> = secret_of(secret_key)?;
+		let out: bytes::Bytes = public_key_of(&secret_key).into();
+		let out: lib_bytes::NonEmpty = out.try_into()?;
+		Ok(out.into())
+	}
+}
+
+impl lib_cryptography::AsymmetricKeyAgreementAlgorithm for X25519Algorithm {
+	fn initiate<S>(public_key: &lib_cryptography::public_key::PublicKey<Self>, info: &[u8]) -> lib_cryptography::Result<(lib_cryptography::encapsulated::Encapsulated<Self>, lib_cryptography::key::Key<S>)>
+	where
+		S: lib_cryptography::SymmetricSetLayout {
+		let public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = public_key.to_fixed().map_err(|_| Error::InvalidPublicKey)?;
+		let (ephemeral_public_key, ephemeral_secret_key) = keypair()?;
+		let shared_secret: zeroize::Zeroizing<[u8; 32]> = agree(&ephemeral_secret_key, public_key)?;
+		let key: lib_cryptography::key::Key<S> = derive(shared_secret.as_ref(), ephemeral_public_key.as_ref(), public_key.as_ref(), info)?;
+		let encapsulated: bytes::Bytes = ephemeral_public_key.into();
+		let encapsulated: lib_bytes::NonEmpty = encapsulated.try_into()?;
+		Ok((encapsulated.into(), key))
+	}
+
+	fn respond<S>(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, encapsulated: &lib_cryptography::encapsulated::Encapsulated<Self>, info: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<S>>
+	where
+		S: lib_cryptography::SymmetricSetLayout {
+		let secret_key: zeroize::Zeroizing<lib_bytes::Fixed<SECRET_KEY_LEN>> = secret_of(secret_key)?;
+		let ephemeral_public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN> = encapsulated.to_fixed().map_err(|_| Error::InvalidPublicKey)?;
+		let shared_secret: zeroize::Zeroizing<[u8; 32]> = agree(&secret_key, ephemeral_public_key)?;
+		derive(shared_secret.as_ref(), ephemeral_public_key.as_ref(), public_key_of(&secret_key).as_ref(), info)
+	}
+}
+
+/// Raw X25519 primitives, exposed so that hybrid constructions can combine
+/// the shared secret with other key material before a single kdf pass.
+/// Secret key material comes back wrapped so that it is wiped on drop.
+pub fn keypair() -> lib_cryptography::Result<(lib_bytes::Fixed<PUBLIC_KEY_LEN>, zeroize::Zeroizing<lib_bytes::Fixed<SECRET_KEY_LEN>>)> {
+	let mut secret_key: zeroize::Zeroizing<lib_bytes::Fixed<SECRET_KEY_LEN>> = zeroize::Zeroizing::new([0; SECRET_KEY_LEN].into());
+	rand::rngs::OsRng.try_fill_bytes(secret_key.as_mut())?;
+	Ok((public_key_of(&secret_key), secret_key))
+}
+
+pub fn public_key_of(secret_key: &lib_bytes::Fixed<SECRET_KEY_LEN>) -> lib_bytes::Fixed<PUBLIC_KEY_LEN> {
+	let secret_key: x25519_dalek::StaticSecret = x25519_dalek::StaticSecret::from(secret_key.into_array());
+	x25519_dalek::PublicKey::from(&secret_key).to_bytes().into()
+}
+
+/// Fails on low order points, which would otherwise force an all zero
+/// shared secret regardless of the secret key.
+pub fn agree(secret_key: &lib_bytes::Fixed<SECRET_KEY_LEN>, public_key: lib_bytes::Fixed<PUBLIC_KEY_LEN>) -> Result<zeroize::Zeroizing<[u8; 32]>, Error> {
+	let secret_key: x25519_dalek::StaticSecret = x25519_dalek::StaticSecret::from(secret_key.into_array());
+	let shared_secret: x25519_dalek::SharedSecret = secret_key.diffie_hellman(&public_key.into_array().into());
+	if !shared_secret.was_contributory() {
+		return Err(Error::NonContributory)
+	}
+	Ok(zeroize::Zeroizing::new(*shared_secret.as_bytes()))
+}
+
+fn secret_of(secret_key: &lib_cryptography::secret_key::SecretKey<X25519Algorithm>) -> Result<zeroize::Zeroizing<lib_bytes::Fixed<SECRET_KEY_LEN>>, Error> {
+	let secret_key: lib_bytes::Fixed<SECRET_KEY_LEN> = secret_key.expose_secret().try_into().map_err(|_| Error::InvalidSecretKey)?;
+	Ok(zeroize::Zeroizing::new(secret_key))
+}
+
+fn derive<S>(shared_secret: &[u8], ephemeral_public_key: &[u8], static_public_key: &[u8], info: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<S>>
+where
+	S: lib_cryptography::SymmetricSetLayout {
+	let mut salt: Vec<u8> = Vec::with_capacity(ephemeral_public_key.len() + static_public_key.len());
+	salt.extend_from_slice(ephemeral_public_key);
+	salt.extend_from_slice(static_public_key);
+	lib_cryptography::kdf::derive(shared_secret, &salt, info)
+}
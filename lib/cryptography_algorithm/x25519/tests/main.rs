@@ -0,0 +1,73 @@
+use cryptography_algorithm_x25519::X25519Algorithm;
+use cryptography_algorithm_chacha20poly1305::ChaCha20Poly1305Algorithm;
+
+fn bytes(hex: &str) -> Vec<u8> {
+	(0..hex.len())
+		.step_by(2)
+		.filter_map(|i| hex.get(i..i + 2))
+		.filter_map(|byte| u8::from_str_radix(byte, 16).ok())
+		.collect()
+}
+
+// RFC 7748 section 6.1
+#[test]
+fn rfc7748_vector() -> lib_cryptography::Result<()> {
+	let alice: lib_bytes::Fixed<32> = bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a").as_slice().try_into()?;
+	let bob: lib_bytes::Fixed<32> = bytes("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb").as_slice().try_into()?;
+	assert_eq!(cryptography_algorithm_x25519::public_key_of(&alice).as_ref(), bytes("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"));
+	assert_eq!(cryptography_algorithm_x25519::public_key_of(&bob).as_ref(), bytes("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f"));
+	let shared: zeroize::Zeroizing<[u8; 32]> = cryptography_algorithm_x25519::agree(&alice, cryptography_algorithm_x25519::public_key_of(&bob))?;
+	assert_eq!(shared.to_vec(), bytes("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"));
+	Ok(())
+}
+
+#[test]
+fn both_sides_derive_the_same_key() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = lib_cryptography::pair::Pair::<X25519Algorithm>::generate()?.into();
+	let (encapsulated, initiator) = <X25519Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<ChaCha20Poly1305Algorithm>(&public_key, b"session")?;
+	let responder = <X25519Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &encapsulated, b"session")?;
+	assert_eq!(initiator.expose_secret().len(), 32);
+	assert_eq!(initiator, responder);
+	Ok(())
+}
+
+#[test]
+fn info_separates_keys() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = lib_cryptography::pair::Pair::<X25519Algorithm>::generate()?.into();
+	let (encapsulated, initiator) = <X25519Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<ChaCha20Poly1305Algorithm>(&public_key, b"session")?;
+	let responder = <X25519Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &encapsulated, b"other")?;
+	assert_ne!(initiator, responder);
+	Ok(())
+}
+
+#[test]
+fn public_key_is_derivable() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = lib_cryptography::pair::Pair::<X25519Algorithm>::generate()?.into();
+	assert_eq!(secret_key.public_key()?, public_key);
+	Ok(())
+}
+
+#[test]
+fn malformed_secret_key_has_no_public_key() -> lib_cryptography::Result<()> {
+	let (_, secret_key): (lib_cryptography::public_key::PublicKey<X25519Algorithm>, lib_cryptography::secret_key::SecretKey<X25519Algorithm>) = lib_cryptography::pair::Pair::<X25519Algorithm>::generate()?.into();
+	let mut extended: Vec<u8> = secret_key.expose_secret().to_vec();
+	extended.push(0);
+	let extended: lib_cryptography::secret_key::SecretKey<X25519Algorithm> = extended.try_into()?;
+	let error: Option<String> = extended.public_key().err().map(|error| error.to_string());
+	assert_eq!(error.as_deref(), Some("invalid secret key"));
+	Ok(())
+}
+
+#[test]
+fn rejects_low_order_point() -> lib_cryptography::Result<()> {
+	let (_, secret_key) = lib_cryptography::pair::Pair::<X25519Algorithm>::generate()?.into();
+	let mut low_order: Vec<u8> = vec![0; 32];
+	if let Some(byte) = low_order.first_mut() {
+		*byte = 1;
+	}
+	let low_order: bytes::Bytes = low_order.into();
+	let low_order: lib_bytes::NonEmpty = low_order.try_into()?;
+	let responder = <X25519Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &low_order.into(), b"session");
+	assert!(responder.is_err());
+	Ok(())
+}
@@ -0,0 +1,160 @@
+use pqcrypto_traits::kem::PublicKey as _;
+use pqcrypto_traits::kem::SecretKey as _;
+use pqcrypto_traits::kem::Ciphertext as _;
+use pqcrypto_traits::kem::SharedSecret as _;
+use pqcrypto_kyber::kyber1024;
+use cryptography_algorithm_kyber1024::Kyber1024Algorithm;
+
+const X25519_PUBLIC_KEY_LEN: usize = cryptography_algorithm_x25519::PUBLIC_KEY_LEN;
+const X25519_SECRET_KEY_LEN: usize = cryptography_algorithm_x25519::SECRET_KEY_LEN;
+
+pub const PUBLIC_KEY_LEN: usize = X25519_PUBLIC_KEY_LEN + cryptography_algorithm_kyber1024::PUBLIC_KEY_LEN;
+pub const SECRET_KEY_LEN: usize = X25519_SECRET_KEY_LEN + cryptography_algorithm_kyber1024::SECRET_KEY_LEN;
+pub const ENCAPSULATED_LEN: usize = X25519_PUBLIC_KEY_LEN + cryptography_algorithm_kyber1024::KEM_CIPHERTEXT_LEN;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("invalid public key")]
+	InvalidPublicKey,
+
+	#[error("invalid secret key")]
+	InvalidSecretKey,
+
+	#[error("invalid encapsulation")]
+	InvalidEncapsulation
+}
+
+/// Hybrid of ephemeral-static X25519 and Kyber1024.
+///
+/// Keys and encapsulations are the x25519 component followed by the kyber
+/// component:
+///
+/// ```text
+/// public_key   = x25519_public_key (32) || kyber1024_public_key (1568)
+/// secret_key   = x25519_secret_key (32) || kyber1024_secret_key (3168)
+/// encapsulated = x25519_ephemeral_public_key (32) || kyber1024_ciphertext (1568)
+/// key          = hkdf_sha256(ikm = x25519_shared || kyber1024_shared, salt = encapsulated || public_key, info)
+/// ```
+///
+/// Both shared secrets feed a single kdf pass, so the key stays secret as long
+/// as either exchange is unbroken.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct X25519Kyber1024Algorithm;
+
+impl lib_cryptography::Algorithm for X25519Kyber1024Algorithm {
+	const ID: u16 = lib_cryptography::id::X25519_KYBER1024;
+}
+
+impl lib_cryptography::AsymmetricKeyGenAlgorithm for X25519Kyber1024Algorithm {
+	fn generate() -> lib_cryptography::Result<lib_cryptography::pair::Pair<Self>> {
+		let (x25519_public_key, x25519_secret_key) = cryptography_algorithm_x25519::keypair()?;
+		let (kyber1024_public_key, kyber1024_secret_key) = kyber1024::keypair();
+		let public_key: lib_bytes::NonEmpty = join(x25519_public_key.as_ref(), kyber1024_public_key.as_bytes())?;
+		let secret_key: zeroize::Zeroizing<Vec<u8>> = join_secret(x25519_secret_key.as_ref(), kyber1024_secret_key.as_bytes());
+		let public_key: lib_cryptography::public_key::PublicKey<_> = public_key.into();
+		let secret_key: lib_cryptography::secret_key::SecretKey<_> = secret_key.as_slice().to_vec().try_into()?;
+		let out: lib_cryptography::pair::Pair<_> = (public_key, secret_key).into();
+		Ok(out)
+	}
+}
+
+impl lib_cryptography::AsymmetricKeyDerivationAlgorithm for X25519Kyber1024Algorithm {
+	fn public_key(secret_key: &lib_cryptography::secret_key::SecretKey<Self>) -> lib_cryptography::Result<lib_cryptography::public_key::PublicKey<Self>> {
+		let (x25519_secret_key, kyber1024_secret_key) = split_secret::<X25519_SECRET_KEY_LEN>(secret_key.expose_secret(), SECRET_KEY_LEN).map_err(|_| Error::InvalidSecretKey)?;
+		let x25519_public_key: lib_bytes::Fixed<X25519_PUBLIC_KEY_LEN> = cryptography_algorithm_x25519::public_key_of(&x25519_secret_key);
+		let kyber1024_secret_key: lib_cryptography::secret_key::SecretKey<Kyber1024Algorithm> = kyber1024_secret_key.to_vec().try_into()?;
+		let kyber1024_public_key: lib_bytes::NonEmpty = kyber1024_secret_key.public_key()?.into();
+		let kyber1024_public_key: bytes::Bytes = kyber1024_public_key.into();
+		let out: lib_bytes::NonEmpty = join(x25519_public_key.as_ref(), &kyber1024_public_key)?;
+		Ok(out.into())
+	}
+}
+
+impl lib_cryptography::AsymmetricKeyAgreementAlgorithm for X25519Kyber1024Algorithm {
+	fn initiate<S>(public_key: &lib_cryptography::public_key::PublicKey<Self>, info: &[u8]) -> lib_cryptography::Result<(lib_cryptography::encapsulated::Encapsulated<Self>, lib_cryptography::key::Key<S>)>
+	where
+		S: lib_cryptography::SymmetricSetLayout {
+		let public_key: lib_bytes::NonEmpty = public_key.to_owned().into();
+		let (x25519_public_key, kyber1024_public_key) = split::<X25519_PUBLIC_KEY_LEN>(public_key.to_owned(), PUBLIC_KEY_LEN).map_err(|_| Error::InvalidPublicKey)?;
+		let kyber1024_public_key: kyber1024::PublicKey = kyber1024::PublicKey::from_bytes(&kyber1024_public_key).map_err(|_| Error::InvalidPublicKey)?;
+		let (ephemeral_public_key, ephemeral_secret_key) = cryptography_algorithm_x25519::keypair()?;
+		let x25519_shared_secret: zeroize::Zeroizing<[u8; 32]> = cryptography_algorithm_x25519::agree(&ephemeral_secret_key, x25519_public_key)?;
+		let (kyber1024_shared_secret, kyber1024_ciphertext) = kyber1024::encapsulate(&kyber1024_public_key);
+		let encapsulated: lib_bytes::NonEmpty = join(ephemeral_public_key.as_ref(), kyber1024_ciphertext.as_bytes())?;
+		let key: lib_cryptography::key::Key<S> = derive(x25519_shared_secret.as_ref(), kyber1024_shared_secret.as_bytes(), encapsulated.to_owned(), public_key, info)?;
+		Ok((encapsulated.into(), key))
+	}
+
+	fn respond<S>(secret_key: &lib_cryptography::secret_key::SecretKey<Self>, encapsulated: &lib_cryptography::encapsulated::Encapsulated<Self>, info: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<S>>
+	where
+		S: lib_cryptography::SymmetricSetLayout {
+		let public_key: lib_bytes::NonEmpty = secret_key.public_key()?.into();
+		let (x25519_secret_key, kyber1024_secret_key) = split_secret::<X25519_SECRET_KEY_LEN>(secret_key.expose_secret(), SECRET_KEY_LEN).map_err(|_| Error::InvalidSecretKey)?;
+		let kyber1024_secret_key: kyber1024::SecretKey = kyber1024::SecretKey::from_bytes(kyber1024_secret_key).map_err(|_| Error::InvalidSecretKey)?;
+		let encapsulated: lib_bytes::NonEmpty = encapsulated.to_owned().into();
+		let (ephemeral_public_key, kyber1024_ciphertext) = split::<X25519_PUBLIC_KEY_LEN>(encapsulated.to_owned(), ENCAPSULATED_LEN).map_err(|_| Error::InvalidEncapsulation)?;
+		let kyber1024_ciphertext: kyber1024::Ciphertext = kyber1024::Ciphertext::from_bytes(&kyber1024_ciphertext).map_err(|_| Error::InvalidEncapsulation)?;
+		let x25519_shared_secret: zeroize::Zeroizing<[u8; 32]> = cryptography_algorithm_x25519::agree(&x25519_secret_key, ephemeral_public_key)?;
+		let kyber1024_shared_secret: kyber1024::SharedSecret = kyber1024::decapsulate(&kyber1024_ciphertext, &kyber1024_secret_key);
+		derive(x25519_shared_secret.as_ref(), kyber1024_shared_secret.as_bytes(), encapsulated, public_key, info)
+	}
+}
+
+fn derive<S>(x25519_shared_secret: &[u8], kyber1024_shared_secret: &[u8], encapsulated: lib_bytes::NonEmpty, public_key: lib_bytes::NonEmpty, info: &[u8]) -> lib_cryptography::Result<lib_cryptography::key::Key<S>>
+where
+	S: lib_cryptography::SymmetricSetLayout {
+	let shared_secret: zeroize::Zeroizing<Vec<u8>> = join_secret(x25519_shared_secret, kyber1024_shared_secret);
+	let encapsulated: bytes::Bytes = encapsulated.into();
+	let public_key: bytes::Bytes = public_key.into();
+	let salt: lib_bytes::NonEmpty = join(&encapsulated, &public_key)?;
+	let salt: bytes::Bytes = salt.into();
+	lib_cryptography::kdf::derive(&shared_secret, &salt, info)
+}
+
+fn join(lhs: &[u8], rhs: &[u8]) -> lib_cryptography::Result<lib_bytes::NonEmpty> {
+	let mut out: Vec<u8> = Vec::with_capacity(lhs.len() + rhs.len());
+	out.extend_from_slice(lhs);
+	out.extend_from_slice(rhs);
+	let out: bytes::Bytes = out.into();
+	out.try_into()
+}
+
+fn join_secret(lhs: &[u8], rhs: &[u8]) -> zeroize::Zeroizing<Vec<u8>> {
+	let mut out: zeroize::Zeroizing<Vec<u8>> = zeroize::Zeroizing::new(Vec::with_capacity(lhs.len() + rhs.len()));
+	out.extend_from_slice(lhs);
+	out.extend_from_slice(rhs);
+	out
+}
+
+/// Secret `value` of exactly `len` bytes as its leading `N` bytes and the
+/// rest, borrowing the rest so no unwiped copy of it is made.
+fn split_secret<const N: usize>(value: &[u8], len: usize) -> Result<(zeroize::Zeroizing<lib_bytes::Fixed<N>>, &[u8]), lib_bytes::Error> {
+	if value.len() != len {
+		return Err(lib_bytes::Error::Length {
+			expected: len,
+			received: value.len()
+		})
+	}
+	let (lhs, rhs) = value.split_at_checked(N).ok_or(lib_bytes::Error::Eof {
+		needed: N,
+		remaining: value.len()
+	})?;
+	Ok((zeroize::Zeroizing::new(lhs.try_into()?), rhs))
+}
+
+/// `value` of exactly `len` bytes as its leading `N` bytes and the rest.
+fn split<const N: usize>(value: lib_bytes::NonEmpty, len: usize) -> Result<(lib_bytes::Fixed<N>, bytes::Bytes), lib_bytes::Error> {
+	let mut reader: lib_bytes::Reader = value.into();
+	if reader.remaining() != len {
+		return Err(lib_bytes::Error::Length {
+			expected: len,
+			received: reader.remaining()
+		})
+	}
+	let lhs: lib_bytes::Fixed<N> = reader.read_fixed()?;
+	Ok((lhs, reader.rest()))
+}
@@ -0,0 +1,84 @@
+use cryptography_algorithm_x25519_kyber1024::X25519Kyber1024Algorithm;
+use cryptography_algorithm_chacha20poly1305::ChaCha20Poly1305Algorithm;
+
+fn pair() -> lib_cryptography::Result<(lib_cryptography::public_key::PublicKey<X25519Kyber1024Algorithm>, lib_cryptography::secret_key::SecretKey<X25519Kyber1024Algorithm>)> {
+	let pair: lib_cryptography::pair::Pair<X25519Kyber1024Algorithm> = lib_cryptography::pair::Pair::generate()?;
+	Ok(pair.into())
+}
+
+fn bytes_of<T: Into<lib_bytes::NonEmpty>>(value: T) -> Vec<u8> {
+	let value: lib_bytes::NonEmpty = value.into();
+	let value: bytes::Bytes = value.into();
+	value.to_vec()
+}
+
+#[test]
+fn both_sides_derive_the_same_key() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = pair()?;
+	let (encapsulated, initiator) = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<ChaCha20Poly1305Algorithm>(&public_key, b"session")?;
+	let responder = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &encapsulated, b"session")?;
+	assert_eq!(initiator, responder);
+	assert_eq!(bytes_of(encapsulated).len(), cryptography_algorithm_x25519_kyber1024::ENCAPSULATED_LEN);
+	Ok(())
+}
+
+#[test]
+fn layout() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = pair()?;
+	assert_eq!(bytes_of(public_key).len(), cryptography_algorithm_x25519_kyber1024::PUBLIC_KEY_LEN);
+	assert_eq!(secret_key.expose_secret().len(), cryptography_algorithm_x25519_kyber1024::SECRET_KEY_LEN);
+	Ok(())
+}
+
+#[test]
+fn public_key_is_derivable() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = pair()?;
+	assert_eq!(secret_key.public_key()?, public_key);
+	Ok(())
+}
+
+#[test]
+fn malformed_secret_key_has_no_public_key() -> lib_cryptography::Result<()> {
+	let (_, secret_key): (lib_cryptography::public_key::PublicKey<X25519Kyber1024Algorithm>, lib_cryptography::secret_key::SecretKey<X25519Kyber1024Algorithm>) = pair()?;
+	let mut extended: Vec<u8> = secret_key.expose_secret().to_vec();
+	extended.push(0);
+	let extended: lib_cryptography::secret_key::SecretKey<X25519Kyber1024Algorithm> = extended.try_into()?;
+	let error: Option<String> = extended.public_key().err().map(|error| error.to_string());
+	assert_eq!(error.as_deref(), Some("invalid secret key"));
+	Ok(())
+}
+
+#[test]
+fn other_secret_key_derives_other_key() -> lib_cryptography::Result<()> {
+	let (public_key, _) = pair()?;
+	let (_, other) = pair()?;
+	let (encapsulated, initiator) = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<ChaCha20Poly1305Algorithm>(&public_key, b"session")?;
+	let responder = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&other, &encapsulated, b"session")?;
+	assert_ne!(initiator, responder);
+	Ok(())
+}
+
+#[test]
+fn tampered_kyber_component_derives_other_key() -> lib_cryptography::Result<()> {
+	let (public_key, secret_key) = pair()?;
+	let (encapsulated, initiator) = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::initiate::<ChaCha20Poly1305Algorithm>(&public_key, b"session")?;
+	let mut tampered: Vec<u8> = bytes_of(encapsulated);
+	if let Some(byte) = tampered.last_mut() {
+		*byte ^= 1;
+	}
+	let tampered: bytes::Bytes = tampered.into();
+	let tampered: lib_bytes::NonEmpty = tampered.try_into()?;
+	let responder = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &tampered.into(), b"session")?;
+	assert_ne!(initiator, responder);
+	Ok(())
+}
+
+#[test]
+fn rejects_truncated_encapsulation() -> lib_cryptography::Result<()> {
+	let (_, secret_key) = pair()?;
+	let truncated: bytes::Bytes = vec![9; 32].into();
+	let truncated: lib_bytes::NonEmpty = truncated.try_into()?;
+	let responder = <X25519Kyber1024Algorithm as lib_cryptography::AsymmetricKeyAgreementAlgorithm>::respond::<ChaCha20Poly1305Algorithm>(&secret_key, &truncated.into(), b"session");
+	assert!(responder.is_err());
+	Ok(())
+}
@@ -264,7 +264,7 @@ where
     }
 
     pub async fn set_route_to_network<'b>(&self, network: &network::Network<'b>, gateway: &std::net::Ipv4Addr) -> Result<()> {
-        let network_cidr: cidr::Cidr = network.cidr().await?.ok_or(anyhow::anyhow!(""))?;
+        let network_cidr: netfilter::Cidr = network.cidr().await?.ok_or(anyhow::anyhow!(""))?;
         self.exec().args(vec!["ip", "route", "add", &format!("{}", network_cidr), "via", &format!("{}", gateway)]).send().await?;
         Ok(())
     }
@@ -3,7 +3,10 @@ use anyhow::anyhow;
 use tokio::io::AsyncWriteExt as _;
 use tokio::io::AsyncReadExt as _;
 use futures_util::StreamExt as _;
+use testcontainers::ImageExt as _;
+use testcontainers::runners::AsyncRunner as _;
 
-pub mod cidr;
 pub mod container;
-pub mod network;
\ No newline at end of file
+pub mod network;
+pub mod runner;
+pub mod scenario;
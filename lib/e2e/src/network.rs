@@ -44,7 +44,7 @@ impl<'a> Network<'a> {
         Ok(found)
     }
 
-    pub async fn cidr(&self) -> Result<Option<cidr::Cidr>> {
+    pub async fn cidr(&self) -> Result<Option<netfilter::Cidr>> {
         let inspect: bollard::secret::NetworkInspect = self.interface.inspect_network(&self.name, None).await?;
         let Some(ipam) = inspect.ipam else {
             return Ok(None)
@@ -58,10 +58,7 @@ impl<'a> Network<'a> {
         let Some(subnet) = &ipam_config.subnet else {
             return Ok(None)
         };
-        let parts: Vec<&str> = subnet.split('/').collect();
-        let addr: std::net::Ipv4Addr = parts[0].parse()?;
-        let mask: u8 = parts[1].parse()?;
-        Ok(Some(cidr::Cidr::new(addr, mask)?))
+        Ok(Some(subnet.parse()?))
     }
 
     pub async fn release(self) -> Result<()> {
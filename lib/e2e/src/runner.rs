@@ -0,0 +1,309 @@
+use super::*;
+
+const QUIC_PORT: u16 = 4001;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Outcome {
+    pub assertion: scenario::Assertion,
+    pub success: bool
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Report {
+    pub outcomes: Vec<Outcome>
+}
+
+impl Report {
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(|outcome| outcome.success)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &Outcome> {
+        self.outcomes.iter().filter(|outcome| !outcome.success)
+    }
+}
+
+/// Instantiates a [`scenario::Scenario`] against a docker daemon.
+///
+/// Node identities are derived from the scenario seed, so peer ids and
+/// therefore dial addresses are known before any container starts and no
+/// rpc round trip is required to wire the topology together.
+#[derive(Debug)]
+#[derive(bon::Builder)]
+pub struct Runner<'a> {
+    interface: &'a bollard::Docker,
+    scenario: scenario::Scenario,
+    node_image: testcontainers::GenericImage,
+    router_image: testcontainers::GenericImage,
+    #[builder(into)]
+    log_dir: std::path::PathBuf
+}
+
+struct Instance<'a> {
+    networks: std::collections::HashMap<String, network::Network<'a>>,
+    containers: Vec<(String, container::Container<'a, testcontainers::GenericImage>)>
+}
+
+impl<'a> Instance<'a> {
+    fn container(&self, name: &str) -> Result<&container::Container<'a, testcontainers::GenericImage>> {
+        self.containers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, container)| container)
+            .ok_or(anyhow!("no container named `{}`", name))
+    }
+
+    fn network(&self, name: &str) -> Result<&network::Network<'a>> {
+        self.networks.get(name).ok_or(anyhow!("no network named `{}`", name))
+    }
+
+    async fn ip(&self, scenario: &scenario::Scenario, name: &str) -> Result<std::net::Ipv4Addr> {
+        let network: &str = if let Some(node) = scenario.node(name) {
+            &node.network
+        } else if let Some(router) = scenario.router(name) {
+            &router.wan
+        } else {
+            return Err(anyhow!("no container named `{}`", name))
+        };
+        let network: &network::Network<'a> = self.network(network)?;
+        self.container(name)?.ip(network).await?.ok_or(anyhow!("`{}` has no address", name))
+    }
+}
+
+impl<'a> Runner<'a> {
+    pub fn seed(&self, node: &str) -> [u8; 32] {
+        seed(self.scenario.seed, node)
+    }
+
+    pub fn peer_id(&self, node: &str) -> Result<libp2p::PeerId> {
+        peer_id(self.scenario.seed, node)
+    }
+
+    pub async fn run(self) -> Result<Report> {
+        let mut instance: Instance<'a> = Instance {
+            networks: std::collections::HashMap::new(),
+            containers: Vec::new()
+        };
+        let report: Result<Report> = self.drive(&mut instance).await;
+        self.collect(instance).await?;
+        report
+    }
+
+    async fn drive(&self, instance: &mut Instance<'a>) -> Result<Report> {
+        let suffix: String = nanoid::nanoid!();
+        for network in &self.scenario.networks {
+            let name: String = format!("{}.{}", network.name, suffix);
+            let value: network::Network<'a> = network::Network::new(self.interface, name).await?;
+            instance.networks.insert(network.name.to_owned(), value);
+        }
+        // routers filter on the addresses of the containers they allow or
+        // deny, nodes dial other nodes and route through their gateway
+        for host in self.scenario.boot_order()? {
+            match host {
+                scenario::Host::Node(node) => self.start_node(instance, node, &suffix).await?,
+                scenario::Host::Router(router) => self.start_router(instance, router, &suffix).await?
+            }
+        }
+        let start: tokio::time::Instant = tokio::time::Instant::now();
+        for event in &self.scenario.events {
+            let at: tokio::time::Instant = start + std::time::Duration::from_secs(event.at_secs);
+            tokio::time::sleep_until(at).await;
+            self.apply(instance, &event.action).await?;
+        }
+        let mut report: Report = Report::default();
+        for assertion in &self.scenario.assertions {
+            let success: bool = self.check(instance, assertion).await?;
+            report.outcomes.push(Outcome {
+                assertion: assertion.to_owned(),
+                success
+            });
+        }
+        Ok(report)
+    }
+
+    async fn start_node(&self, instance: &mut Instance<'a>, node: &scenario::Node, suffix: &str) -> Result<()> {
+        let mut cmd: Vec<String> = vec![
+            node.role.binary(node.malicious).to_owned(),
+            String::from("--seed"),
+            hex(&self.seed(&node.name))
+        ];
+        for dial in &node.dial {
+            let ip: std::net::Ipv4Addr = instance.ip(&self.scenario, dial).await?;
+            let peer_id: libp2p::PeerId = self.peer_id(dial)?;
+            cmd.push(String::from("--dial"));
+            cmd.push(format!("/ip4/{}/udp/{}/quic-v1/p2p/{}", ip, QUIC_PORT, peer_id));
+        }
+        let container: testcontainers::ContainerAsync<_> = self.node_image
+            .to_owned()
+            .with_privileged(true)
+            .with_container_name(format!("{}.{}", node.name, suffix))
+            .with_startup_timeout(std::time::Duration::from_mins(1))
+            .with_cmd(cmd)
+            .start()
+            .await?;
+        let container: container::Container<'a, _> = container::Container::new(self.interface, container);
+        container.connect_to(instance.network(&node.network)?).await?;
+        if let Some(gateway) = &node.gateway {
+            let router: &scenario::Router = self.scenario.router(gateway).ok_or(anyhow!("no router named `{}`", gateway))?;
+            let router_lan: &network::Network<'a> = instance.network(&router.lan)?;
+            let gateway: std::net::Ipv4Addr = instance.container(gateway)?.ip(router_lan).await?.ok_or(anyhow!("gateway has no lan address"))?;
+            container.set_default_gateway(&gateway).await?;
+        }
+        instance.containers.push((node.name.to_owned(), container));
+        Ok(())
+    }
+
+    async fn start_router(&self, instance: &mut Instance<'a>, router: &scenario::Router, suffix: &str) -> Result<()> {
+        let container: testcontainers::ContainerAsync<_> = self.router_image
+            .to_owned()
+            .with_privileged(true)
+            .with_container_name(format!("{}.{}", router.name, suffix))
+            .with_startup_timeout(std::time::Duration::from_mins(1))
+            .with_cmd(["sleep", "infinity"])
+            .start()
+            .await?;
+        let container: container::Container<'a, _> = container::Container::new(self.interface, container);
+        let lan: &network::Network<'a> = instance.network(&router.lan)?;
+        let wan: &network::Network<'a> = instance.network(&router.wan)?;
+        container.connect_to(lan).await?;
+        container.connect_to(wan).await?;
+        let lan_eth: String = format!("eth{}", container.eth(lan).await?);
+        let wan_eth: String = format!("eth{}", container.eth(wan).await?);
+        let public: std::net::Ipv4Addr = container.ip(wan).await?.ok_or(anyhow!("router has no wan address"))?;
+        let interfaces: router::Interfaces = router::Interfaces {
+            lan: lan_eth.to_owned(),
+            wan: wan_eth.to_owned(),
+            public
+        };
+        let mut rules: Vec<netfilter::Rule> = router::rules(router.nat, &interfaces, None);
+        // the profile accepts every outbound flow, the scenario filters are
+        // inserted ahead of it in order
+        let quic_port: String = QUIC_PORT.to_string();
+        let mut filters: Vec<netfilter::Rule> = Vec::new();
+        for name in &router.deny {
+            let ip: String = instance.ip(&self.scenario, name).await?.to_string();
+            filters.push(netfilter::rule(&["-d", &ip, "-p", "udp", "--dport", &quic_port, "-j", "DROP"]));
+        }
+        for name in &router.allow {
+            let ip: String = instance.ip(&self.scenario, name).await?.to_string();
+            filters.push(netfilter::rule(&["-d", &ip, "-p", "udp", "--dport", &quic_port, "-j", "ACCEPT"]));
+        }
+        if !router.allow.is_empty() {
+            filters.push(netfilter::rule(&["-i", &lan_eth, "-o", &wan_eth, "-m", "conntrack", "--ctstate", "NEW", "-j", "DROP"]));
+        }
+        for (index, filter) in filters.into_iter().enumerate() {
+            let position: String = (index + 1).to_string();
+            let mut rule: netfilter::Rule = netfilter::rule(&["-I", "FORWARD", &position]);
+            rule.extend(filter);
+            rules.push(rule);
+        }
+        container.exec().args(vec!["sh", "-c", "command -v iptables || apk add iptables"]).send().await?;
+        container.exec().args(vec!["sysctl", "-w", "net.ipv4.ip_forward=1"]).send().await?;
+        for rule in rules {
+            container.exec().arg("iptables").args(rule).send().await?;
+        }
+        instance.containers.push((router.name.to_owned(), container));
+        Ok(())
+    }
+
+    async fn apply(&self, instance: &Instance<'a>, action: &scenario::Action) -> Result<()> {
+        match action {
+            scenario::Action::Kill { target } => {
+                instance.container(target)?.stop().await?;
+            },
+            scenario::Action::Start { target } => {
+                instance.container(target)?.start().await?;
+            },
+            scenario::Action::Partition { lhs, rhs } => {
+                let lhs_ip: std::net::Ipv4Addr = instance.ip(&self.scenario, lhs).await?;
+                let rhs_ip: std::net::Ipv4Addr = instance.ip(&self.scenario, rhs).await?;
+                for (name, ip) in [(lhs, rhs_ip), (rhs, lhs_ip)] {
+                    let container: &container::Container<'a, _> = instance.container(name)?;
+                    container.exec().args(vec!["iptables", "-A", "INPUT", "-s", &ip.to_string(), "-j", "DROP"]).send().await?;
+                    container.exec().args(vec!["iptables", "-A", "OUTPUT", "-d", &ip.to_string(), "-j", "DROP"]).send().await?;
+                }
+            },
+            scenario::Action::Block { target, port, protocol } => {
+                let container: &container::Container<'a, _> = instance.container(target)?;
+                let protocol: String = protocol.to_string();
+                let port: String = port.to_string();
+                container.exec().args(vec!["iptables", "-A", "INPUT", "-p", &protocol, "--dport", &port, "-j", "DROP"]).send().await?;
+                container.exec().args(vec!["iptables", "-A", "OUTPUT", "-p", &protocol, "--dport", &port, "-j", "DROP"]).send().await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn check(&self, instance: &Instance<'a>, assertion: &scenario::Assertion) -> Result<bool> {
+        match assertion {
+            scenario::Assertion::Reachable { from, to } => {
+                let ip: std::net::Ipv4Addr = instance.ip(&self.scenario, to).await?;
+                Ok(instance.container(from)?.can_reach(&ip).await)
+            },
+            scenario::Assertion::Unreachable { from, to } => {
+                let ip: std::net::Ipv4Addr = instance.ip(&self.scenario, to).await?;
+                Ok(!instance.container(from)?.can_reach(&ip).await)
+            },
+            scenario::Assertion::LogContains { node, pattern } => {
+                let (stdout, stderr) = instance.container(node)?.logs().await?;
+                Ok(stdout.contains(pattern) || stderr.contains(pattern))
+            },
+            scenario::Assertion::LogOmits { node, pattern } => {
+                let (stdout, stderr) = instance.container(node)?.logs().await?;
+                Ok(!stdout.contains(pattern) && !stderr.contains(pattern))
+            }
+        }
+    }
+
+    async fn collect(&self, instance: Instance<'a>) -> Result<()> {
+        let log_dir: std::path::PathBuf = self.log_dir.join(&self.scenario.name);
+        if log_dir.exists() {
+            tokio::fs::remove_dir_all(&log_dir).await?;
+        }
+        tokio::fs::create_dir_all(&log_dir).await?;
+        for (name, container) in instance.containers {
+            let path: std::path::PathBuf = log_dir.join(format!("{}.log", name));
+            container.write_logs_to_file(&path).await?;
+            container.release().await?;
+        }
+        for (_, network) in instance.networks {
+            network.release().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic 32 byte node seed, accepted by the node `--seed` flag.
+pub fn seed(scenario_seed: u64, node: &str) -> [u8; 32] {
+    let mut out: [u8; 32] = [0; 32];
+    for (index, chunk) in out.chunks_mut(8).enumerate() {
+        let mut state: u64 = 0xcbf2_9ce4_8422_2325;
+        let bytes = scenario_seed.to_be_bytes().into_iter()
+            .chain(node.bytes())
+            .chain(std::iter::once(index as u8));
+        for byte in bytes {
+            state ^= u64::from(byte);
+            state = state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        chunk.copy_from_slice(&state.to_be_bytes());
+    }
+    out
+}
+
+pub fn peer_id(scenario_seed: u64, node: &str) -> Result<libp2p::PeerId> {
+    let mut seed: [u8; 32] = seed(scenario_seed, node);
+    let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::ed25519_from_bytes(&mut seed)?;
+    Ok(keypair.public().to_peer_id())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
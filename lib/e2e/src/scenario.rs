@@ -0,0 +1,329 @@
+use super::*;
+
+/// Simulated topology loaded from toml, see `scenario/*.toml` for examples.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(default)]
+    #[serde(rename = "network")]
+    pub networks: Vec<Network>,
+    #[serde(default)]
+    #[serde(rename = "router")]
+    pub routers: Vec<Router>,
+    #[serde(default)]
+    #[serde(rename = "node")]
+    pub nodes: Vec<Node>,
+    #[serde(default)]
+    #[serde(rename = "event")]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    #[serde(rename = "assertion")]
+    pub assertions: Vec<Assertion>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Network {
+    pub name: String
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Router {
+    pub name: String,
+    pub lan: String,
+    pub wan: String,
+
+    #[serde(default)]
+    pub nat: router::Profile,
+
+    /// Nodes whose quic port may be reached through this router. When empty
+    /// every outbound flow from the lan is forwarded.
+    #[serde(default)]
+    pub allow: Vec<String>,
+
+    #[serde(default)]
+    pub deny: Vec<String>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    Bootstrap,
+    Client,
+    Server,
+    Relay
+}
+
+impl Role {
+    pub const fn binary(&self, malicious: bool) -> &'static str {
+        match (self, malicious) {
+            (Self::Bootstrap, false) => "./bootstrap",
+            (Self::Client, false) => "./client",
+            (Self::Server, false) => "./server",
+            (Self::Relay, false) => "./relay",
+            (Self::Bootstrap, true) => "./malicious_bootstrap",
+            (Self::Client, true) => "./malicious_client",
+            (Self::Server, true) => "./malicious_server",
+            (Self::Relay, true) => "./malicious_relay"
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Node {
+    pub name: String,
+    pub role: Role,
+    #[serde(default)]
+    pub malicious: bool,
+    pub network: String,
+
+    /// Nodes without a gateway sit directly on their network.
+    pub gateway: Option<String>,
+
+    #[serde(default)]
+    pub dial: Vec<String>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum Host<'a> {
+    Node(&'a Node),
+    Router(&'a Router)
+}
+
+impl Host<'_> {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Node(node) => &node.name,
+            Self::Router(router) => &router.name
+        }
+    }
+
+    /// The nodes a node dials and its gateway, the containers a router
+    /// filters on.
+    fn dependencies(&self) -> Vec<&str> {
+        match self {
+            Self::Node(node) => node.dial.iter().chain(node.gateway.iter()).map(String::as_str).collect(),
+            Self::Router(router) => router.allow.iter().chain(router.deny.iter()).map(String::as_str).collect()
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Event {
+    pub at_secs: u64,
+    #[serde(flatten)]
+    pub action: Action
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(tag = "action")]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Kill {
+        target: String
+    },
+    Start {
+        target: String
+    },
+
+    Partition {
+        lhs: String,
+        rhs: String
+    },
+
+    Block {
+        target: String,
+        port: u16,
+        protocol: netfilter::Protocol
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(tag = "kind")]
+#[serde(rename_all = "kebab-case")]
+pub enum Assertion {
+    Reachable {
+        from: String,
+        to: String
+    },
+    Unreachable {
+        from: String,
+        to: String
+    },
+    LogContains {
+        node: String,
+        pattern: String
+    },
+    LogOmits {
+        node: String,
+        pattern: String
+    }
+}
+
+impl Scenario {
+    pub fn from_toml_at(path: &std::path::Path) -> Result<Self> {
+        let content: String = std::fs::read_to_string(path)?;
+        content.parse()
+    }
+
+    pub fn node(&self, name: &str) -> Option<&Node> {
+        self.nodes.iter().find(|node| node.name == name)
+    }
+
+    pub fn router(&self, name: &str) -> Option<&Router> {
+        self.routers.iter().find(|router| router.name == name)
+    }
+
+    fn has_network(&self, name: &str) -> bool {
+        self.networks.iter().any(|network| network.name == name)
+    }
+
+    fn has_container(&self, name: &str) -> bool {
+        self.node(name).is_some() || self.router(name).is_some()
+    }
+
+    /// Every container comes after the containers it depends on.
+    pub fn boot_order(&self) -> Result<Vec<Host<'_>>> {
+        let mut out: Vec<Host<'_>> = Vec::with_capacity(self.nodes.len() + self.routers.len());
+        let mut pending: Vec<Host<'_>> = self.routers.iter().map(Host::Router).chain(self.nodes.iter().map(Host::Node)).collect();
+        while !pending.is_empty() {
+            let before: usize = pending.len();
+            let mut index: usize = 0;
+            while index < pending.len() {
+                let ready: bool = pending[index].dependencies().iter().all(|dependency| out.iter().any(|host| host.name() == *dependency));
+                if ready {
+                    out.push(pending.remove(index));
+                } else {
+                    index += 1;
+                }
+            }
+            if pending.len() == before {
+                return Err(anyhow!("dial, gateway or router dependencies form a cycle"))
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut names: std::collections::HashSet<&str> = std::collections::HashSet::new();
+        for name in self.nodes.iter().map(|node| &node.name).chain(self.routers.iter().map(|router| &router.name)) {
+            if !names.insert(name) {
+                return Err(anyhow!("duplicate container name `{}`", name))
+            }
+        }
+        for router in &self.routers {
+            if !self.has_network(&router.lan) || !self.has_network(&router.wan) {
+                return Err(anyhow!("router `{}` references an unknown network", router.name))
+            }
+            for name in router.allow.iter().chain(router.deny.iter()) {
+                if !self.has_container(name) {
+                    return Err(anyhow!("router `{}` references unknown container `{}`", router.name, name))
+                }
+            }
+        }
+        for node in &self.nodes {
+            if !self.has_network(&node.network) {
+                return Err(anyhow!("node `{}` references unknown network `{}`", node.name, node.network))
+            }
+            if let Some(gateway) = &node.gateway {
+                let Some(router) = self.router(gateway) else {
+                    return Err(anyhow!("node `{}` references unknown gateway `{}`", node.name, gateway))
+                };
+                if router.lan != node.network {
+                    return Err(anyhow!("node `{}` is not on the lan of gateway `{}`", node.name, gateway))
+                }
+            }
+            for dial in &node.dial {
+                if self.node(dial).is_none() {
+                    return Err(anyhow!("node `{}` dials unknown node `{}`", node.name, dial))
+                }
+            }
+        }
+        for event in &self.events {
+            let targets: Vec<&String> = match &event.action {
+                Action::Kill { target } | Action::Start { target } | Action::Block { target, .. } => vec![target],
+                Action::Partition { lhs, rhs } => vec![lhs, rhs]
+            };
+            for target in targets {
+                if !self.has_container(target) {
+                    return Err(anyhow!("event references unknown container `{}`", target))
+                }
+            }
+        }
+        for assertion in &self.assertions {
+            let targets: Vec<&String> = match assertion {
+                Assertion::Reachable { from, to } | Assertion::Unreachable { from, to } => vec![from, to],
+                Assertion::LogContains { node, .. } | Assertion::LogOmits { node, .. } => vec![node]
+            };
+            for target in targets {
+                if !self.has_container(target) {
+                    return Err(anyhow!("assertion references unknown container `{}`", target))
+                }
+            }
+        }
+        self.boot_order()?;
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Scenario {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut out: Self = toml::from_str(s)?;
+        out.validate()?;
+        out.events.sort_by_key(|event| event.at_secs);
+        Ok(out)
+    }
+}
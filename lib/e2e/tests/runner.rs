@@ -0,0 +1,31 @@
+#[tokio::test]
+#[ignore = "needs a docker daemon and the `node:latest` image"]
+async fn dial_router() -> anyhow::Result<()> {
+    let path: std::path::PathBuf = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("scenario")
+        .join("dial_router.toml");
+    let scenario: e2e::scenario::Scenario = e2e::scenario::Scenario::from_toml_at(&path)?;
+    let docker: bollard::Docker = bollard::Docker::connect_with_local_defaults()?;
+    let report: e2e::runner::Report = e2e::runner::Runner::builder()
+        .interface(&docker)
+        .scenario(scenario)
+        .node_image(testcontainers::GenericImage::new("node", "latest"))
+        .router_image(testcontainers::GenericImage::new("alpine", "latest"))
+        .log_dir(std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("log"))
+        .build()
+        .run()
+        .await?;
+    for outcome in report.failures() {
+        eprintln!("failed: {:?}", outcome.assertion);
+    }
+    assert!(report.is_success());
+    Ok(())
+}
+
+#[test]
+fn dial_addresses_follow_the_seed() -> anyhow::Result<()> {
+    let mut seed: [u8; 32] = e2e::runner::seed(3, "bootstrap");
+    let keypair: libp2p::identity::Keypair = libp2p::identity::Keypair::ed25519_from_bytes(&mut seed)?;
+    assert_eq!(e2e::runner::peer_id(3, "bootstrap")?, keypair.public().to_peer_id());
+    Ok(())
+}
@@ -0,0 +1,194 @@
+#[test]
+fn parses_bundled_scenarios() -> anyhow::Result<()> {
+    let dir: std::path::PathBuf = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("scenario");
+    for entry in std::fs::read_dir(dir)? {
+        let path: std::path::PathBuf = entry?.path();
+        let scenario: e2e::scenario::Scenario = e2e::scenario::Scenario::from_toml_at(&path)?;
+        assert!(!scenario.nodes.is_empty());
+    }
+    Ok(())
+}
+
+#[test]
+fn boot_order_respects_dials() -> anyhow::Result<()> {
+    let scenario: e2e::scenario::Scenario = r#"
+        name = "order"
+
+        [[network]]
+        name = "wan"
+
+        [[node]]
+        name = "client"
+        role = "client"
+        network = "wan"
+        dial = ["relay"]
+
+        [[node]]
+        name = "relay"
+        role = "relay"
+        network = "wan"
+        dial = ["bootstrap"]
+
+        [[node]]
+        name = "bootstrap"
+        role = "bootstrap"
+        network = "wan"
+    "#.parse()?;
+    let order: Vec<String> = scenario.boot_order()?.into_iter().map(|host| host.name().to_owned()).collect();
+    assert_eq!(order, vec!["bootstrap", "relay", "client"]);
+    Ok(())
+}
+
+#[test]
+fn rejects_dial_cycle() {
+    let scenario: anyhow::Result<e2e::scenario::Scenario> = r#"
+        name = "cycle"
+
+        [[network]]
+        name = "wan"
+
+        [[node]]
+        name = "a"
+        role = "relay"
+        network = "wan"
+        dial = ["b"]
+
+        [[node]]
+        name = "b"
+        role = "relay"
+        network = "wan"
+        dial = ["a"]
+    "#.parse();
+    assert!(scenario.is_err());
+}
+
+#[test]
+fn rejects_gateway_off_lan() {
+    let scenario: anyhow::Result<e2e::scenario::Scenario> = r#"
+        name = "gateway"
+
+        [[network]]
+        name = "wan"
+
+        [[network]]
+        name = "lan"
+
+        [[router]]
+        name = "router"
+        lan = "lan"
+        wan = "wan"
+
+        [[node]]
+        name = "client"
+        role = "client"
+        network = "wan"
+        gateway = "router"
+    "#.parse();
+    assert!(scenario.is_err());
+}
+
+#[test]
+fn events_are_sorted() -> anyhow::Result<()> {
+    let scenario: e2e::scenario::Scenario = r#"
+        name = "events"
+
+        [[network]]
+        name = "wan"
+
+        [[node]]
+        name = "relay"
+        role = "relay"
+        network = "wan"
+
+        [[event]]
+        at-secs = 20
+        action = "start"
+        target = "relay"
+
+        [[event]]
+        at-secs = 10
+        action = "kill"
+        target = "relay"
+    "#.parse()?;
+    let at: Vec<u64> = scenario.events.iter().map(|event| event.at_secs).collect();
+    assert_eq!(at, vec![10, 20]);
+    assert_eq!(scenario.events[0].action, e2e::scenario::Action::Kill { target: String::from("relay") });
+    Ok(())
+}
+
+#[test]
+fn peer_id_is_deterministic() -> anyhow::Result<()> {
+    assert_eq!(e2e::runner::peer_id(1, "relay")?, e2e::runner::peer_id(1, "relay")?);
+    assert_ne!(e2e::runner::peer_id(1, "relay")?, e2e::runner::peer_id(2, "relay")?);
+    assert_ne!(e2e::runner::seed(1, "relay"), e2e::runner::seed(1, "client"));
+    Ok(())
+}
+
+#[test]
+fn boot_order_interleaves_routers() -> anyhow::Result<()> {
+    let scenario: e2e::scenario::Scenario = r#"
+        name = "order"
+
+        [[network]]
+        name = "wan"
+
+        [[network]]
+        name = "lan"
+
+        [[router]]
+        name = "router"
+        lan = "lan"
+        wan = "wan"
+        nat = "symmetric"
+        allow = ["bootstrap"]
+
+        [[node]]
+        name = "relay"
+        role = "relay"
+        network = "wan"
+        dial = ["server"]
+
+        [[node]]
+        name = "server"
+        role = "server"
+        network = "lan"
+        gateway = "router"
+        dial = ["bootstrap"]
+
+        [[node]]
+        name = "bootstrap"
+        role = "bootstrap"
+        network = "wan"
+    "#.parse()?;
+    let order: Vec<String> = scenario.boot_order()?.into_iter().map(|host| host.name().to_owned()).collect();
+    // a public node dialing a private one starts after the router and the
+    // private node, the router after the node it filters on
+    assert_eq!(order, vec!["bootstrap", "router", "server", "relay"]);
+    Ok(())
+}
+
+#[test]
+fn rejects_router_filtering_on_its_own_lan() {
+    let scenario: anyhow::Result<e2e::scenario::Scenario> = r#"
+        name = "cycle"
+
+        [[network]]
+        name = "wan"
+
+        [[network]]
+        name = "lan"
+
+        [[router]]
+        name = "router"
+        lan = "lan"
+        wan = "wan"
+        deny = ["server"]
+
+        [[node]]
+        name = "server"
+        role = "server"
+        network = "lan"
+        gateway = "router"
+    "#.parse();
+    assert!(scenario.is_err());
+}
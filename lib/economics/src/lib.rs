@@ -0,0 +1,107 @@
+#![no_std]
+
+//! Domain pricing shared by the Soroban contracts and the mock chains, in
+//! integer arithmetic only so every implementation charges exactly the same.
+
+/// Fractional bits of the fixed point [`entropy`] and [`information`].
+pub const FRACTION_BITS: u32 = 16;
+
+/// One bit in fixed point.
+pub const BIT: u64 = 1 << FRACTION_BITS;
+
+/// Denominator of tax rates, a rate of [`BASIS_POINTS`] is 100% a year.
+pub const BASIS_POINTS: u64 = 10_000;
+
+/// Seconds [`harberger_tax`] rates are per.
+pub const YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Renewal fee of a domain with `traffic`, falling linearly from `max_fee`
+/// without traffic to `min_fee` once traffic reaches `target_traffic`. Busy
+/// domains pay less to stay, idle ones pay to squat.
+pub fn renew_fee(min_fee: u64, max_fee: u64, traffic: u64, target_traffic: u64) -> u64 {
+	let max_fee: u64 = max_fee.max(min_fee);
+	let range: u128 = u128::from(max_fee - min_fee);
+	let traffic: u128 = u128::from(traffic.min(target_traffic));
+	let discount: u128 = range
+		.saturating_mul(traffic)
+		.checked_div(u128::from(target_traffic))
+		.unwrap_or(range);
+	let discount: u64 = u64::try_from(discount).unwrap_or(u64::MAX);
+	max_fee.saturating_sub(discount).max(min_fee)
+}
+
+/// Tax accrued on a domain assessed at `value` over `elapsed` seconds, at
+/// `rate` basis points a year. Rounds down, so paying in several instalments
+/// never costs more than paying once.
+pub fn harberger_tax(value: u64, rate: u64, elapsed: u64) -> u64 {
+	let tax: u128 = u128::from(value)
+		.saturating_mul(u128::from(rate))
+		.saturating_mul(u128::from(elapsed))
+		/ (u128::from(BASIS_POINTS) * u128::from(YEAR));
+	u64::try_from(tax).unwrap_or(u64::MAX)
+}
+
+/// Shannon entropy of `name` per byte, in fixed point bits.
+pub fn entropy(name: &[u8]) -> u64 {
+	let length: u64 = u64::try_from(name.len()).unwrap_or(u64::MAX);
+	information(name).checked_div(length).unwrap_or_default()
+}
+
+/// Shannon information of `name` as a whole, in fixed point bits: its
+/// [`entropy`] times its length, computed as `n log n - Σ c log c` over the
+/// byte counts `c` so no division rounds it.
+pub fn information(name: &[u8]) -> u64 {
+	let mut counts: [u64; 256] = [0; 256];
+	for byte in name {
+		if let Some(count) = counts.get_mut(usize::from(*byte)) {
+			*count += 1;
+		}
+	}
+	let length: u64 = u64::try_from(name.len()).unwrap_or(u64::MAX);
+	let total: u128 = weighted_log2(length);
+	let parts: u128 = counts
+		.iter()
+		.map(|count| weighted_log2(*count))
+		.fold(0, u128::saturating_add);
+	u64::try_from(total.saturating_sub(parts)).unwrap_or(u64::MAX)
+}
+
+/// Mint fee of `name`: `base_fee` for names carrying at least
+/// `target_information` bits, and for shorter or more repetitive names
+/// `base_fee` scaled by how far short they fall, up to `target_information`
+/// times for a name of a single bit or less.
+pub fn name_fee(base_fee: u64, name: &[u8], target_information: u64) -> u64 {
+	let target: u128 = u128::from(target_information) << FRACTION_BITS;
+	let information: u128 = u128::from(information(name).max(BIT));
+	if information >= target {
+		return base_fee
+	}
+	let fee: u128 = u128::from(base_fee).saturating_mul(target) / information;
+	u64::try_from(fee).unwrap_or(u64::MAX)
+}
+
+/// `count * log2(count)` in fixed point, zero for a zero count.
+fn weighted_log2(count: u64) -> u128 {
+	u128::from(count) * u128::from(log2(count))
+}
+
+/// `log2(x)` in fixed point, rounded down, zero for a zero `x`. Squares the
+/// mantissa once per fractional bit.
+fn log2(x: u64) -> u64 {
+	const SCALE: u32 = 32;
+	if x == 0 {
+		return 0
+	}
+	let integer: u32 = x.ilog2();
+	let mut log: u64 = u64::from(integer) << FRACTION_BITS;
+	// mantissa in [1, 2) with `SCALE` fractional bits
+	let mut mantissa: u128 = (u128::from(x) << SCALE) >> integer;
+	for bit in (0..FRACTION_BITS).rev() {
+		mantissa = (mantissa * mantissa) >> SCALE;
+		if mantissa >= 2 << SCALE {
+			mantissa >>= 1;
+			log |= 1 << bit;
+		}
+	}
+	log
+}
@@ -0,0 +1,89 @@
+use economics::BIT;
+use economics::YEAR;
+
+#[test]
+fn renew_fee_falls_with_traffic() {
+	assert_eq!(economics::renew_fee(10, 100, 0, 1000), 100);
+	assert_eq!(economics::renew_fee(10, 100, 500, 1000), 55);
+	assert_eq!(economics::renew_fee(10, 100, 1000, 1000), 10);
+	assert_eq!(economics::renew_fee(10, 100, 5000, 1000), 10);
+	assert_eq!(economics::renew_fee(10, 100, 0, 0), 10);
+}
+
+#[test]
+fn harberger_tax_accrues_by_rate_and_time() {
+	assert_eq!(economics::harberger_tax(1000, 500, YEAR), 50);
+	assert_eq!(economics::harberger_tax(1000, 500, YEAR / 2), 25);
+	assert_eq!(economics::harberger_tax(1000, 500, 0), 0);
+	assert_eq!(economics::harberger_tax(1000, 0, YEAR), 0);
+}
+
+#[test]
+fn entropy_of_known_names() {
+	assert_eq!(economics::entropy(b""), 0);
+	assert_eq!(economics::entropy(b"aaaa"), 0);
+	assert_eq!(economics::entropy(b"ab"), BIT);
+	assert_eq!(economics::entropy(b"abcd"), 2 * BIT);
+	assert_eq!(economics::information(b"abcd"), 8 * BIT);
+	assert_eq!(economics::information(b"abcdefgh"), 24 * BIT);
+}
+
+#[test]
+fn short_and_repetitive_names_cost_more() {
+	assert_eq!(economics::name_fee(300, b"abcdefgh", 16), 300);
+	assert_eq!(economics::name_fee(300, b"abcd", 16), 600);
+	assert_eq!(economics::name_fee(300, b"aaaaaaaa", 16), 300 * 16);
+	assert_eq!(economics::name_fee(300, b"", 16), 300 * 16);
+}
+
+proptest::proptest! {
+	#[test]
+	fn renew_fee_stays_between_min_and_max(min_fee: u64, max_fee: u64, traffic: u64, target_traffic: u64) {
+		let fee: u64 = economics::renew_fee(min_fee, max_fee, traffic, target_traffic);
+		proptest::prop_assert!(fee >= min_fee);
+		proptest::prop_assert!(fee <= max_fee.max(min_fee));
+	}
+
+	#[test]
+	fn renew_fee_never_rises_with_traffic(min_fee: u64, max_fee: u64, traffic: u64, more: u64, target_traffic: u64) {
+		let fee: u64 = economics::renew_fee(min_fee, max_fee, traffic, target_traffic);
+		let busier: u64 = economics::renew_fee(min_fee, max_fee, traffic.saturating_add(more), target_traffic);
+		proptest::prop_assert!(busier <= fee);
+	}
+
+	#[test]
+	fn harberger_tax_instalments_never_cost_more(value in 0..u64::from(u32::MAX), rate in 0..10_000u64, first in 0..10 * YEAR, second in 0..10 * YEAR) {
+		let once: u64 = economics::harberger_tax(value, rate, first + second);
+		let twice: u64 = economics::harberger_tax(value, rate, first) + economics::harberger_tax(value, rate, second);
+		proptest::prop_assert!(twice <= once);
+		proptest::prop_assert!(economics::harberger_tax(value, rate, first) <= economics::harberger_tax(value, rate, first + second));
+	}
+
+	#[test]
+	fn entropy_is_bounded_by_alphabet(name: Vec<u8>) {
+		let distinct: u64 = u64::try_from(name.iter().collect::<std::collections::BTreeSet<_>>().len()).unwrap_or(u64::MAX);
+		let entropy: u64 = economics::entropy(&name);
+		// the floor of log2 of the distinct byte count, plus one bit of slack for rounding
+		let bound: u64 = match distinct {
+			0 => 0,
+			distinct => u64::from(distinct.ilog2() + 1) * BIT
+		};
+		proptest::prop_assert!(entropy <= bound);
+	}
+
+	#[test]
+	fn entropy_ignores_byte_order(mut name: Vec<u8>) {
+		let entropy: u64 = economics::entropy(&name);
+		name.reverse();
+		proptest::prop_assert_eq!(economics::entropy(&name), entropy);
+		name.sort_unstable();
+		proptest::prop_assert_eq!(economics::entropy(&name), entropy);
+	}
+
+	#[test]
+	fn name_fee_is_at_least_base_and_at_most_target_times(base_fee in 0..u64::from(u32::MAX), name: Vec<u8>, target in 1..64u64) {
+		let fee: u64 = economics::name_fee(base_fee, &name, target);
+		proptest::prop_assert!(fee >= base_fee);
+		proptest::prop_assert!(fee <= base_fee * target);
+	}
+}
@@ -0,0 +1,20 @@
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+fn main() -> Result<()> {
+    let out_dir: std::path::PathBuf = "proto_target".into();
+    if !out_dir.exists() {
+        std::fs::create_dir(&out_dir)?;
+    }
+    println!("cargo:rerun-if-changed=proto/mchain.proto");
+    let proto_paths: Vec<_> = vec!["proto/mchain.proto"];
+    let proto_inclusions: Vec<_> = vec!["proto"];
+    // vendored so the mock chain builds without a system protoc
+    let mut config: prost_build::Config = prost_build::Config::new();
+    config.protoc_executable(protoc_bin_vendored::protoc_bin_path()?);
+    tonic_prost_build::configure()
+        .build_client(true)
+        .build_server(true)
+        .out_dir(out_dir)
+        .compile_with_config(config, &proto_paths, &proto_inclusions)?;
+    Ok(())
+}
@@ -0,0 +1,20 @@
+//! gRPC messages and services of the `mchain` mock chain, shared by the
+//! service and its clients without pulling in the service itself.
+
+include!("../proto_target/mchain.rs");
+
+const AUTHORIZATION_LABEL: &[u8] = b"an-mchain-call-v1";
+
+/// Message the attested key of an account signs to authorize `method`,
+/// given `request` with its authorization still empty and the account's
+/// current nonce from `AccountOf`.
+pub fn authorization_message<T>(method: &str, nonce: u64, request: &T) -> Vec<u8>
+where
+	T: prost::Message {
+	let mut message: Vec<u8> = AUTHORIZATION_LABEL.to_vec();
+	message.extend_from_slice(&(method.len() as u64).to_be_bytes());
+	message.extend_from_slice(method.as_bytes());
+	message.extend_from_slice(&nonce.to_be_bytes());
+	message.extend_from_slice(&request.encode_to_vec());
+	message
+}
@@ -0,0 +1,878 @@
+pub const DEFAULT_TERM: u64 = 365 * 24 * 60 * 60;
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("insufficient balance, {required} required but {available} available")]
+	InsufficientBalance {
+		required: u64,
+		available: u64
+	},
+
+	#[error("amount must be greater than zero")]
+	ZeroAmount,
+
+	#[error("amount overflows")]
+	Overflow,
+
+	#[error("key is not attested to any account")]
+	NotAttested,
+
+	#[error("attestation signature does not verify")]
+	InvalidAttestation,
+
+	#[error("no pending commitment")]
+	NoCommitment,
+
+	#[error("domain {0} is taken")]
+	DomainTaken(String),
+
+	#[error("domain {0} is not registered")]
+	UnknownDomain(String),
+
+	#[error("domain {0} is owned by another account")]
+	NotOwner(String),
+
+	#[error("nothing to claim")]
+	NothingToClaim,
+
+	#[error("domain {0} has no agreement key")]
+	NoAgreementKey(String),
+
+	#[error("proof signature does not verify")]
+	InvalidProof,
+
+	#[error("proof already submitted")]
+	ProofReplayed,
+
+	#[error("call is not signed by the account's attested key")]
+	Unauthorized
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(PartialOrd)]
+#[derive(Ord)]
+#[derive(Hash)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[derive(derive_more::From)]
+#[derive(derive_more::Deref)]
+pub struct Account(pub Vec<u8>);
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(PartialOrd)]
+#[derive(Ord)]
+#[derive(Hash)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[derive(derive_more::From)]
+#[derive(derive_more::Deref)]
+pub struct Key(pub Vec<u8>);
+
+/// `nonce` tells transits over the same path apart.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Transit {
+	pub domain: String,
+	pub src: Key,
+	pub dst: Key,
+	pub relays: Vec<Key>,
+	pub nonce: u64
+}
+
+impl Transit {
+	pub const LABEL: &[u8] = b"an-transit-v1";
+
+	/// `LABEL || domain || nonce (8) || src || dst || count (4) || relays`,
+	/// every field but the nonce and count prefixed with its length (4),
+	/// integers big endian.
+	pub fn message(&self) -> Result<Vec<u8>> {
+		let mut out: Vec<u8> = Self::LABEL.to_vec();
+		prefixed(&mut out, self.domain.as_bytes())?;
+		out.extend_from_slice(&self.nonce.to_be_bytes());
+		prefixed(&mut out, &self.src)?;
+		prefixed(&mut out, &self.dst)?;
+		let count: u32 = self.relays.len().try_into().map_err(|_| Error::Overflow)?;
+		out.extend_from_slice(&count.to_be_bytes());
+		for relay in &self.relays {
+			prefixed(&mut out, relay)?;
+		}
+		Ok(out)
+	}
+}
+
+/// `relay_sigs` in the order of `transit.relays`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Proof {
+	pub transit: Transit,
+	pub header: Vec<u8>,
+	pub src_sig: Vec<u8>,
+	pub dst_sig: Vec<u8>,
+	pub relay_sigs: Vec<Vec<u8>>
+}
+
+impl Proof {
+	/// The [`lib_packet::multi_party`] message over [`Transit::message`],
+	/// signed by src, relays in path order, then dst.
+	pub fn message(&self) -> Result<Vec<u8>> {
+		if self.header.len() != lib_packet::header::LEN {
+			return Err(Error::InvalidProof)
+		}
+		let transit: &Transit = &self.transit;
+		let count: u16 = (transit.relays.len() + 2).try_into().map_err(|_| Error::Overflow)?;
+		let mut out: Vec<u8> = lib_packet::multi_party::LABEL.to_vec();
+		out.extend_from_slice(&self.header);
+		out.extend_from_slice(&count.to_be_bytes());
+		out.extend_from_slice(&transit.src);
+		for relay in &transit.relays {
+			out.extend_from_slice(relay);
+		}
+		out.extend_from_slice(&transit.dst);
+		out.extend_from_slice(&transit.message()?);
+		Ok(out)
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Parameters {
+	pub mint_fee: u64,
+	pub target_information: u64,
+	pub min_renew_fee: u64,
+	pub max_renew_fee: u64,
+	pub target_traffic: u64,
+	pub harberger_tax_rate: u64,
+	pub fee: u64,
+	pub congestion_charge: u64,
+	pub term: u64
+}
+
+impl Default for Parameters {
+	fn default() -> Self {
+		Self {
+			mint_fee: 300,
+			target_information: 16,
+			min_renew_fee: 10,
+			max_renew_fee: 100,
+			target_traffic: 1000,
+			harberger_tax_rate: 500,
+			fee: 1,
+			congestion_charge: 2,
+			term: DEFAULT_TERM
+		}
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub enum Event {
+	Funded {
+		account: Account,
+		amount: u64
+	},
+	Transferred {
+		from: Account,
+		to: Account,
+		amount: u64
+	},
+	Locked {
+		account: Account,
+		amount: u64,
+		until: u64
+	},
+	Unlocked {
+		account: Account,
+		amount: u64
+	},
+	Attested {
+		account: Account,
+		key: Key
+	},
+	CommitmentOpened {
+		relay: Account,
+		account: Account
+	},
+	CommitmentAccepted {
+		relay: Account,
+		account: Account
+	},
+	DomainMinted {
+		domain: String,
+		owner: Account,
+		fee: u64,
+		expires_at: u64
+	},
+	DomainRenewed {
+		domain: String,
+		fee: u64,
+		expires_at: u64
+	},
+	AgreementKeyPublished {
+		domain: String,
+		key: Vec<u8>
+	},
+	ProofReceived {
+		proof: Proof,
+		paid: u64
+	},
+	TrafficRecorded {
+		domain: String,
+		amount: u64
+	},
+	Claimed {
+		account: Account,
+		amount: u64
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Record {
+	pub sequence: u64,
+	pub at: u64,
+	pub event: Event
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+struct Lock {
+	amount: u64,
+	until: u64
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+struct Registration {
+	owner: Account,
+	expires_at: u64,
+	value: u64,
+	taxed_at: u64,
+	#[serde(default)]
+	agreement_key: Option<Vec<u8>>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(Default)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+struct State {
+	now: u64,
+	#[serde(with = "pairs")]
+	balances: std::collections::BTreeMap<Account, u64>,
+	#[serde(with = "pairs")]
+	locks: std::collections::BTreeMap<Account, Lock>,
+	#[serde(with = "pairs")]
+	attestations: std::collections::BTreeMap<Key, Account>,
+	#[serde(with = "pairs")]
+	foreign_attestations: std::collections::BTreeMap<Account, Key>,
+	// (relay, account) to whether the account accepted
+	#[serde(with = "pairs")]
+	commitments: std::collections::BTreeMap<(Account, Account), bool>,
+	#[serde(with = "pairs")]
+	domains: std::collections::BTreeMap<String, Registration>,
+	#[serde(with = "pairs")]
+	traffic: std::collections::BTreeMap<String, u64>,
+	#[serde(with = "pairs")]
+	claimable: std::collections::BTreeMap<Account, u64>,
+	#[serde(with = "pairs")]
+	spent: std::collections::BTreeMap<Account, u64>,
+	#[serde(with = "pairs")]
+	claimed: std::collections::BTreeMap<Account, u64>,
+	#[serde(with = "pairs")]
+	first_seen: std::collections::BTreeMap<Account, u64>,
+	#[serde(default)]
+	proofs: std::collections::BTreeSet<Vec<u8>>,
+	// authorized calls of each account so far
+	#[serde(default)]
+	#[serde(with = "pairs")]
+	nonces: std::collections::BTreeMap<Account, u64>,
+	events: Vec<Record>
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+pub struct Snapshot(State);
+
+impl Snapshot {
+	/// For stores that append the log rather than rewrite it with the state.
+	pub fn split(self) -> (Self, Vec<Record>) {
+		let Self(mut state) = self;
+		let events: Vec<Record> = std::mem::take(&mut state.events);
+		(Self(state), events)
+	}
+
+	pub fn join(self, events: Vec<Record>) -> Self {
+		let Self(state) = self;
+		Self(State {
+			events,
+			..state
+		})
+	}
+}
+
+/// Time only moves through [`MockChain::advance`] and every map is ordered,
+/// so the same calls from the same seed always give the same state and log.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct MockChain {
+	parameters: Parameters,
+	state: State
+}
+
+impl MockChain {
+	pub fn new(seed: u64) -> Self {
+		Self::with_parameters(seed, Parameters::default())
+	}
+
+	pub fn with_parameters(seed: u64, parameters: Parameters) -> Self {
+		Self {
+			parameters,
+			state: State {
+				now: seed,
+				..State::default()
+			}
+		}
+	}
+
+	pub const fn parameters(&self) -> &Parameters {
+		&self.parameters
+	}
+
+	pub const fn now(&self) -> u64 {
+		self.state.now
+	}
+
+	pub fn advance(&mut self, seconds: u64) {
+		self.state.now = self.state.now.saturating_add(seconds);
+		let now: u64 = self.state.now;
+		let expired: Vec<Account> = self.state.locks
+			.iter()
+			.filter(|(_, lock)| lock.until <= now)
+			.map(|(account, _)| account.to_owned())
+			.collect();
+		for account in expired {
+			if let Some(lock) = self.state.locks.remove(&account) {
+				let balance: &mut u64 = self.state.balances.entry(account.to_owned()).or_default();
+				*balance = balance.saturating_add(lock.amount);
+				self.append(Event::Unlocked {
+					account,
+					amount: lock.amount
+				});
+			}
+		}
+	}
+
+	pub fn events(&self) -> &[Record] {
+		&self.state.events
+	}
+
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot(self.state.to_owned())
+	}
+
+	pub fn restore(&mut self, snapshot: Snapshot) {
+		let Snapshot(state) = snapshot;
+		self.state = state;
+	}
+
+	pub fn fund(&mut self, account: &Account, amount: u64) -> Result {
+		nonzero(amount)?;
+		let balance: u64 = self.balance_of(account).checked_add(amount).ok_or(Error::Overflow)?;
+		self.state.balances.insert(account.to_owned(), balance);
+		self.seen(account);
+		self.append(Event::Funded {
+			account: account.to_owned(),
+			amount
+		});
+		Ok(())
+	}
+
+	pub fn balance_of(&self, account: &Account) -> u64 {
+		self.state.balances.get(account).copied().unwrap_or_default()
+	}
+
+	pub fn transfer(&mut self, from: &Account, to: &Account, amount: u64) -> Result {
+		nonzero(amount)?;
+		self.debit(from, amount)?;
+		let balance: u64 = self.balance_of(to).checked_add(amount).ok_or(Error::Overflow)?;
+		self.state.balances.insert(to.to_owned(), balance);
+		self.seen(to);
+		self.append(Event::Transferred {
+			from: from.to_owned(),
+			to: to.to_owned(),
+			amount
+		});
+		Ok(())
+	}
+
+	/// The lock holds until `duration` from now or its existing end,
+	/// whichever is later.
+	pub fn lock(&mut self, account: &Account, amount: u64, duration: u64) -> Result {
+		nonzero(amount)?;
+		self.debit(account, amount)?;
+		let until: u64 = self.state.now.saturating_add(duration);
+		let lock: Lock = match self.state.locks.get(account) {
+			Some(lock) => Lock {
+				amount: lock.amount.checked_add(amount).ok_or(Error::Overflow)?,
+				until: lock.until.max(until)
+			},
+			None => Lock {
+				amount,
+				until
+			}
+		};
+		let until: u64 = lock.until;
+		self.state.locks.insert(account.to_owned(), lock);
+		self.append(Event::Locked {
+			account: account.to_owned(),
+			amount,
+			until
+		});
+		Ok(())
+	}
+
+	pub fn locked_balance_of(&self, account: &Account) -> u64 {
+		self.state.locks.get(account).map(|lock| lock.amount).unwrap_or_default()
+	}
+
+	pub fn lock_expiry_of(&self, account: &Account) -> Option<u64> {
+		self.state.locks.get(account).map(|lock| lock.until)
+	}
+
+	/// A later attestation of either side replaces the earlier link.
+	pub fn attest(&mut self, account: &Account, key: &Key, signature: &[u8]) -> Result {
+		if !verify(account, key, signature) {
+			return Err(Error::InvalidAttestation)
+		}
+		if let Some(previous) = self.state.foreign_attestations.remove(account) {
+			self.state.attestations.remove(&previous);
+		}
+		if let Some(previous) = self.state.attestations.remove(key) {
+			self.state.foreign_attestations.remove(&previous);
+		}
+		self.state.attestations.insert(key.to_owned(), account.to_owned());
+		self.state.foreign_attestations.insert(account.to_owned(), key.to_owned());
+		self.seen(account);
+		self.append(Event::Attested {
+			account: account.to_owned(),
+			key: key.to_owned()
+		});
+		Ok(())
+	}
+
+	pub fn attestation(&self, key: &Key) -> Option<&Account> {
+		self.state.attestations.get(key)
+	}
+
+	pub fn foreign_attestation(&self, account: &Account) -> Option<&Key> {
+		self.state.foreign_attestations.get(account)
+	}
+
+	pub fn nonce_of(&self, account: &Account) -> u64 {
+		self.state.nonces.get(account).copied().unwrap_or_default()
+	}
+
+	/// Uses up the nonce of `account` given the signature of its attested key
+	/// over `message`, which has to cover [`MockChain::nonce_of`] so no
+	/// authorization counts twice.
+	pub fn authorize(&mut self, account: &Account, message: &[u8], signature: &[u8]) -> Result {
+		let key: &Key = self.foreign_attestation(account).ok_or(Error::Unauthorized)?;
+		if !verify(message, key, signature) {
+			return Err(Error::Unauthorized)
+		}
+		let nonce: u64 = self.nonce_of(account).checked_add(1).ok_or(Error::Overflow)?;
+		self.state.nonces.insert(account.to_owned(), nonce);
+		Ok(())
+	}
+
+	pub fn account_of(&self, key: &Key) -> Result<Account> {
+		self.attestation(key).cloned().ok_or(Error::NotAttested)
+	}
+
+	pub fn open_commitment(&mut self, relay: &Account, account: &Account) -> Result {
+		self.state.commitments.insert((relay.to_owned(), account.to_owned()), false);
+		self.append(Event::CommitmentOpened {
+			relay: relay.to_owned(),
+			account: account.to_owned()
+		});
+		Ok(())
+	}
+
+	pub fn accept_commitment(&mut self, account: &Account) -> Result {
+		let relays: Vec<Account> = self.state.commitments
+			.iter()
+			.filter(|((_, committed), accepted)| committed == account && !**accepted)
+			.map(|((relay, _), _)| relay.to_owned())
+			.collect();
+		if relays.is_empty() {
+			return Err(Error::NoCommitment)
+		}
+		for relay in relays {
+			self.state.commitments.insert((relay.to_owned(), account.to_owned()), true);
+			self.append(Event::CommitmentAccepted {
+				relay,
+				account: account.to_owned()
+			});
+		}
+		Ok(())
+	}
+
+	pub fn is_committed(&self, relay: &Account, account: &Account) -> bool {
+		self.state.commitments.get(&(relay.to_owned(), account.to_owned())).copied().unwrap_or_default()
+	}
+
+	pub fn has_sufficient_balance(&self, account: &Account) -> bool {
+		self.locked_balance_of(account) >= self.parameters.fee
+	}
+
+	pub fn mint(&mut self, owner: &Account, domain: &str) -> Result {
+		if self.is_registered(domain) {
+			return Err(Error::DomainTaken(domain.to_owned()))
+		}
+		let fee: u64 = self.mint_fee(domain);
+		self.spend(owner, fee)?;
+		let expires_at: u64 = self.state.now.saturating_add(self.parameters.term);
+		self.state.domains.insert(domain.to_owned(), Registration {
+			owner: owner.to_owned(),
+			expires_at,
+			value: fee,
+			taxed_at: self.state.now,
+			agreement_key: None
+		});
+		self.append(Event::DomainMinted {
+			domain: domain.to_owned(),
+			owner: owner.to_owned(),
+			fee,
+			expires_at
+		});
+		Ok(())
+	}
+
+	pub fn renew(&mut self, owner: &Account, domain: &str) -> Result {
+		let registration: &Registration = self.state.domains
+			.get(domain)
+			.filter(|registration| registration.expires_at > self.state.now)
+			.ok_or_else(|| Error::UnknownDomain(domain.to_owned()))?;
+		if &registration.owner != owner {
+			return Err(Error::NotOwner(domain.to_owned()))
+		}
+		let expires_at: u64 = registration.expires_at.saturating_add(self.parameters.term);
+		let fee: u64 = self.renew_fee(domain).saturating_add(self.harberger_tax_of(domain));
+		self.spend(owner, fee)?;
+		if let Some(registration) = self.state.domains.get_mut(domain) {
+			registration.expires_at = expires_at;
+			registration.taxed_at = self.state.now;
+		}
+		self.append(Event::DomainRenewed {
+			domain: domain.to_owned(),
+			fee,
+			expires_at
+		});
+		Ok(())
+	}
+
+	pub fn owner_of(&self, domain: &str) -> Option<&Account> {
+		self.state.domains
+			.get(domain)
+			.filter(|registration| registration.expires_at > self.state.now)
+			.map(|registration| &registration.owner)
+	}
+
+	pub fn publish_agreement_key(&mut self, owner: &Account, domain: &str, key: &[u8]) -> Result {
+		if self.owner_of(domain).ok_or_else(|| Error::UnknownDomain(domain.to_owned()))? != owner {
+			return Err(Error::NotOwner(domain.to_owned()))
+		}
+		if let Some(registration) = self.state.domains.get_mut(domain) {
+			registration.agreement_key = Some(key.to_vec());
+		}
+		self.append(Event::AgreementKeyPublished {
+			domain: domain.to_owned(),
+			key: key.to_vec()
+		});
+		Ok(())
+	}
+
+	pub fn agreement_key_of(&self, domain: &str) -> Option<&[u8]> {
+		self.state.domains
+			.get(domain)
+			.filter(|registration| registration.expires_at > self.state.now)
+			.and_then(|registration| registration.agreement_key.as_deref())
+	}
+
+	pub fn expiry_of(&self, domain: &str) -> Option<u64> {
+		self.state.domains.get(domain).map(|registration| registration.expires_at)
+	}
+
+	fn is_registered(&self, domain: &str) -> bool {
+		self.owner_of(domain).is_some()
+	}
+
+	pub fn record_traffic(&mut self, domain: &str, amount: u64) {
+		let traffic: &mut u64 = self.state.traffic.entry(domain.to_owned()).or_default();
+		*traffic = traffic.saturating_add(amount);
+		self.append(Event::TrafficRecorded {
+			domain: domain.to_owned(),
+			amount
+		});
+	}
+
+	pub fn traffic(&self, domain: &str) -> u64 {
+		self.state.traffic.get(domain).copied().unwrap_or_default()
+	}
+
+	pub fn renew_fee(&self, domain: &str) -> u64 {
+		let Parameters {
+			min_renew_fee,
+			max_renew_fee,
+			target_traffic,
+			..
+		} = self.parameters;
+		lib_economics::renew_fee(min_renew_fee, max_renew_fee, self.traffic(domain), target_traffic)
+	}
+
+	pub fn mint_fee(&self, domain: &str) -> u64 {
+		lib_economics::name_fee(self.parameters.mint_fee, domain.as_bytes(), self.parameters.target_information)
+	}
+
+	pub fn harberger_tax_of(&self, domain: &str) -> u64 {
+		self.state.domains
+			.get(domain)
+			.map(|registration| {
+				let elapsed: u64 = self.state.now.saturating_sub(registration.taxed_at);
+				lib_economics::harberger_tax(registration.value, self.parameters.harberger_tax_rate, elapsed)
+			})
+			.unwrap_or_default()
+	}
+
+	pub const fn fee(&self) -> u64 {
+		self.parameters.fee
+	}
+
+	pub const fn congestion_charge(&self) -> u64 {
+		self.parameters.congestion_charge
+	}
+
+	/// As on the contract, a transit needs a relay, listed once, and a
+	/// registered domain.
+	pub fn submit_proof(&mut self, proof: &Proof) -> Result {
+		let transit: &Transit = &proof.transit;
+		if transit.relays.is_empty() || transit.relays.len() != proof.relay_sigs.len() {
+			return Err(Error::InvalidProof)
+		}
+		let relays: std::collections::BTreeSet<&Key> = transit.relays.iter().collect();
+		if relays.len() != transit.relays.len() {
+			return Err(Error::InvalidProof)
+		}
+		if self.owner_of(&transit.domain).is_none() {
+			return Err(Error::UnknownDomain(transit.domain.to_owned()))
+		}
+		let message: Vec<u8> = transit.message()?;
+		if self.state.proofs.contains(&message) {
+			return Err(Error::ProofReplayed)
+		}
+		let signed: Vec<u8> = proof.message()?;
+		let signed: bool = verify(&signed, &transit.src, &proof.src_sig)
+			&& verify(&signed, &transit.dst, &proof.dst_sig)
+			&& transit.relays.iter().zip(&proof.relay_sigs).all(|(relay, relay_sig)| verify(&signed, relay, relay_sig));
+		if !signed {
+			return Err(Error::InvalidProof)
+		}
+		let src: Account = self.account_of(&transit.src)?;
+		self.account_of(&transit.dst)?;
+		let relays: Vec<Account> = transit.relays
+			.iter()
+			.map(|relay| self.account_of(relay))
+			.collect::<Result<_>>()?;
+		let relay_count: u64 = u64::try_from(relays.len()).map_err(|_| Error::Overflow)?;
+		let paid: u64 = self.parameters.fee.checked_mul(relay_count).ok_or(Error::Overflow)?;
+		let available: u64 = self.locked_balance_of(&src);
+		if available < paid {
+			return Err(Error::InsufficientBalance {
+				required: paid,
+				available
+			})
+		}
+		if let Some(lock) = self.state.locks.get_mut(&src) {
+			lock.amount = lock.amount.saturating_sub(paid);
+		}
+		self.add_spent(&src, paid);
+		for relay in relays {
+			let claimable: &mut u64 = self.state.claimable.entry(relay).or_default();
+			*claimable = claimable.saturating_add(self.parameters.fee);
+		}
+		self.state.proofs.insert(message);
+		self.record_traffic(&transit.domain, 1);
+		self.append(Event::ProofReceived {
+			proof: proof.to_owned(),
+			paid
+		});
+		Ok(())
+	}
+
+	pub fn claimable_of(&self, account: &Account) -> u64 {
+		self.state.claimable.get(account).copied().unwrap_or_default()
+	}
+
+	pub fn claim(&mut self, account: &Account) -> Result<u64> {
+		let amount: u64 = self.state.claimable.remove(account).unwrap_or_default();
+		nonzero(amount).map_err(|_| Error::NothingToClaim)?;
+		let balance: u64 = self.balance_of(account).saturating_add(amount);
+		self.state.balances.insert(account.to_owned(), balance);
+		let claimed: &mut u64 = self.state.claimed.entry(account.to_owned()).or_default();
+		*claimed = claimed.saturating_add(amount);
+		self.append(Event::Claimed {
+			account: account.to_owned(),
+			amount
+		});
+		Ok(amount)
+	}
+
+	pub fn total_spend(&self, account: &Account) -> u64 {
+		self.state.spent.get(account).copied().unwrap_or_default()
+	}
+
+	pub fn total_claim(&self, account: &Account) -> u64 {
+		self.state.claimed.get(account).copied().unwrap_or_default()
+	}
+
+	pub fn age(&self, account: &Account) -> Option<u64> {
+		let first_seen: u64 = self.state.first_seen.get(account).copied()?;
+		Some(self.state.now.saturating_sub(first_seen))
+	}
+
+	fn debit(&mut self, account: &Account, amount: u64) -> Result {
+		let available: u64 = self.balance_of(account);
+		let balance: u64 = available.checked_sub(amount).ok_or(Error::InsufficientBalance {
+			required: amount,
+			available
+		})?;
+		self.state.balances.insert(account.to_owned(), balance);
+		Ok(())
+	}
+
+	fn spend(&mut self, account: &Account, amount: u64) -> Result {
+		self.debit(account, amount)?;
+		self.add_spent(account, amount);
+		Ok(())
+	}
+
+	fn add_spent(&mut self, account: &Account, amount: u64) {
+		let spent: &mut u64 = self.state.spent.entry(account.to_owned()).or_default();
+		*spent = spent.saturating_add(amount);
+	}
+
+	fn seen(&mut self, account: &Account) {
+		let now: u64 = self.state.now;
+		self.state.first_seen.entry(account.to_owned()).or_insert(now);
+	}
+
+	fn append(&mut self, event: Event) {
+		let sequence: u64 = u64::try_from(self.state.events.len()).unwrap_or(u64::MAX);
+		self.state.events.push(Record {
+			sequence,
+			at: self.state.now,
+			event
+		});
+	}
+}
+
+fn nonzero(amount: u64) -> Result {
+	if amount == 0 {
+		return Err(Error::ZeroAmount)
+	}
+	Ok(())
+}
+
+fn prefixed(out: &mut Vec<u8>, field: &[u8]) -> Result {
+	let len: u32 = field.len().try_into().map_err(|_| Error::Overflow)?;
+	out.extend_from_slice(&len.to_be_bytes());
+	out.extend_from_slice(field);
+	Ok(())
+}
+
+fn verify(message: &[u8], key: &Key, signature: &[u8]) -> bool {
+	let verified: lib_cryptography::Result<bool> = (|| {
+		let message: bytes::Bytes = bytes::Bytes::copy_from_slice(message);
+		let message: lib_bytes::NonEmpty = message.try_into()?;
+		let message: lib_cryptography::message::Message = message.into();
+		let public_key: bytes::Bytes = bytes::Bytes::copy_from_slice(key);
+		let public_key: lib_bytes::NonEmpty = public_key.try_into()?;
+		let public_key: lib_cryptography::public_key::PublicKey<lib_cryptography_algorithm_agile::AgileAlgorithm> = public_key.into();
+		let signature: bytes::Bytes = bytes::Bytes::copy_from_slice(signature);
+		let signature: lib_bytes::NonEmpty = signature.try_into()?;
+		let signature: lib_cryptography::signature::Signature<lib_cryptography_algorithm_agile::AgileAlgorithm> = signature.into();
+		<lib_cryptography_algorithm_agile::AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::verify(&public_key, &message, &signature)
+	})();
+	verified.unwrap_or_default()
+}
+
+// ordered maps as sequences of pairs, so snapshots encode in formats whose
+// map keys must be strings
+mod pairs {
+	pub fn serialize<S, K, V>(map: &std::collections::BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+		K: serde::Serialize,
+		V: serde::Serialize {
+		serializer.collect_seq(map)
+	}
+
+	pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<std::collections::BTreeMap<K, V>, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+		K: serde::Deserialize<'de>,
+		K: Ord,
+		V: serde::Deserialize<'de> {
+		let pairs: Vec<(K, V)> = serde::Deserialize::deserialize(deserializer)?;
+		Ok(pairs.into_iter().collect())
+	}
+}
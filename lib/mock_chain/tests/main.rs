@@ -0,0 +1,345 @@
+use mock_chain::Account;
+use mock_chain::Error;
+use mock_chain::Event;
+use mock_chain::Key;
+use mock_chain::MockChain;
+use mock_chain::Proof;
+use mock_chain::Transit;
+
+type AgileAlgorithm = lib_cryptography_algorithm_agile::AgileAlgorithm;
+
+fn account(name: &str) -> Account {
+	name.as_bytes().to_vec().into()
+}
+
+fn sign(secret_key: &lib_cryptography::secret_key::SecretKey<AgileAlgorithm>, message: &[u8]) -> lib_cryptography::Result<Vec<u8>> {
+	let message: bytes::Bytes = bytes::Bytes::copy_from_slice(message);
+	let message: lib_bytes::NonEmpty = message.try_into()?;
+	let message: lib_cryptography::message::Message = message.into();
+	let signature: lib_cryptography::signature::Signature<AgileAlgorithm> = <AgileAlgorithm as lib_cryptography::AsymmetricSignatureAlgorithm>::sign(secret_key, &message)?;
+	let signature: lib_bytes::NonEmpty = signature.into();
+	let signature: bytes::Bytes = signature.into();
+	Ok(signature.to_vec())
+}
+
+/// Attests a fresh key to `account`, returning the key with its secret.
+fn party(chain: &mut MockChain, account: &Account) -> lib_cryptography::Result<(Key, lib_cryptography::secret_key::SecretKey<AgileAlgorithm>)> {
+	let pair: lib_cryptography::pair::Pair<AgileAlgorithm> = AgileAlgorithm::generate_with(lib_cryptography_algorithm_agile::Tag::Ed25519)?;
+	let (public_key, secret_key) = pair.into();
+	let signature: Vec<u8> = sign(&secret_key, account)?;
+	let public_key: lib_bytes::NonEmpty = public_key.into();
+	let public_key: bytes::Bytes = public_key.into();
+	let key: Key = public_key.to_vec().into();
+	chain.attest(account, &key, &signature)?;
+	Ok((key, secret_key))
+}
+
+fn attest(chain: &mut MockChain, account: &Account) -> lib_cryptography::Result<Key> {
+	let (key, _) = party(chain, account)?;
+	Ok(key)
+}
+
+/// Transit from `client` to `server` through `relay` for `domain`, signed
+/// by all three.
+fn proof(chain: &mut MockChain, domain: &str, nonce: u64) -> lib_cryptography::Result<Proof> {
+	hops(chain, domain, nonce, 1)
+}
+
+/// [`proof`] passing `relay` `count` times in a row.
+fn hops(chain: &mut MockChain, domain: &str, nonce: u64, count: usize) -> lib_cryptography::Result<Proof> {
+	let (src, src_secret_key) = party(chain, &account("client"))?;
+	let (dst, dst_secret_key) = party(chain, &account("server"))?;
+	let (relay, relay_secret_key) = party(chain, &account("relay"))?;
+	let transit: Transit = Transit {
+		domain: domain.to_owned(),
+		src,
+		dst,
+		relays: vec![relay; count],
+		nonce
+	};
+	let mut proof: Proof = Proof {
+		transit,
+		header: vec![0; lib_packet::header::LEN],
+		src_sig: Vec::new(),
+		dst_sig: Vec::new(),
+		relay_sigs: Vec::new()
+	};
+	let message: Vec<u8> = proof.message()?;
+	proof.src_sig = sign(&src_secret_key, &message)?;
+	proof.dst_sig = sign(&dst_secret_key, &message)?;
+	proof.relay_sigs = vec![sign(&relay_secret_key, &message)?; count];
+	Ok(proof)
+}
+
+/// Mints `domain` to a server of its own, so proofs can count towards it.
+fn register(chain: &mut MockChain, domain: &str) -> mock_chain::Result {
+	let owner: Account = account("owner");
+	chain.fund(&owner, chain.mint_fee(domain))?;
+	chain.mint(&owner, domain)
+}
+
+#[test]
+fn transfers_and_locks() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(1000);
+	let alice: Account = account("alice");
+	let bob: Account = account("bob");
+	chain.fund(&alice, 100)?;
+	chain.transfer(&alice, &bob, 40)?;
+	assert_eq!(chain.balance_of(&alice), 60);
+	assert_eq!(chain.balance_of(&bob), 40);
+	assert_eq!(chain.transfer(&bob, &alice, 41), Err(Error::InsufficientBalance {
+		required: 41,
+		available: 40
+	}));
+	chain.lock(&alice, 50, 60)?;
+	assert_eq!(chain.locked_balance_of(&alice), 50);
+	assert_eq!(chain.lock_expiry_of(&alice), Some(1060));
+	chain.advance(59);
+	assert_eq!(chain.locked_balance_of(&alice), 50);
+	chain.advance(1);
+	assert_eq!(chain.locked_balance_of(&alice), 0);
+	assert_eq!(chain.balance_of(&alice), 60);
+	Ok(())
+}
+
+#[test]
+fn authorization_uses_up_nonce() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	assert_eq!(chain.authorize(&alice, b"call 0", &[0; 66]), Err(Error::Unauthorized));
+	let (_, secret_key) = party(&mut chain, &alice)?;
+	let signature: Vec<u8> = sign(&secret_key, b"call 0")?;
+	assert_eq!(chain.authorize(&account("mallory"), b"call 0", &signature), Err(Error::Unauthorized));
+	assert_eq!(chain.authorize(&alice, b"call 1", &signature), Err(Error::Unauthorized));
+	assert_eq!(chain.nonce_of(&alice), 0);
+	chain.authorize(&alice, b"call 0", &signature)?;
+	assert_eq!(chain.nonce_of(&alice), 1);
+	Ok(())
+}
+
+#[test]
+fn attestation_requires_signature_over_account() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	let key: Key = attest(&mut chain, &alice)?;
+	assert_eq!(chain.attestation(&key), Some(&alice));
+	assert_eq!(chain.foreign_attestation(&alice), Some(&key));
+	assert_eq!(chain.attest(&account("mallory"), &key, &[0; 66]), Err(Error::InvalidAttestation));
+	Ok(())
+}
+
+#[test]
+fn domains_mint_renew_and_expire() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	let bob: Account = account("bob");
+	chain.fund(&alice, 1000)?;
+	chain.fund(&bob, 1000)?;
+	chain.mint(&alice, "hello.an")?;
+	assert_eq!(chain.owner_of("hello.an"), Some(&alice));
+	assert_eq!(chain.mint(&bob, "hello.an"), Err(Error::DomainTaken(String::from("hello.an"))));
+	assert_eq!(chain.renew(&bob, "hello.an"), Err(Error::NotOwner(String::from("hello.an"))));
+	assert_eq!(chain.renew_fee("hello.an"), 100);
+	chain.record_traffic("hello.an", 500);
+	assert_eq!(chain.renew_fee("hello.an"), 55);
+	chain.renew(&alice, "hello.an")?;
+	assert_eq!(chain.balance_of(&alice), 1000 - 300 - 55);
+	assert_eq!(chain.total_spend(&alice), 355);
+	chain.advance(2 * mock_chain::DEFAULT_TERM);
+	assert_eq!(chain.owner_of("hello.an"), None);
+	chain.mint(&bob, "hello.an")?;
+	assert_eq!(chain.owner_of("hello.an"), Some(&bob));
+	Ok(())
+}
+
+#[test]
+fn agreement_keys_are_published_by_the_owner_until_expiry() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	let bob: Account = account("bob");
+	chain.fund(&alice, 1000)?;
+	chain.fund(&bob, 1000)?;
+	assert_eq!(chain.publish_agreement_key(&alice, "hello.an", &[1; 32]), Err(Error::UnknownDomain(String::from("hello.an"))));
+	chain.mint(&alice, "hello.an")?;
+	assert_eq!(chain.agreement_key_of("hello.an"), None);
+	assert_eq!(chain.publish_agreement_key(&bob, "hello.an", &[2; 32]), Err(Error::NotOwner(String::from("hello.an"))));
+	chain.publish_agreement_key(&alice, "hello.an", &[1; 32])?;
+	assert_eq!(chain.agreement_key_of("hello.an"), Some(&[1; 32][..]));
+	assert!(matches!(chain.events().last().map(|record| &record.event), Some(Event::AgreementKeyPublished { .. })));
+	chain.advance(mock_chain::DEFAULT_TERM);
+	assert_eq!(chain.agreement_key_of("hello.an"), None);
+	chain.mint(&bob, "hello.an")?;
+	assert_eq!(chain.agreement_key_of("hello.an"), None);
+	Ok(())
+}
+
+#[test]
+fn short_names_cost_more_and_renewals_owe_harberger_tax() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	chain.fund(&alice, 10_000)?;
+	assert_eq!(chain.mint_fee("hello.an"), 300);
+	assert_eq!(chain.mint_fee("aa.an"), lib_economics::name_fee(300, b"aa.an", 16));
+	assert!(chain.mint_fee("aa.an") > 300);
+	chain.mint(&alice, "hello.an")?;
+	assert_eq!(chain.harberger_tax_of("hello.an"), 0);
+	chain.advance(mock_chain::DEFAULT_TERM / 2);
+	assert_eq!(chain.harberger_tax_of("hello.an"), 7);
+	chain.renew(&alice, "hello.an")?;
+	assert_eq!(chain.balance_of(&alice), 10_000 - 300 - 100 - 7);
+	assert_eq!(chain.harberger_tax_of("hello.an"), 0);
+	Ok(())
+}
+
+#[test]
+fn proofs_pay_relays_from_source_lock() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let client: Account = account("client");
+	let relay: Account = account("relay");
+	register(&mut chain, "hello.an")?;
+	let proof: Proof = proof(&mut chain, "hello.an", 0)?;
+	assert!(chain.submit_proof(&proof).is_err());
+	chain.fund(&client, 10)?;
+	chain.lock(&client, 10, 60)?;
+	assert!(chain.has_sufficient_balance(&client));
+	chain.submit_proof(&proof)?;
+	assert_eq!(chain.locked_balance_of(&client), 9);
+	assert_eq!(chain.claimable_of(&relay), 1);
+	assert_eq!(chain.claim(&relay)?, 1);
+	assert_eq!(chain.claim(&relay), Err(Error::NothingToClaim));
+	assert_eq!(chain.total_claim(&relay), 1);
+	assert_eq!(chain.total_spend(&client), 1);
+	Ok(())
+}
+
+#[test]
+fn proofs_settle_once_and_count_traffic() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let client: Account = account("client");
+	chain.fund(&client, 10)?;
+	chain.lock(&client, 10, 60)?;
+	register(&mut chain, "hello.an")?;
+	let first: Proof = proof(&mut chain, "hello.an", 0)?;
+	chain.submit_proof(&first)?;
+	assert_eq!(chain.submit_proof(&first), Err(Error::ProofReplayed));
+	assert_eq!(chain.traffic("hello.an"), 1);
+	assert!(chain.events().iter().any(|record| record.event == Event::TrafficRecorded {
+		domain: "hello.an".to_owned(),
+		amount: 1
+	}));
+	let second: Proof = proof(&mut chain, "hello.an", 1)?;
+	chain.submit_proof(&second)?;
+	assert_eq!(chain.traffic("hello.an"), 2);
+	assert_eq!(chain.locked_balance_of(&client), 8);
+	Ok(())
+}
+
+#[test]
+fn proofs_need_every_signature() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let client: Account = account("client");
+	chain.fund(&client, 10)?;
+	chain.lock(&client, 10, 60)?;
+	register(&mut chain, "hello.an")?;
+	register(&mut chain, "other.an")?;
+	let signed: Proof = proof(&mut chain, "hello.an", 0)?;
+	let mut forged: Proof = signed.to_owned();
+	forged.relay_sigs = vec![forged.src_sig.to_owned()];
+	assert_eq!(chain.submit_proof(&forged), Err(Error::InvalidProof));
+	let mut unsigned: Proof = signed.to_owned();
+	unsigned.relay_sigs.clear();
+	assert_eq!(chain.submit_proof(&unsigned), Err(Error::InvalidProof));
+	let mut moved: Proof = signed.to_owned();
+	moved.transit.domain = "other.an".to_owned();
+	assert_eq!(chain.submit_proof(&moved), Err(Error::InvalidProof));
+	let mut rewrapped: Proof = signed.to_owned();
+	if let Some(byte) = rewrapped.header.first_mut() {
+		*byte ^= 1;
+	}
+	assert_eq!(chain.submit_proof(&rewrapped), Err(Error::InvalidProof));
+	rewrapped.header.pop();
+	assert_eq!(chain.submit_proof(&rewrapped), Err(Error::InvalidProof));
+	assert_eq!(chain.locked_balance_of(&client), 10);
+	assert_eq!(chain.traffic("hello.an"), 0);
+	chain.submit_proof(&signed)?;
+	Ok(())
+}
+
+#[test]
+fn proofs_need_a_relay_and_a_registered_domain() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let client: Account = account("client");
+	chain.fund(&client, 10)?;
+	chain.lock(&client, 10, 60)?;
+	let unknown: Proof = proof(&mut chain, "hello.an", 0)?;
+	assert_eq!(chain.submit_proof(&unknown), Err(Error::UnknownDomain("hello.an".to_owned())));
+	register(&mut chain, "hello.an")?;
+	let mut direct: Proof = proof(&mut chain, "hello.an", 1)?;
+	direct.transit.relays.clear();
+	direct.relay_sigs.clear();
+	assert_eq!(chain.submit_proof(&direct), Err(Error::InvalidProof));
+	let twice: Proof = hops(&mut chain, "hello.an", 2, 2)?;
+	assert_eq!(chain.submit_proof(&twice), Err(Error::InvalidProof));
+	assert_eq!(chain.traffic("hello.an"), 0);
+	assert_eq!(chain.locked_balance_of(&client), 10);
+	Ok(())
+}
+
+#[test]
+fn commitments_are_opened_then_accepted() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let relay: Account = account("relay");
+	let client: Account = account("client");
+	assert_eq!(chain.accept_commitment(&client), Err(Error::NoCommitment));
+	chain.open_commitment(&relay, &client)?;
+	assert!(!chain.is_committed(&relay, &client));
+	chain.accept_commitment(&client)?;
+	assert!(chain.is_committed(&relay, &client));
+	Ok(())
+}
+
+#[test]
+fn snapshot_restores_state_and_log() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	let alice: Account = account("alice");
+	chain.fund(&alice, 10)?;
+	let snapshot: mock_chain::Snapshot = chain.snapshot();
+	chain.advance(5);
+	chain.fund(&alice, 10)?;
+	assert_eq!(chain.events().len(), 2);
+	chain.restore(snapshot);
+	assert_eq!(chain.balance_of(&alice), 10);
+	assert_eq!(chain.now(), 0);
+	assert_eq!(chain.events().len(), 1);
+	Ok(())
+}
+
+#[test]
+fn snapshot_splits_off_log() -> lib_cryptography::Result<()> {
+	let mut chain: MockChain = MockChain::new(0);
+	chain.fund(&account("alice"), 10)?;
+	let snapshot: mock_chain::Snapshot = chain.snapshot();
+	let (state, events): (mock_chain::Snapshot, Vec<mock_chain::Record>) = snapshot.to_owned().split();
+	assert_eq!(events.as_slice(), chain.events());
+	assert_eq!(state.join(events), snapshot);
+	Ok(())
+}
+
+#[test]
+fn same_seed_same_log() -> lib_cryptography::Result<()> {
+	let run = || -> lib_cryptography::Result<MockChain> {
+		let mut chain: MockChain = MockChain::new(42);
+		chain.fund(&account("alice"), 10)?;
+		chain.advance(3);
+		chain.lock(&account("alice"), 5, 10)?;
+		chain.advance(10);
+		Ok(chain)
+	};
+	let chain: MockChain = run()?;
+	assert_eq!(chain.events(), run()?.events());
+	assert_eq!(chain.age(&account("alice")), Some(13));
+	let at: Vec<u64> = chain.events().iter().map(|record| record.at).collect();
+	assert_eq!(at, vec![42, 45, 55]);
+	assert!(matches!(chain.events().last().map(|record| &record.event), Some(Event::Unlocked { amount: 5, .. })));
+	Ok(())
+}
@@ -0,0 +1,106 @@
+use super::*;
+
+/// Ipv4 or ipv6 network, parsed from `<addr>/<mask>` or from a bare address
+/// covering that single host.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(try_from = "String")]
+#[serde(into = "String")]
+pub struct Cidr {
+	addr: std::net::IpAddr,
+	mask: u8
+}
+
+impl Cidr {
+	pub fn new(addr: std::net::IpAddr, mask: u8) -> Result<Self, Error> {
+		if mask > Self::max_mask(&addr) {
+			return Err(Error::MalformedCidr(format!("{}/{}", addr, mask)))
+		}
+		Ok(Self {
+			addr,
+			mask
+		})
+	}
+
+	pub const fn addr(&self) -> std::net::IpAddr {
+		self.addr
+	}
+
+	pub const fn mask(&self) -> u8 {
+		self.mask
+	}
+
+	pub fn contains(&self, addr: &std::net::IpAddr) -> bool {
+		match (self.addr, addr) {
+			(std::net::IpAddr::V4(lhs), std::net::IpAddr::V4(rhs)) => {
+				let lhs: u32 = lhs.to_bits();
+				let rhs: u32 = rhs.to_bits();
+				let mask: u32 = u32::MAX.checked_shl(32 - u32::from(self.mask)).unwrap_or(0);
+				lhs & mask == rhs & mask
+			},
+			(std::net::IpAddr::V6(lhs), std::net::IpAddr::V6(rhs)) => {
+				let lhs: u128 = lhs.to_bits();
+				let rhs: u128 = rhs.to_bits();
+				let mask: u128 = u128::MAX.checked_shl(128 - u32::from(self.mask)).unwrap_or(0);
+				lhs & mask == rhs & mask
+			},
+			_ => false
+		}
+	}
+
+	const fn max_mask(addr: &std::net::IpAddr) -> u8 {
+		match addr {
+			std::net::IpAddr::V4(_) => 32,
+			std::net::IpAddr::V6(_) => 128
+		}
+	}
+}
+
+impl From<std::net::Ipv4Addr> for Cidr {
+	fn from(value: std::net::Ipv4Addr) -> Self {
+		Self {
+			addr: std::net::IpAddr::V4(value),
+			mask: 32
+		}
+	}
+}
+
+impl std::str::FromStr for Cidr {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let malformed = || Error::MalformedCidr(s.to_owned());
+		let Some((addr, mask)) = s.split_once('/') else {
+			let addr: std::net::IpAddr = s.parse().map_err(|_| malformed())?;
+			return Self::new(addr, Self::max_mask(&addr))
+		};
+		let addr: std::net::IpAddr = addr.parse().map_err(|_| malformed())?;
+		let mask: u8 = mask.parse().map_err(|_| malformed())?;
+		Self::new(addr, mask).map_err(|_| malformed())
+	}
+}
+
+impl TryFrom<String> for Cidr {
+	type Error = Error;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		value.parse()
+	}
+}
+
+impl From<Cidr> for String {
+	fn from(value: Cidr) -> Self {
+		value.to_string()
+	}
+}
+
+impl std::fmt::Display for Cidr {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}", self.addr, self.mask)
+	}
+}
@@ -0,0 +1,30 @@
+//! Addressing and iptables primitives shared by the simulation items, the
+//! e2e runner and the in-process censorship transport.
+
+pub mod cidr;
+pub mod port;
+
+pub use cidr::Cidr;
+pub use port::Port;
+pub use port::Protocol;
+
+/// Iptables invocation without the leading `iptables`.
+pub type Rule = Vec<String>;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("malformed cidr `{0}`")]
+	MalformedCidr(String),
+	#[error("malformed port `{0}`, expected `<tcp|udp>/<port>`")]
+	MalformedPort(String),
+	#[error("malformed protocol `{0}`, expected `tcp` or `udp`")]
+	MalformedProtocol(String)
+}
+
+pub fn rule(tokens: &[&str]) -> Rule {
+	tokens.iter().map(|token| (*token).to_owned()).collect()
+}
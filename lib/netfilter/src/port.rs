@@ -0,0 +1,58 @@
+use super::*;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+#[derive(serde::Serialize)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+	Tcp,
+	Udp
+}
+
+impl std::str::FromStr for Protocol {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"tcp" => Ok(Self::Tcp),
+			"udp" => Ok(Self::Udp),
+			_ => Err(Error::MalformedProtocol(s.to_owned()))
+		}
+	}
+}
+
+impl std::fmt::Display for Protocol {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Tcp => write!(f, "tcp"),
+			Self::Udp => write!(f, "udp")
+		}
+	}
+}
+
+/// Port of a protocol, parsed from `<tcp|udp>/<port>`.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Port {
+	pub protocol: Protocol,
+	pub port: u16
+}
+
+impl std::str::FromStr for Port {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let malformed = || Error::MalformedPort(s.to_owned());
+		let (protocol, port) = s.split_once('/').ok_or_else(malformed)?;
+		let protocol: Protocol = protocol.parse().map_err(|_| malformed())?;
+		let port: u16 = port.parse().map_err(|_| malformed())?;
+		Ok(Self { protocol, port })
+	}
+}
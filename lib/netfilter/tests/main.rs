@@ -0,0 +1,34 @@
+#[test]
+fn parses_cidrs_of_both_families() -> Result<(), netfilter::Error> {
+	let cidr: netfilter::Cidr = "10.1.0.0/16".parse()?;
+	assert_eq!(cidr.to_string(), "10.1.0.0/16");
+	assert!(cidr.contains(&std::net::IpAddr::from([10, 1, 200, 3])));
+	assert!(!cidr.contains(&std::net::IpAddr::from([10, 2, 0, 1])));
+	assert_eq!("10.0.0.1".parse::<netfilter::Cidr>()?.mask(), 32);
+	assert_eq!("fd00::1".parse::<netfilter::Cidr>()?.mask(), 128);
+	assert_eq!("fd00::/8".parse::<netfilter::Cidr>()?.to_string(), "fd00::/8");
+	Ok(())
+}
+
+#[test]
+fn rejects_malformed_input() {
+	assert!("10.0.0.0/33".parse::<netfilter::Cidr>().is_err());
+	assert!("fd00::/129".parse::<netfilter::Cidr>().is_err());
+	assert!("nope".parse::<netfilter::Cidr>().is_err());
+	assert!("sctp/80".parse::<netfilter::Port>().is_err());
+	assert!("udp".parse::<netfilter::Port>().is_err());
+	assert!("udp/65536".parse::<netfilter::Port>().is_err());
+}
+
+#[test]
+fn parses_ports() -> Result<(), netfilter::Error> {
+	let port: netfilter::Port = "udp/4001".parse()?;
+	assert_eq!(port, netfilter::Port { protocol: netfilter::Protocol::Udp, port: 4001 });
+	assert_eq!(port.protocol.to_string(), "udp");
+	Ok(())
+}
+
+#[test]
+fn rule_keeps_tokens_in_order() {
+	assert_eq!(netfilter::rule(&["-P", "FORWARD", "DROP"]), vec!["-P", "FORWARD", "DROP"]);
+}
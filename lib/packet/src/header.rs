@@ -0,0 +1,210 @@
+use rand::RngCore as _;
+
+pub const VERSION: u8 = 1;
+pub const NONCE_LEN: usize = 16;
+
+/// `version (1) || protocol (2) || algorithm (2) || created_at (8) || expires_at (8) || nonce (16)`,
+/// integers big endian, times in unix seconds.
+pub const LEN: usize = 1 + 2 + 2 + 8 + 8 + NONCE_LEN;
+
+/// How far in the future `created_at` may lie before a packet is rejected,
+/// absorbing clock drift between peers.
+pub const MAX_CLOCK_SKEW: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Lifetime given by [`crate::Unsigned::sign`].
+pub const DEFAULT_TTL: std::time::Duration = std::time::Duration::from_secs(300);
+
+/// Longest lifetime a verifier accepts. A replay cache has to remember a
+/// packet until it expires, so a signer picking a far `expires_at` could
+/// otherwise pin entries and fill the cache.
+pub const MAX_TTL: std::time::Duration = std::time::Duration::from_secs(3600);
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("header is {0} bytes, expected {LEN}")]
+	Length(usize),
+
+	#[error("unsupported header version {0}")]
+	Version(u8),
+
+	#[error("packet is for protocol {received}, expected {expected}")]
+	Protocol {
+		expected: u16,
+		received: u16
+	},
+
+	#[error("packet is signed with algorithm {received}, expected {expected}")]
+	Algorithm {
+		expected: u16,
+		received: u16
+	},
+
+	#[error("algorithm {0} is not supported")]
+	Unsupported(u16),
+
+	#[error("algorithm {0} is not accepted by policy")]
+	Rejected(u16),
+
+	#[error("packet was created in the future")]
+	NotYetValid,
+
+	#[error("packet has expired")]
+	Expired,
+
+	#[error("packet lives longer than {} seconds", MAX_TTL.as_secs())]
+	TooLong,
+
+	#[error("packet was already seen")]
+	Replayed,
+
+	#[error("replay cache is full")]
+	Full,
+
+	#[error(transparent)]
+	Bytes(#[from] lib_bytes::Error)
+}
+
+/// Signed alongside the content so a packet cannot be moved to another
+/// protocol, relabelled with another algorithm, or accepted after it expires.
+/// The nonce lets a [`crate::replay::Cache`] tell a replay from a fresh packet
+/// with the same content.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Header {
+	version: u8,
+	protocol: u16,
+	algorithm: u16,
+	created_at: u64,
+	expires_at: u64,
+	nonce: [u8; NONCE_LEN]
+}
+
+impl Header {
+	/// A fresh header valid from `created_at` for `ttl`, with a random nonce.
+	pub fn new(protocol: u16, algorithm: u16, created_at: u64, ttl: std::time::Duration) -> crate::Result<Self> {
+		let mut nonce: [u8; NONCE_LEN] = [0; NONCE_LEN];
+		rand::rngs::OsRng.try_fill_bytes(&mut nonce)?;
+		Ok(Self {
+			version: VERSION,
+			protocol,
+			algorithm,
+			created_at,
+			expires_at: created_at.saturating_add(ttl.as_secs()),
+			nonce
+		})
+	}
+
+	pub const fn version(&self) -> u8 {
+		self.version
+	}
+
+	pub const fn protocol(&self) -> u16 {
+		self.protocol
+	}
+
+	pub const fn algorithm(&self) -> u16 {
+		self.algorithm
+	}
+
+	pub const fn created_at(&self) -> u64 {
+		self.created_at
+	}
+
+	pub const fn expires_at(&self) -> u64 {
+		self.expires_at
+	}
+
+	pub const fn nonce(&self) -> &[u8; NONCE_LEN] {
+		&self.nonce
+	}
+
+	/// Fails unless the header targets `protocol` with `algorithm`, `now`
+	/// lies inside its validity window and that window spans at most
+	/// [`MAX_TTL`].
+	pub const fn check(&self, protocol: u16, algorithm: u16, now: u64) -> Result<(), Error> {
+		if self.protocol != protocol {
+			return Err(Error::Protocol {
+				expected: protocol,
+				received: self.protocol
+			})
+		}
+		if self.algorithm != algorithm {
+			return Err(Error::Algorithm {
+				expected: algorithm,
+				received: self.algorithm
+			})
+		}
+		if self.created_at > now.saturating_add(MAX_CLOCK_SKEW.as_secs()) {
+			return Err(Error::NotYetValid)
+		}
+		if now >= self.expires_at {
+			return Err(Error::Expired)
+		}
+		if self.expires_at.saturating_sub(self.created_at) > MAX_TTL.as_secs() {
+			return Err(Error::TooLong)
+		}
+		Ok(())
+	}
+
+	pub fn encode(&self) -> [u8; LEN] {
+		let mut out: [u8; LEN] = [0; LEN];
+		let fields: [&[u8]; 6] = [
+			&[self.version],
+			&self.protocol.to_be_bytes(),
+			&self.algorithm.to_be_bytes(),
+			&self.created_at.to_be_bytes(),
+			&self.expires_at.to_be_bytes(),
+			&self.nonce
+		];
+		let mut rest: &mut [u8] = &mut out;
+		for field in fields {
+			let (head, tail) = rest.split_at_mut(field.len());
+			head.copy_from_slice(field);
+			rest = tail;
+		}
+		out
+	}
+}
+
+impl Header {
+	/// Reads a header off the front of `reader`, leaving whatever follows.
+	pub fn read(reader: &mut lib_bytes::Reader) -> std::result::Result<Self, Error> {
+		let version: u8 = reader.read_u8()?;
+		if version != VERSION {
+			return Err(Error::Version(version))
+		}
+		Ok(Self {
+			version,
+			protocol: reader.read_u16()?,
+			algorithm: reader.read_u16()?,
+			created_at: reader.read_u64()?,
+			expires_at: reader.read_u64()?,
+			nonce: reader.read_fixed::<NONCE_LEN>()?.into_array()
+		})
+	}
+}
+
+impl TryFrom<&[u8]> for Header {
+	type Error = Error;
+
+	fn try_from(value: &[u8]) -> std::result::Result<Self, Self::Error> {
+		if value.len() != LEN {
+			return Err(Error::Length(value.len()))
+		}
+		let mut reader: lib_bytes::Reader = bytes::Bytes::copy_from_slice(value).into();
+		let out: Self = Self::read(&mut reader)?;
+		reader.finish()?;
+		Ok(out)
+	}
+}
+
+/// Current unix time in seconds, a clock before the epoch reads as zero.
+pub fn now() -> u64 {
+	std::time::SystemTime::now()
+		.duration_since(std::time::UNIX_EPOCH)
+		.map_or(0, |since| since.as_secs())
+}
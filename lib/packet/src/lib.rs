@@ -1,7 +1,23 @@
+pub mod header;
+pub mod multi_party;
+pub mod policy;
+pub mod replay;
+
 pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
 
+/// Largest encoded packet any parser here accepts, before looking inside it.
+pub const MAX_LEN: usize = 1 << 20;
+
+/// Wire identifier of the protocol a packet belongs to, carried in its
+/// [`header::Header`] so a packet signed for one protocol is rejected by
+/// every other.
+pub trait Protocol {
+	const ID: u16;
+}
+
 pub struct IsUnsetSigner;
 pub struct IsUnsetSignature;
+pub struct IsUnsetHeader;
 pub struct IsUnsetLayout;
 pub struct IsUnsetAlgorithm;
 pub struct IsUnsetProtocol;
@@ -17,23 +33,71 @@ pub struct IsUnsigned;
 #[derive(Eq)]
 #[derive(derive_more::Deref)]
 #[derive(derive_more::DerefMut)]
-pub struct Packet<A = IsUnsigned, B = IsUnsetLayout, C = IsUnsetAlgorithm, D = IsUnsetProtocol, E = IsUnsetSigner, F = IsUnsetSignature> {
+pub struct Packet<A = IsUnsigned, B = IsUnsetLayout, C = IsUnsetAlgorithm, D = IsUnsetProtocol, E = IsUnsetSigner, F = IsUnsetSignature, G = IsUnsetHeader> {
 	phantom_data: std::marker::PhantomData<(A, C, D)>,
 	#[deref]
 	#[deref_mut]
 	content: B,
 	signer: E,
-	signature: F
+	signature: F,
+	header: G
 }
 
 pub type Unsigned<A = IsUnsetLayout, B = IsUnsetProtocol> = Packet<IsUnsigned, A, IsUnsetAlgorithm, B>;
 
 impl<A, B> Unsigned<A, B> {
+	pub const fn new(content: A) -> Self {
+		Self {
+			phantom_data: std::marker::PhantomData,
+			content,
+			signer: IsUnsetSigner,
+			signature: IsUnsetSignature,
+			header: IsUnsetHeader
+		}
+	}
+
 	pub fn content(&self) -> &A {
 		&self.content
 	}
 }
 
+impl<A, B> Unsigned<A, B>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
+	B: Protocol {
+	/// Signs the content under a fresh [`header::Header`] valid for
+	/// [`header::DEFAULT_TTL`] and marks the packet with the signer's public
+	/// key. The result is verified by construction, encoding it yields
+	/// `header || signer || signature || content`.
+	pub fn sign<C>(self, secret_key: &lib_cryptography::secret_key::SecretKey<C>) -> Result<MarkedSignedVerified<A, C, B>>
+	where
+		C: lib_cryptography::AsymmetricSignatureAlgorithm,
+		C: lib_cryptography::AsymmetricKeyDerivationAlgorithm,
+		C: lib_cryptography::AsymmetricTaggedLayout {
+		self.sign_with_ttl(secret_key, header::DEFAULT_TTL)
+	}
+
+	pub fn sign_with_ttl<C>(self, secret_key: &lib_cryptography::secret_key::SecretKey<C>, ttl: std::time::Duration) -> Result<MarkedSignedVerified<A, C, B>>
+	where
+		C: lib_cryptography::AsymmetricSignatureAlgorithm,
+		C: lib_cryptography::AsymmetricKeyDerivationAlgorithm,
+		C: lib_cryptography::AsymmetricTaggedLayout {
+		let signer: lib_cryptography::public_key::PublicKey<C> = C::public_key(secret_key)?;
+		let algorithm: u16 = C::algorithm_of(&signer).ok_or(header::Error::Unsupported(C::ID))?;
+		let header: header::Header = header::Header::new(B::ID, algorithm, header::now(), ttl)?;
+		let message: lib_cryptography::message::Message = signed_message(&header, self.content.to_owned())?;
+		let signature: lib_cryptography::signature::Signature<C> = C::sign(secret_key, &message)?;
+		Ok(Packet {
+			phantom_data: std::marker::PhantomData,
+			content: self.content,
+			signer,
+			signature,
+			header
+		})
+	}
+}
+
 impl<A, B> TryFrom<lib_cryptography::message::Message> for Unsigned<A, B> 
 where
 	A: TryFrom<lib_bytes::NonEmpty, Error = Box<dyn std::error::Error>> {
@@ -46,7 +110,8 @@ where
 			phantom_data: std::marker::PhantomData,
 			content: out,
 			signer: IsUnsetSigner,
-			signature: IsUnsetSignature
+			signature: IsUnsetSignature,
+			header: IsUnsetHeader
 		};
 		Ok(out)
 	}
@@ -63,7 +128,8 @@ where
 			phantom_data: std::marker::PhantomData,
 			content: out,
 			signer: IsUnsetSigner,
-			signature: IsUnsetSignature
+			signature: IsUnsetSignature,
+			header: IsUnsetHeader
 		};
 		Ok(out)
 	}
@@ -81,7 +147,7 @@ where
 	}
 }
 
-pub type MarkedSignedVerified<A = IsUnsetLayout, B = IsUnsetAlgorithm, C = IsUnsetProtocol> = Packet<IsMarkedSignedVerified, A, B, C, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>>;
+pub type MarkedSignedVerified<A = IsUnsetLayout, B = IsUnsetAlgorithm, C = IsUnsetProtocol> = Packet<IsMarkedSignedVerified, A, B, C, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>, header::Header>;
 
 impl<A, B, C> MarkedSignedVerified<A, B, C> {
 	pub fn content(&self) -> &A {
@@ -95,126 +161,210 @@ impl<A, B, C> MarkedSignedVerified<A, B, C> {
 	pub fn signature(&self) -> &lib_cryptography::signature::Signature<B> {
 		&self.signature
 	}
+
+	pub const fn header(&self) -> &header::Header {
+		&self.header
+	}
+
+	/// Records the packet in `cache`, failing if it was accepted before.
+	/// Verifiers of anything that must not count twice, such as proofs,
+	/// call this after parsing.
+	pub fn check_replay<T>(self, cache: &mut T) -> Result<Self>
+	where
+		T: replay::Cache {
+		let signer: lib_bytes::NonEmpty = (*self.signer).to_owned();
+		let signer: bytes::Bytes = signer.into();
+		cache.insert(&signer, &self.header, header::now())?;
+		Ok(self)
+	}
 }
 
 impl<A, B, C> TryFrom<MarkedSignedUnverified<A, B, C>> for MarkedSignedVerified<A, B, C>
 where
 	A: Clone,
 	A: Into<lib_bytes::NonEmpty>,
-	B: lib_cryptography::AsymmetricSignatureAlgorithm {
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
 	type Error = Box<dyn std::error::Error>;
 
 	fn try_from(value: MarkedSignedUnverified<A, B, C>) -> std::result::Result<Self, Self::Error> {
-		let (content, signer, signature) = value.into();
-		let message: lib_bytes::NonEmpty = content.to_owned().try_into()?;
-		let message: lib_cryptography::message::Message = message.into();
-		if !B::verify(&signer, &message, &signature)? {
-			return Err(<Box<dyn std::error::Error>>::from(String::from("invalid")))
-		}
-		let out: Self = Self {
-			phantom_data: std::marker::PhantomData,
-			content,
-			signer,
-			signature
-		};
-		Ok(out)
+		value.verify_at(header::now())
 	}
 }
 
 impl<A, B, C> TryFrom<lib_bytes::NonEmpty> for MarkedSignedVerified<A, B, C>
 where
 	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
 	A: TryFrom<lib_bytes::NonEmpty, Error = Box<dyn std::error::Error>>,
-	B: lib_cryptography::AsymmetricSetLayout,
-	B: lib_cryptography::AsymmetricSignatureAlgorithm {
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	C: Protocol {
 	type Error = Box<dyn std::error::Error>;
 
 	fn try_from(value: lib_bytes::NonEmpty) -> std::result::Result<Self, Self::Error> {
-		let mut bytes: bytes::Bytes = value.into();
-		let header_len: usize = B::PUBLIC_KEY_LEN + B::SIGNATURE_LEN;
-		if bytes.len() <= header_len {
-			return Err(<Box<dyn std::error::Error>>::from(String::from("packet too short; insufficient header length")))
-		}
-		let signer: bytes::Bytes = bytes.split_to(B::PUBLIC_KEY_LEN);
-		let signer: lib_bytes::NonEmpty = signer.try_into()?;
-		let signer: lib_cryptography::public_key::PublicKey<B> = signer.into();
-		let signature: bytes::Bytes = bytes.split_to(B::SIGNATURE_LEN);
-		let signature: lib_bytes::NonEmpty = signature.try_into()?;
-		let signature: lib_cryptography::signature::Signature<B> = signature.into();
-		let content: lib_bytes::NonEmpty = bytes.try_into()?;
-		let content: lib_cryptography::message::Message = content.into();
-		if !B::verify(&signer, &content, &signature)? {
-			return Err(<Box<dyn std::error::Error>>::from(String::from("invalid")))
-		}
-		let content: Unsigned<A, C> = content.try_into()?;
-		let content: A = content.content().to_owned();
-		Ok(Self {
-			phantom_data: std::marker::PhantomData,
-			content,
-			signer,
-			signature
-		})
+		let out: MarkedSignedUnverified<A, B, C> = value.try_into()?;
+		out.verify()
 	}
 }
 
-impl<A, B, C> Into<(A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>)> for Packet<IsMarkedSignedVerified, A, B, C, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>> {
-	fn into(self) -> (A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>) {
+impl<A, B, C> From<MarkedSignedVerified<A, B, C>> for (A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>, header::Header) {
+	fn from(value: MarkedSignedVerified<A, B, C>) -> Self {
 		(
-			self.content,
-			self.signer,
-			self.signature
+			value.content,
+			value.signer,
+			value.signature,
+			value.header
 		)
 	}
 }
 
-pub type MarkedSignedUnverified<A = IsUnsetLayout, B = IsUnsetAlgorithm, C = IsUnsetProtocol> = Packet<IsMarkedSignedUnverified, A, B, C, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>>;
+impl<A, B, C> TryInto<lib_bytes::NonEmpty> for MarkedSignedVerified<A, B, C>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_into(self) -> std::result::Result<lib_bytes::NonEmpty, Self::Error> {
+		let (content, signer, signature, header) = self.into();
+		encode(content, signer, signature, &header)
+	}
+}
+
+pub type MarkedSignedUnverified<A = IsUnsetLayout, B = IsUnsetAlgorithm, C = IsUnsetProtocol> = Packet<IsMarkedSignedUnverified, A, B, C, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>, header::Header>;
+
+impl<A, B, C> MarkedSignedUnverified<A, B, C> {
+	pub const fn header(&self) -> &header::Header {
+		&self.header
+	}
+}
 
 impl<A, B, C> MarkedSignedUnverified<A, B, C> 
 where
 	A: Clone,
 	A: Into<lib_bytes::NonEmpty>,
-	B: lib_cryptography::AsymmetricSignatureAlgorithm {
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
 	pub fn verify(self) -> Result<MarkedSignedVerified<A, B, C>> {
-		self.try_into()
+		self.verify_at(header::now())
+	}
+
+	/// Verifies as [`Self::verify`] does, after rejecting any algorithm
+	/// `policy` does not accept.
+	pub fn verify_with(self, policy: &policy::Policy) -> Result<MarkedSignedVerified<A, B, C>> {
+		self.verify_with_at(policy, header::now())
+	}
+
+	pub fn verify_with_at(self, policy: &policy::Policy, now: u64) -> Result<MarkedSignedVerified<A, B, C>> {
+		policy.check(self.header.algorithm())?;
+		self.verify_at(now)
+	}
+
+	/// Checks that the header names `C` and the signer's algorithm and is
+	/// valid at `now` (unix seconds), then the signature over
+	/// `header || content`.
+	pub fn verify_at(self, now: u64) -> Result<MarkedSignedVerified<A, B, C>> {
+		let algorithm: u16 = B::algorithm_of(&self.signer).ok_or(header::Error::Unsupported(self.header.algorithm()))?;
+		self.header.check(C::ID, algorithm, now)?;
+		let message: lib_cryptography::message::Message = signed_message(&self.header, self.content.to_owned())?;
+		if !B::verify(&self.signer, &message, &self.signature)? {
+			return Err(<Box<dyn std::error::Error>>::from(String::from("invalid")))
+		}
+		Ok(Packet {
+			phantom_data: std::marker::PhantomData,
+			content: self.content,
+			signer: self.signer,
+			signature: self.signature,
+			header: self.header
+		})
 	}
 }
 
 impl<A, B, C> TryFrom<lib_bytes::NonEmpty> for MarkedSignedUnverified<A, B, C>
 where
-	A: Clone,
 	A: TryFrom<lib_bytes::NonEmpty, Error = Box<dyn std::error::Error>>,
-	B: lib_cryptography::AsymmetricSetLayout {
+	B: lib_cryptography::AsymmetricTaggedLayout {
 	type Error = Box<dyn std::error::Error>;
 
+	/// Reads the header first, the algorithm it names decides how long the
+	/// signer and signature after it are.
 	fn try_from(value: lib_bytes::NonEmpty) -> std::result::Result<Self, Self::Error> {
-		let mut bytes: bytes::Bytes = value.into();
-		let header_len: usize = B::PUBLIC_KEY_LEN + B::SIGNATURE_LEN;
-		if bytes.len() <= header_len {
-			return Err(<Box<dyn std::error::Error>>::from(String::from("packet too short; insufficient header length")))
-		}
-		let signer: bytes::Bytes = bytes.split_to(B::PUBLIC_KEY_LEN);
-		let signer: lib_bytes::NonEmpty = signer.try_into()?;
+		let value: lib_bytes::Bounded<{ header::LEN + 1 }, MAX_LEN> = value.try_into()?;
+		let mut reader: lib_bytes::Reader = bytes::Bytes::from(value).into();
+		let header: header::Header = header::Header::read(&mut reader)?;
+		let public_key_len: usize = B::public_key_len(header.algorithm()).ok_or(header::Error::Unsupported(header.algorithm()))?;
+		let signature_len: usize = B::signature_len(header.algorithm()).ok_or(header::Error::Unsupported(header.algorithm()))?;
+		let signer: lib_bytes::NonEmpty = reader.read_bounded::<1, MAX_LEN>(public_key_len)?.into();
 		let signer: lib_cryptography::public_key::PublicKey<B> = signer.into();
-		let signature: bytes::Bytes = bytes.split_to(B::SIGNATURE_LEN);
-		let signature: lib_bytes::NonEmpty = signature.try_into()?;
+		let signature: lib_bytes::NonEmpty = reader.read_bounded::<1, MAX_LEN>(signature_len)?.into();
 		let signature: lib_cryptography::signature::Signature<B> = signature.into();
+		let bytes: bytes::Bytes = reader.rest();
 		let content: lib_bytes::NonEmpty = bytes.try_into()?;
 		let content: A = content.try_into()?;
 		Ok(Self {
 			phantom_data: std::marker::PhantomData,
-			content: content.try_into()?,
+			content,
 			signer,
-			signature
+			signature,
+			header
 		})
 	}
 }
 
-impl<A, B, C> Into<(A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>)> for MarkedSignedUnverified<A, B, C> {
-	fn into(self) -> (A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>) {
+impl<A, B, C> From<MarkedSignedUnverified<A, B, C>> for (A, lib_cryptography::public_key::PublicKey<B>, lib_cryptography::signature::Signature<B>, header::Header) {
+	fn from(value: MarkedSignedUnverified<A, B, C>) -> Self {
 		(
-			self.content,
-			self.signer,
-			self.signature
+			value.content,
+			value.signer,
+			value.signature,
+			value.header
 		)
 	}
-}
\ No newline at end of file
+}
+
+impl<A, B, C> TryInto<lib_bytes::NonEmpty> for MarkedSignedUnverified<A, B, C>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_into(self) -> std::result::Result<lib_bytes::NonEmpty, Self::Error> {
+		let (content, signer, signature, header) = self.into();
+		encode(content, signer, signature, &header)
+	}
+}
+
+/// `header || content`, the bytes the signature covers.
+fn signed_message<A>(header: &header::Header, content: A) -> Result<lib_cryptography::message::Message>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	let content: lib_bytes::NonEmpty = content.into();
+	let content: bytes::Bytes = content.into();
+	let mut out: Vec<u8> = Vec::with_capacity(header::LEN + content.len());
+	out.extend_from_slice(&header.encode());
+	out.extend_from_slice(&content);
+	let out: bytes::Bytes = out.into();
+	let out: lib_bytes::NonEmpty = out.try_into()?;
+	Ok(out.into())
+}
+
+/// Writes `header || signer || signature || content`, the layout the marked
+/// parsers split on [`header::LEN`] and the signer and signature lengths of
+/// the algorithm the header names.
+fn encode<A, B>(content: A, signer: lib_cryptography::public_key::PublicKey<B>, signature: lib_cryptography::signature::Signature<B>, header: &header::Header) -> Result<lib_bytes::NonEmpty>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	let content: lib_bytes::NonEmpty = content.into();
+	let content: bytes::Bytes = content.into();
+	let signer: lib_bytes::NonEmpty = signer.into();
+	let signer: bytes::Bytes = signer.into();
+	let signature: lib_bytes::NonEmpty = signature.into();
+	let signature: bytes::Bytes = signature.into();
+	let mut out: Vec<u8> = Vec::with_capacity(header::LEN + signer.len() + signature.len() + content.len());
+	out.extend_from_slice(&header.encode());
+	out.extend_from_slice(&signer);
+	out.extend_from_slice(&signature);
+	out.extend_from_slice(&content);
+	let out: bytes::Bytes = out.into();
+	out.try_into()
+}
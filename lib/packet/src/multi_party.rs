@@ -0,0 +1,415 @@
+use super::*;
+
+/// Prefixed to what every party signs, so no signature collected here also
+/// verifies as a single party packet.
+pub const LABEL: &[u8] = b"an-packet-multi-party-v1";
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("packet has no signers")]
+	NoSigners,
+
+	#[error("packet has more than {} signers", u16::MAX)]
+	TooManySigners,
+
+	#[error("signers use different algorithms")]
+	MixedAlgorithms,
+
+	#[error("signer {0} appears more than once")]
+	DuplicateSigner(usize),
+
+	#[error("key is not among the signers")]
+	NotASigner,
+
+	#[error("signature of signer {0} does not verify")]
+	InvalidSignature(usize),
+
+	#[error("{0} signatures are missing")]
+	Incomplete(usize),
+
+	#[error("malformed multi party packet")]
+	Malformed
+}
+
+/// Packet collecting signatures from an ordered set of signers.
+///
+/// Every signer signs `LABEL || header || signer_count (2, big endian) ||
+/// signers || content`, so a signature only counts for this exact set in this
+/// exact order. Signatures are added one at a time, in any order, and each
+/// is verified as it comes in; once all are present [`Partial::complete`]
+/// yields a [`Complete`] packet.
+///
+/// ```text
+/// packet = header || signer_count (2) || signers || (present (1) || signature?)* || content
+/// ```
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Partial<A, B, C> {
+	phantom_data: std::marker::PhantomData<C>,
+	content: A,
+	header: header::Header,
+	signers: Vec<lib_cryptography::public_key::PublicKey<B>>,
+	signatures: Vec<Option<lib_cryptography::signature::Signature<B>>>
+}
+
+impl<A, B, C> Partial<A, B, C> {
+	pub fn content(&self) -> &A {
+		&self.content
+	}
+
+	pub const fn header(&self) -> &header::Header {
+		&self.header
+	}
+
+	pub fn signers(&self) -> &[lib_cryptography::public_key::PublicKey<B>] {
+		&self.signers
+	}
+
+	pub fn is_complete(&self) -> bool {
+		self.signatures.iter().all(Option::is_some)
+	}
+
+	/// Signers whose signature is still outstanding, in signer order.
+	pub fn missing(&self) -> impl Iterator<Item = &lib_cryptography::public_key::PublicKey<B>> {
+		self.signers.iter().zip(&self.signatures).filter(|(_, signature)| signature.is_none()).map(|(signer, _)| signer)
+	}
+
+	/// Hands back the packet unchanged while signatures are missing.
+	pub fn complete(self) -> std::result::Result<Complete<A, B, C>, Self> {
+		if !self.is_complete() {
+			return Err(self)
+		}
+		Ok(Complete {
+			phantom_data: std::marker::PhantomData,
+			content: self.content,
+			header: self.header,
+			signers: self.signers,
+			signatures: self.signatures.into_iter().flatten().collect()
+		})
+	}
+}
+
+impl<A, B, C> Partial<A, B, C>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
+	/// An unsigned packet for `signers` under a fresh [`header::Header`] valid
+	/// for [`header::DEFAULT_TTL`].
+	pub fn new(content: A, signers: Vec<lib_cryptography::public_key::PublicKey<B>>) -> Result<Self> {
+		Self::new_with_ttl(content, signers, header::DEFAULT_TTL)
+	}
+
+	pub fn new_with_ttl(content: A, signers: Vec<lib_cryptography::public_key::PublicKey<B>>, ttl: std::time::Duration) -> Result<Self> {
+		let algorithm: u16 = algorithm_of(&signers)?;
+		let header: header::Header = header::Header::new(C::ID, algorithm, header::now(), ttl)?;
+		let signatures: Vec<Option<lib_cryptography::signature::Signature<B>>> = signers.iter().map(|_| None).collect();
+		Ok(Self {
+			phantom_data: std::marker::PhantomData,
+			content,
+			header,
+			signers,
+			signatures
+		})
+	}
+
+	/// Adds the signature of the signer owning `secret_key`.
+	pub fn sign(&mut self, secret_key: &lib_cryptography::secret_key::SecretKey<B>) -> Result
+	where
+		B: lib_cryptography::AsymmetricKeyDerivationAlgorithm {
+		let signer: lib_cryptography::public_key::PublicKey<B> = B::public_key(secret_key)?;
+		let index: usize = self.index_of(&signer)?;
+		let message: lib_cryptography::message::Message = self.message()?;
+		let signature: lib_cryptography::signature::Signature<B> = B::sign(secret_key, &message)?;
+		if let Some(slot) = self.signatures.get_mut(index) {
+			*slot = Some(signature);
+		}
+		Ok(())
+	}
+
+	/// Adds a signature `signer` produced elsewhere, after verifying it.
+	pub fn insert(&mut self, signer: &lib_cryptography::public_key::PublicKey<B>, signature: lib_cryptography::signature::Signature<B>) -> Result {
+		let index: usize = self.index_of(signer)?;
+		let message: lib_cryptography::message::Message = self.message()?;
+		if !B::verify(signer, &message, &signature)? {
+			return Err(Error::InvalidSignature(index).into())
+		}
+		if let Some(slot) = self.signatures.get_mut(index) {
+			*slot = Some(signature);
+		}
+		Ok(())
+	}
+
+	/// Verifies every signature present, then that the header names `C` and
+	/// the signers' algorithm and is valid at `now` (unix seconds).
+	pub fn verify_at(&self, now: u64) -> Result {
+		let algorithm: u16 = algorithm_of(&self.signers)?;
+		self.header.check(C::ID, algorithm, now)?;
+		let message: lib_cryptography::message::Message = self.message()?;
+		for (index, (signer, signature)) in self.signers.iter().zip(&self.signatures).enumerate() {
+			let Some(signature) = signature else {
+				continue
+			};
+			if !B::verify(signer, &message, signature)? {
+				return Err(Error::InvalidSignature(index).into())
+			}
+		}
+		Ok(())
+	}
+
+	fn index_of(&self, signer: &lib_cryptography::public_key::PublicKey<B>) -> Result<usize> {
+		let index: usize = self.signers.iter().position(|other| **other == **signer).ok_or(Error::NotASigner)?;
+		Ok(index)
+	}
+
+	fn message(&self) -> Result<lib_cryptography::message::Message> {
+		message_of(&self.header, &self.signers, &self.content)
+	}
+}
+
+impl<A, B, C> TryFrom<lib_bytes::NonEmpty> for Partial<A, B, C>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
+	A: TryFrom<lib_bytes::NonEmpty, Error = Box<dyn std::error::Error>>,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
+	type Error = Box<dyn std::error::Error>;
+
+	/// Parses and verifies every signature present, a partial packet never
+	/// carries a signature that does not verify.
+	fn try_from(value: lib_bytes::NonEmpty) -> std::result::Result<Self, Self::Error> {
+		let value: lib_bytes::Bounded<{ header::LEN + 1 }, MAX_LEN> = value.try_into()?;
+		let mut reader: lib_bytes::Reader = bytes::Bytes::from(value).into();
+		let header: header::Header = header::Header::read(&mut reader)?;
+		let public_key_len: usize = B::public_key_len(header.algorithm()).ok_or(header::Error::Unsupported(header.algorithm()))?;
+		let signature_len: usize = B::signature_len(header.algorithm()).ok_or(header::Error::Unsupported(header.algorithm()))?;
+		let count: u16 = reader.read_u16()?;
+		// every signer takes at least a key and a presence byte, a count the
+		// input cannot hold is rejected before anything is allocated for it
+		if usize::from(count) * (public_key_len + 1) > reader.remaining() {
+			return Err(Error::Malformed.into())
+		}
+		let mut signers: Vec<lib_cryptography::public_key::PublicKey<B>> = Vec::with_capacity(count.into());
+		for _ in 0..count {
+			let signer: lib_bytes::NonEmpty = reader.read_bounded::<1, MAX_LEN>(public_key_len)?.into();
+			signers.push(signer.into());
+		}
+		let mut signatures: Vec<Option<lib_cryptography::signature::Signature<B>>> = Vec::with_capacity(count.into());
+		for _ in 0..count {
+			let signature: Option<lib_cryptography::signature::Signature<B>> = match reader.read_u8()? {
+				0 => None,
+				1 => {
+					let signature: lib_bytes::NonEmpty = reader.read_bounded::<1, MAX_LEN>(signature_len)?.into();
+					Some(signature.into())
+				},
+				_ => return Err(Error::Malformed.into())
+			};
+			signatures.push(signature);
+		}
+		let bytes: bytes::Bytes = reader.rest();
+		let content: lib_bytes::NonEmpty = bytes.try_into()?;
+		let content: A = content.try_into()?;
+		let out: Self = Self {
+			phantom_data: std::marker::PhantomData,
+			content,
+			header,
+			signers,
+			signatures
+		};
+		out.verify_at(header::now())?;
+		Ok(out)
+	}
+}
+
+impl<A, B, C> TryInto<lib_bytes::NonEmpty> for Partial<A, B, C>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_into(self) -> std::result::Result<lib_bytes::NonEmpty, Self::Error> {
+		let content: lib_bytes::NonEmpty = self.content.into();
+		let content: bytes::Bytes = content.into();
+		let mut out: Vec<u8> = self.header.encode().to_vec();
+		encode_signers(&mut out, &self.signers)?;
+		for signature in self.signatures {
+			match signature {
+				Some(signature) => {
+					let signature: lib_bytes::NonEmpty = signature.into();
+					let signature: bytes::Bytes = signature.into();
+					out.push(1);
+					out.extend_from_slice(&signature);
+				},
+				None => out.push(0)
+			}
+		}
+		out.extend_from_slice(&content);
+		let out: bytes::Bytes = out.into();
+		out.try_into()
+	}
+}
+
+/// Multi party packet carrying a verified signature from every signer.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Complete<A, B, C> {
+	phantom_data: std::marker::PhantomData<C>,
+	content: A,
+	header: header::Header,
+	signers: Vec<lib_cryptography::public_key::PublicKey<B>>,
+	signatures: Vec<lib_cryptography::signature::Signature<B>>
+}
+
+impl<A, B, C> Complete<A, B, C> {
+	pub fn content(&self) -> &A {
+		&self.content
+	}
+
+	pub const fn header(&self) -> &header::Header {
+		&self.header
+	}
+
+	pub fn signers(&self) -> &[lib_cryptography::public_key::PublicKey<B>] {
+		&self.signers
+	}
+
+	/// Signatures in signer order.
+	pub fn signatures(&self) -> &[lib_cryptography::signature::Signature<B>] {
+		&self.signatures
+	}
+
+	/// Records the packet in `cache` under its whole signer set, failing if it
+	/// was accepted before.
+	pub fn check_replay<T>(self, cache: &mut T) -> Result<Self>
+	where
+		T: replay::Cache {
+		let mut signers: Vec<u8> = Vec::new();
+		for signer in &self.signers {
+			let signer: bytes::Bytes = (**signer).to_owned().into();
+			signers.extend_from_slice(&signer);
+		}
+		cache.insert(&signers, &self.header, header::now())?;
+		Ok(self)
+	}
+}
+
+impl<A, B, C> Complete<A, B, C>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
+	/// Verifies the header as [`Partial::verify_at`] does, then every
+	/// signature.
+	pub fn verify_at(&self, now: u64) -> Result {
+		let algorithm: u16 = algorithm_of(&self.signers)?;
+		self.header.check(C::ID, algorithm, now)?;
+		if self.signatures.len() != self.signers.len() {
+			return Err(Error::Incomplete(self.signers.len().abs_diff(self.signatures.len())).into())
+		}
+		let message: lib_cryptography::message::Message = message_of(&self.header, &self.signers, &self.content)?;
+		for (index, (signer, signature)) in self.signers.iter().zip(&self.signatures).enumerate() {
+			if !B::verify(signer, &message, signature)? {
+				return Err(Error::InvalidSignature(index).into())
+			}
+		}
+		Ok(())
+	}
+
+	pub fn verify(&self) -> Result {
+		self.verify_at(header::now())
+	}
+}
+
+impl<A, B, C> From<Complete<A, B, C>> for Partial<A, B, C> {
+	fn from(value: Complete<A, B, C>) -> Self {
+		Self {
+			phantom_data: std::marker::PhantomData,
+			content: value.content,
+			header: value.header,
+			signers: value.signers,
+			signatures: value.signatures.into_iter().map(Some).collect()
+		}
+	}
+}
+
+impl<A, B, C> TryFrom<lib_bytes::NonEmpty> for Complete<A, B, C>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty>,
+	A: TryFrom<lib_bytes::NonEmpty, Error = Box<dyn std::error::Error>>,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricTaggedLayout,
+	C: Protocol {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: lib_bytes::NonEmpty) -> std::result::Result<Self, Self::Error> {
+		let out: Partial<A, B, C> = value.try_into()?;
+		out.complete().map_err(|out| Error::Incomplete(out.missing().count()).into())
+	}
+}
+
+impl<A, B, C> TryInto<lib_bytes::NonEmpty> for Complete<A, B, C>
+where
+	A: Into<lib_bytes::NonEmpty> {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_into(self) -> std::result::Result<lib_bytes::NonEmpty, Self::Error> {
+		let out: Partial<A, B, C> = self.into();
+		out.try_into()
+	}
+}
+
+/// The one algorithm every signer uses, signers must be distinct.
+fn algorithm_of<B>(signers: &[lib_cryptography::public_key::PublicKey<B>]) -> Result<u16>
+where
+	B: lib_cryptography::AsymmetricTaggedLayout {
+	let first: &lib_cryptography::public_key::PublicKey<B> = signers.first().ok_or(Error::NoSigners)?;
+	let algorithm: u16 = B::algorithm_of(first).ok_or(Error::MixedAlgorithms)?;
+	for (index, signer) in signers.iter().enumerate() {
+		if B::algorithm_of(signer) != Some(algorithm) {
+			return Err(Error::MixedAlgorithms.into())
+		}
+		if signers.iter().take(index).any(|other| **other == **signer) {
+			return Err(Error::DuplicateSigner(index).into())
+		}
+	}
+	Ok(algorithm)
+}
+
+/// `LABEL || header || signer_count (2) || signers || content`, what every
+/// signer signs.
+fn message_of<A, B>(header: &header::Header, signers: &[lib_cryptography::public_key::PublicKey<B>], content: &A) -> Result<lib_cryptography::message::Message>
+where
+	A: Clone,
+	A: Into<lib_bytes::NonEmpty> {
+	let content: lib_bytes::NonEmpty = content.to_owned().into();
+	let content: bytes::Bytes = content.into();
+	let mut out: Vec<u8> = LABEL.to_vec();
+	out.extend_from_slice(&header.encode());
+	encode_signers(&mut out, signers)?;
+	out.extend_from_slice(&content);
+	let out: bytes::Bytes = out.into();
+	let out: lib_bytes::NonEmpty = out.try_into()?;
+	Ok(out.into())
+}
+
+/// `signer_count (2, big endian) || signers`.
+fn encode_signers<B>(out: &mut Vec<u8>, signers: &[lib_cryptography::public_key::PublicKey<B>]) -> Result {
+	let count: u16 = signers.len().try_into().map_err(|_| Error::TooManySigners)?;
+	out.extend_from_slice(&count.to_be_bytes());
+	for signer in signers {
+		let signer: bytes::Bytes = (**signer).to_owned().into();
+		out.extend_from_slice(&signer);
+	}
+	Ok(())
+}
@@ -0,0 +1,59 @@
+use super::*;
+
+/// Signature algorithms a verifier accepts, by [`lib_cryptography::id`].
+///
+/// Checked against the algorithm the [`header::Header`] names before the
+/// signature is, so a network can move to post quantum keys one verifier at a
+/// time: first accept classical and post quantum signers alike, then reject
+/// the classical ones once every signer has migrated.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Policy {
+	accepted: std::collections::BTreeSet<u16>
+}
+
+impl Policy {
+	pub fn new<T>(accepted: T) -> Self
+	where
+		T: IntoIterator<Item = u16> {
+		Self {
+			accepted: accepted.into_iter().collect()
+		}
+	}
+
+	#[must_use]
+	pub fn accept(mut self, algorithm: u16) -> Self {
+		self.accepted.insert(algorithm);
+		self
+	}
+
+	#[must_use]
+	pub fn reject(mut self, algorithm: u16) -> Self {
+		self.accepted.remove(&algorithm);
+		self
+	}
+
+	pub fn accepts(&self, algorithm: u16) -> bool {
+		self.accepted.contains(&algorithm)
+	}
+
+	pub fn check(&self, algorithm: u16) -> std::result::Result<(), header::Error> {
+		if !self.accepts(algorithm) {
+			return Err(header::Error::Rejected(algorithm))
+		}
+		Ok(())
+	}
+}
+
+/// Every signature algorithm in the registry.
+impl Default for Policy {
+	fn default() -> Self {
+		Self::new([
+			lib_cryptography::id::ED25519,
+			lib_cryptography::id::DILITHIUM3,
+			lib_cryptography::id::ED25519_DILITHIUM3
+		])
+	}
+}
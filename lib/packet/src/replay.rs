@@ -0,0 +1,53 @@
+use super::*;
+
+/// Remembers which packets a verifier has accepted. A packet only has to be
+/// remembered until its header expires, after that the expiry check rejects
+/// it on its own.
+pub trait Cache {
+	/// Records the packet `signer` marked with `header`, failing with
+	/// [`header::Error::Replayed`] if it was recorded before.
+	fn insert(&mut self, signer: &[u8], header: &header::Header, now: u64) -> std::result::Result<(), header::Error>;
+}
+
+/// In memory [`Cache`] holding at most `capacity` unexpired packets. When full
+/// it drops expired entries, and if none have expired it fails closed rather
+/// than forgetting a packet that could still be replayed.
+#[derive(Debug)]
+pub struct Memory {
+	capacity: usize,
+	seen: std::collections::HashMap<(bytes::Bytes, [u8; header::NONCE_LEN]), u64>
+}
+
+impl Memory {
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity,
+			seen: std::collections::HashMap::default()
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.seen.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.seen.is_empty()
+	}
+}
+
+impl Cache for Memory {
+	fn insert(&mut self, signer: &[u8], header: &header::Header, now: u64) -> std::result::Result<(), header::Error> {
+		let key: (bytes::Bytes, [u8; header::NONCE_LEN]) = (bytes::Bytes::copy_from_slice(signer), header.nonce().to_owned());
+		if self.seen.get(&key).is_some_and(|expires_at| now < *expires_at) {
+			return Err(header::Error::Replayed)
+		}
+		if self.seen.len() >= self.capacity {
+			self.seen.retain(|_, expires_at| now < *expires_at);
+		}
+		if self.seen.len() >= self.capacity {
+			return Err(header::Error::Full)
+		}
+		self.seen.insert(key, header.expires_at());
+		Ok(())
+	}
+}
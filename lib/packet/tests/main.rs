@@ -0,0 +1,391 @@
+use cryptography_algorithm_dilithium3::Dilithium3;
+use cryptography_algorithm_ed25519::Ed25519Algorithm;
+use cryptography_algorithm_ed25519_dilithium3::Ed25519Dilithium3Algorithm;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Payload(lib_bytes::NonEmpty);
+
+impl TryFrom<lib_bytes::NonEmpty> for Payload {
+	type Error = Box<dyn std::error::Error>;
+
+	fn try_from(value: lib_bytes::NonEmpty) -> Result<Self, Self::Error> {
+		Ok(Self(value))
+	}
+}
+
+impl From<Payload> for lib_bytes::NonEmpty {
+	fn from(value: Payload) -> Self {
+		value.0
+	}
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Reservation;
+
+impl packet::Protocol for Reservation {
+	const ID: u16 = 1;
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+struct Proof;
+
+impl packet::Protocol for Proof {
+	const ID: u16 = 2;
+}
+
+fn payload(content: &[u8]) -> packet::Result<Payload> {
+	let content: bytes::Bytes = bytes::Bytes::copy_from_slice(content);
+	let content: lib_bytes::NonEmpty = content.try_into()?;
+	Ok(Payload(content))
+}
+
+fn bytes_of<T: Into<lib_bytes::NonEmpty>>(value: T) -> bytes::Bytes {
+	let value: lib_bytes::NonEmpty = value.into();
+	value.into()
+}
+
+fn round_trip<B>() -> packet::Result
+where
+	B: lib_cryptography::AsymmetricKeyGenAlgorithm,
+	B: lib_cryptography::AsymmetricKeyDerivationAlgorithm,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricSetLayout,
+	B: std::fmt::Debug,
+	B: PartialEq {
+	let (public_key, secret_key) = lib_cryptography::pair::Pair::<B>::generate()?.into();
+	let content: Payload = payload(b"signed packet")?;
+	let signed: packet::MarkedSignedVerified<Payload, B, Reservation> = packet::Unsigned::<Payload, Reservation>::new(content.to_owned()).sign(&secret_key)?;
+	assert_eq!(signed.signer(), &public_key);
+	let encoded: lib_bytes::NonEmpty = signed.try_into()?;
+	let encoded: bytes::Bytes = encoded.into();
+	assert_eq!(encoded.len(), B::PUBLIC_KEY_LEN + B::SIGNATURE_LEN + packet::header::LEN + b"signed packet".len());
+	assert_eq!(encoded.get(packet::header::LEN..packet::header::LEN + B::PUBLIC_KEY_LEN), Some(bytes_of(public_key.to_owned()).as_ref()));
+	assert!(encoded.ends_with(b"signed packet"));
+
+	let decoded: lib_bytes::NonEmpty = encoded.to_owned().try_into()?;
+	let decoded: packet::MarkedSignedVerified<Payload, B, Reservation> = decoded.try_into()?;
+	assert_eq!(decoded.content(), &content);
+	assert_eq!(decoded.signer(), &public_key);
+
+	let unverified: lib_bytes::NonEmpty = encoded.to_owned().try_into()?;
+	let unverified: packet::MarkedSignedUnverified<Payload, B, Reservation> = unverified.try_into()?;
+	assert_eq!(unverified.verify()?.content(), &content);
+
+	let unverified: lib_bytes::NonEmpty = encoded.to_owned().try_into()?;
+	let unverified: packet::MarkedSignedUnverified<Payload, B, Reservation> = unverified.try_into()?;
+	let reencoded: lib_bytes::NonEmpty = unverified.try_into()?;
+	assert_eq!(bytes_of(reencoded), encoded);
+	Ok(())
+}
+
+fn rejects_tampering<B>() -> packet::Result
+where
+	B: lib_cryptography::AsymmetricKeyGenAlgorithm,
+	B: lib_cryptography::AsymmetricKeyDerivationAlgorithm,
+	B: lib_cryptography::AsymmetricSignatureAlgorithm,
+	B: lib_cryptography::AsymmetricSetLayout,
+	B: std::fmt::Debug,
+	B: PartialEq {
+	let (_, secret_key) = lib_cryptography::pair::Pair::<B>::generate()?.into();
+	let signed: packet::MarkedSignedVerified<Payload, B, Reservation> = packet::Unsigned::<Payload, Reservation>::new(payload(b"signed packet")?).sign(&secret_key)?;
+	let encoded: lib_bytes::NonEmpty = signed.try_into()?;
+	let mut tampered: Vec<u8> = bytes_of(encoded).to_vec();
+	if let Some(byte) = tampered.last_mut() {
+		*byte ^= 1;
+	}
+	let tampered: bytes::Bytes = tampered.into();
+	let tampered: lib_bytes::NonEmpty = tampered.try_into()?;
+	let tampered: packet::Result<packet::MarkedSignedVerified<Payload, B, Reservation>> = tampered.try_into();
+	assert!(tampered.is_err());
+	Ok(())
+}
+
+fn signed(ttl: std::time::Duration) -> packet::Result<lib_bytes::NonEmpty> {
+	let (_, secret_key) = lib_cryptography::pair::Pair::<Ed25519Algorithm>::generate()?.into();
+	let signed: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = packet::Unsigned::<Payload, Reservation>::new(payload(b"signed packet")?).sign_with_ttl(&secret_key, ttl)?;
+	signed.try_into()
+}
+
+#[test]
+fn rejects_expired() -> packet::Result {
+	let expired: packet::Result<packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation>> = signed(std::time::Duration::ZERO)?.try_into();
+	assert!(expired.is_err());
+	Ok(())
+}
+
+#[test]
+fn rejects_lifetime_over_max_ttl() -> packet::Result {
+	let pinned: packet::Result<packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation>> = signed(packet::header::MAX_TTL + std::time::Duration::from_secs(1))?.try_into();
+	assert_eq!(pinned.map(drop).map_err(|error| error.to_string()), Err(packet::header::Error::TooLong.to_string()));
+	let longest: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = signed(packet::header::MAX_TTL)?.try_into()?;
+	let mut cache: packet::replay::Memory = packet::replay::Memory::new(1);
+	longest.check_replay(&mut cache)?;
+	Ok(())
+}
+
+#[test]
+fn rejects_other_protocol() -> packet::Result {
+	let other: packet::Result<packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Proof>> = signed(packet::header::DEFAULT_TTL)?.try_into();
+	assert!(other.is_err());
+	Ok(())
+}
+
+#[test]
+fn checks_header_before_signature() -> packet::Result {
+	let encoded: bytes::Bytes = signed(packet::header::DEFAULT_TTL)?.into();
+	let mut tampered: Vec<u8> = encoded.to_vec();
+	if let Some(byte) = tampered.get_mut(packet::header::LEN + 32) {
+		*byte ^= 1;
+	}
+	let tampered: bytes::Bytes = tampered.into();
+	let tampered: lib_bytes::NonEmpty = tampered.try_into()?;
+	let other: packet::Result<packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Proof>> = tampered.try_into();
+	let error: Box<dyn std::error::Error> = other.err().ok_or("tampered packet verified")?;
+	assert!(matches!(error.downcast_ref(), Some(packet::header::Error::Protocol { .. })));
+	Ok(())
+}
+
+#[test]
+fn rejects_other_algorithm() -> packet::Result {
+	let encoded: bytes::Bytes = signed(packet::header::DEFAULT_TTL)?.into();
+	let unverified: lib_bytes::NonEmpty = encoded.try_into()?;
+	let unverified: packet::MarkedSignedUnverified<Payload, Ed25519Algorithm, Reservation> = unverified.try_into()?;
+	assert_eq!(unverified.header().algorithm(), lib_cryptography::id::ED25519);
+	assert_eq!(unverified.header().protocol(), 1);
+	let header: packet::header::Header = unverified.header().to_owned();
+	let error: Option<packet::header::Error> = header.check(1, lib_cryptography::id::DILITHIUM3, packet::header::now()).err();
+	assert!(matches!(error, Some(packet::header::Error::Algorithm { .. })));
+	Ok(())
+}
+
+#[test]
+fn truncated_header_keeps_read_error() -> packet::Result {
+	let header: packet::header::Header = packet::header::Header::new(1, lib_cryptography::id::ED25519, packet::header::now(), packet::header::DEFAULT_TTL)?;
+	let encoded: bytes::Bytes = bytes::Bytes::copy_from_slice(&header.encode()[..packet::header::LEN - 1]);
+	let mut reader: lib_bytes::Reader = encoded.into();
+	let error: Option<packet::header::Error> = packet::header::Header::read(&mut reader).err();
+	assert!(matches!(error, Some(packet::header::Error::Bytes(lib_bytes::Error::Eof { needed: packet::header::NONCE_LEN, remaining })) if remaining == packet::header::NONCE_LEN - 1));
+	let error: Option<packet::header::Error> = packet::header::Header::try_from(&header.encode()[..3]).err();
+	assert!(matches!(error, Some(packet::header::Error::Length(3))));
+	Ok(())
+}
+
+#[test]
+fn rejects_replay() -> packet::Result {
+	let mut cache: packet::replay::Memory = packet::replay::Memory::new(16);
+	let encoded: lib_bytes::NonEmpty = signed(packet::header::DEFAULT_TTL)?;
+	let first: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = encoded.to_owned().try_into()?;
+	first.check_replay(&mut cache)?;
+	let replayed: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = encoded.try_into()?;
+	assert!(replayed.check_replay(&mut cache).is_err());
+	let fresh: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = signed(packet::header::DEFAULT_TTL)?.try_into()?;
+	fresh.check_replay(&mut cache)?;
+	assert_eq!(cache.len(), 2);
+	Ok(())
+}
+
+#[test]
+fn replay_cache_fails_closed() -> packet::Result {
+	let mut cache: packet::replay::Memory = packet::replay::Memory::new(1);
+	let first: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = signed(packet::header::DEFAULT_TTL)?.try_into()?;
+	first.check_replay(&mut cache)?;
+	let second: packet::MarkedSignedVerified<Payload, Ed25519Algorithm, Reservation> = signed(packet::header::DEFAULT_TTL)?.try_into()?;
+	assert!(second.check_replay(&mut cache).is_err());
+	Ok(())
+}
+
+#[test]
+fn ed25519_round_trip() -> packet::Result {
+	round_trip::<Ed25519Algorithm>()
+}
+
+#[test]
+fn ed25519_rejects_tampering() -> packet::Result {
+	rejects_tampering::<Ed25519Algorithm>()
+}
+
+#[test]
+fn dilithium3_round_trip() -> packet::Result {
+	round_trip::<Dilithium3>()
+}
+
+#[test]
+fn dilithium3_rejects_tampering() -> packet::Result {
+	rejects_tampering::<Dilithium3>()
+}
+
+#[test]
+fn ed25519_dilithium3_round_trip() -> packet::Result {
+	round_trip::<Ed25519Dilithium3Algorithm>()
+}
+
+#[test]
+fn ed25519_dilithium3_rejects_tampering() -> packet::Result {
+	rejects_tampering::<Ed25519Dilithium3Algorithm>()
+}
+
+type MultiParty = packet::multi_party::Partial<Payload, Ed25519Algorithm, Reservation>;
+
+fn parties(count: usize) -> packet::Result<Vec<(lib_cryptography::public_key::PublicKey<Ed25519Algorithm>, lib_cryptography::secret_key::SecretKey<Ed25519Algorithm>)>> {
+	(0..count).map(|_| Ok(lib_cryptography::pair::Pair::<Ed25519Algorithm>::generate()?.into())).collect()
+}
+
+#[test]
+fn multi_party_completes_once_every_signer_signed() -> packet::Result {
+	let parties = parties(3)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut partial: MultiParty = MultiParty::new(payload(b"transit proof")?, signers.to_owned())?;
+	for (signed, (public_key, secret_key)) in parties.iter().rev().enumerate() {
+		assert!(!partial.is_complete());
+		assert_eq!(partial.missing().count(), 3 - signed);
+		assert!(partial.missing().any(|missing| missing == public_key));
+		partial.sign(secret_key)?;
+	}
+	let complete: packet::multi_party::Complete<Payload, Ed25519Algorithm, Reservation> = partial.complete().map_err(|_| "incomplete")?;
+	assert_eq!(complete.signers(), signers.as_slice());
+	assert_eq!(complete.signatures().len(), 3);
+
+	complete.verify()?;
+
+	let encoded: lib_bytes::NonEmpty = complete.to_owned().try_into()?;
+	let decoded: packet::multi_party::Complete<Payload, Ed25519Algorithm, Reservation> = encoded.try_into()?;
+	assert_eq!(decoded, complete);
+	Ok(())
+}
+
+#[test]
+fn multi_party_complete_rejects_expired() -> packet::Result {
+	let parties = parties(2)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut partial: MultiParty = MultiParty::new_with_ttl(payload(b"transit proof")?, signers, std::time::Duration::from_secs(1))?;
+	for (_, secret_key) in &parties {
+		partial.sign(secret_key)?;
+	}
+	let complete: packet::multi_party::Complete<Payload, Ed25519Algorithm, Reservation> = partial.complete().map_err(|_| "incomplete")?;
+	assert!(complete.verify_at(packet::header::now()).is_ok());
+	assert!(complete.verify_at(packet::header::now() + 60).is_err());
+	Ok(())
+}
+
+#[test]
+fn multi_party_partial_travels_between_signers() -> packet::Result {
+	let parties = parties(2)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut partial: MultiParty = MultiParty::new(payload(b"transit proof")?, signers)?;
+	if let Some((_, secret_key)) = parties.first() {
+		partial.sign(secret_key)?;
+	}
+	let encoded: lib_bytes::NonEmpty = partial.try_into()?;
+	let incomplete: packet::Result<packet::multi_party::Complete<Payload, Ed25519Algorithm, Reservation>> = encoded.to_owned().try_into();
+	assert!(incomplete.is_err());
+	let mut partial: MultiParty = encoded.try_into()?;
+	assert_eq!(partial.missing().count(), 1);
+	if let Some((_, secret_key)) = parties.get(1) {
+		partial.sign(secret_key)?;
+	}
+	assert!(partial.is_complete());
+	Ok(())
+}
+
+#[test]
+fn multi_party_rejects_outsiders_and_bad_signatures() -> packet::Result {
+	let parties = parties(3)?;
+	let signers: Vec<_> = parties.iter().take(2).map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut partial: MultiParty = MultiParty::new(payload(b"transit proof")?, signers.to_owned())?;
+	if let Some((_, outsider)) = parties.get(2) {
+		assert!(partial.sign(outsider).is_err());
+	}
+	// a signature over the same content for another signer set does not count
+	// for this one
+	let mut reordered: MultiParty = MultiParty::new(payload(b"transit proof")?, signers.into_iter().rev().collect())?;
+	if let Some((public_key, secret_key)) = parties.first() {
+		reordered.sign(secret_key)?;
+		let complete: packet::multi_party::Complete<Payload, Ed25519Algorithm, Reservation> = {
+			let (_, other) = parties.get(1).ok_or("missing party")?;
+			reordered.sign(other)?;
+			reordered.complete().map_err(|_| "incomplete")?
+		};
+		let position: usize = complete.signers().iter().position(|signer| signer == public_key).ok_or("missing signer")?;
+		let signature = complete.signatures().get(position).ok_or("missing signature")?.to_owned();
+		assert!(partial.insert(public_key, signature).is_err());
+		assert_eq!(partial.missing().count(), 2);
+	}
+	Ok(())
+}
+
+#[test]
+fn multi_party_rejects_tampering() -> packet::Result {
+	let parties = parties(2)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut partial: MultiParty = MultiParty::new(payload(b"transit proof")?, signers)?;
+	for (_, secret_key) in &parties {
+		partial.sign(secret_key)?;
+	}
+	let encoded: lib_bytes::NonEmpty = partial.try_into()?;
+	let mut tampered: Vec<u8> = bytes_of(encoded).to_vec();
+	if let Some(byte) = tampered.last_mut() {
+		*byte ^= 1;
+	}
+	let tampered: bytes::Bytes = tampered.into();
+	let tampered: lib_bytes::NonEmpty = tampered.try_into()?;
+	let tampered: packet::Result<MultiParty> = tampered.try_into();
+	assert!(tampered.is_err());
+	Ok(())
+}
+
+#[test]
+fn multi_party_rejects_duplicate_signers() -> packet::Result {
+	let parties = parties(1)?;
+	let signers: Vec<_> = parties.iter().chain(&parties).map(|(public_key, _)| public_key.to_owned()).collect();
+	assert!(MultiParty::new(payload(b"transit proof")?, signers).is_err());
+	assert!(MultiParty::new(payload(b"transit proof")?, Vec::new()).is_err());
+	Ok(())
+}
+
+#[test]
+fn signer_count_beyond_input_is_malformed() -> packet::Result {
+	let parties = parties(2)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let multi: MultiParty = MultiParty::new(payload(b"transit proof")?, signers)?;
+	let multi: lib_bytes::NonEmpty = multi.try_into()?;
+	let multi: bytes::Bytes = multi.into();
+	let mut multi: Vec<u8> = multi.to_vec();
+	multi[packet::header::LEN..packet::header::LEN + 2].copy_from_slice(&u16::MAX.to_be_bytes());
+	let multi: lib_bytes::NonEmpty = bytes::Bytes::from(multi).try_into()?;
+	let multi: packet::Result<MultiParty> = multi.try_into();
+	assert_eq!(multi.err().map(|error| error.to_string()), Some("malformed multi party packet".to_owned()));
+	Ok(())
+}
+
+#[test]
+fn truncated_input_is_an_error_not_a_panic() -> packet::Result {
+	let single: bytes::Bytes = signed(packet::header::DEFAULT_TTL)?.into();
+	let parties = parties(2)?;
+	let signers: Vec<_> = parties.iter().map(|(public_key, _)| public_key.to_owned()).collect();
+	let mut multi: MultiParty = MultiParty::new(payload(b"transit proof")?, signers)?;
+	for (_, secret_key) in &parties {
+		multi.sign(secret_key)?;
+	}
+	let multi: lib_bytes::NonEmpty = multi.try_into()?;
+	let multi: bytes::Bytes = multi.into();
+	for len in 1..single.len() {
+		let truncated: lib_bytes::NonEmpty = single.slice(..len).try_into()?;
+		let truncated: packet::Result<packet::MarkedSignedUnverified<Payload, Ed25519Algorithm, Reservation>> = truncated.try_into();
+		assert!(truncated.is_err() || len > packet::header::LEN + 32 + 64);
+	}
+	for len in 1..multi.len() {
+		let truncated: lib_bytes::NonEmpty = multi.slice(..len).try_into()?;
+		let truncated: packet::Result<MultiParty> = truncated.try_into();
+		assert!(truncated.is_err());
+	}
+	Ok(())
+}
@@ -0,0 +1,247 @@
+use lib_cryptography::AsymmetricKeyAgreementAlgorithm;
+use lib_cryptography::AsymmetricKeyDerivationAlgorithm;
+use lib_cryptography::SymmetricAeadAlgorithm;
+use lib_cryptography::SymmetricSetLayout;
+use rand::RngCore as _;
+
+pub type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Mixed into every key derivation and every frame's associated data.
+pub const LABEL: &[u8] = b"an-session-v1";
+
+pub const COUNTER_LEN: usize = 8;
+
+pub const NONCE_LEN: usize = 32;
+
+const CLIENT_TO_SERVER: &[u8] = b"client-to-server";
+const CLIENT_NONCE_PREFIX: [u8; 4] = *b"c2s\0";
+const SERVER_NONCE_PREFIX: [u8; 4] = *b"s2c\0";
+const SERVER_TO_CLIENT: &[u8] = b"server-to-client";
+const CONFIRMATION: &[u8] = b"key-confirmation";
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("domain is longer than {} bytes", u16::MAX)]
+	DomainTooLong,
+
+	#[error("payload is empty")]
+	EmptyPayload,
+
+	#[error("frame is shorter than its counter")]
+	MalformedFrame,
+
+	#[error("frame counter {received} is not the expected {expected}")]
+	Replay {
+		expected: u64,
+		received: u64
+	},
+
+	#[error("frame counter exhausted, the session must be re-established")]
+	CounterExhausted,
+
+	#[error("frame nonce does not follow its counter")]
+	NonceMismatch,
+
+	#[error("accept is shorter than its nonce")]
+	MalformedAccept,
+
+	#[error("server did not confirm the session keys")]
+	Unconfirmed
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Copy)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub enum Role {
+	Client,
+	Server
+}
+
+/// End to end encrypted session between a client and a server.
+///
+/// ```text
+/// accept = nonce (32) || frame(CONFIRMATION)
+/// frame  = counter (8, big endian) || aead(payload, associated_data = LABEL || direction || counter)
+/// ```
+///
+/// The server's nonce salts the keys, so a replayed encapsulation never lands
+/// on an earlier session's keys. Counters must arrive strictly in sequence,
+/// so a relay cannot replay, reorder or drop a frame unnoticed.
+#[derive(Debug)]
+pub struct Session<S> {
+	role: Role,
+	sealing_key: lib_cryptography::key::Key<S>,
+	opening_key: lib_cryptography::key::Key<S>,
+	sealed: u64,
+	sealing_nonce: lib_cryptography::nonce::Sequence,
+	opened: u64
+}
+
+impl<S> Session<S>
+where
+	S: SymmetricAeadAlgorithm,
+	S: SymmetricSetLayout {
+	/// The session only exists once [`Pending::confirm`] accepted the
+	/// server's answer.
+	pub fn initiate<A>(public_key: &lib_cryptography::public_key::PublicKey<A>, domain: &str) -> Result<(lib_cryptography::encapsulated::Encapsulated<A>, Pending<S>)>
+	where
+		A: AsymmetricKeyAgreementAlgorithm {
+		let info: Vec<u8> = info(public_key, domain)?;
+		let (encapsulated, master) = A::initiate::<S>(public_key, &info)?;
+		let salt: lib_bytes::NonEmpty = (*encapsulated).to_owned();
+		let out: Pending<S> = Pending {
+			master,
+			salt: salt.into()
+		};
+		Ok((encapsulated, out))
+	}
+
+	pub fn respond<A>(secret_key: &lib_cryptography::secret_key::SecretKey<A>, domain: &str, encapsulated: &lib_cryptography::encapsulated::Encapsulated<A>) -> Result<(bytes::Bytes, Self)>
+	where
+		A: AsymmetricKeyAgreementAlgorithm,
+		A: AsymmetricKeyDerivationAlgorithm {
+		let public_key: lib_cryptography::public_key::PublicKey<A> = A::public_key(secret_key)?;
+		let info: Vec<u8> = info(&public_key, domain)?;
+		let master: lib_cryptography::key::Key<S> = A::respond::<S>(secret_key, encapsulated, &info)?;
+		let mut nonce: [u8; NONCE_LEN] = [0; NONCE_LEN];
+		rand::rngs::OsRng.try_fill_bytes(&mut nonce)?;
+		let encapsulated: lib_bytes::NonEmpty = (**encapsulated).to_owned();
+		let encapsulated: bytes::Bytes = encapsulated.into();
+		let mut out: Self = Self::from_master(Role::Server, &master, &[&encapsulated, &nonce[..]].concat())?;
+		let confirmation: bytes::Bytes = out.seal(CONFIRMATION)?;
+		let accept: bytes::Bytes = [&nonce[..], &confirmation].concat().into();
+		Ok((accept, out))
+	}
+
+	pub const fn role(&self) -> Role {
+		self.role
+	}
+
+	pub fn seal(&mut self, payload: &[u8]) -> Result<bytes::Bytes> {
+		let counter: u64 = self.sealed;
+		let next: u64 = counter.checked_add(1).ok_or(Error::CounterExhausted)?;
+		let message: bytes::Bytes = bytes::Bytes::copy_from_slice(payload);
+		let message: lib_bytes::NonEmpty = message.try_into().map_err(|_| Error::EmptyPayload)?;
+		let message: lib_cryptography::message::Message = message.into();
+		let nonce: [u8; 12] = self.sealing_nonce.advance().map_err(|_| Error::CounterExhausted)?;
+		let associated_data: Vec<u8> = associated_data(self.role, counter);
+		let sealed: lib_cryptography::encrypted::Encrypted<S> = self.sealing_key.encrypt_with_nonce(&nonce, &message, &associated_data)?;
+		let sealed: lib_bytes::NonEmpty = sealed.into();
+		let sealed: bytes::Bytes = sealed.into();
+		let mut out: Vec<u8> = Vec::with_capacity(COUNTER_LEN + sealed.len());
+		out.extend_from_slice(&counter.to_be_bytes());
+		out.extend_from_slice(&sealed);
+		self.sealed = next;
+		Ok(out.into())
+	}
+
+	/// A frame that fails to authenticate leaves the expected counter
+	/// untouched.
+	pub fn open(&mut self, frame: &[u8]) -> Result<bytes::Bytes> {
+		let (counter, sealed) = frame.split_first_chunk::<COUNTER_LEN>().ok_or(Error::MalformedFrame)?;
+		let counter: u64 = u64::from_be_bytes(*counter);
+		if counter != self.opened {
+			return Err(Error::Replay {
+				expected: self.opened,
+				received: counter
+			}.into())
+		}
+		let next: u64 = counter.checked_add(1).ok_or(Error::CounterExhausted)?;
+		let peer: Role = match self.role {
+			Role::Client => Role::Server,
+			Role::Server => Role::Client
+		};
+		if sealed.get(..12) != Some(nonce_of(peer, counter).as_slice()) {
+			return Err(Error::NonceMismatch.into())
+		}
+		let sealed: bytes::Bytes = bytes::Bytes::copy_from_slice(sealed);
+		let sealed: lib_bytes::NonEmpty = sealed.try_into().map_err(|_| Error::MalformedFrame)?;
+		let sealed: lib_cryptography::encrypted::Encrypted<S> = sealed.into();
+		let associated_data: Vec<u8> = associated_data(peer, counter);
+		let out: lib_cryptography::message::Message = self.opening_key.decrypt_with_associated_data(&sealed, &associated_data)?;
+		let out: lib_bytes::NonEmpty = out.into();
+		self.opened = next;
+		Ok(out.into())
+	}
+
+	/// `salt` is the encapsulation followed by the server's nonce.
+	fn from_master(role: Role, master: &lib_cryptography::key::Key<S>, salt: &[u8]) -> Result<Self> {
+		let client_to_server: lib_cryptography::key::Key<S> = lib_cryptography::kdf::derive(master.expose_secret(), salt, &[LABEL, CLIENT_TO_SERVER].concat())?;
+		let server_to_client: lib_cryptography::key::Key<S> = lib_cryptography::kdf::derive(master.expose_secret(), salt, &[LABEL, SERVER_TO_CLIENT].concat())?;
+		let (sealing_key, opening_key) = match role {
+			Role::Client => (client_to_server, server_to_client),
+			Role::Server => (server_to_client, client_to_server)
+		};
+		Ok(Self {
+			role,
+			sealing_key,
+			opening_key,
+			sealed: 0,
+			sealing_nonce: lib_cryptography::nonce::Sequence::new(nonce_prefix(role)),
+			opened: 0
+		})
+	}
+}
+
+#[derive(Debug)]
+pub struct Pending<S> {
+	master: lib_cryptography::key::Key<S>,
+	salt: bytes::Bytes
+}
+
+impl<S> Pending<S>
+where
+	S: SymmetricAeadAlgorithm,
+	S: SymmetricSetLayout {
+	/// Fails unless the accept was sealed under the keys this client
+	/// derived, so no payload goes to a server that cannot read it.
+	pub fn confirm(self, accept: &[u8]) -> Result<Session<S>> {
+		let (nonce, confirmation) = accept.split_first_chunk::<NONCE_LEN>().ok_or(Error::MalformedAccept)?;
+		let mut out: Session<S> = Session::from_master(Role::Client, &self.master, &[&self.salt, &nonce[..]].concat())?;
+		let confirmed: bool = out.open(confirmation).is_ok_and(|payload| payload.as_ref() == CONFIRMATION);
+		if !confirmed {
+			return Err(Error::Unconfirmed.into())
+		}
+		Ok(out)
+	}
+}
+
+/// `LABEL || domain_len (2, big endian) || domain || public_key`, the length
+/// prefix keeps a domain from running into the key bytes.
+fn info<A>(public_key: &lib_cryptography::public_key::PublicKey<A>, domain: &str) -> Result<Vec<u8>> {
+	let domain_len: u16 = domain.len().try_into().map_err(|_| Error::DomainTooLong)?;
+	let public_key: lib_bytes::NonEmpty = (**public_key).to_owned();
+	let public_key: bytes::Bytes = public_key.into();
+	let mut out: Vec<u8> = Vec::with_capacity(LABEL.len() + 2 + domain.len() + public_key.len());
+	out.extend_from_slice(LABEL);
+	out.extend_from_slice(&domain_len.to_be_bytes());
+	out.extend_from_slice(domain.as_bytes());
+	out.extend_from_slice(&public_key);
+	Ok(out)
+}
+
+fn nonce_prefix(sender: Role) -> [u8; 4] {
+	match sender {
+		Role::Client => CLIENT_NONCE_PREFIX,
+		Role::Server => SERVER_NONCE_PREFIX
+	}
+}
+
+fn nonce_of(sender: Role, counter: u64) -> [u8; 12] {
+	let mut out: [u8; 12] = [0; 12];
+	let (prefix, rest) = out.split_at_mut(4);
+	prefix.copy_from_slice(&nonce_prefix(sender));
+	rest.copy_from_slice(&counter.to_be_bytes());
+	out
+}
+
+fn associated_data(sender: Role, counter: u64) -> Vec<u8> {
+	let direction: &[u8] = match sender {
+		Role::Client => CLIENT_TO_SERVER,
+		Role::Server => SERVER_TO_CLIENT
+	};
+	[LABEL, direction, &counter.to_be_bytes()].concat()
+}
@@ -0,0 +1,161 @@
+use cryptography_algorithm_chacha20poly1305::ChaCha20Poly1305Algorithm;
+use cryptography_algorithm_x25519_kyber1024::X25519Kyber1024Algorithm;
+use cryptography_algorithm_x25519::X25519Algorithm;
+
+type Session = session::Session<ChaCha20Poly1305Algorithm>;
+
+const DOMAIN: &str = "example.an";
+
+fn pair<A>() -> session::Result<(lib_cryptography::public_key::PublicKey<A>, lib_cryptography::secret_key::SecretKey<A>)>
+where
+	A: lib_cryptography::AsymmetricKeyGenAlgorithm {
+	let pair: lib_cryptography::pair::Pair<A> = A::generate()?;
+	Ok(pair.into())
+}
+
+fn sessions() -> session::Result<(Session, Session)> {
+	let (public_key, secret_key) = pair::<X25519Kyber1024Algorithm>()?;
+	let (encapsulated, pending) = Session::initiate(&public_key, DOMAIN)?;
+	let (accept, server) = Session::respond(&secret_key, DOMAIN, &encapsulated)?;
+	let client: Session = pending.confirm(&accept)?;
+	Ok((client, server))
+}
+
+#[test]
+fn round_trip_in_both_directions() -> session::Result {
+	let (mut client, mut server) = sessions()?;
+	for i in 0..4_u8 {
+		let frame: bytes::Bytes = client.seal(&[i; 32])?;
+		assert_eq!(server.open(&frame)?.as_ref(), &[i; 32]);
+		let frame: bytes::Bytes = server.seal(&[i; 16])?;
+		assert_eq!(client.open(&frame)?.as_ref(), &[i; 16]);
+	}
+	Ok(())
+}
+
+#[test]
+fn relay_sees_only_ciphertext() -> session::Result {
+	let (mut client, _) = sessions()?;
+	let payload: &[u8] = b"GET /index.html HTTP/1.1";
+	let frame: bytes::Bytes = client.seal(payload)?;
+	assert!(!frame.windows(payload.len()).any(|window| window == payload));
+	Ok(())
+}
+
+#[test]
+fn tampered_frame_is_rejected_without_desync() -> session::Result {
+	let (mut client, mut server) = sessions()?;
+	let frame: bytes::Bytes = client.seal(b"hello")?;
+	let mut tampered: Vec<u8> = frame.to_vec();
+	if let Some(last) = tampered.last_mut() {
+		*last ^= 1;
+	}
+	assert!(server.open(&tampered).is_err());
+	assert_eq!(server.open(&frame)?.as_ref(), b"hello");
+	Ok(())
+}
+
+#[test]
+fn nonces_follow_the_frame_counter() -> session::Result {
+	let (mut client, mut server) = sessions()?;
+	for counter in 0..3_u64 {
+		let frame: bytes::Bytes = client.seal(b"hello")?;
+		let nonce: &[u8] = frame.get(session::COUNTER_LEN..session::COUNTER_LEN + 12).ok_or("frame too short")?;
+		assert_eq!(nonce.get(4..), Some(counter.to_be_bytes().as_slice()));
+		server.open(&frame)?;
+	}
+	let frame: bytes::Bytes = client.seal(b"hello")?;
+	let mut shifted: Vec<u8> = frame.to_vec();
+	if let Some(byte) = shifted.get_mut(session::COUNTER_LEN + 11) {
+		*byte ^= 1;
+	}
+	assert!(server.open(&shifted).is_err());
+	assert_eq!(server.open(&frame)?.as_ref(), b"hello");
+	Ok(())
+}
+
+#[test]
+fn replayed_frame_is_rejected() -> session::Result {
+	let (mut client, mut server) = sessions()?;
+	let frame: bytes::Bytes = client.seal(b"pay 10")?;
+	server.open(&frame)?;
+	assert!(server.open(&frame).is_err());
+	Ok(())
+}
+
+#[test]
+fn reordered_frame_is_rejected() -> session::Result {
+	let (mut client, mut server) = sessions()?;
+	let _first: bytes::Bytes = client.seal(b"first")?;
+	let second: bytes::Bytes = client.seal(b"second")?;
+	assert!(server.open(&second).is_err());
+	Ok(())
+}
+
+#[test]
+fn reflected_frame_is_rejected() -> session::Result {
+	let (mut client, _) = sessions()?;
+	let frame: bytes::Bytes = client.seal(b"hello")?;
+	assert!(client.open(&frame).is_err());
+	Ok(())
+}
+
+#[test]
+fn wrong_domain_is_unconfirmed() -> session::Result {
+	let (public_key, secret_key) = pair::<X25519Kyber1024Algorithm>()?;
+	let (encapsulated, pending) = Session::initiate(&public_key, DOMAIN)?;
+	let (accept, _) = Session::respond(&secret_key, "other.an", &encapsulated)?;
+	assert!(pending.confirm(&accept).is_err());
+	Ok(())
+}
+
+#[test]
+fn wrong_server_key_is_unconfirmed() -> session::Result {
+	let (public_key, _) = pair::<X25519Algorithm>()?;
+	let (_, impostor) = pair::<X25519Algorithm>()?;
+	let (encapsulated, pending) = Session::initiate(&public_key, DOMAIN)?;
+	let (accept, _) = Session::respond(&impostor, DOMAIN, &encapsulated)?;
+	assert_eq!(pending.confirm(&accept).map(drop).map_err(|error| error.to_string()), Err("server did not confirm the session keys".to_owned()));
+	Ok(())
+}
+
+#[test]
+fn tampered_accept_is_unconfirmed() -> session::Result {
+	let (public_key, secret_key) = pair::<X25519Kyber1024Algorithm>()?;
+	let (encapsulated, pending) = Session::initiate(&public_key, DOMAIN)?;
+	let (accept, _) = Session::respond(&secret_key, DOMAIN, &encapsulated)?;
+	let mut tampered: Vec<u8> = accept.to_vec();
+	if let Some(first) = tampered.first_mut() {
+		*first ^= 1;
+	}
+	assert!(pending.confirm(&tampered).is_err());
+	Ok(())
+}
+
+#[test]
+fn truncated_accept_is_malformed() -> session::Result {
+	let (public_key, _) = pair::<X25519Kyber1024Algorithm>()?;
+	let (_, pending) = Session::initiate(&public_key, DOMAIN)?;
+	assert_eq!(pending.confirm(&[0; session::NONCE_LEN - 1]).map(drop).map_err(|error| error.to_string()), Err("accept is shorter than its nonce".to_owned()));
+	Ok(())
+}
+
+#[test]
+fn replayed_encapsulation_gets_fresh_keys() -> session::Result {
+	let (public_key, secret_key) = pair::<X25519Kyber1024Algorithm>()?;
+	let (encapsulated, pending) = Session::initiate(&public_key, DOMAIN)?;
+	let (accept, _) = Session::respond(&secret_key, DOMAIN, &encapsulated)?;
+	let mut client: Session = pending.confirm(&accept)?;
+	let recorded: bytes::Bytes = client.seal(b"pay 10")?;
+	let (replayed_accept, mut replayed) = Session::respond(&secret_key, DOMAIN, &encapsulated)?;
+	assert_ne!(accept, replayed_accept);
+	assert!(replayed.open(&recorded).is_err());
+	Ok(())
+}
+
+#[test]
+fn empty_payload_is_rejected() -> session::Result {
+	let (mut client, _) = sessions()?;
+	assert!(client.seal(b"").is_err());
+	Ok(())
+}
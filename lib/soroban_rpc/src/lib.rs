@@ -0,0 +1,467 @@
+use stellar_xdr::curr::ReadXdr as _;
+use stellar_xdr::curr::WriteXdr as _;
+
+pub use stellar_xdr::curr as xdr;
+
+pub const TESTNET_PASSPHRASE: &str = "Test SDF Network ; September 2015";
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+#[derive(thiserror::Error)]
+pub enum Error {
+	#[error("transport failed: {0}")]
+	Transport(#[from] reqwest::Error),
+
+	#[error("rpc error {code}: {message}")]
+	Rpc {
+		code: i64,
+		message: String
+	},
+
+	#[error("xdr: {0}")]
+	Xdr(#[from] xdr::Error),
+
+	#[error("account {0} not found")]
+	UnknownAccount(String),
+
+	#[error("simulation failed: {0}")]
+	Simulation(String),
+
+	#[error("transaction {hash} rejected with status {status}")]
+	Rejected {
+		hash: String,
+		status: String
+	},
+
+	#[error("transaction {0} failed")]
+	Failed(String),
+
+	#[error("transaction {0} not settled in time")]
+	Timeout(String),
+
+	#[error("malformed response: {0}")]
+	Malformed(&'static str)
+}
+
+/// Contract event, from a settled transaction or [`Client::events`].
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Event {
+	pub contract: Option<xdr::ContractId>,
+	pub topics: Vec<xdr::ScVal>,
+	pub data: xdr::ScVal
+}
+
+/// What a settled [`Client::invoke`] left on the ledger.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+#[derive(Eq)]
+pub struct Outcome {
+	pub hash: String,
+	pub ledger: Option<u32>,
+	pub value: xdr::ScVal,
+	pub events: Vec<Event>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct Response<T> {
+	result: Option<T>,
+	error: Option<RpcError>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct RpcError {
+	code: i64,
+	message: String
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct LedgerEntries {
+	#[serde(default)]
+	entries: Vec<LedgerEntry>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct LedgerEntry {
+	xdr: String
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Simulation {
+	error: Option<String>,
+	transaction_data: Option<String>,
+	min_resource_fee: Option<String>,
+	#[serde(default)]
+	results: Vec<SimulationResult>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct SimulationResult {
+	#[serde(default)]
+	auth: Vec<String>,
+	xdr: String
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct Sent {
+	status: String,
+	hash: String
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Settled {
+	status: String,
+	ledger: Option<u32>,
+	result_meta_xdr: Option<String>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+struct Events {
+	#[serde(default)]
+	events: Vec<RawEvent>
+}
+
+#[derive(Debug)]
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawEvent {
+	contract_id: Option<String>,
+	#[serde(default)]
+	topic: Vec<String>,
+	value: String
+}
+
+/// Soroban JSON-RPC client invoking contracts as one account.
+///
+/// [`Client::invoke`] walks the full lifecycle: it reads the account
+/// sequence, simulates the call for its footprint, resource fee and auth,
+/// signs the assembled transaction, submits it and polls until it settles.
+/// Contract `require_auth` on the invoking account is covered by the
+/// envelope signature, so auth entries are passed through as simulated.
+/// Clones invoke one at a time, as each transaction takes the sequence the
+/// last one used up. [`Client::simulate`] stops after simulation, for read
+/// only calls.
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Client {
+	http: reqwest::Client,
+	endpoint: String,
+	network_id: [u8; 32],
+	signing_key: ed25519_dalek::SigningKey,
+	base_fee: u32,
+	poll_interval: std::time::Duration,
+	poll_attempts: u32,
+	invocations: std::sync::Arc<tokio::sync::Mutex<()>>
+}
+
+#[bon::bon]
+impl Client {
+	#[builder]
+	pub fn new(
+		endpoint: String,
+		network_passphrase: Option<String>,
+		secret_key: [u8; 32],
+		base_fee: Option<u32>,
+		poll_interval: Option<std::time::Duration>,
+		poll_attempts: Option<u32>
+	) -> Self {
+		let network_passphrase: String = network_passphrase.unwrap_or_else(|| String::from(TESTNET_PASSPHRASE));
+		let network_id: [u8; 32] = <sha2::Sha256 as sha2::Digest>::digest(network_passphrase.as_bytes()).into();
+		Self {
+			http: reqwest::Client::new(),
+			endpoint,
+			network_id,
+			signing_key: ed25519_dalek::SigningKey::from_bytes(&secret_key),
+			base_fee: base_fee.unwrap_or(100),
+			poll_interval: poll_interval.unwrap_or(std::time::Duration::from_secs(1)),
+			poll_attempts: poll_attempts.unwrap_or(30),
+			invocations: std::sync::Arc::new(tokio::sync::Mutex::new(()))
+		}
+	}
+}
+
+impl Client {
+	pub fn account(&self) -> xdr::AccountId {
+		let key: [u8; 32] = self.signing_key.verifying_key().to_bytes();
+		xdr::AccountId(xdr::PublicKey::PublicKeyTypeEd25519(xdr::Uint256(key)))
+	}
+
+	pub fn address(&self) -> xdr::ScAddress {
+		xdr::ScAddress::Account(self.account())
+	}
+
+	pub async fn sequence(&self) -> Result<i64> {
+		let key: xdr::LedgerKey = xdr::LedgerKey::Account(xdr::LedgerKeyAccount {
+			account_id: self.account()
+		});
+		let params: serde_json::Value = serde_json::json!({
+			"keys": [key.to_xdr_base64(xdr::Limits::none())?]
+		});
+		let LedgerEntries {
+			entries
+		} = self.request("getLedgerEntries", params).await?;
+		let entry: LedgerEntry = entries.into_iter().next().ok_or_else(|| Error::UnknownAccount(self.account().to_string()))?;
+		let xdr::LedgerEntryData::Account(account) = xdr::LedgerEntryData::from_xdr_base64(entry.xdr, xdr::Limits::none())? else {
+			return Err(Error::Malformed("ledger entry is not an account"))
+		};
+		let xdr::SequenceNumber(sequence) = account.seq_num;
+		Ok(sequence)
+	}
+
+	/// Value `function` would return, without submitting anything.
+	pub async fn simulate(&self, contract: &xdr::ContractId, function: &str, args: Vec<xdr::ScVal>) -> Result<xdr::ScVal> {
+		// sequence is not checked in simulation
+		let transaction: xdr::Transaction = self.transaction(0, contract, function, args)?;
+		let (_, _, _, value) = self.simulation(&transaction).await?;
+		Ok(value)
+	}
+
+	pub async fn invoke(&self, contract: &xdr::ContractId, function: &str, args: Vec<xdr::ScVal>) -> Result<Outcome> {
+		let _invocation: tokio::sync::MutexGuard<()> = self.invocations.lock().await;
+		let sequence: i64 = self.sequence().await?;
+		let mut transaction: xdr::Transaction = self.transaction(sequence.saturating_add(1), contract, function, args)?;
+		let (data, fee, auth, _) = self.simulation(&transaction).await?;
+		transaction.ext = xdr::TransactionExt::V1(data);
+		transaction.fee = transaction.fee.saturating_add(fee);
+		if let Some(xdr::Operation {
+			body: xdr::OperationBody::InvokeHostFunction(operation),
+			..
+		}) = transaction.operations.first().cloned() {
+			let operation: xdr::InvokeHostFunctionOp = xdr::InvokeHostFunctionOp {
+				auth: auth.try_into()?,
+				..operation
+			};
+			transaction.operations = vec![xdr::Operation {
+				source_account: None,
+				body: xdr::OperationBody::InvokeHostFunction(operation)
+			}].try_into()?;
+		}
+		let envelope: xdr::TransactionEnvelope = self.sign(transaction)?;
+		let hash: String = self.send(&envelope).await?;
+		self.settle(hash).await
+	}
+
+	/// Events `contract` emitted from `start_ledger` on.
+	pub async fn events(&self, contract: &xdr::ContractId, start_ledger: u32) -> Result<Vec<Event>> {
+		let params: serde_json::Value = serde_json::json!({
+			"startLedger": start_ledger,
+			"filters": [{
+				"type": "contract",
+				"contractIds": [contract.to_string()]
+			}]
+		});
+		let Events {
+			events
+		} = self.request("getEvents", params).await?;
+		events
+			.into_iter()
+			.map(|event| {
+				let contract: Option<xdr::ContractId> = event.contract_id
+					.map(|contract| contract.parse())
+					.transpose()?;
+				let topics: Vec<xdr::ScVal> = event.topic
+					.into_iter()
+					.map(|topic| xdr::ScVal::from_xdr_base64(topic, xdr::Limits::none()))
+					.collect::<std::result::Result<_, _>>()?;
+				let data: xdr::ScVal = xdr::ScVal::from_xdr_base64(event.value, xdr::Limits::none())?;
+				Ok(Event {
+					contract,
+					topics,
+					data
+				})
+			})
+			.collect()
+	}
+
+	async fn request<T>(&self, method: &str, params: serde_json::Value) -> Result<T>
+	where
+		T: serde::de::DeserializeOwned {
+		let body: serde_json::Value = serde_json::json!({
+			"jsonrpc": "2.0",
+			"id": 1,
+			"method": method,
+			"params": params
+		});
+		let response: Response<T> = self.http
+			.post(&self.endpoint)
+			.json(&body)
+			.send()
+			.await?
+			.error_for_status()?
+			.json()
+			.await?;
+		if let Some(RpcError {
+			code,
+			message
+		}) = response.error {
+			return Err(Error::Rpc {
+				code,
+				message
+			})
+		}
+		response.result.ok_or(Error::Malformed("response carries neither result nor error"))
+	}
+
+	fn transaction(&self, sequence: i64, contract: &xdr::ContractId, function: &str, args: Vec<xdr::ScVal>) -> Result<xdr::Transaction> {
+		let function: xdr::StringM<32> = function.try_into()?;
+		let operation: xdr::InvokeHostFunctionOp = xdr::InvokeHostFunctionOp {
+			host_function: xdr::HostFunction::InvokeContract(xdr::InvokeContractArgs {
+				contract_address: xdr::ScAddress::Contract(contract.to_owned()),
+				function_name: xdr::ScSymbol(function),
+				args: args.try_into()?
+			}),
+			auth: xdr::VecM::default()
+		};
+		Ok(xdr::Transaction {
+			source_account: self.account().into(),
+			fee: self.base_fee,
+			seq_num: xdr::SequenceNumber(sequence),
+			cond: xdr::Preconditions::None,
+			memo: xdr::Memo::None,
+			operations: vec![xdr::Operation {
+				source_account: None,
+				body: xdr::OperationBody::InvokeHostFunction(operation)
+			}].try_into()?,
+			ext: xdr::TransactionExt::V0
+		})
+	}
+
+	/// Resource data, resource fee and auth entries of `transaction`, and
+	/// the value its call returned.
+	async fn simulation(&self, transaction: &xdr::Transaction) -> Result<(xdr::SorobanTransactionData, u32, Vec<xdr::SorobanAuthorizationEntry>, xdr::ScVal)> {
+		let envelope: xdr::TransactionEnvelope = xdr::TransactionEnvelope::Tx(xdr::TransactionV1Envelope {
+			tx: transaction.to_owned(),
+			signatures: xdr::VecM::default()
+		});
+		let params: serde_json::Value = serde_json::json!({
+			"transaction": envelope.to_xdr_base64(xdr::Limits::none())?
+		});
+		let simulation: Simulation = self.request("simulateTransaction", params).await?;
+		if let Some(error) = simulation.error {
+			return Err(Error::Simulation(error))
+		}
+		let data: String = simulation.transaction_data.ok_or(Error::Malformed("simulation without transaction data"))?;
+		let data: xdr::SorobanTransactionData = xdr::SorobanTransactionData::from_xdr_base64(data, xdr::Limits::none())?;
+		let fee: u32 = simulation.min_resource_fee
+			.as_deref()
+			.unwrap_or("0")
+			.parse()
+			.map_err(|_| Error::Malformed("resource fee is not a number"))?;
+		let result: SimulationResult = simulation.results.into_iter().next().ok_or(Error::Malformed("simulation without result"))?;
+		let auth: Vec<xdr::SorobanAuthorizationEntry> = result.auth
+			.into_iter()
+			.map(|entry| xdr::SorobanAuthorizationEntry::from_xdr_base64(entry, xdr::Limits::none()))
+			.collect::<std::result::Result<_, _>>()?;
+		let value: xdr::ScVal = xdr::ScVal::from_xdr_base64(result.xdr, xdr::Limits::none())?;
+		Ok((data, fee, auth, value))
+	}
+
+	fn sign(&self, transaction: xdr::Transaction) -> Result<xdr::TransactionEnvelope> {
+		let mut envelope: xdr::TransactionV1Envelope = xdr::TransactionV1Envelope {
+			tx: transaction,
+			signatures: xdr::VecM::default()
+		};
+		let hash: [u8; 32] = envelope.hash(self.network_id)?;
+		let signature: ed25519_dalek::Signature = ed25519_dalek::Signer::sign(&self.signing_key, &hash);
+		let key: [u8; 32] = self.signing_key.verifying_key().to_bytes();
+		let hint: [u8; 4] = [key[28], key[29], key[30], key[31]];
+		let signature: xdr::DecoratedSignature = xdr::DecoratedSignature {
+			hint: xdr::SignatureHint(hint),
+			signature: xdr::Signature(signature.to_bytes().try_into()?)
+		};
+		envelope.signatures = vec![signature].try_into()?;
+		Ok(xdr::TransactionEnvelope::Tx(envelope))
+	}
+
+	async fn send(&self, envelope: &xdr::TransactionEnvelope) -> Result<String> {
+		let params: serde_json::Value = serde_json::json!({
+			"transaction": envelope.to_xdr_base64(xdr::Limits::none())?
+		});
+		let Sent {
+			status,
+			hash
+		} = self.request("sendTransaction", params).await?;
+		match status.as_str() {
+			"PENDING" | "DUPLICATE" => Ok(hash),
+			_ => Err(Error::Rejected {
+				hash,
+				status
+			})
+		}
+	}
+
+	async fn settle(&self, hash: String) -> Result<Outcome> {
+		for _ in 0..self.poll_attempts {
+			let params: serde_json::Value = serde_json::json!({
+				"hash": hash
+			});
+			let Settled {
+				status,
+				ledger,
+				result_meta_xdr
+			} = self.request("getTransaction", params).await?;
+			match status.as_str() {
+				"SUCCESS" => {
+					let meta: String = result_meta_xdr.ok_or(Error::Malformed("settled transaction without meta"))?;
+					let meta: xdr::TransactionMeta = xdr::TransactionMeta::from_xdr_base64(meta, xdr::Limits::none())?;
+					let (value, events) = outcome_of(meta);
+					return Ok(Outcome {
+						hash,
+						ledger,
+						value,
+						events
+					})
+				},
+				"FAILED" => return Err(Error::Failed(hash)),
+				_ => tokio::time::sleep(self.poll_interval).await
+			}
+		}
+		Err(Error::Timeout(hash))
+	}
+}
+
+fn outcome_of(meta: xdr::TransactionMeta) -> (xdr::ScVal, Vec<Event>) {
+	let (value, events): (xdr::ScVal, Vec<xdr::ContractEvent>) = match meta {
+		xdr::TransactionMeta::V3(meta) => meta.soroban_meta
+			.map(|meta| (meta.return_value, meta.events.to_vec()))
+			.unwrap_or((xdr::ScVal::Void, Vec::new())),
+		xdr::TransactionMeta::V4(meta) => {
+			let value: xdr::ScVal = meta.soroban_meta
+				.and_then(|meta| meta.return_value)
+				.unwrap_or(xdr::ScVal::Void);
+			let events: Vec<xdr::ContractEvent> = meta.operations
+				.iter()
+				.flat_map(|operation| operation.events.iter().cloned())
+				.collect();
+			(value, events)
+		},
+		_ => (xdr::ScVal::Void, Vec::new())
+	};
+	let events: Vec<Event> = events
+		.into_iter()
+		.map(|event| {
+			let xdr::ContractEventBody::V0(body) = event.body;
+			Event {
+				contract: event.contract_id,
+				topics: body.topics.to_vec(),
+				data: body.data
+			}
+		})
+		.collect();
+	(value, events)
+}
@@ -0,0 +1,264 @@
+use soroban_rpc::xdr;
+use soroban_rpc::xdr::ReadXdr as _;
+
+const SECRET_KEY: [u8; 32] = [7; 32];
+
+/// JSON-RPC server answering each method from a queue of canned results and
+/// keeping every request it saw.
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(Default)]
+struct Stub {
+	results: std::sync::Arc<std::sync::Mutex<std::collections::HashMap<String, std::collections::VecDeque<serde_json::Value>>>>,
+	requests: std::sync::Arc<std::sync::Mutex<Vec<serde_json::Value>>>
+}
+
+impl Stub {
+	fn answer(&self, method: &str, result: serde_json::Value) -> &Self {
+		self.results
+			.lock()
+			.unwrap()
+			.entry(String::from(method))
+			.or_default()
+			.push_back(result);
+		self
+	}
+
+	fn params_of(&self, method: &str) -> Vec<serde_json::Value> {
+		self.requests
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|request| request["method"] == method)
+			.map(|request| request["params"].to_owned())
+			.collect()
+	}
+
+	async fn serve(&self) -> soroban_rpc::Client {
+		let stub: Self = self.to_owned();
+		let app: axum::Router = axum::Router::new().route("/", axum::routing::post(move |axum::Json(request): axum::Json<serde_json::Value>| {
+			let stub: Stub = stub.to_owned();
+			async move {
+				let method: String = request["method"].as_str().unwrap_or_default().to_owned();
+				stub.requests.lock().unwrap().push(request);
+				let result: Option<serde_json::Value> = stub.results
+					.lock()
+					.unwrap()
+					.get_mut(&method)
+					.and_then(std::collections::VecDeque::pop_front);
+				axum::Json(match result {
+					Some(result) => serde_json::json!({ "jsonrpc": "2.0", "id": 1, "result": result }),
+					None => serde_json::json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": method } })
+				})
+			}
+		}));
+		let listener: tokio::net::TcpListener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address: std::net::SocketAddr = listener.local_addr().unwrap();
+		tokio::spawn(async move {
+			axum::serve(listener, app).await.unwrap();
+		});
+		soroban_rpc::Client::builder()
+			.endpoint(format!("http://{}", address))
+			.secret_key(SECRET_KEY)
+			.poll_interval(std::time::Duration::from_millis(1))
+			.poll_attempts(3)
+			.build()
+	}
+}
+
+fn contract() -> xdr::ContractId {
+	xdr::ContractId(xdr::Hash([9; 32]))
+}
+
+fn base64<T>(value: &T) -> String
+where
+	T: xdr::WriteXdr {
+	value.to_xdr_base64(xdr::Limits::none()).unwrap()
+}
+
+fn account_entry(account: xdr::AccountId, sequence: i64) -> serde_json::Value {
+	let entry: xdr::LedgerEntryData = xdr::LedgerEntryData::Account(xdr::AccountEntry {
+		account_id: account,
+		balance: 0,
+		seq_num: xdr::SequenceNumber(sequence),
+		num_sub_entries: 0,
+		inflation_dest: None,
+		flags: 0,
+		home_domain: xdr::String32::default(),
+		thresholds: xdr::Thresholds([1, 0, 0, 0]),
+		signers: xdr::VecM::default(),
+		ext: xdr::AccountEntryExt::V0
+	});
+	serde_json::json!({ "entries": [{ "xdr": base64(&entry) }], "latestLedger": 10 })
+}
+
+fn simulation(value: &xdr::ScVal) -> serde_json::Value {
+	serde_json::json!({
+		"transactionData": base64(&xdr::SorobanTransactionData::default()),
+		"minResourceFee": "500",
+		"results": [{ "auth": [], "xdr": base64(value) }],
+		"latestLedger": 10
+	})
+}
+
+fn event(topic: &str, data: xdr::ScVal) -> xdr::ContractEvent {
+	let topic: xdr::ScSymbol = xdr::ScSymbol(topic.try_into().unwrap());
+	xdr::ContractEvent {
+		ext: xdr::ExtensionPoint::V0,
+		contract_id: Some(contract()),
+		type_: xdr::ContractEventType::Contract,
+		body: xdr::ContractEventBody::V0(xdr::ContractEventV0 {
+			topics: vec![xdr::ScVal::Symbol(topic)].try_into().unwrap(),
+			data
+		})
+	}
+}
+
+fn settled(value: xdr::ScVal, events: Vec<xdr::ContractEvent>) -> serde_json::Value {
+	let meta: xdr::TransactionMeta = xdr::TransactionMeta::V3(xdr::TransactionMetaV3 {
+		ext: xdr::ExtensionPoint::V0,
+		tx_changes_before: xdr::LedgerEntryChanges::default(),
+		operations: xdr::VecM::default(),
+		tx_changes_after: xdr::LedgerEntryChanges::default(),
+		soroban_meta: Some(xdr::SorobanTransactionMeta {
+			ext: xdr::SorobanTransactionMetaExt::V0,
+			events: events.try_into().unwrap(),
+			return_value: value,
+			diagnostic_events: xdr::VecM::default()
+		})
+	});
+	serde_json::json!({ "status": "SUCCESS", "ledger": 12, "resultMetaXdr": base64(&meta) })
+}
+
+fn submitted(stub: &Stub) -> xdr::TransactionV1Envelope {
+	let params: Vec<serde_json::Value> = stub.params_of("sendTransaction");
+	let envelope: &str = params[0]["transaction"].as_str().unwrap();
+	let xdr::TransactionEnvelope::Tx(envelope) = xdr::TransactionEnvelope::from_xdr_base64(envelope, xdr::Limits::none()).unwrap() else {
+		panic!("not a v1 envelope")
+	};
+	envelope
+}
+
+#[tokio::test]
+async fn simulate_decodes_the_returned_value() -> soroban_rpc::Result {
+	let stub: Stub = Stub::default();
+	stub.answer("simulateTransaction", simulation(&xdr::ScVal::U64(7)));
+	let client: soroban_rpc::Client = stub.serve().await;
+	let value: xdr::ScVal = client.simulate(&contract(), "attestation", vec![xdr::ScVal::U32(1)]).await?;
+	assert_eq!(value, xdr::ScVal::U64(7));
+	assert!(stub.params_of("sendTransaction").is_empty());
+	Ok(())
+}
+
+#[tokio::test]
+async fn invoke_assembles_signs_and_settles() -> soroban_rpc::Result {
+	let stub: Stub = Stub::default();
+	let client: soroban_rpc::Client = stub.serve().await;
+	stub.answer("getLedgerEntries", account_entry(client.account(), 41))
+		.answer("simulateTransaction", simulation(&xdr::ScVal::Void))
+		.answer("sendTransaction", serde_json::json!({ "status": "PENDING", "hash": "abc" }))
+		.answer("getTransaction", serde_json::json!({ "status": "NOT_FOUND" }))
+		.answer("getTransaction", settled(xdr::ScVal::Bool(true), vec![event("mint", xdr::ScVal::Void)]));
+	let outcome: soroban_rpc::Outcome = client.invoke(&contract(), "mint", vec![xdr::ScVal::Address(client.address())]).await?;
+	assert_eq!(outcome.hash, "abc");
+	assert_eq!(outcome.ledger, Some(12));
+	assert_eq!(outcome.value, xdr::ScVal::Bool(true));
+	assert_eq!(outcome.events.len(), 1);
+	assert_eq!(outcome.events[0].contract, Some(contract()));
+
+	let envelope: xdr::TransactionV1Envelope = submitted(&stub);
+	assert_eq!(envelope.tx.seq_num, xdr::SequenceNumber(42));
+	assert_eq!(envelope.tx.fee, 600);
+	assert!(matches!(envelope.tx.ext, xdr::TransactionExt::V1(_)));
+	let network_id: [u8; 32] = <sha2::Sha256 as sha2::Digest>::digest(soroban_rpc::TESTNET_PASSPHRASE.as_bytes()).into();
+	let hash: [u8; 32] = envelope.hash(network_id)?;
+	let signature: ed25519_dalek::Signature = ed25519_dalek::Signature::from_slice(&envelope.signatures[0].signature.0).unwrap();
+	let verifying_key: ed25519_dalek::VerifyingKey = ed25519_dalek::SigningKey::from_bytes(&SECRET_KEY).verifying_key();
+	assert!(verifying_key.verify_strict(&hash, &signature).is_ok());
+	Ok(())
+}
+
+#[tokio::test]
+async fn concurrent_invocations_take_turns() -> soroban_rpc::Result {
+	let stub: Stub = Stub::default();
+	let client: soroban_rpc::Client = stub.serve().await;
+	for sequence in [41, 42] {
+		stub.answer("getLedgerEntries", account_entry(client.account(), sequence))
+			.answer("simulateTransaction", simulation(&xdr::ScVal::Void))
+			.answer("sendTransaction", serde_json::json!({ "status": "PENDING", "hash": "abc" }))
+			.answer("getTransaction", settled(xdr::ScVal::Void, Vec::new()));
+	}
+	let other: soroban_rpc::Client = client.to_owned();
+	let contract: xdr::ContractId = contract();
+	let (first, second) = tokio::join!(
+		client.invoke(&contract, "mint", Vec::new()),
+		other.invoke(&contract, "renew", Vec::new())
+	);
+	first?;
+	second?;
+	// each invocation settles before the next reads the sequence
+	let methods: Vec<serde_json::Value> = stub.requests
+		.lock()
+		.unwrap()
+		.iter()
+		.map(|request| request["method"].to_owned())
+		.collect();
+	let lifecycle: [&str; 4] = ["getLedgerEntries", "simulateTransaction", "sendTransaction", "getTransaction"];
+	assert_eq!(methods, [lifecycle, lifecycle].concat());
+	Ok(())
+}
+
+#[tokio::test]
+async fn simulation_errors_stop_submission() {
+	let stub: Stub = Stub::default();
+	let client: soroban_rpc::Client = stub.serve().await;
+	stub.answer("getLedgerEntries", account_entry(client.account(), 1))
+		.answer("simulateTransaction", serde_json::json!({ "error": "HostError: Error(Auth, InvalidAction)", "latestLedger": 10 }));
+	let outcome: soroban_rpc::Result<soroban_rpc::Outcome> = client.invoke(&contract(), "lock", Vec::new()).await;
+	assert!(matches!(outcome, Err(soroban_rpc::Error::Simulation(_))));
+	assert!(stub.params_of("sendTransaction").is_empty());
+}
+
+#[tokio::test]
+async fn failed_and_unsettled_transactions_surface() {
+	let stub: Stub = Stub::default();
+	let client: soroban_rpc::Client = stub.serve().await;
+	for _ in 0..2 {
+		stub.answer("getLedgerEntries", account_entry(client.account(), 1))
+			.answer("simulateTransaction", simulation(&xdr::ScVal::Void))
+			.answer("sendTransaction", serde_json::json!({ "status": "PENDING", "hash": "abc" }));
+	}
+	stub.answer("getTransaction", serde_json::json!({ "status": "FAILED" }));
+	for _ in 0..3 {
+		stub.answer("getTransaction", serde_json::json!({ "status": "NOT_FOUND" }));
+	}
+	let failed: soroban_rpc::Result<soroban_rpc::Outcome> = client.invoke(&contract(), "claim", Vec::new()).await;
+	assert!(matches!(failed, Err(soroban_rpc::Error::Failed(_))));
+	let unsettled: soroban_rpc::Result<soroban_rpc::Outcome> = client.invoke(&contract(), "claim", Vec::new()).await;
+	assert!(matches!(unsettled, Err(soroban_rpc::Error::Timeout(_))));
+}
+
+#[tokio::test]
+async fn events_decode_topics_and_data() -> soroban_rpc::Result {
+	let stub: Stub = Stub::default();
+	let topic: xdr::ScVal = xdr::ScVal::Symbol(xdr::ScSymbol("attest".try_into()?));
+	stub.answer("getEvents", serde_json::json!({
+		"events": [{
+			"type": "contract",
+			"ledger": 11,
+			"contractId": contract().to_string(),
+			"topic": [base64(&topic)],
+			"value": base64(&xdr::ScVal::U32(3))
+		}],
+		"latestLedger": 12
+	}));
+	let client: soroban_rpc::Client = stub.serve().await;
+	let events: Vec<soroban_rpc::Event> = client.events(&contract(), 10).await?;
+	assert_eq!(events, vec![soroban_rpc::Event {
+		contract: Some(contract()),
+		topics: vec![topic],
+		data: xdr::ScVal::U32(3)
+	}]);
+	assert_eq!(stub.params_of("getEvents")[0]["startLedger"], 10);
+	Ok(())
+}